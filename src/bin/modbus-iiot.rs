@@ -0,0 +1,754 @@
+#![allow( clippy::needless_late_init,
+		  clippy::needless_return,
+		  clippy::redundant_field_names,
+		  clippy::tabs_in_doc_comments )]
+
+extern crate modbus_iiot;
+
+use std::convert::TryFrom;
+use std::env;
+use std::fs::File;
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+use std::process;
+use std::time::Duration;
+use modbus_iiot::capture::hexlog::HexLogWriter;
+use modbus_iiot::capture::observer::BoxedFrameObserver;
+use modbus_iiot::capture::pcap::{PcapLinkType, PcapWriter};
+use modbus_iiot::core::consts::*;
+use modbus_iiot::core::datatable::DataTable;
+use modbus_iiot::core::decoder::*;
+use modbus_iiot::core::discovery::*;
+use modbus_iiot::core::modbusreturn::get_message_of_exception_code;
+use modbus_iiot::core::pdu::*;
+use modbus_iiot::tags::datatype::*;
+use modbus_iiot::tcp::master::TcpClient;
+use modbus_iiot::tcp::scanner::*;
+
+//	===============================================================================================
+
+const USAGE : &str = "\
+Usage: modbus-iiot [OPTIONS] <HOST> read <coils|di|hr|ir> <ADDRESS> [QUANTITY]
+       modbus-iiot [OPTIONS] <HOST> write <coil|register> <ADDRESS> <VALUE>
+       modbus-iiot [OPTIONS] <HOST> write <coils|registers> <ADDRESS> <VALUE>...
+       modbus-iiot [OPTIONS] <HOST> discover [coils|di|hr|ir|all] [FIRST] [LAST]
+       modbus-iiot [OPTIONS] <RANGE> scan
+       modbus-iiot [OPTIONS] decode [tcp|rtu|ascii] [request|response] <HEX>...
+
+Ranges are a host, 192.168.1.10-20, 192.168.1.10-192.168.2.20 or 192.168.1.0/24.
+
+Discover probes the readable address ranges of the tables, by default all tables from 0 to 9999.
+
+Decode explains a MODBUS TCP, RTU or ASCII frame from a hex dump like 01 03 00 00 00 0A C5 CD,
+the format and the message kind are detected unless given.
+
+Addresses and values are decimal or hexadecimal with 0x, coils are on/off, true/false or 1/0.
+The quantity of registers counts values of the data type.
+
+Options:
+  -p, --port <PORT>       TCP port (default 502)
+  -u, --unit <ID>         unit identifier (default 1), a list like 1-10,247 to scan
+  -t, --timeout <MS>      read and write timeout in milliseconds (default 500)
+  -f, --format <FORMAT>   output as table, csv or json (default table)
+  -T, --type <TYPE>       register data type u16, i16, u32, i32, f32, u64, i64 or f64 (default u16)
+  -o, --order <ORDER>     word order ABCD, BADC, CDAB or DCBA (default ABCD)
+  -c, --capture <FILE>    record the frames to a pcap file (*.pcap) or else a hex log
+  -h, --help              print this help";
+
+const DEFAULT_TIMEOUT : u64 = 500;
+
+//	===============================================================================================
+
+#[derive( Clone, Copy, Debug, PartialEq )]
+enum OutputFormat
+{
+	Table,
+	Csv,
+	Json
+}
+
+#[derive( Debug, PartialEq )]
+enum Command
+{
+	Read { table : DataTable, address : u16, quantity : u16 },
+	WriteCoils { address : u16, values : Vec< bool >, single : bool },
+	WriteRegisters { address : u16, values : Vec< f64 >, single : bool },
+	Discover { tables : Vec< DataTable >, first_address : u16, last_address : u16 },
+	Scan { addresses : Vec< IpAddr >, units : Vec< u8 > },
+	Decode { bytes : Vec< u8 >, format : Option< FrameFormat >, kind : Option< MessageKind > }
+}
+
+#[derive( Debug, PartialEq )]
+struct Options
+{
+	host : String,
+	port : u16,
+	unit : u8,
+	timeout : Duration,
+	format : OutputFormat,
+	data_type : DataType,
+	word_order : WordOrder,
+	capture : Option< String >,
+	command : Command
+}
+
+//	===============================================================================================
+
+fn main ()
+{
+	let arguments : Vec< String > = env::args ().skip ( 1 ).collect ();
+
+	if arguments.is_empty () || arguments.iter ().any ( | argument | argument == "-h" || argument == "--help" )
+	{
+		println! ( "{}", USAGE );
+		return;
+	}
+
+	match parse_arguments ( &arguments ).and_then ( | options | run ( &options ) )
+	{
+		Ok( output )	=> { print! ( "{}", output ); }
+		Err( message )	=>
+		{
+			eprintln! ( "error: {}", message );
+			process::exit ( 1 );
+		}
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_arguments ()
+{
+	let arguments : Vec< String > = [ "-p", "5020", "192.168.1.10", "read", "hr", "0x10", "4", "--type", "f32", "-o", "cdab", "-f", "json" ]
+		.iter ().map ( | argument | argument.to_string () ).collect ();
+	let options : Options = parse_arguments ( &arguments ).unwrap ();
+
+	assert_eq! ( options.host, "192.168.1.10" );
+	assert_eq! ( options.port, 5020 );
+	assert_eq! ( options.unit, MODBUS_DEFAULT_UNIT_IDENTIFIER );
+	assert_eq! ( options.format, OutputFormat::Json );
+	assert_eq! ( options.data_type, DataType::Float32 );
+	assert_eq! ( options.word_order, WordOrder::LittleEndianByteSwap );
+	assert_eq! ( options.command, Command::Read { table : DataTable::HoldingRegisters, address : 0x10, quantity : 4 } );
+
+	let arguments : Vec< String > = [ "::1", "write", "coils", "3", "on", "0", "true", "-u", "7" ]
+		.iter ().map ( | argument | argument.to_string () ).collect ();
+	let options : Options = parse_arguments ( &arguments ).unwrap ();
+
+	assert_eq! ( options.unit, 7 );
+	assert_eq! ( options.command, Command::WriteCoils { address : 3, values : vec![ true, false, true ], single : false } );
+
+	let arguments : Vec< String > = [ "10.0.0.1-3", "scan", "-u", "1-2" ].iter ().map ( | argument | argument.to_string () ).collect ();
+	let options : Options = parse_arguments ( &arguments ).unwrap ();
+
+	match options.command
+	{
+		Command::Scan { addresses, units }	=> { assert_eq! ( ( addresses.len (), units ), ( 3, vec![ 1, 2 ] ) ); }
+		command								=> { panic! ( "unexpected command {:?}", command ); }
+	}
+
+	let arguments : Vec< String > = [ "host", "discover", "hr", "100" ].iter ().map ( | argument | argument.to_string () ).collect ();
+	let options : Options = parse_arguments ( &arguments ).unwrap ();
+
+	assert_eq! ( options.command, Command::Discover { tables : vec![ DataTable::HoldingRegisters ], first_address : 100, last_address : 9999 } );
+
+	let arguments : Vec< String > = [ "decode", "rtu", "0x01,0x03", "0000", "00 0A", "C5CD" ].iter ().map ( | argument | argument.to_string () ).collect ();
+	let options : Options = parse_arguments ( &arguments ).unwrap ();
+
+	assert_eq! ( options.command, Command::Decode { bytes : vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD ], format : Some( FrameFormat::Rtu ), kind : None } );
+
+	let arguments : Vec< String > = [ "host", "write", "coil", "3" ].iter ().map ( | argument | argument.to_string () ).collect ();
+	assert! ( parse_arguments ( &arguments ).is_err () );
+
+	let arguments : Vec< String > = [ "host", "read", "xy", "3" ].iter ().map ( | argument | argument.to_string () ).collect ();
+	assert! ( parse_arguments ( &arguments ).is_err () );
+
+	let out_of_range : [ ( &[ &str ], &str ); 4 ] = [ ( &[ "-p", "70000", "host", "read", "hr", "0" ], "70000" ),
+													  ( &[ "-u", "300", "host", "read", "hr", "0" ], "300" ),
+													  ( &[ "host", "write", "register", "70000", "5" ], "70000" ),
+													  ( &[ "host", "read", "hr", "0", "65536" ], "65536" ) ];
+
+	for ( arguments, number ) in out_of_range.iter ()
+	{
+		let arguments : Vec< String > = arguments.iter ().map ( | argument | argument.to_string () ).collect ();
+		assert_eq! ( parse_arguments ( &arguments ).err (), Some( format! ( "number '{}' out of range", number ) ) );
+	}
+}
+
+fn parse_arguments ( arguments : &[ String ] ) -> Result< Options, String >
+{
+	let mut port : u16 = MODBUS_TCP_PORT;
+	let mut unit : String = MODBUS_DEFAULT_UNIT_IDENTIFIER.to_string ();
+	let mut timeout : u64 = DEFAULT_TIMEOUT;
+	let mut format : OutputFormat = OutputFormat::Table;
+	let mut data_type : DataType = DataType::UInt16;
+	let mut word_order : WordOrder = WordOrder::BigEndian;
+	let mut capture : Option< String > = None;
+	let mut positionals : Vec< &str > = vec![];
+
+	let mut iterator = arguments.iter ();
+
+	while let Some( argument ) = iterator.next ()
+	{
+		if !argument.starts_with ( '-' ) || argument.parse::< f64 > ().is_ok ()
+		{
+			positionals.push ( argument );
+			continue;
+		}
+
+		let value : &str = iterator.next ().ok_or ( format! ( "missing value of option {}", argument ) )?;
+
+		match argument.as_str ()
+		{
+			"-p" | "--port"		=> { port = parse_integer ( value )?; }
+			"-u" | "--unit"		=> { unit = value.to_string (); }
+			"-t" | "--timeout"	=> { timeout = parse_number ( value )?; }
+			"-f" | "--format"	=> { format = parse_format ( value )?; }
+			"-T" | "--type"		=> { data_type = DataType::parse ( value )?; }
+			"-o" | "--order"	=> { word_order = WordOrder::parse ( value )?; }
+			"-c" | "--capture"	=> { capture = Some( value.to_string () ); }
+			_					=> { return Err( format! ( "unknown option {}", argument ) ); }
+		}
+	}
+
+	if positionals.first () == Some( &"decode" )
+	{
+		let mut frame_format : Option< FrameFormat > = None;
+		let mut kind : Option< MessageKind > = None;
+		let mut bytes : Vec< u8 > = vec![];
+
+		for positional in &positionals[ 1.. ]
+		{
+			match positional.to_lowercase ().as_str ()
+			{
+				"tcp"		=> { frame_format = Some( FrameFormat::Tcp ); }
+				"rtu"		=> { frame_format = Some( FrameFormat::Rtu ); }
+				"ascii"		=> { frame_format = Some( FrameFormat::Ascii ); }
+				"request"	=> { kind = Some( MessageKind::Request ); }
+				"response"	=> { kind = Some( MessageKind::Response ); }
+				_			=> { bytes.extend ( parse_hex_bytes ( positional )? ); }
+			}
+		}
+
+		if bytes.is_empty ()
+		{
+			return Err( "missing frame to decode, see --help".to_string () );
+		}
+
+		return Ok( Options
+				   {
+					   host : String::new (),
+					   port : port,
+					   unit : MODBUS_DEFAULT_UNIT_IDENTIFIER,
+					   timeout : Duration::from_millis ( timeout ),
+					   format : format,
+					   data_type : data_type,
+					   word_order : word_order,
+					   capture : capture,
+					   command : Command::Decode { bytes : bytes, format : frame_format, kind : kind }
+				   } );
+	}
+
+	if positionals.len () == 2 && positionals[ 1 ] == "scan"
+	{
+		return Ok( Options
+				   {
+					   host : positionals[ 0 ].to_string (),
+					   port : port,
+					   unit : MODBUS_DEFAULT_UNIT_IDENTIFIER,
+					   timeout : Duration::from_millis ( timeout ),
+					   format : format,
+					   data_type : data_type,
+					   word_order : word_order,
+					   capture : capture,
+					   command : Command::Scan { addresses : parse_address_range ( positionals[ 0 ] )?, units : parse_unit_identifiers ( &unit )? }
+				   } );
+	}
+
+	if positionals.len () >= 2 && positionals.len () <= 5 && positionals[ 1 ] == "discover"
+	{
+		let tables : Vec< DataTable >;
+
+		match positionals.get ( 2 )
+		{
+			None | Some( &"all" )	=> { tables = vec![ DataTable::Coils, DataTable::DiscreteInputs, DataTable::HoldingRegisters, DataTable::InputRegisters ]; }
+			Some( table )			=> { tables = vec![ parse_table ( table )? ]; }
+		}
+
+		let first_address : u16 = match positionals.get ( 3 ) { Some( value ) => parse_integer ( value )?, None => 0 };
+		let last_address : u16 = match positionals.get ( 4 ) { Some( value ) => parse_integer ( value )?, None => 9999 };
+
+		return Ok( Options
+				   {
+					   host : positionals[ 0 ].to_string (),
+					   port : port,
+					   unit : parse_integer ( &unit )?,
+					   timeout : Duration::from_millis ( timeout ),
+					   format : format,
+					   data_type : data_type,
+					   word_order : word_order,
+					   capture : capture,
+					   command : Command::Discover { tables : tables, first_address : first_address, last_address : last_address }
+				   } );
+	}
+
+	if positionals.len () < 4
+	{
+		return Err( "missing arguments, see --help".to_string () );
+	}
+
+	let address : u16 = parse_integer ( positionals[ 3 ] )?;
+	let values : &[ &str ] = &positionals[ 4.. ];
+
+	let command : Command;
+
+	match ( positionals[ 1 ], positionals[ 2 ] )
+	{
+		( "read", table )				=>
+		{
+			let quantity : u16 = match values.first () { Some( value ) => parse_integer ( value )?, None => 1 };
+
+			command = Command::Read { table : parse_table ( table )?, address : address, quantity : quantity };
+		}
+		( "write", "coil" ) |
+		( "write", "coils" )			=>
+		{
+			let single : bool = positionals[ 2 ] == "coil";
+
+			verify_value_count ( values.len (), single )?;
+			command = Command::WriteCoils { address : address, values : values.iter ().map ( | value | parse_coil ( value ) ).collect::< Result< Vec< bool >, String > > ()?, single : single };
+		}
+		( "write", "register" ) |
+		( "write", "registers" )		=>
+		{
+			let single : bool = positionals[ 2 ] == "register";
+
+			verify_value_count ( values.len (), single )?;
+			command = Command::WriteRegisters { address : address, values : values.iter ().map ( | value | parse_value ( value ) ).collect::< Result< Vec< f64 >, String > > ()?, single : single };
+		}
+		( action, object )				=> { return Err( format! ( "unknown command {} {}", action, object ) ); }
+	}
+
+	return Ok( Options
+			   {
+				   host : positionals[ 0 ].to_string (),
+				   port : port,
+				   unit : parse_integer ( &unit )?,
+				   timeout : Duration::from_millis ( timeout ),
+				   format : format,
+				   data_type : data_type,
+				   word_order : word_order,
+				   capture : capture,
+				   command : command
+			   } );
+}
+
+fn verify_value_count ( count : usize, single : bool ) -> Result< (), String >
+{
+	if count == 0 || ( single && count > 1 )
+	{
+		return Err( format! ( "expected {} value(s) to write, got {}", if single { "one" } else { "one or more" }, count ) );
+	}
+
+	return Ok( () );
+}
+
+fn parse_number ( text : &str ) -> Result< u64, String >
+{
+	let reply : Result< u64, std::num::ParseIntError >;
+
+	if text.starts_with ( "0x" ) || text.starts_with ( "0X" )
+	{
+		reply = u64::from_str_radix ( &text[ 2.. ], 16 );
+	}
+	else
+	{
+		reply = text.parse::< u64 > ();
+	}
+
+	return reply.map_err ( | _ | format! ( "invalid number '{}'", text ) );
+}
+
+///	Parses a number which has to fit into the integer type, e.g. a port, address or unit identifier.
+fn parse_integer< T : TryFrom< u64 > > ( text : &str ) -> Result< T, String >
+{
+	return T::try_from ( parse_number ( text )? ).map_err ( | _ | format! ( "number '{}' out of range", text ) );
+}
+
+fn parse_value ( text : &str ) -> Result< f64, String >
+{
+	if let Ok( number ) = parse_number ( text )
+	{
+		return Ok( number as f64 );
+	}
+
+	return text.parse::< f64 > ().map_err ( | _ | format! ( "invalid value '{}'", text ) );
+}
+
+fn parse_coil ( text : &str ) -> Result< bool, String >
+{
+	match text.to_lowercase ().as_str ()
+	{
+		"1" | "on" | "true"		=> { return Ok( true ); }
+		"0" | "off" | "false"	=> { return Ok( false ); }
+		_						=> { return Err( format! ( "invalid coil value '{}'", text ) ); }
+	}
+}
+
+fn parse_table ( text : &str ) -> Result< DataTable, String >
+{
+	match text.to_lowercase ().as_str ()
+	{
+		"coils" | "co"							=> { return Ok( DataTable::Coils ); }
+		"di" | "inputs" | "discrete_inputs"		=> { return Ok( DataTable::DiscreteInputs ); }
+		"hr" | "holding" | "holding_registers"	=> { return Ok( DataTable::HoldingRegisters ); }
+		"ir" | "input" | "input_registers"		=> { return Ok( DataTable::InputRegisters ); }
+		_										=> { return Err( format! ( "unknown table '{}'", text ) ); }
+	}
+}
+
+fn parse_format ( text : &str ) -> Result< OutputFormat, String >
+{
+	match text.to_lowercase ().as_str ()
+	{
+		"table"	=> { return Ok( OutputFormat::Table ); }
+		"csv"	=> { return Ok( OutputFormat::Csv ); }
+		"json"	=> { return Ok( OutputFormat::Json ); }
+		_		=> { return Err( format! ( "unknown format '{}'", text ) ); }
+	}
+}
+
+//	===============================================================================================
+
+///	Connects to the device, executes the command and returns the formatted output.
+fn run ( options : &Options ) -> Result< String, String >
+{
+	if let Command::Scan { ref addresses, ref units } = options.command
+	{
+		return Ok( scan ( addresses, units, options ) );
+	}
+
+	if let Command::Decode { ref bytes, format, kind } = options.command
+	{
+		return decode ( bytes, format, kind, options.format );
+	}
+
+	let mut client : TcpClient = TcpClient::new_with_port_and_unitid ( &options.host,
+																	   options.port,
+																	   options.unit );
+
+	client.set_timeout ( options.timeout );
+
+	if let Some( ref path ) = options.capture
+	{
+		client.set_frame_observer ( Some( create_capture ( path, options )? ) );
+	}
+
+	client.connect ()?;
+
+	let reply : Result< String, String > = execute_command ( &mut client,
+															 options );
+
+	client.disconnect ();
+
+	return reply;
+}
+
+fn execute_command ( client : &mut TcpClient, options : &Options ) -> Result< String, String >
+{
+	let mut buffer : [ u8; MODBUS_MAXIMUM_PDU_SIZE as usize ] = [ 0; MODBUS_MAXIMUM_PDU_SIZE as usize ];
+	let registers_per_value : u16 = options.data_type.get_quantity ();
+
+	match options.command
+	{
+		Command::Read { table, address, quantity }						=>
+		{
+			let request : RequestPdu;
+
+			match table
+			{
+				DataTable::Coils				=> { request = RequestPdu::ReadCoils { address : address, quantity : quantity }; }
+				DataTable::DiscreteInputs		=> { request = RequestPdu::ReadDiscreteInputs { address : address, quantity : quantity }; }
+				DataTable::HoldingRegisters		=> { request = RequestPdu::ReadHoldingRegisters { address : address, quantity : quantity.saturating_mul ( registers_per_value ) }; }
+				DataTable::InputRegisters		=> { request = RequestPdu::ReadInputRegisters { address : address, quantity : quantity.saturating_mul ( registers_per_value ) }; }
+			}
+
+			match verify_response ( client.send_request ( options.unit, &request, &mut buffer )? )?
+			{
+				ResponsePdu::ReadCoils { values } |
+				ResponsePdu::ReadDiscreteInputs { values }				=>
+				{
+					let rows : Vec< Vec< String > > = values.iter ().take ( quantity as usize ).enumerate ()
+						.map ( | ( index, value ) | vec![ ( address as usize + index ).to_string (), value.to_string () ] ).collect ();
+
+					return Ok( format_rows ( &[ "address", "value" ], &rows, options.format ) );
+				}
+				ResponsePdu::ReadHoldingRegisters { values } |
+				ResponsePdu::ReadInputRegisters { values }				=>
+				{
+					let rows : Vec< Vec< String > > = decode_values ( &values.to_vec (), address, options.data_type, options.word_order )?;
+
+					return Ok( format_rows ( &[ "address", "value" ], &rows, options.format ) );
+				}
+				response												=> { return Err( format! ( "unexpected response {:?}", response ) ); }
+			}
+		}
+		Command::WriteCoils { address, ref values, single }				=>
+		{
+			let request : RequestPdu;
+
+			if single
+			{
+				request = RequestPdu::WriteSingleCoil { address : address, value : values[ 0 ] };
+			}
+			else
+			{
+				request = RequestPdu::WriteMultipleCoils { address : address, values : Coils::Unpacked( values ) };
+			}
+
+			verify_response ( client.send_request ( options.unit, &request, &mut buffer )? )?;
+
+			return Ok( format! ( "wrote {} coil(s) at {}\n", values.len (), address ) );
+		}
+		Command::WriteRegisters { address, ref values, single }			=>
+		{
+			let mut registers : Vec< u16 > = vec![];
+
+			for value in values
+			{
+				registers.extend ( encode_registers ( *value, options.data_type, options.word_order )? );
+			}
+
+			let request : RequestPdu;
+
+			if single && registers.len () == 1
+			{
+				request = RequestPdu::WriteSingleRegister { address : address, value : registers[ 0 ] };
+			}
+			else
+			{
+				request = RequestPdu::WriteMultipleRegisters { address : address, values : Registers::Words( &registers ) };
+			}
+
+			verify_response ( client.send_request ( options.unit, &request, &mut buffer )? )?;
+
+			return Ok( format! ( "wrote {} register(s) at {}\n", registers.len (), address ) );
+		}
+		Command::Discover { ref tables, first_address, last_address }	=>
+		{
+			let mut discovery : Discovery = Discovery::new ();
+
+			discovery.set_tables ( tables.clone () );
+			discovery.set_address_range ( first_address, last_address );
+
+			let rows : Vec< Vec< String > > = discovery.discover ( client ).get_ranges ().iter ()
+				.map ( | range | vec![ range.get_table ().to_string (),
+									   range.get_first_address ().to_string (),
+									   range.get_last_address ().to_string (),
+									   range.get_quantity ().to_string (),
+									   range.get_status ().to_string () ] ).collect ();
+
+			return Ok( format_rows ( &[ "table", "first", "last", "quantity", "status" ], &rows, options.format ) );
+		}
+		Command::Scan { .. } |
+		Command::Decode { .. }											=> { return Err( "command needs no connection".to_string () ); }
+	}
+}
+
+///	Creates the pcap writer with the addresses of the device if they are known, or the hex log writer.
+fn create_capture ( path : &str, options : &Options ) -> Result< BoxedFrameObserver, String >
+{
+	let file : File = File::create ( path ).map_err ( | error | format! ( "{}: {}", path, error ) )?;
+
+	if !path.to_lowercase ().ends_with ( ".pcap" )
+	{
+		return Ok( Box::new ( HexLogWriter::new ( file ) ) );
+	}
+
+	let mut writer : PcapWriter< File > = PcapWriter::new ( file,
+															 PcapLinkType::Tcp )?;
+
+	if let Ok( address ) = options.host.parse::< Ipv4Addr > ()
+	{
+		writer.set_addresses ( SocketAddrV4::new ( Ipv4Addr::new ( 192, 0, 2, 1 ), 49152 ),
+							   SocketAddrV4::new ( address, options.port ) );
+	}
+
+	return Ok( Box::new ( writer ) );
+}
+
+///	Decodes the frame, as indented text for the table format and as rows of fields otherwise.
+fn decode ( bytes : &[ u8 ], format : Option< FrameFormat >, kind : Option< MessageKind >, output_format : OutputFormat ) -> Result< String, String >
+{
+	let mut decoder : FrameDecoder = FrameDecoder::new ();
+
+	decoder.set_format ( format );
+	decoder.set_kind ( kind );
+
+	let frame : DecodedFrame = decoder.decode ( bytes )?;
+
+	if output_format == OutputFormat::Table
+	{
+		return Ok( frame.to_string () );
+	}
+
+	let mut rows : Vec< Vec< String > > = vec![ vec![ "frame".to_string (), format! ( "{} {}", frame.get_format (), frame.get_kind () ) ],
+												vec![ "valid".to_string (), frame.is_valid ().to_string () ] ];
+
+	rows.extend ( frame.get_fields ().iter ().map ( | field | vec![ field.get_name ().to_string (), field.get_value ().to_string () ] ) );
+
+	return Ok( format_rows ( &[ "field", "value" ], &rows, output_format ) );
+}
+
+///	Scans the hosts and lists each unit which answered, hosts without units with an empty unit.
+fn scan ( addresses : &[ IpAddr ], units : &[ u8 ], options : &Options ) -> String
+{
+	let mut scanner : Scanner = Scanner::new ( options.port );
+
+	scanner.set_unit_identifiers ( units.to_vec () );
+	scanner.set_connect_timeout ( options.timeout );
+	scanner.set_timeout ( options.timeout );
+
+	let mut rows : Vec< Vec< String > > = vec![];
+
+	for result in scanner.scan ( addresses )
+	{
+		if result.get_units ().is_empty ()
+		{
+			rows.push ( vec![ result.get_address ().to_string (), String::new (), String::new (), String::new () ] );
+		}
+
+		for unit in result.get_units ()
+		{
+			rows.push ( vec![ result.get_address ().to_string (),
+							  unit.get_unit_identifier ().to_string (),
+							  unit.get_duration_in_milliseconds ().to_string (),
+							  unit.get_exception_code ().map ( | code | code.to_string () ).unwrap_or_default () ] );
+		}
+	}
+
+	return format_rows ( &[ "host", "unit", "milliseconds", "exception" ], &rows, options.format );
+}
+
+///	Turns exception responses into errors with the message of the exception code.
+fn verify_response ( response : ResponsePdu ) -> Result< ResponsePdu, String >
+{
+	if let ResponsePdu::Exception { exception_code, .. } = response
+	{
+		return Err( get_message_of_exception_code ( exception_code ).unwrap_or_default () );
+	}
+
+	return Ok( response );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_decode_values ()
+{
+	let rows : Vec< Vec< String > > = decode_values ( &[ 0x0000, 0x3FC0, 0x0000, 0xC020 ], 100, DataType::Float32, WordOrder::LittleEndianByteSwap ).unwrap ();
+
+	assert_eq! ( rows, vec![ vec![ "100".to_string (), "1.5".to_string () ], vec![ "102".to_string (), "-2.5".to_string () ] ] );
+
+	let rows : Vec< Vec< String > > = decode_values ( &[ 1, 2 ], 65535, DataType::UInt16, WordOrder::BigEndian ).unwrap ();
+
+	assert_eq! ( rows[ 1 ][ 0 ], "65536" );
+}
+
+///	Decodes the registers into values of the data type, each with its first address.
+fn decode_values ( registers : &[ u16 ], address : u16, data_type : DataType, word_order : WordOrder ) -> Result< Vec< Vec< String > >, String >
+{
+	let mut reply : Vec< Vec< String > > = vec![];
+
+	let quantity : usize = data_type.get_quantity () as usize;
+
+	for ( index, chunk ) in registers.chunks ( quantity ).enumerate ()
+	{
+		let value : f64 = decode_registers ( chunk,
+											 data_type,
+											 word_order )?;
+
+		reply.push ( vec![ ( address as usize + index * quantity ).to_string (), value.to_string () ] );
+	}
+
+	return Ok( reply );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_format_rows ()
+{
+	let rows : Vec< Vec< String > > = vec![ vec![ "0".to_string (), "true".to_string () ], vec![ "1".to_string (), "NaN".to_string () ] ];
+
+	assert_eq! ( format_rows ( &[ "address", "value" ], &rows, OutputFormat::Table ), "address  value\n      0   true\n      1    NaN\n" );
+	assert_eq! ( format_rows ( &[ "address", "value" ], &rows, OutputFormat::Csv ), "address,value\n0,true\n1,NaN\n" );
+	assert_eq! ( format_rows ( &[ "address", "value" ], &rows, OutputFormat::Json ), "[{\"address\":0,\"value\":true},{\"address\":1,\"value\":null}]\n" );
+	assert_eq! ( format_rows ( &[ "host", "unit" ], &[ vec![ "10.0.0.1".to_string (), String::new () ] ], OutputFormat::Json ), "[{\"host\":\"10.0.0.1\",\"unit\":null}]\n" );
+	assert_eq! ( format_rows ( &[ "a", "b", "c" ], &[ vec![ "01".to_string (), ".5".to_string (), "1.".to_string () ] ], OutputFormat::Json ), "[{\"a\":1,\"b\":0.5,\"c\":1}]\n" );
+}
+
+///	Formats the rows with the column names. In JSON numbers and booleans are written
+/// as literals, empty values and numbers like `NaN` of floats as `null`.
+fn format_rows ( columns : &[ &str ], rows : &[ Vec< String > ], format : OutputFormat ) -> String
+{
+	let mut reply : String = String::new ();
+
+	match format
+	{
+		OutputFormat::Table	=>
+		{
+			let widths : Vec< usize > = columns.iter ().enumerate ()
+				.map ( | ( index, column ) | rows.iter ().map ( | row | row[ index ].len () ).fold ( column.len (), usize::max ) ).collect ();
+
+			let header : Vec< String > = columns.iter ().map ( | column | column.to_string () ).collect ();
+
+			for row in Some( &header ).into_iter ().chain ( rows )
+			{
+				let cells : Vec< String > = row.iter ().zip ( &widths ).map ( | ( cell, width ) | format! ( "{:>1$}", cell, width ) ).collect ();
+				reply.push_str ( &cells.join ( "  " ) );
+				reply.push ( '\n' );
+			}
+		}
+		OutputFormat::Csv	=>
+		{
+			reply.push_str ( &columns.join ( "," ) );
+			reply.push ( '\n' );
+
+			for row in rows
+			{
+				reply.push_str ( &row.join ( "," ) );
+				reply.push ( '\n' );
+			}
+		}
+		OutputFormat::Json	=>
+		{
+			let entries : Vec< String > = rows.iter ().map ( | row |
+			{
+				let fields : Vec< String > = columns.iter ().zip ( row ).map ( | ( column, value ) | format! ( "\"{}\":{}", column, format_json_value ( value ) ) ).collect ();
+
+				format! ( "{{{}}}", fields.join ( "," ) )
+			} ).collect ();
+
+			reply.push_str ( &format! ( "[{}]\n", entries.join ( "," ) ) );
+		}
+	}
+
+	return reply;
+}
+
+fn format_json_value ( value : &str ) -> String
+{
+	if value == "true" || value == "false"
+	{
+		return value.to_string ();
+	}
+
+	match value.parse::< f64 > ()
+	{
+		Ok( number ) if number.is_finite ()	=> { return number.to_string (); }
+		Ok( _ )								=> { return "null".to_string (); }
+		Err( _ ) if value.is_empty ()		=> { return "null".to_string (); }
+		Err( _ )							=> { return format! ( "\"{}\"", value.replace ( '\\', "\\\\" ).replace ( '"', "\\\"" ) ); }
+	}
+}
@@ -0,0 +1,136 @@
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+use capture::observer::*;
+
+//	===============================================================================================
+
+/// Writes the observed frames as lines of a human-readable log, e.g.
+/// `2026-10-19T08:15:02.125Z TX 00 01 00 00 00 06 01 03 00 00 00 01`
+/// with the UTC time, TX for sent and RX for received frames.
+pub struct HexLogWriter< W : Write >
+{
+	writer : W,
+	error : Option< String >
+}
+
+impl< W : Write > HexLogWriter< W >
+{
+	pub fn new ( writer : W ) -> HexLogWriter< W >
+	{
+		return HexLogWriter
+		{
+			writer : writer,
+			error : None
+		};
+	}
+
+	///	Returns the first error of writing an observed frame.
+	pub fn get_error ( &self ) -> Option< &String >
+	{
+		return self.error.as_ref ();
+	}
+
+	pub fn get_writer ( &mut self ) -> &mut W
+	{
+		return &mut self.writer;
+	}
+
+	///	Writes the line of the frame observed at the time.
+	pub fn write_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ], time : SystemTime ) -> Result< (), String >
+	{
+		let marker : &str;
+
+		match direction
+		{
+			FrameDirection::Sent		=> { marker = "TX"; }
+			FrameDirection::Received	=> { marker = "RX"; }
+		}
+
+		writeln! ( self.writer, "{} {} {}", format_timestamp ( time ), marker, format_hex ( frame ) ).map_err ( | error | error.to_string () )?;
+		self.writer.flush ().map_err ( | error | error.to_string () )?;
+
+		return Ok( () );
+	}
+}
+
+impl< W : Write > FrameObserver for HexLogWriter< W >
+{
+	fn observe_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ] )
+	{
+		if let Err( message ) = self.write_frame ( direction, frame, SystemTime::now () )
+		{
+			if self.error.is_none ()
+			{
+				self.error = Some( message );
+			}
+		}
+	}
+}
+
+#[test]
+fn test_hex_log_writer ()
+{
+	let mut writer : HexLogWriter< Vec< u8 > > = HexLogWriter::new ( vec![] );
+	let time : SystemTime = UNIX_EPOCH + ::std::time::Duration::from_millis ( 1_760_861_702_125 );
+
+	writer.write_frame ( FrameDirection::Sent, &[ 0x01, 0x03, 0xAB ], time ).unwrap ();
+	writer.write_frame ( FrameDirection::Received, &[], time ).unwrap ();
+
+	assert_eq! ( String::from_utf8 ( writer.get_writer ().clone () ).unwrap (),
+				 "2025-10-19T08:15:02.125Z TX 01 03 AB\n2025-10-19T08:15:02.125Z RX \n" );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_format_hex ()
+{
+	assert_eq! ( format_hex ( &[ 0x00, 0x0F, 0xFF ] ), "00 0F FF" );
+	assert_eq! ( format_hex ( &[] ), "" );
+}
+
+///	Formats the bytes as upper case hexadecimal pairs separated by spaces.
+pub fn format_hex ( bytes : &[ u8 ] ) -> String
+{
+	return bytes.iter ().map ( | byte | format! ( "{:02X}", byte ) ).collect::< Vec< String > > ().join ( " " );
+}
+
+#[test]
+fn test_format_timestamp ()
+{
+	assert_eq! ( format_timestamp ( UNIX_EPOCH ), "1970-01-01T00:00:00.000Z" );
+	assert_eq! ( format_timestamp ( UNIX_EPOCH + ::std::time::Duration::from_millis ( 951_782_400_001 ) ), "2000-02-29T00:00:00.001Z" );
+}
+
+///	Formats the time as UTC in ISO 8601 with milliseconds.
+pub fn format_timestamp ( time : SystemTime ) -> String
+{
+	let duration = time.duration_since ( UNIX_EPOCH ).unwrap_or_default ();
+	let seconds : u64 = duration.as_secs ();
+	let ( year, month, day ) = convert_days_to_date ( ( seconds / 86400 ) as i64 );
+
+	return format! ( "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+					 year,
+					 month,
+					 day,
+					 seconds % 86400 / 3600,
+					 seconds % 3600 / 60,
+					 seconds % 60,
+					 duration.subsec_millis () );
+}
+
+///	Converts days since 1970-01-01 to the date of the proleptic Gregorian calendar.
+fn convert_days_to_date ( days : i64 ) -> ( i64, u32, u32 )
+{
+	let shifted_days : i64 = days + 719_468;
+	let era : i64 = shifted_days.div_euclid ( 146_097 );
+	let day_of_era : i64 = shifted_days - era * 146_097;
+	let year_of_era : i64 = ( day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096 ) / 365;
+	let day_of_year : i64 = day_of_era - ( 365 * year_of_era + year_of_era / 4 - year_of_era / 100 );
+	let month_index : i64 = ( 5 * day_of_year + 2 ) / 153;
+	let day : u32 = ( day_of_year - ( 153 * month_index + 2 ) / 5 + 1 ) as u32;
+	let month : u32 = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+	let year : i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+	return ( year, month, day );
+}
@@ -0,0 +1,5 @@
+
+
+pub mod hexlog;
+pub mod observer;
+pub mod pcap;
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+//	===============================================================================================
+
+/// A boxed observer, which a master owns.
+pub type BoxedFrameObserver = Box< dyn FrameObserver + Send >;
+
+//	===============================================================================================
+
+/// The direction of a frame seen by the side which sends the requests.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum FrameDirection
+{
+	Sent,
+	Received
+}
+
+//	===============================================================================================
+
+/// Gets every ADU (the whole frame with header or address and CRC)
+/// a master sends or receives, e.g. to record the traffic.
+pub trait FrameObserver
+{
+	fn observe_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ] );
+}
+
+impl< F : FnMut ( FrameDirection, &[ u8 ] ) > FrameObserver for F
+{
+	fn observe_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ] )
+	{
+		self ( direction, frame );
+	}
+}
+
+///	A shared observer records the frames of several masters or stays accessible to the caller.
+impl< O : FrameObserver > FrameObserver for Arc< Mutex< O > >
+{
+	fn observe_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ] )
+	{
+		if let Ok( mut observer ) = self.lock ()
+		{
+			observer.observe_frame ( direction, frame );
+		}
+	}
+}
+
+//	===============================================================================================
+
+///	Passes the frame to the observer if there is one.
+pub fn notify_observer ( observer : &mut Option< BoxedFrameObserver >, direction : FrameDirection, frame : &[ u8 ] )
+{
+	if let Some( ref mut observer ) = *observer
+	{
+		observer.observe_frame ( direction, frame );
+	}
+}
@@ -0,0 +1,327 @@
+use std::io::Write;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{SystemTime, UNIX_EPOCH};
+use capture::observer::*;
+use core::consts::*;
+
+//	===============================================================================================
+
+const PCAP_MAGIC_NUMBER : u32 = 0xA1B2_C3D4;
+const PCAP_VERSION_MAJOR : u16 = 2;
+const PCAP_VERSION_MINOR : u16 = 4;
+const PCAP_SNAPSHOT_LENGTH : u32 = 0xFFFF;
+
+///	Raw IP packets without a link layer header.
+const LINKTYPE_RAW : u32 = 101;
+///	The first link type reserved for private use (DLT_USER0).
+const LINKTYPE_USER0 : u32 = 147;
+
+const IPV4_HEADER_SIZE : usize = 20;
+const IPV4_TIME_TO_LIVE : u8 = 64;
+const IPV4_PROTOCOL_TCP : u8 = 6;
+const TCP_HEADER_SIZE : usize = 20;
+const TCP_FLAGS_PSH_ACK : u8 = 0x18;
+const TCP_WINDOW_SIZE : u16 = 0xFFFF;
+const CAPTURE_CLIENT_PORT : u16 = 49152;
+
+//	===============================================================================================
+
+/// The link type of a capture file.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum PcapLinkType
+{
+	///	MODBUS TCP frames in synthetic IPv4 and TCP headers with the server on port 502,
+	/// which Wireshark decodes as Modbus/TCP.
+	Tcp,
+	///	MODBUS RTU frames as DLT_USER0, which Wireshark decodes after assigning
+	/// the protocol `mbrtu` to DLT 147 in the DLT_USER preferences.
+	Rtu
+}
+
+//	===============================================================================================
+
+/// Writes the observed frames to a capture file in the pcap format.
+/// Each frame is flushed, so the file stays readable if the program ends unexpectedly.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::fs::File;
+/// use modbus_iiot::capture::pcap::{PcapLinkType, PcapWriter};
+/// use modbus_iiot::core::ethernet::EthernetMaster;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let file = File::create("modbus.pcap").unwrap();
+/// let mut client = TcpClient::new("127.0.0.1");
+///
+/// client.set_frame_observer(Some(Box::new(PcapWriter::new(file, PcapLinkType::Tcp).unwrap())));
+///
+/// if client.connect().is_ok()
+/// {
+///     let _ = client.read_holding_registers(0, 10);
+///     client.disconnect();
+/// }
+/// ```
+pub struct PcapWriter< W : Write >
+{
+	writer : W,
+	link_type : PcapLinkType,
+	client_address : SocketAddrV4,
+	server_address : SocketAddrV4,
+	client_sequence : u32,
+	server_sequence : u32,
+	identification : u16,
+	error : Option< String >
+}
+
+impl< W : Write > PcapWriter< W >
+{
+	///	Creates the writer and writes the file header.
+	pub fn new ( writer : W, link_type : PcapLinkType ) -> Result< PcapWriter< W >, String >
+	{
+		let mut reply : PcapWriter< W > = PcapWriter
+		{
+			writer : writer,
+			link_type : link_type,
+			client_address : SocketAddrV4::new ( Ipv4Addr::new ( 192, 0, 2, 1 ), CAPTURE_CLIENT_PORT ),
+			server_address : SocketAddrV4::new ( Ipv4Addr::new ( 192, 0, 2, 2 ), MODBUS_TCP_PORT ),
+			client_sequence : 1,
+			server_sequence : 1,
+			identification : 0,
+			error : None
+		};
+
+		let network : u32;
+
+		match link_type
+		{
+			PcapLinkType::Tcp	=> { network = LINKTYPE_RAW; }
+			PcapLinkType::Rtu	=> { network = LINKTYPE_USER0; }
+		}
+
+		let mut header : Vec< u8 > = vec![];
+
+		header.extend_from_slice ( &PCAP_MAGIC_NUMBER.to_le_bytes () );
+		header.extend_from_slice ( &PCAP_VERSION_MAJOR.to_le_bytes () );
+		header.extend_from_slice ( &PCAP_VERSION_MINOR.to_le_bytes () );
+		header.extend_from_slice ( &0i32.to_le_bytes () );
+		header.extend_from_slice ( &0u32.to_le_bytes () );
+		header.extend_from_slice ( &PCAP_SNAPSHOT_LENGTH.to_le_bytes () );
+		header.extend_from_slice ( &network.to_le_bytes () );
+
+		reply.write_bytes ( &header )?;
+
+		return Ok( reply );
+	}
+
+	///	Sets the addresses of the synthetic IPv4 and TCP headers,
+	/// by default 192.0.2.1:49152 for the client and 192.0.2.2:502 for the server.
+	pub fn set_addresses ( &mut self, client_address : SocketAddrV4, server_address : SocketAddrV4 )
+	{
+		self.client_address = client_address;
+		self.server_address = server_address;
+	}
+
+	///	Returns the first error of writing an observed frame.
+	pub fn get_error ( &self ) -> Option< &String >
+	{
+		return self.error.as_ref ();
+	}
+
+	pub fn get_writer ( &mut self ) -> &mut W
+	{
+		return &mut self.writer;
+	}
+
+	///	Writes the frame as a packet captured at the time.
+	pub fn write_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ], time : SystemTime ) -> Result< (), String >
+	{
+		let packet : Vec< u8 >;
+
+		match self.link_type
+		{
+			PcapLinkType::Tcp	=> { packet = self.create_tcp_packet ( direction, frame ); }
+			PcapLinkType::Rtu	=> { packet = frame.to_vec (); }
+		}
+
+		let timestamp = time.duration_since ( UNIX_EPOCH ).unwrap_or_default ();
+		let mut record : Vec< u8 > = vec![];
+
+		record.extend_from_slice ( &( timestamp.as_secs () as u32 ).to_le_bytes () );
+		record.extend_from_slice ( &timestamp.subsec_micros ().to_le_bytes () );
+		record.extend_from_slice ( &( packet.len () as u32 ).to_le_bytes () );
+		record.extend_from_slice ( &( packet.len () as u32 ).to_le_bytes () );
+		record.extend_from_slice ( &packet );
+
+		return self.write_bytes ( &record );
+	}
+
+	fn write_bytes ( &mut self, bytes : &[ u8 ] ) -> Result< (), String >
+	{
+		self.writer.write_all ( bytes ).map_err ( | error | error.to_string () )?;
+		self.writer.flush ().map_err ( | error | error.to_string () )?;
+
+		return Ok( () );
+	}
+
+	///	Sent frames go from the client to the server, sequence and acknowledgment
+	/// numbers count the payload of each direction like a real connection.
+	fn create_tcp_packet ( &mut self, direction : FrameDirection, frame : &[ u8 ] ) -> Vec< u8 >
+	{
+		let source : SocketAddrV4;
+		let destination : SocketAddrV4;
+		let sequence : u32;
+		let acknowledgment : u32;
+
+		match direction
+		{
+			FrameDirection::Sent		=>
+			{
+				source = self.client_address;
+				destination = self.server_address;
+				sequence = self.client_sequence;
+				acknowledgment = self.server_sequence;
+				self.client_sequence = self.client_sequence.wrapping_add ( frame.len () as u32 );
+			}
+			FrameDirection::Received	=>
+			{
+				source = self.server_address;
+				destination = self.client_address;
+				sequence = self.server_sequence;
+				acknowledgment = self.client_sequence;
+				self.server_sequence = self.server_sequence.wrapping_add ( frame.len () as u32 );
+			}
+		}
+
+		self.identification = self.identification.wrapping_add ( 1 );
+
+		let total_length : usize = IPV4_HEADER_SIZE + TCP_HEADER_SIZE + frame.len ();
+		let mut reply : Vec< u8 > = Vec::with_capacity ( total_length );
+
+		reply.extend_from_slice ( &[ 0x45, 0x00 ] );
+		reply.extend_from_slice ( &( total_length as u16 ).to_be_bytes () );
+		reply.extend_from_slice ( &self.identification.to_be_bytes () );
+		reply.extend_from_slice ( &[ 0x40, 0x00, IPV4_TIME_TO_LIVE, IPV4_PROTOCOL_TCP, 0x00, 0x00 ] );
+		reply.extend_from_slice ( &source.ip ().octets () );
+		reply.extend_from_slice ( &destination.ip ().octets () );
+
+		let header_checksum : u16 = compute_internet_checksum ( &reply, 0 );
+		reply[ 10..12 ].copy_from_slice ( &header_checksum.to_be_bytes () );
+
+		reply.extend_from_slice ( &source.port ().to_be_bytes () );
+		reply.extend_from_slice ( &destination.port ().to_be_bytes () );
+		reply.extend_from_slice ( &sequence.to_be_bytes () );
+		reply.extend_from_slice ( &acknowledgment.to_be_bytes () );
+		reply.extend_from_slice ( &[ ( TCP_HEADER_SIZE as u8 / 4 ) << 4, TCP_FLAGS_PSH_ACK ] );
+		reply.extend_from_slice ( &TCP_WINDOW_SIZE.to_be_bytes () );
+		reply.extend_from_slice ( &[ 0x00, 0x00, 0x00, 0x00 ] );
+		reply.extend_from_slice ( frame );
+
+		//	The TCP checksum covers a pseudo header of the addresses, the protocol and the TCP length.
+		let mut pseudo_header : Vec< u8 > = vec![];
+
+		pseudo_header.extend_from_slice ( &reply[ 12..20 ] );
+		pseudo_header.extend_from_slice ( &[ 0x00, IPV4_PROTOCOL_TCP ] );
+		pseudo_header.extend_from_slice ( &( ( TCP_HEADER_SIZE + frame.len () ) as u16 ).to_be_bytes () );
+
+		let segment_checksum : u16 = compute_internet_checksum ( &reply[ IPV4_HEADER_SIZE.. ],
+																 sum_words ( &pseudo_header ) );
+		reply[ IPV4_HEADER_SIZE + 16..IPV4_HEADER_SIZE + 18 ].copy_from_slice ( &segment_checksum.to_be_bytes () );
+
+		return reply;
+	}
+}
+
+impl< W : Write > FrameObserver for PcapWriter< W >
+{
+	fn observe_frame ( &mut self, direction : FrameDirection, frame : &[ u8 ] )
+	{
+		if let Err( message ) = self.write_frame ( direction, frame, SystemTime::now () )
+		{
+			if self.error.is_none ()
+			{
+				self.error = Some( message );
+			}
+		}
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_compute_internet_checksum ()
+{
+	let header : Vec< u8 > = vec![ 0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11,
+								   0x00, 0x00, 0xC0, 0xA8, 0x00, 0x01, 0xC0, 0xA8, 0x00, 0xC7 ];
+
+	assert_eq! ( compute_internet_checksum ( &header, 0 ), 0xB861 );
+}
+
+///	Computes the one's complement checksum of IPv4 and TCP, starting with the sum of other words.
+fn compute_internet_checksum ( bytes : &[ u8 ], initial_sum : u32 ) -> u16
+{
+	let mut sum : u32 = initial_sum + sum_words ( bytes );
+
+	while sum > 0xFFFF
+	{
+		sum = ( sum & 0xFFFF ) + ( sum >> 16 );
+	}
+
+	return !( sum as u16 );
+}
+
+fn sum_words ( bytes : &[ u8 ] ) -> u32
+{
+	let mut reply : u32 = 0;
+
+	for chunk in bytes.chunks ( 2 )
+	{
+		let high : u32 = ( chunk[ 0 ] as u32 ) << 8;
+		let low : u32 = chunk.get ( 1 ).map ( | byte | *byte as u32 ).unwrap_or ( 0 );
+
+		reply += high | low;
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_pcap_writer ()
+{
+	let mut writer : PcapWriter< Vec< u8 > > = PcapWriter::new ( vec![], PcapLinkType::Tcp ).unwrap ();
+	let request : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x01 ];
+	let response : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34 ];
+
+	writer.observe_frame ( FrameDirection::Sent, &request );
+	writer.observe_frame ( FrameDirection::Received, &response );
+
+	let bytes : &Vec< u8 > = writer.get_writer ();
+
+	assert_eq! ( bytes[ 0..4 ], [ 0xD4, 0xC3, 0xB2, 0xA1 ] );
+	assert_eq! ( bytes[ 20..24 ], LINKTYPE_RAW.to_le_bytes () );
+
+	let packet : &[ u8 ] = &bytes[ 40..40 + 40 + request.len () ];
+
+	assert_eq! ( bytes[ 32..36 ], ( packet.len () as u32 ).to_le_bytes () );
+	assert_eq! ( compute_internet_checksum ( &packet[ 0..20 ], 0 ), 0 );
+	assert_eq! ( packet[ 12..20 ], [ 192, 0, 2, 1, 192, 0, 2, 2 ] );
+	assert_eq! ( packet[ 22..24 ], [ 0x01, 0xF6 ] );
+	assert_eq! ( packet[ 40.. ], request[ .. ] );
+
+	let response_packet : &[ u8 ] = &bytes[ 40 + 16 + packet.len ().. ];
+
+	assert_eq! ( response_packet[ 20..22 ], [ 0x01, 0xF6 ] );
+	assert_eq! ( response_packet[ 28..32 ], ( 1 + request.len () as u32 ).to_be_bytes () );
+	assert_eq! ( response_packet[ 40.. ], response[ .. ] );
+
+	let mut rtu_writer : PcapWriter< Vec< u8 > > = PcapWriter::new ( vec![], PcapLinkType::Rtu ).unwrap ();
+
+	rtu_writer.observe_frame ( FrameDirection::Sent, &[ 0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A ] );
+
+	assert_eq! ( rtu_writer.get_writer ()[ 20..24 ], LINKTYPE_USER0.to_le_bytes () );
+	assert_eq! ( rtu_writer.get_writer ()[ 40.. ], [ 0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A ] );
+	assert! ( rtu_writer.get_error ().is_none () );
+}
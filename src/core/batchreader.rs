@@ -0,0 +1,661 @@
+use std::cmp;
+use core::consts::*;
+use core::datatable::*;
+use core::ethernet::EthernetMaster;
+use core::modbusreturn::*;
+#[cfg( test )]
+use core::testmaster::TestMaster;
+#[cfg( feature = "serde" )]
+use std::convert::TryFrom;
+
+//	===============================================================================================
+
+pub const BATCH_DEFAULT_MAXIMUM_GAP : u16 = 10;
+
+const BATCH_ADDRESS_RANGE : u32 = 0x10000;
+
+//	===============================================================================================
+
+/// A single item of a register map which should be read by the `BatchReader`.
+/// The quantity is the number of coils or registers the tag occupies
+/// (e.g. 2 registers for a 32 bit value).
+#[derive( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ), serde( try_from = "BatchTagFields" ) )]
+pub struct BatchTag
+{
+	table : DataTable,
+	address : u16,
+	quantity : u16
+}
+
+#[test]
+fn test_batch_tag_new ()
+{
+	assert_eq! ( BatchTag::new ( DataTable::HoldingRegisters, 10, 0 ).unwrap ().get_quantity (), 1 );
+	assert_eq! ( BatchTag::new ( DataTable::HoldingRegisters, 65534, 2 ).unwrap ().get_address (), 65534 );
+	assert! ( BatchTag::new ( DataTable::HoldingRegisters, 65535, 2 ).is_none () );
+	assert! ( BatchTag::new ( DataTable::Coils, 65535, 0 ).is_some () );
+	assert! ( BatchTag::new_from_reference ( 465536, 2 ).is_none () );
+}
+
+impl BatchTag
+{
+	///	Creates a tag, `None` if it does not fit into the 65536 addresses of the table.
+	pub fn new ( table : DataTable, address : u16, quantity : u16 ) -> Option< BatchTag >
+	{
+		let mut reply : Option< BatchTag > = None;
+		let tag : BatchTag = BatchTag
+		{
+			table : table,
+			address : address,
+			quantity : cmp::max ( quantity,
+								  1 )
+		};
+
+		if tag.get_end_address () <= BATCH_ADDRESS_RANGE
+		{
+			reply = Some( tag );
+		}
+
+		return reply;
+	}
+
+	///	Creates a tag from a Modicon style reference number like 40001.
+	pub fn new_from_reference ( reference : u32, quantity : u16 ) -> Option< BatchTag >
+	{
+		let mut reply : Option< BatchTag > = None;
+
+		if let Some( ( table, address ) ) = parse_reference ( reference )
+		{
+			reply = BatchTag::new ( table,
+									address,
+									quantity );
+		}
+
+		return reply;
+	}
+
+	pub fn get_address ( &self ) -> u16
+	{
+		return self.address;
+	}
+
+	pub fn get_quantity ( &self ) -> u16
+	{
+		return self.quantity;
+	}
+
+	pub fn get_table ( &self ) -> DataTable
+	{
+		return self.table;
+	}
+
+	fn get_end_address ( &self ) -> u32
+	{
+		return self.address as u32 + self.quantity as u32;
+	}
+}
+
+///	Deserialized tags are created by `BatchTag::new`, which ensures a quantity of at least 1
+/// and rejects tags beyond the address range.
+#[cfg( feature = "serde" )]
+#[derive( Deserialize )]
+struct BatchTagFields
+{
+	table : DataTable,
+	address : u16,
+	quantity : u16
+}
+
+#[cfg( feature = "serde" )]
+impl TryFrom< BatchTagFields > for BatchTag
+{
+	type Error = String;
+
+	fn try_from ( fields : BatchTagFields ) -> Result< BatchTag, String >
+	{
+		return BatchTag::new ( fields.table,
+							   fields.address,
+							   fields.quantity ).ok_or ( "tag exceeds the address range".to_string () );
+	}
+}
+
+//	===============================================================================================
+
+/// Limits used to merge tags into requests. The maximum gap is the number
+/// of unused coils or registers which may be read to join two tags.
+#[derive( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ), serde( from = "BatchConfigurationFields" ) )]
+pub struct BatchConfiguration
+{
+	maximum_gap : u16,
+	maximum_pdu_size : u16
+}
+
+impl Default for BatchConfiguration
+{
+	fn default () -> BatchConfiguration
+	{
+		return Self::new ();
+	}
+}
+
+impl BatchConfiguration
+{
+	pub fn new () -> BatchConfiguration
+	{
+		return Self::new_with_limits ( BATCH_DEFAULT_MAXIMUM_GAP,
+									   MODBUS_MAXIMUM_PDU_SIZE );
+	}
+
+	pub fn new_with_limits ( maximum_gap : u16, maximum_pdu_size : u16 ) -> BatchConfiguration
+	{
+		return BatchConfiguration
+		{
+			maximum_gap : maximum_gap,
+			maximum_pdu_size : cmp::min ( maximum_pdu_size,
+										  MODBUS_MAXIMUM_PDU_SIZE )
+		};
+	}
+
+	pub fn get_maximum_gap ( &self ) -> u16
+	{
+		return self.maximum_gap;
+	}
+
+	pub fn get_maximum_pdu_size ( &self ) -> u16
+	{
+		return self.maximum_pdu_size;
+	}
+
+	///	Returns the number of coils or registers which fit into
+	/// a response PDU of the configured size.
+	pub fn get_maximum_quantity ( &self, table : DataTable ) -> u16
+	{
+		let data_bytes : u16 = self.maximum_pdu_size.saturating_sub ( 2 );	// -2 for FunctionCode and ByteCount
+		let quantity : u16;
+
+		if table.is_bit_table ()
+		{
+			quantity = data_bytes.saturating_mul ( 8 );
+		}
+		else
+		{
+			quantity = data_bytes / 2;
+		}
+
+		return cmp::max ( cmp::min ( quantity,
+									 table.get_maximum_read_quantity () ),
+						  1 );
+	}
+}
+
+///	Deserialized configurations are created by `BatchConfiguration::new_with_limits`,
+/// which limits the PDU size to the maximum of the protocol.
+#[cfg( feature = "serde" )]
+#[derive( Deserialize )]
+struct BatchConfigurationFields
+{
+	maximum_gap : u16,
+	maximum_pdu_size : u16
+}
+
+#[cfg( feature = "serde" )]
+impl From< BatchConfigurationFields > for BatchConfiguration
+{
+	fn from ( fields : BatchConfigurationFields ) -> BatchConfiguration
+	{
+		return BatchConfiguration::new_with_limits ( fields.maximum_gap,
+													 fields.maximum_pdu_size );
+	}
+}
+
+//	===============================================================================================
+
+/// One read request of a batch and the indices of the tags it serves.
+#[derive( Clone, Debug, PartialEq )]
+pub struct BatchRequest
+{
+	table : DataTable,
+	starting_address : u16,
+	quantity : u16,
+	tag_indices : Vec< usize >
+}
+
+impl BatchRequest
+{
+	pub fn get_quantity ( &self ) -> u16
+	{
+		return self.quantity;
+	}
+
+	pub fn get_starting_address ( &self ) -> u16
+	{
+		return self.starting_address;
+	}
+
+	pub fn get_table ( &self ) -> DataTable
+	{
+		return self.table;
+	}
+
+	pub fn get_tag_indices ( &self ) -> &Vec< usize >
+	{
+		return &self.tag_indices;
+	}
+}
+
+//	===============================================================================================
+
+/// The value read for a single tag of a batch.
+#[derive( Clone, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum BatchValue
+{
+	Bad( String ),
+	Coils( Vec< bool > ),
+	Registers( Vec< u16 > )
+}
+
+impl BatchValue
+{
+	pub fn is_bad ( &self ) -> bool
+	{
+		let reply : bool;
+
+		match *self
+		{
+			BatchValue::Bad( _ )	=> { reply = true; }
+			_						=> { reply = false; }
+		}
+
+		return reply;
+	}
+
+	pub fn is_good ( &self ) -> bool
+	{
+		return !self.is_bad ();
+	}
+}
+
+//	===============================================================================================
+
+/// Reads a scattered register map with a minimal number of requests.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::core::batchreader::{BatchConfiguration, BatchReader, BatchTag};
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let mut client = TcpClient::new("127.0.0.1");
+/// let mut reader = BatchReader::new(BatchConfiguration::new());
+///
+/// let temperature = reader.add_tag(BatchTag::new_from_reference(40001, 1).unwrap());
+/// let counter = reader.add_tag(BatchTag::new_from_reference(40003, 2).unwrap());
+///
+/// if client.connect().is_ok()
+/// {
+///     let values = reader.read(&mut client);
+///     println!("{:?} {:?}", values[temperature], values[counter]);
+///
+///     client.disconnect();
+/// }
+/// ```
+pub struct BatchReader
+{
+	configuration : BatchConfiguration,
+	requests : Vec< BatchRequest >,
+	tags : Vec< BatchTag >
+}
+
+impl BatchReader
+{
+	pub fn new ( configuration : BatchConfiguration ) -> BatchReader
+	{
+		return BatchReader
+		{
+			configuration : configuration,
+			requests : vec![],
+			tags : vec![]
+		};
+	}
+
+	///	Adds a tag and returns its index in the result of `read`.
+	pub fn add_tag ( &mut self, tag : BatchTag ) -> usize
+	{
+		self.tags.push ( tag );
+		self.requests = plan_batch_requests ( &self.tags,
+											  &self.configuration );
+
+		return self.tags.len () - 1;
+	}
+
+	pub fn get_requests ( &self ) -> &Vec< BatchRequest >
+	{
+		return &self.requests;
+	}
+
+	pub fn get_tags ( &self ) -> &Vec< BatchTag >
+	{
+		return &self.tags;
+	}
+
+	///	Issues all planned requests and returns one value per tag,
+	/// in the order the tags were added.
+	pub fn read< T : EthernetMaster > ( &self, master : &mut T ) -> Vec< BatchValue >
+	{
+		return execute_batch_requests ( master,
+										&self.tags,
+										&self.requests );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_plan_batch_requests ()
+{
+	let tags : Vec< BatchTag > = vec![ BatchTag::new ( DataTable::HoldingRegisters, 0, 1 ).unwrap (),
+									   BatchTag::new ( DataTable::HoldingRegisters, 2, 1 ).unwrap (),
+									   BatchTag::new ( DataTable::HoldingRegisters, 9, 11 ).unwrap (),
+									   BatchTag::new ( DataTable::HoldingRegisters, 100, 2 ).unwrap (),
+									   BatchTag::new ( DataTable::Coils, 5, 1 ).unwrap (),
+									   BatchTag::new ( DataTable::HoldingRegisters, 1, 1 ).unwrap () ];
+
+	let result_1 : Vec< BatchRequest > = plan_batch_requests ( &tags,
+															   &BatchConfiguration::new () );
+	assert_eq! ( result_1.len (), 3 );
+	assert_eq! ( result_1[ 0 ].get_table (), DataTable::Coils );
+	assert_eq! ( result_1[ 0 ].get_starting_address (), 5 );
+	assert_eq! ( result_1[ 0 ].get_quantity (), 1 );
+	assert_eq! ( result_1[ 1 ].get_table (), DataTable::HoldingRegisters );
+	assert_eq! ( result_1[ 1 ].get_starting_address (), 0 );
+	assert_eq! ( result_1[ 1 ].get_quantity (), 20 );
+	assert_eq! ( result_1[ 1 ].get_tag_indices (), &vec![ 0, 5, 1, 2 ] );
+	assert_eq! ( result_1[ 2 ].get_starting_address (), 100 );
+	assert_eq! ( result_1[ 2 ].get_quantity (), 2 );
+
+	let result_2 : Vec< BatchRequest > = plan_batch_requests ( &tags,
+															   &BatchConfiguration::new_with_limits ( 0, 253 ) );
+	assert_eq! ( result_2.len (), 4 );
+	assert_eq! ( result_2[ 1 ].get_quantity (), 3 );
+
+	let result_3 : Vec< BatchRequest > = plan_batch_requests ( &tags,
+															   &BatchConfiguration::new_with_limits ( 200, 253 ) );
+	assert_eq! ( result_3.len (), 2 );
+	assert_eq! ( result_3[ 1 ].get_quantity (), 102 );
+
+	let result_4 : Vec< BatchRequest > = plan_batch_requests ( &tags,
+															   &BatchConfiguration::new_with_limits ( 200, 42 ) );
+	assert_eq! ( result_4.len (), 3 );
+	assert_eq! ( result_4[ 1 ].get_quantity (), 20 );
+	assert_eq! ( result_4[ 2 ].get_starting_address (), 100 );
+
+	let oversized_tags : Vec< BatchTag > = vec![ BatchTag::new ( DataTable::InputRegisters, 0, 126 ).unwrap (),
+												 BatchTag::new ( DataTable::InputRegisters, 1, 1 ).unwrap (),
+												 BatchTag::new ( DataTable::Coils, 0, 2001 ).unwrap () ];
+
+	let result_5 : Vec< BatchRequest > = plan_batch_requests ( &oversized_tags,
+															   &BatchConfiguration::new () );
+	assert_eq! ( result_5.len (), 1 );
+	assert_eq! ( result_5[ 0 ].get_starting_address (), 1 );
+	assert_eq! ( result_5[ 0 ].get_quantity (), 1 );
+	assert_eq! ( result_5[ 0 ].get_tag_indices (), &vec![ 1 ] );
+}
+
+///	Merges the tags of each table into as few read requests as possible.
+/// Tags exceeding the maximum quantity of a request are not requested
+/// and read as `BatchValue::Bad`.
+pub fn plan_batch_requests ( tags : &[ BatchTag ], configuration : &BatchConfiguration ) -> Vec< BatchRequest >
+{
+	let mut reply : Vec< BatchRequest > = vec![];
+
+	let tables : [ DataTable; 4 ] = [ DataTable::Coils,
+									  DataTable::DiscreteInputs,
+									  DataTable::HoldingRegisters,
+									  DataTable::InputRegisters ];
+
+	for table in tables.iter ()
+	{
+		let maximum_quantity : u32 = configuration.get_maximum_quantity ( *table ) as u32;
+
+		let mut indices : Vec< usize > = ( 0..tags.len () ).filter ( | index | tags[ *index ].table == *table &&
+																	   tags[ *index ].quantity as u32 <= maximum_quantity )
+														   .collect ();
+		indices.sort_by_key ( | index | ( tags[ *index ].address, tags[ *index ].quantity ) );
+
+		let mut current : Option< BatchRequest > = None;
+		let mut current_end : u32 = 0;
+
+		for index in indices
+		{
+			let tag : &BatchTag = &tags[ index ];
+
+			if let Some( mut request ) = current.take ()
+			{
+				let gap : u32 = ( tag.address as u32 ).saturating_sub ( current_end );
+				let end : u32 = cmp::max ( current_end,
+										   tag.get_end_address () );
+
+				if gap <= configuration.maximum_gap as u32 &&
+				   end - request.starting_address as u32 <= maximum_quantity
+				{
+					request.tag_indices.push ( index );
+					request.quantity = ( end - request.starting_address as u32 ) as u16;
+					current_end = end;
+					current = Some( request );
+				}
+				else
+				{
+					reply.push ( request );
+				}
+			}
+
+			if current.is_none ()
+			{
+				current = Some( BatchRequest
+								{
+									table : *table,
+									starting_address : tag.address,
+									quantity : tag.quantity,
+									tag_indices : vec![ index ]
+								} );
+				current_end = tag.get_end_address ();
+			}
+		}
+
+		if let Some( request ) = current
+		{
+			reply.push ( request );
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+///	Plans, issues and scatters a batch read in one call.
+pub fn read_batch< T : EthernetMaster > ( master : &mut T, tags : &[ BatchTag ], configuration : &BatchConfiguration ) -> Vec< BatchValue >
+{
+	let requests : Vec< BatchRequest > = plan_batch_requests ( tags,
+															   configuration );
+
+	return execute_batch_requests ( master,
+									tags,
+									&requests );
+}
+
+fn execute_batch_requests< T : EthernetMaster > ( master : &mut T, tags : &[ BatchTag ], requests : &[ BatchRequest ] ) -> Vec< BatchValue >
+{
+	let mut reply : Vec< BatchValue > = vec![ BatchValue::Bad( "tag exceeds the maximum quantity of a request".to_string () ); tags.len () ];
+
+	for request in requests
+	{
+		let response : BatchValue = execute_batch_request ( master,
+															request );
+
+		for index in request.get_tag_indices ()
+		{
+			reply[ *index ] = scatter_batch_value ( &response,
+													request.starting_address,
+													&tags[ *index ] );
+		}
+	}
+
+	return reply;
+}
+
+fn execute_batch_request< T : EthernetMaster > ( master : &mut T, request : &BatchRequest ) -> BatchValue
+{
+	let reply : BatchValue;
+
+	if request.table.is_bit_table ()
+	{
+		let response : ModbusReturnCoils;
+
+		if request.table == DataTable::Coils
+		{
+			response = master.read_coils ( request.starting_address,
+										   request.quantity );
+		}
+		else
+		{
+			response = master.read_discrete_inputs ( request.starting_address,
+													 request.quantity );
+		}
+
+		match response
+		{
+			ModbusReturnCoils::Good( mut good )	=> { reply = BatchValue::Coils( good.get_data () ); }
+			ModbusReturnCoils::Bad( bad )		=> { reply = BatchValue::Bad( bad.get_message () ); }
+			ModbusReturnCoils::None				=> { reply = BatchValue::Bad( "no response".to_string () ); }
+		}
+	}
+	else
+	{
+		let response : ModbusReturnRegisters;
+
+		if request.table == DataTable::HoldingRegisters
+		{
+			response = master.read_holding_registers ( request.starting_address,
+													   request.quantity );
+		}
+		else
+		{
+			response = master.read_input_registers ( request.starting_address,
+													 request.quantity );
+		}
+
+		match response
+		{
+			ModbusReturnRegisters::Good( mut good )	=> { reply = BatchValue::Registers( good.get_data () ); }
+			ModbusReturnRegisters::Bad( bad )		=> { reply = BatchValue::Bad( bad.get_message () ); }
+			ModbusReturnRegisters::None				=> { reply = BatchValue::Bad( "no response".to_string () ); }
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_scatter_batch_value ()
+{
+	let tag : BatchTag = BatchTag::new ( DataTable::HoldingRegisters, 12, 2 ).unwrap ();
+
+	let result_1 : BatchValue = scatter_batch_value ( &BatchValue::Registers( vec![ 1, 2, 3, 4, 5 ] ),
+													  10,
+													  &tag );
+	assert_eq! ( result_1, BatchValue::Registers( vec![ 3, 4 ] ) );
+
+	let result_2 : BatchValue = scatter_batch_value ( &BatchValue::Registers( vec![ 1, 2, 3 ] ),
+													  10,
+													  &tag );
+	assert! ( result_2.is_bad () );
+
+	let result_3 : BatchValue = scatter_batch_value ( &BatchValue::Coils( vec![ true, false, true, true ] ),
+													  10,
+													  &tag );
+	assert_eq! ( result_3, BatchValue::Coils( vec![ true, true ] ) );
+
+	let result_4 : BatchValue = scatter_batch_value ( &BatchValue::Bad( "timeout".to_string () ),
+													  10,
+													  &tag );
+	assert_eq! ( result_4, BatchValue::Bad( "timeout".to_string () ) );
+}
+
+fn scatter_batch_value ( response : &BatchValue, starting_address : u16, tag : &BatchTag ) -> BatchValue
+{
+	let reply : BatchValue;
+
+	let begin : usize = ( tag.address - starting_address ) as usize;
+	let end : usize = begin + tag.quantity as usize;
+
+	match *response
+	{
+		BatchValue::Coils( ref values ) if values.len () >= end =>
+		{
+			reply = BatchValue::Coils( values[ begin..end ].to_vec () );
+		}
+		BatchValue::Registers( ref values ) if values.len () >= end =>
+		{
+			reply = BatchValue::Registers( values[ begin..end ].to_vec () );
+		}
+		BatchValue::Bad( ref message ) =>
+		{
+			reply = BatchValue::Bad( message.clone () );
+		}
+		_ =>
+		{
+			reply = BatchValue::Bad( "modbus response data is too short".to_string () );
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_batch_reader ()
+{
+	let mut master : TestMaster = TestMaster::new ( ( 0..200 ).collect () );
+
+	let mut reader : BatchReader = BatchReader::new ( BatchConfiguration::new () );
+	let index_1 : usize = reader.add_tag ( BatchTag::new_from_reference ( 40001, 1 ).unwrap () );
+	let index_2 : usize = reader.add_tag ( BatchTag::new_from_reference ( 40003, 1 ).unwrap () );
+	let index_3 : usize = reader.add_tag ( BatchTag::new_from_reference ( 40010, 11 ).unwrap () );
+	let index_4 : usize = reader.add_tag ( BatchTag::new_from_reference ( 40150, 2 ).unwrap () );
+	let index_5 : usize = reader.add_tag ( BatchTag::new_from_reference ( 1, 3 ).unwrap () );
+	let index_6 : usize = reader.add_tag ( BatchTag::new_from_reference ( 10001, 1 ).unwrap () );
+	let index_7 : usize = reader.add_tag ( BatchTag::new_from_reference ( 30001, 1 ).unwrap () );
+	assert_eq! ( reader.get_requests ().len (), 5 );
+
+	let result : Vec< BatchValue > = reader.read ( &mut master );
+	assert_eq! ( result.len (), 7 );
+	assert_eq! ( result[ index_1 ], BatchValue::Registers( vec![ 0 ] ) );
+	assert_eq! ( result[ index_2 ], BatchValue::Registers( vec![ 2 ] ) );
+	assert_eq! ( result[ index_3 ], BatchValue::Registers( ( 9..20 ).collect () ) );
+	assert_eq! ( result[ index_4 ], BatchValue::Registers( vec![ 149, 150 ] ) );
+	assert_eq! ( result[ index_5 ], BatchValue::Coils( vec![ true, true, true ] ) );
+	assert! ( result[ index_6 ].is_bad () );
+	assert! ( result[ index_7 ].is_bad () );
+
+	assert_eq! ( master.requests.len (), 4 );
+	assert_eq! ( master.requests[ 1 ], ( FUNCTION_CODE_READ_DISCRETE_INPUTS, 0, 1 ) );
+	assert_eq! ( master.requests[ 2 ], ( FUNCTION_CODE_READ_HOLDING_REGISTERS, 0, 20 ) );
+	assert_eq! ( master.requests[ 3 ], ( FUNCTION_CODE_READ_HOLDING_REGISTERS, 149, 2 ) );
+
+	let mut oversized_reader : BatchReader = BatchReader::new ( BatchConfiguration::new_with_limits ( 10, 42 ) );
+	let index_8 : usize = oversized_reader.add_tag ( BatchTag::new_from_reference ( 40001, 21 ).unwrap () );
+	let index_9 : usize = oversized_reader.add_tag ( BatchTag::new_from_reference ( 40030, 20 ).unwrap () );
+
+	let oversized_result : Vec< BatchValue > = oversized_reader.read ( &mut master );
+	assert_eq! ( oversized_result[ index_8 ], BatchValue::Bad( "tag exceeds the maximum quantity of a request".to_string () ) );
+	assert_eq! ( oversized_result[ index_9 ], BatchValue::Registers( ( 29..49 ).collect () ) );
+	assert_eq! ( master.requests.len (), 5 );
+}
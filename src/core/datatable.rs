@@ -0,0 +1,169 @@
+use std::fmt;
+use core::consts::*;
+
+//	===============================================================================================
+
+/// The four primary tables of the MODBUS data model.
+#[derive( Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum DataTable
+{
+	Coils,
+	DiscreteInputs,
+	HoldingRegisters,
+	InputRegisters
+}
+
+impl DataTable
+{
+	///	Parses table names like `coil`, `di`, `input_registers` or `4x` (case insensitive).
+	pub fn parse ( name : &str ) -> Result< DataTable, String >
+	{
+		let reply : Result< DataTable, String >;
+
+		match name.trim ().to_lowercase ().replace ( ' ', "_" ).as_str ()
+		{
+			"co" | "coil" | "coils" | "0x"								=> { reply = Ok( DataTable::Coils ); }
+			"di" | "discrete_input" | "discrete_inputs" | "1x"			=> { reply = Ok( DataTable::DiscreteInputs ); }
+			"hr" | "holding_register" | "holding_registers" | "4x"		=> { reply = Ok( DataTable::HoldingRegisters ); }
+			"ir" | "input_register" | "input_registers" | "3x"			=> { reply = Ok( DataTable::InputRegisters ); }
+			_															=> { reply = Err( format! ( "unknown table '{}'", name ) ); }
+		}
+
+		return reply;
+	}
+
+	///	Returns the function code which is used to read the table.
+	pub fn get_read_function_code ( &self ) -> u8
+	{
+		let reply : u8;
+
+		match *self
+		{
+			DataTable::Coils			=> { reply = FUNCTION_CODE_READ_COILS; }
+			DataTable::DiscreteInputs	=> { reply = FUNCTION_CODE_READ_DISCRETE_INPUTS; }
+			DataTable::HoldingRegisters	=> { reply = FUNCTION_CODE_READ_HOLDING_REGISTERS; }
+			DataTable::InputRegisters	=> { reply = FUNCTION_CODE_READ_INPUT_REGISTERS; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the maximum quantity of items a single read request
+	/// may ask for (2000 bits or 125 registers).
+	pub fn get_maximum_read_quantity ( &self ) -> u16
+	{
+		let reply : u16;
+
+		if self.is_bit_table ()
+		{
+			reply = MODBUS_MAXIMUM_QUANTITY_OF_READ_BITS;
+		}
+		else
+		{
+			reply = MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS;
+		}
+
+		return reply;
+	}
+
+	///	Returns true for coils and discrete inputs.
+	pub fn is_bit_table ( &self ) -> bool
+	{
+		return *self == DataTable::Coils || *self == DataTable::DiscreteInputs;
+	}
+
+	///	Returns true for the tables a master is allowed to write (coils and holding registers).
+	pub fn is_writable ( &self ) -> bool
+	{
+		return *self == DataTable::Coils || *self == DataTable::HoldingRegisters;
+	}
+}
+
+impl fmt::Display for DataTable
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		let name : &str;
+
+		match *self
+		{
+			DataTable::Coils			=> { name = "coils"; }
+			DataTable::DiscreteInputs	=> { name = "discrete_inputs"; }
+			DataTable::HoldingRegisters	=> { name = "holding_registers"; }
+			DataTable::InputRegisters	=> { name = "input_registers"; }
+		}
+
+		return write! ( f, "{}", name );
+	}
+}
+
+#[test]
+fn test_data_table_parse ()
+{
+	assert_eq! ( DataTable::parse ( "Coil" ), Ok( DataTable::Coils ) );
+	assert_eq! ( DataTable::parse ( "DI" ), Ok( DataTable::DiscreteInputs ) );
+	assert_eq! ( DataTable::parse ( "input registers" ), Ok( DataTable::InputRegisters ) );
+	assert_eq! ( DataTable::parse ( "4x" ), Ok( DataTable::HoldingRegisters ) );
+	assert! ( DataTable::parse ( "registers" ).is_err () );
+
+	for table in [ DataTable::Coils, DataTable::DiscreteInputs, DataTable::HoldingRegisters, DataTable::InputRegisters ].iter ()
+	{
+		assert_eq! ( DataTable::parse ( &table.to_string () ), Ok( *table ) );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_reference ()
+{
+	assert_eq! ( parse_reference ( 1 ), Some( ( DataTable::Coils, 0 ) ) );
+	assert_eq! ( parse_reference ( 10001 ), Some( ( DataTable::DiscreteInputs, 0 ) ) );
+	assert_eq! ( parse_reference ( 30010 ), Some( ( DataTable::InputRegisters, 9 ) ) );
+	assert_eq! ( parse_reference ( 40001 ), Some( ( DataTable::HoldingRegisters, 0 ) ) );
+	assert_eq! ( parse_reference ( 49999 ), Some( ( DataTable::HoldingRegisters, 9998 ) ) );
+	assert_eq! ( parse_reference ( 400001 ), Some( ( DataTable::HoldingRegisters, 0 ) ) );
+	assert_eq! ( parse_reference ( 465536 ), Some( ( DataTable::HoldingRegisters, 65535 ) ) );
+	assert_eq! ( parse_reference ( 0 ), None );
+	assert_eq! ( parse_reference ( 40000 ), None );
+	assert_eq! ( parse_reference ( 20001 ), None );
+	assert_eq! ( parse_reference ( 465537 ), None );
+}
+
+///	Parses a Modicon style reference number (e.g. 40001 or 400001)
+/// into the data table and the zero based protocol address.
+pub fn parse_reference ( reference : u32 ) -> Option< ( DataTable, u16 ) >
+{
+	let mut reply : Option< ( DataTable, u16 ) > = None;
+
+	let divisor : u32;
+
+	if reference > 99999
+	{
+		divisor = 100000;
+	}
+	else
+	{
+		divisor = 10000;
+	}
+
+	let table : Option< DataTable > =
+		match reference / divisor
+		{
+			0 => Some( DataTable::Coils ),
+			1 => Some( DataTable::DiscreteInputs ),
+			3 => Some( DataTable::InputRegisters ),
+			4 => Some( DataTable::HoldingRegisters ),
+			_ => None
+		};
+
+	let offset : u32 = reference % divisor;
+
+	if offset > 0 && offset <= 0x10000
+	{
+		reply = table.map ( | table | ( table, ( offset - 1 ) as u16 ) );
+	}
+
+	return reply;
+}
@@ -93,11 +93,11 @@ fn test_extract_byte_from_bytearray ()
 	assert! ( result_4.is_none () );
 }
 
-pub fn extract_byte_from_bytearray ( source_array : &Vec< u8 >, start_index : u8 ) -> Option< u8 >
+pub fn extract_byte_from_bytearray ( source_array : &[ u8 ], start_index : u8 ) -> Option< u8 >
 {
 	let reply : Option< u8 >;
 
-	if let Some( result ) = extract_bytes_from_bytearray ( &source_array, 
+	if let Some( result ) = extract_bytes_from_bytearray ( source_array, 
 														   start_index, 
 														   1 )
 	{
@@ -153,25 +153,28 @@ fn test_extract_bytes_from_bytearray ()
 																		4, 
 																		1 );
 	assert! ( result_6.is_none () );
+
+	let long_array : Vec< u8 > = vec![ 0xAA; 259 ];
+	let result_7 : Option< Vec< u8 > > = extract_bytes_from_bytearray ( &long_array, 
+																		8, 
+																		251 );
+	assert_eq! ( result_7.unwrap ().len (), 251 );
 }
 
-pub fn extract_bytes_from_bytearray ( source_array : &Vec< u8 >, start_index : u8, byte_count : u8 ) -> Option< Vec< u8 > >
+pub fn extract_bytes_from_bytearray ( source_array : &[ u8 ], start_index : u8, byte_count : u8 ) -> Option< Vec< u8 > >
 {
 	let reply : Option< Vec< u8 > >;
 	
-	let source_length : u8 = source_array.len () as u8;
-	let verify_length : u8 = start_index + byte_count;
+	let source_length : usize = source_array.len ();
+	let verify_length : usize = start_index as usize + byte_count as usize;
 
 	if verify_length <= source_length
 	{
 		let mut copy_array : Vec< u8 > = vec![];
 
-		for index in start_index..verify_length
-		{
-			copy_array.push ( source_array [ index as usize ] );
-		}
+		copy_array.extend_from_slice ( &source_array[ start_index as usize..verify_length ] );
 
-		if copy_array.len () > 0
+		if !copy_array.is_empty ()
 		{
 			reply = Some( copy_array.clone () );
 		}
@@ -205,11 +208,11 @@ fn test_extract_word_from_bytearray ()
 	assert! ( result_2.is_none () );
 }
 
-pub fn extract_word_from_bytearray ( source_array : &Vec< u8 >, start_index : u8 ) -> Option< u16 >
+pub fn extract_word_from_bytearray ( source_array : &[ u8 ], start_index : u8 ) -> Option< u16 >
 {
 	let reply : Option< u16 >;
 
-	if let Some( result ) = extract_bytes_from_bytearray ( &source_array, 
+	if let Some( result ) = extract_bytes_from_bytearray ( source_array, 
 														   start_index, 
 														   2 )
 	{
@@ -266,12 +269,12 @@ fn test_transform_bytes_to_word ()
 	assert_eq! ( result, value );
 }
 
-pub fn transform_bytes_to_word ( bytes : &Vec< u8 >, index : u8 ) -> u16
+pub fn transform_bytes_to_word ( bytes : &[ u8 ], index : u8 ) -> u16
 {
 	let mut reply : u16;
 
 	reply = ( bytes[ index as usize ] as u16 ) << 8;
-    reply = reply | ( bytes[ index as usize + 1 ] as u16 ); 
+    reply |= bytes[ index as usize + 1 ] as u16;
 
 	return reply;
 }
@@ -309,12 +312,12 @@ fn test_transform_bytes_to_words ()
 	assert_eq! ( result_4.len (), 0 );
 }
 
-pub fn transform_bytes_to_words ( databytes : &Vec< u8 >, start_index : u8, word_count :u8 ) -> Vec< u16 >
+pub fn transform_bytes_to_words ( databytes : &[ u8 ], start_index : u8, word_count :u8 ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	let source_length : u8 = databytes.len () as u8;
-	let verify_length : u8 = start_index + ( word_count * 2 );
+	let source_length : usize = databytes.len ();
+	let verify_length : usize = start_index as usize + ( word_count as usize * 2 );
 
 	if verify_length <= source_length
 	{
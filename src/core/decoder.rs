@@ -0,0 +1,766 @@
+use std::fmt;
+use capture::hexlog::format_hex;
+use core::consts::*;
+use core::modbusreturn::get_message_of_exception_code;
+use core::pdu::*;
+use rtu::crc::calculate_crc;
+
+//	===============================================================================================
+
+const ASCII_START_CHARACTER : u8 = b':';
+const MBAP_LENGTH_OFFSET : usize = 6;
+
+//	===============================================================================================
+
+/// The framing of a MODBUS message.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum FrameFormat
+{
+	///	MBAP header and PDU.
+	Tcp,
+	///	Slave address, PDU and CRC.
+	Rtu,
+	///	Colon, slave address, PDU and LRC as hexadecimal characters, CR and LF.
+	Ascii
+}
+
+impl fmt::Display for FrameFormat
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		match *self
+		{
+			FrameFormat::Tcp	=> { return write! ( f, "MODBUS TCP" ); }
+			FrameFormat::Rtu	=> { return write! ( f, "MODBUS RTU" ); }
+			FrameFormat::Ascii	=> { return write! ( f, "MODBUS ASCII" ); }
+		}
+	}
+}
+
+/// Whether a message is a request of a client or a response of a server.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum MessageKind
+{
+	Request,
+	Response
+}
+
+impl fmt::Display for MessageKind
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		match *self
+		{
+			MessageKind::Request	=> { return write! ( f, "request" ); }
+			MessageKind::Response	=> { return write! ( f, "response" ); }
+		}
+	}
+}
+
+//	===============================================================================================
+
+/// A named part of a decoded frame with its explanation.
+#[derive( Clone, Debug, Eq, PartialEq )]
+pub struct DecodedField
+{
+	name : String,
+	value : String
+}
+
+impl DecodedField
+{
+	pub fn get_name ( &self ) -> &str
+	{
+		return &self.name;
+	}
+
+	pub fn get_value ( &self ) -> &str
+	{
+		return &self.value;
+	}
+}
+
+//	===============================================================================================
+
+/// The explanation of a frame, field by field in the order of the frame.
+#[derive( Clone, Debug, Eq, PartialEq )]
+pub struct DecodedFrame
+{
+	format : FrameFormat,
+	kind : MessageKind,
+	fields : Vec< DecodedField >,
+	valid : bool
+}
+
+impl DecodedFrame
+{
+	pub fn get_format ( &self ) -> FrameFormat
+	{
+		return self.format;
+	}
+
+	pub fn get_kind ( &self ) -> MessageKind
+	{
+		return self.kind;
+	}
+
+	pub fn get_fields ( &self ) -> &Vec< DecodedField >
+	{
+		return &self.fields;
+	}
+
+	///	Returns the value of the first field with the name.
+	pub fn get_field ( &self, name : &str ) -> Option< &str >
+	{
+		return self.fields.iter ().find ( | field | field.name == name ).map ( | field | field.value.as_str () );
+	}
+
+	///	Returns false if the length, the checksum or the PDU is invalid.
+	pub fn is_valid ( &self ) -> bool
+	{
+		return self.valid;
+	}
+
+	fn add_field ( &mut self, name : &str, value : String )
+	{
+		self.fields.push ( DecodedField { name : name.to_string (), value : value } );
+	}
+
+	fn add_error ( &mut self, message : String )
+	{
+		self.add_field ( "error", message );
+		self.valid = false;
+	}
+}
+
+impl fmt::Display for DecodedFrame
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		let width : usize = self.fields.iter ().map ( | field | field.name.len () ).max ().unwrap_or ( 0 );
+
+		writeln! ( f, "{} {}{}", self.format, self.kind, if self.valid { "" } else { " (invalid)" } )?;
+
+		for field in &self.fields
+		{
+			writeln! ( f, "  {:width$}  {}", field.name, field.value, width = width )?;
+		}
+
+		return Ok( () );
+	}
+}
+
+//	===============================================================================================
+
+/// Explains raw MODBUS TCP, RTU and ASCII frames, e.g. from hex dumps of logs.
+/// Without a format or message kind set, both are detected from the bytes.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::core::decoder::*;
+///
+/// let bytes = parse_hex_bytes("00 01 00 00 00 06 11 03 00 6B 00 03").unwrap();
+/// let frame = FrameDecoder::new().decode(&bytes).unwrap();
+///
+/// assert_eq!(frame.get_field("starting address"), Some("107 (0x006B)"));
+/// print!("{}", frame);
+/// ```
+#[derive( Clone, Debug, Default )]
+pub struct FrameDecoder
+{
+	format : Option< FrameFormat >,
+	kind : Option< MessageKind >
+}
+
+impl FrameDecoder
+{
+	pub fn new () -> FrameDecoder
+	{
+		return FrameDecoder
+		{
+			format : None,
+			kind : None
+		};
+	}
+
+	pub fn set_format ( &mut self, format : Option< FrameFormat > )
+	{
+		self.format = format;
+	}
+
+	///	Sets the kind of messages, which is needed for the few requests and
+	/// responses with the same layout, like reads of 17 to 24 coils.
+	pub fn set_kind ( &mut self, kind : Option< MessageKind > )
+	{
+		self.kind = kind;
+	}
+
+	///	Decodes the frame. Errors of the content are fields of the decoded frame,
+	/// only frames too short for their header and checksum are refused.
+	pub fn decode ( &self, bytes : &[ u8 ] ) -> Result< DecodedFrame, String >
+	{
+		let format : FrameFormat = self.format.unwrap_or_else ( || detect_frame_format ( bytes ) );
+		let mut reply : DecodedFrame = DecodedFrame
+		{
+			format : format,
+			kind : MessageKind::Request,
+			fields : vec![],
+			valid : true
+		};
+
+		match format
+		{
+			FrameFormat::Tcp	=>
+			{
+				if bytes.len () <= MODBUS_HEADER_SIZE as usize
+				{
+					return Err( format! ( "{} bytes are too short for a MODBUS TCP frame", bytes.len () ) );
+				}
+
+				let protocol_identifier : u16 = read_word ( bytes, 2 );
+				let length : usize = read_word ( bytes, 4 ) as usize;
+
+				reply.add_field ( "transaction identifier", format_word ( read_word ( bytes, 0 ) ) );
+
+				if protocol_identifier == MODBUS_PROTOCOL_IDENTIFIER_TCP
+				{
+					reply.add_field ( "protocol identifier", "0 (MODBUS)".to_string () );
+				}
+				else
+				{
+					reply.add_field ( "protocol identifier", format! ( "{} (not MODBUS)", protocol_identifier ) );
+					reply.valid = false;
+				}
+
+				if length == bytes.len () - MBAP_LENGTH_OFFSET
+				{
+					reply.add_field ( "length", length.to_string () );
+				}
+				else
+				{
+					reply.add_field ( "length", format! ( "{} (invalid, {} bytes follow)", length, bytes.len () - MBAP_LENGTH_OFFSET ) );
+					reply.valid = false;
+				}
+
+				reply.add_field ( "unit identifier", bytes[ 6 ].to_string () );
+
+				self.decode_pdu ( &mut reply, &bytes[ MODBUS_HEADER_SIZE as usize.. ] );
+			}
+			FrameFormat::Rtu	=>
+			{
+				if bytes.len () < 2 + MODBUS_RTU_CRC_LENGTH
+				{
+					return Err( format! ( "{} bytes are too short for a MODBUS RTU frame", bytes.len () ) );
+				}
+
+				let end : usize = bytes.len () - MODBUS_RTU_CRC_LENGTH;
+				let crc : u16 = bytes[ end ] as u16 | ( ( bytes[ end + 1 ] as u16 ) << 8 );
+
+				reply.add_field ( "slave address", format_slave_address ( bytes[ 0 ] ) );
+
+				self.decode_pdu ( &mut reply, &bytes[ 1..end ] );
+
+				add_checksum_field ( &mut reply, "crc", crc as u32, calculate_crc ( &bytes[ 0..end ] ) as u32, 4 );
+			}
+			FrameFormat::Ascii	=>
+			{
+				let data : Vec< u8 > = decode_ascii_frame ( bytes )?;
+
+				if data.len () < 3
+				{
+					return Err( format! ( "{} bytes are too short for a MODBUS ASCII frame", data.len () ) );
+				}
+
+				let end : usize = data.len () - 1;
+
+				reply.add_field ( "slave address", format_slave_address ( data[ 0 ] ) );
+
+				self.decode_pdu ( &mut reply, &data[ 1..end ] );
+
+				add_checksum_field ( &mut reply, "lrc", data[ end ] as u32, calculate_lrc ( &data[ 0..end ] ) as u32, 2 );
+			}
+		}
+
+		return Ok( reply );
+	}
+
+	fn decode_pdu ( &self, frame : &mut DecodedFrame, pdu : &[ u8 ] )
+	{
+		let function_code : u8 = pdu[ 0 ];
+
+		if function_code & MODBUS_EXCEPTION_FLAG != 0
+		{
+			frame.kind = MessageKind::Response;
+		}
+		else if let Some( kind ) = self.kind
+		{
+			frame.kind = kind;
+		}
+		else if RequestPdu::decode ( pdu ).is_err () && ResponsePdu::decode ( pdu ).is_ok ()
+		{
+			frame.kind = MessageKind::Response;
+		}
+
+		frame.add_field ( "function code", format! ( "0x{:02X} {}", function_code & !MODBUS_EXCEPTION_FLAG, get_function_name ( function_code & !MODBUS_EXCEPTION_FLAG ).unwrap_or ( "Unknown Function" ) ) );
+
+		match frame.kind
+		{
+			MessageKind::Request	=>
+			{
+				match RequestPdu::decode ( pdu )
+				{
+					Ok( request )	=> { add_request_fields ( frame, &request ); }
+					Err( error )	=> { frame.add_error ( error.to_string () ); }
+				}
+			}
+			MessageKind::Response	=>
+			{
+				match ResponsePdu::decode ( pdu )
+				{
+					Ok( response )	=> { add_response_fields ( frame, &response ); }
+					Err( error )	=> { frame.add_error ( error.to_string () ); }
+				}
+			}
+		}
+	}
+}
+
+#[cfg( test )]
+use rtu::crc::append_crc;
+
+#[test]
+fn test_frame_decoder_tcp ()
+{
+	let decoder : FrameDecoder = FrameDecoder::new ();
+	let request : DecodedFrame = decoder.decode ( &[ 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x11, 0x03, 0x00, 0x6B, 0x00, 0x03 ] ).unwrap ();
+
+	assert_eq! ( ( request.get_format (), request.get_kind (), request.is_valid () ), ( FrameFormat::Tcp, MessageKind::Request, true ) );
+	assert_eq! ( request.get_field ( "unit identifier" ), Some( "17" ) );
+	assert_eq! ( request.get_field ( "function code" ), Some( "0x03 Read Holding Registers" ) );
+	assert_eq! ( request.get_field ( "starting address" ), Some( "107 (0x006B)" ) );
+	assert_eq! ( request.get_field ( "quantity" ), Some( "3" ) );
+
+	let response : DecodedFrame = decoder.decode ( &[ 0x00, 0x01, 0x00, 0x00, 0x00, 0x07, 0x11, 0x03, 0x04, 0x02, 0x2B, 0xFF, 0xFF ] ).unwrap ();
+
+	assert_eq! ( response.get_kind (), MessageKind::Response );
+	assert_eq! ( response.get_field ( "byte count" ), Some( "4" ) );
+	assert_eq! ( response.get_field ( "value 1" ), Some( "555 (0x022B)" ) );
+	assert_eq! ( response.get_field ( "value 2" ), Some( "65535 (0xFFFF)" ) );
+
+	let exception : DecodedFrame = decoder.decode ( &[ 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02 ] ).unwrap ();
+
+	assert_eq! ( ( exception.get_kind (), exception.is_valid () ), ( MessageKind::Response, true ) );
+	assert_eq! ( exception.get_field ( "function code" ), Some( "0x03 Read Holding Registers" ) );
+	assert_eq! ( exception.get_field ( "exception code" ), Some( "Exception Code 02 - ILLEGAL DATA ADDRESS" ) );
+
+	let mut tcp_decoder : FrameDecoder = FrameDecoder::new ();
+
+	tcp_decoder.set_format ( Some( FrameFormat::Tcp ) );
+
+	let invalid_length : DecodedFrame = tcp_decoder.decode ( &[ 0x00, 0x02, 0x00, 0x00, 0x00, 0x04, 0x01, 0x83, 0x02 ] ).unwrap ();
+
+	assert! ( !invalid_length.is_valid () );
+	assert_eq! ( invalid_length.get_field ( "length" ), Some( "4 (invalid, 3 bytes follow)" ) );
+	assert! ( tcp_decoder.decode ( &[ 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01 ] ).is_err () );
+}
+
+#[test]
+fn test_frame_decoder_rtu_and_ascii ()
+{
+	let mut decoder : FrameDecoder = FrameDecoder::new ();
+	let mut request : Vec< u8 > = vec![ 0x11, 0x0F, 0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01 ];
+
+	append_crc ( &mut request );
+
+	let frame : DecodedFrame = decoder.decode ( &request ).unwrap ();
+
+	assert_eq! ( ( frame.get_format (), frame.get_kind (), frame.is_valid () ), ( FrameFormat::Rtu, MessageKind::Request, true ) );
+	assert_eq! ( frame.get_field ( "values" ), Some( "1 0 1 1 0 0 1 1 1 0" ) );
+	assert_eq! ( frame.get_field ( "crc" ), Some( "0x0BBF (valid)" ) );
+
+	let frame : DecodedFrame = decoder.decode ( &[ 0x01, 0x83, 0x02, 0xC0, 0xF2 ] ).unwrap ();
+
+	assert! ( !frame.is_valid () );
+	assert_eq! ( frame.get_field ( "crc" ), Some( "0xF2C0 (invalid, expected 0xF1C0)" ) );
+
+	let frame : DecodedFrame = decoder.decode ( b":1103006B00037E\r\n" ).unwrap ();
+
+	assert_eq! ( ( frame.get_format (), frame.is_valid () ), ( FrameFormat::Ascii, true ) );
+	assert_eq! ( frame.get_field ( "slave address" ), Some( "17" ) );
+	assert_eq! ( frame.get_field ( "lrc" ), Some( "0x7E (valid)" ) );
+
+	decoder.set_kind ( Some( MessageKind::Response ) );
+
+	let frame : DecodedFrame = decoder.decode ( b":1103006B00037E" ).unwrap ();
+
+	assert! ( !frame.is_valid () );
+	assert! ( frame.get_field ( "error" ).is_some () );
+	assert! ( decoder.decode ( b":11G3" ).is_err () );
+}
+
+//	===============================================================================================
+
+fn add_request_fields ( frame : &mut DecodedFrame, request : &RequestPdu )
+{
+	match *request
+	{
+		RequestPdu::ReadCoils { address, quantity } |
+		RequestPdu::ReadDiscreteInputs { address, quantity } |
+		RequestPdu::ReadHoldingRegisters { address, quantity } |
+		RequestPdu::ReadInputRegisters { address, quantity }		=>
+		{
+			frame.add_field ( "starting address", format_word ( address ) );
+			frame.add_field ( "quantity", quantity.to_string () );
+		}
+		RequestPdu::WriteSingleCoil { address, value }				=>
+		{
+			frame.add_field ( "output address", format_word ( address ) );
+			frame.add_field ( "value", format_coil ( value ) );
+		}
+		RequestPdu::WriteSingleRegister { address, value }			=>
+		{
+			frame.add_field ( "register address", format_word ( address ) );
+			frame.add_field ( "value", format_word ( value ) );
+		}
+		RequestPdu::ReadExceptionStatus |
+		RequestPdu::GetCommEventCounter |
+		RequestPdu::GetCommEventLog |
+		RequestPdu::ReportServerId									=> {}
+		RequestPdu::Diagnostics { sub_function, data }				=>
+		{
+			frame.add_field ( "sub-function", format_word ( sub_function ) );
+			frame.add_field ( "data", format_hex ( data ) );
+		}
+		RequestPdu::WriteMultipleCoils { address, ref values }		=>
+		{
+			frame.add_field ( "starting address", format_word ( address ) );
+			frame.add_field ( "quantity", values.len ().to_string () );
+			frame.add_field ( "values", format_coils ( values ) );
+		}
+		RequestPdu::WriteMultipleRegisters { address, ref values }	=>
+		{
+			frame.add_field ( "starting address", format_word ( address ) );
+			frame.add_field ( "quantity", values.len ().to_string () );
+			add_register_fields ( frame, values );
+		}
+		RequestPdu::ReadFileRecord { sub_requests } |
+		RequestPdu::WriteFileRecord { sub_requests }				=>
+		{
+			frame.add_field ( "byte count", sub_requests.len ().to_string () );
+			frame.add_field ( "sub-requests", format_hex ( sub_requests ) );
+		}
+		RequestPdu::MaskWriteRegister { address, and_mask, or_mask }	=>
+		{
+			frame.add_field ( "register address", format_word ( address ) );
+			frame.add_field ( "and mask", format! ( "0x{:04X}", and_mask ) );
+			frame.add_field ( "or mask", format! ( "0x{:04X}", or_mask ) );
+		}
+		RequestPdu::ReadWriteMultipleRegisters { read_address, read_quantity, write_address, ref values }	=>
+		{
+			frame.add_field ( "read starting address", format_word ( read_address ) );
+			frame.add_field ( "quantity to read", read_quantity.to_string () );
+			frame.add_field ( "write starting address", format_word ( write_address ) );
+			frame.add_field ( "quantity to write", values.len ().to_string () );
+			add_register_fields ( frame, values );
+		}
+		RequestPdu::ReadFifoQueue { address }						=> { frame.add_field ( "fifo pointer address", format_word ( address ) ); }
+		RequestPdu::ReadDeviceIdentification { read_device_id_code, object_id }	=>
+		{
+			frame.add_field ( "mei type", format! ( "0x{:02X} Read Device Identification", MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION ) );
+			frame.add_field ( "read device id code", read_device_id_code.to_string () );
+			frame.add_field ( "object id", format! ( "0x{:02X}", object_id ) );
+		}
+		RequestPdu::Custom { data, .. }								=> { frame.add_field ( "data", format_hex ( data ) ); }
+	}
+}
+
+fn add_response_fields ( frame : &mut DecodedFrame, response : &ResponsePdu )
+{
+	match *response
+	{
+		ResponsePdu::ReadCoils { ref values } |
+		ResponsePdu::ReadDiscreteInputs { ref values }					=>
+		{
+			frame.add_field ( "byte count", values.len ().div_ceil ( 8 ).to_string () );
+			frame.add_field ( "values", format_coils ( values ) );
+		}
+		ResponsePdu::ReadHoldingRegisters { ref values } |
+		ResponsePdu::ReadInputRegisters { ref values } |
+		ResponsePdu::ReadWriteMultipleRegisters { ref values }			=>
+		{
+			frame.add_field ( "byte count", ( values.len () * 2 ).to_string () );
+			add_register_fields ( frame, values );
+		}
+		ResponsePdu::WriteSingleCoil { address, value }					=>
+		{
+			frame.add_field ( "output address", format_word ( address ) );
+			frame.add_field ( "value", format_coil ( value ) );
+		}
+		ResponsePdu::WriteSingleRegister { address, value }				=>
+		{
+			frame.add_field ( "register address", format_word ( address ) );
+			frame.add_field ( "value", format_word ( value ) );
+		}
+		ResponsePdu::ReadExceptionStatus { status }						=> { frame.add_field ( "status", format! ( "0x{:02X}", status ) ); }
+		ResponsePdu::Diagnostics { sub_function, data }					=>
+		{
+			frame.add_field ( "sub-function", format_word ( sub_function ) );
+			frame.add_field ( "data", format_hex ( data ) );
+		}
+		ResponsePdu::GetCommEventCounter { status, event_count }		=>
+		{
+			frame.add_field ( "status", format! ( "0x{:04X}", status ) );
+			frame.add_field ( "event count", event_count.to_string () );
+		}
+		ResponsePdu::GetCommEventLog { status, event_count, message_count, events }	=>
+		{
+			frame.add_field ( "status", format! ( "0x{:04X}", status ) );
+			frame.add_field ( "event count", event_count.to_string () );
+			frame.add_field ( "message count", message_count.to_string () );
+			frame.add_field ( "events", format_hex ( events ) );
+		}
+		ResponsePdu::WriteMultipleCoils { address, quantity } |
+		ResponsePdu::WriteMultipleRegisters { address, quantity }		=>
+		{
+			frame.add_field ( "starting address", format_word ( address ) );
+			frame.add_field ( "quantity", quantity.to_string () );
+		}
+		ResponsePdu::ReportServerId { data : bytes } |
+		ResponsePdu::ReadFileRecord { sub_responses : bytes } |
+		ResponsePdu::WriteFileRecord { sub_requests : bytes }			=>
+		{
+			frame.add_field ( "byte count", bytes.len ().to_string () );
+			frame.add_field ( "data", format_hex ( bytes ) );
+		}
+		ResponsePdu::MaskWriteRegister { address, and_mask, or_mask }	=>
+		{
+			frame.add_field ( "register address", format_word ( address ) );
+			frame.add_field ( "and mask", format! ( "0x{:04X}", and_mask ) );
+			frame.add_field ( "or mask", format! ( "0x{:04X}", or_mask ) );
+		}
+		ResponsePdu::ReadFifoQueue { ref values }						=>
+		{
+			frame.add_field ( "fifo count", values.len ().to_string () );
+			add_register_fields ( frame, values );
+		}
+		ResponsePdu::ReadDeviceIdentification { read_device_id_code, conformity_level, more_follows, next_object_id, ref objects }	=>
+		{
+			frame.add_field ( "mei type", format! ( "0x{:02X} Read Device Identification", MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION ) );
+			frame.add_field ( "read device id code", read_device_id_code.to_string () );
+			frame.add_field ( "conformity level", format! ( "0x{:02X}", conformity_level ) );
+			frame.add_field ( "more follows", more_follows.to_string () );
+			frame.add_field ( "next object id", format! ( "0x{:02X}", next_object_id ) );
+			frame.add_field ( "number of objects", objects.len ().to_string () );
+
+			for ( object_id, value ) in objects.iter ()
+			{
+				frame.add_field ( &format! ( "object 0x{:02X}", object_id ), String::from_utf8_lossy ( value ).into_owned () );
+			}
+		}
+		ResponsePdu::Exception { exception_code, .. }					=>
+		{
+			frame.add_field ( "exception code", get_message_of_exception_code ( exception_code ).unwrap_or_default () );
+		}
+		ResponsePdu::Custom { data, .. }								=> { frame.add_field ( "data", format_hex ( data ) ); }
+	}
+}
+
+fn add_register_fields ( frame : &mut DecodedFrame, values : &Registers )
+{
+	for ( index, value ) in values.iter ().enumerate ()
+	{
+		frame.add_field ( &format! ( "value {}", index + 1 ), format_word ( value ) );
+	}
+}
+
+fn add_checksum_field ( frame : &mut DecodedFrame, name : &str, received : u32, expected : u32, digits : usize )
+{
+	if received == expected
+	{
+		frame.add_field ( name, format! ( "0x{:0digits$X} (valid)", received, digits = digits ) );
+	}
+	else
+	{
+		frame.add_field ( name, format! ( "0x{:0digits$X} (invalid, expected 0x{:0digits$X})", received, expected, digits = digits ) );
+		frame.valid = false;
+	}
+}
+
+//	===============================================================================================
+
+fn read_word ( bytes : &[ u8 ], offset : usize ) -> u16
+{
+	return ( ( bytes[ offset ] as u16 ) << 8 ) | bytes[ offset + 1 ] as u16;
+}
+
+fn format_word ( value : u16 ) -> String
+{
+	return format! ( "{} (0x{:04X})", value, value );
+}
+
+fn format_slave_address ( address : u8 ) -> String
+{
+	if address == 0
+	{
+		return "0 (broadcast)".to_string ();
+	}
+
+	return address.to_string ();
+}
+
+fn format_coil ( value : bool ) -> String
+{
+	return if value { "ON".to_string () } else { "OFF".to_string () };
+}
+
+fn format_coils ( values : &Coils ) -> String
+{
+	return values.iter ().map ( | value | if value { "1" } else { "0" } ).collect::< Vec< &str > > ().join ( " " );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_detect_frame_format ()
+{
+	assert_eq! ( detect_frame_format ( &[ 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x11, 0x03, 0x00, 0x6B, 0x00, 0x03 ] ), FrameFormat::Tcp );
+	assert_eq! ( detect_frame_format ( &[ 0x11, 0x03, 0x00, 0x6B, 0x00, 0x03, 0x76, 0x87 ] ), FrameFormat::Rtu );
+	assert_eq! ( detect_frame_format ( b":1103006B00037E\r\n" ), FrameFormat::Ascii );
+}
+
+///	Detects the framing: ASCII frames start with a colon, TCP frames
+/// have the protocol identifier 0 and the length of the following bytes.
+pub fn detect_frame_format ( bytes : &[ u8 ] ) -> FrameFormat
+{
+	let reply : FrameFormat;
+
+	if bytes.first () == Some( &ASCII_START_CHARACTER )
+	{
+		reply = FrameFormat::Ascii;
+	}
+	else if bytes.len () > MODBUS_HEADER_SIZE as usize &&
+			read_word ( bytes, 2 ) == MODBUS_PROTOCOL_IDENTIFIER_TCP &&
+			read_word ( bytes, 4 ) as usize == bytes.len () - MBAP_LENGTH_OFFSET
+	{
+		reply = FrameFormat::Tcp;
+	}
+	else
+	{
+		reply = FrameFormat::Rtu;
+	}
+
+	return reply;
+}
+
+#[test]
+fn test_parse_hex_bytes ()
+{
+	assert_eq! ( parse_hex_bytes ( "01 03 0x6B,0X00" ), Ok( vec![ 0x01, 0x03, 0x6B, 0x00 ] ) );
+	assert_eq! ( parse_hex_bytes ( "0103006b" ), Ok( vec![ 0x01, 0x03, 0x00, 0x6B ] ) );
+	assert_eq! ( parse_hex_bytes ( " :0103\r\n" ), Ok( b":0103".to_vec () ) );
+	assert! ( parse_hex_bytes ( "010" ).is_err () );
+	assert! ( parse_hex_bytes ( "0g" ).is_err () );
+	assert! ( parse_hex_bytes ( "€a" ).is_err () );
+	assert! ( parse_hex_bytes ( "+f" ).is_err () );
+}
+
+///	Parses a hex dump with bytes separated by spaces or commas, with or without `0x`,
+/// or pairs of digits without separators. Text starting with a colon is a MODBUS ASCII frame
+/// and returned as its characters.
+pub fn parse_hex_bytes ( text : &str ) -> Result< Vec< u8 >, String >
+{
+	let trimmed : &str = text.trim ();
+	let mut reply : Vec< u8 > = vec![];
+
+	if trimmed.starts_with ( ':' )
+	{
+		return Ok( trimmed.as_bytes ().to_vec () );
+	}
+
+	for token in trimmed.split ( | character : char | character.is_whitespace () || character == ',' ).filter ( | token | !token.is_empty () )
+	{
+		let digits : &str = token.trim_start_matches ( "0x" ).trim_start_matches ( "0X" );
+
+		if digits.is_empty () || digits.len () % 2 != 0 || !digits.bytes ().all ( | digit | digit.is_ascii_hexdigit () )
+		{
+			return Err( format! ( "'{}' is no sequence of hexadecimal bytes", token ) );
+		}
+
+		for index in ( 0..digits.len () ).step_by ( 2 )
+		{
+			let byte : u8 = u8::from_str_radix ( &digits[ index..index + 2 ], 16 ).map_err ( | _ | format! ( "'{}' is no sequence of hexadecimal bytes", token ) )?;
+
+			reply.push ( byte );
+		}
+	}
+
+	return Ok( reply );
+}
+
+///	Returns the bytes of an ASCII frame, which are the hexadecimal characters between the colon and CR LF.
+fn decode_ascii_frame ( frame : &[ u8 ] ) -> Result< Vec< u8 >, String >
+{
+	let text : &str = ::std::str::from_utf8 ( frame ).map_err ( | _ | "MODBUS ASCII frame with non ASCII characters".to_string () )?;
+	let content : &str = text.trim_end_matches ( [ '\r', '\n' ] ).trim_start_matches ( ':' );
+
+	if content.contains ( | character : char | !character.is_ascii_hexdigit () )
+	{
+		return Err( "MODBUS ASCII frame with non hexadecimal characters".to_string () );
+	}
+
+	return parse_hex_bytes ( content );
+}
+
+#[test]
+fn test_calculate_lrc ()
+{
+	assert_eq! ( calculate_lrc ( &[ 0x11, 0x03, 0x00, 0x6B, 0x00, 0x03 ] ), 0x7E );
+	assert_eq! ( calculate_lrc ( &[] ), 0x00 );
+}
+
+///	Calculates the LRC of a MODBUS ASCII frame, the two's complement of the sum of the bytes.
+pub fn calculate_lrc ( bytes : &[ u8 ] ) -> u8
+{
+	return bytes.iter ().fold ( 0u8, | sum, byte | sum.wrapping_add ( *byte ) ).wrapping_neg ();
+}
+
+#[test]
+fn test_get_function_name ()
+{
+	assert_eq! ( get_function_name ( FUNCTION_CODE_WRITE_MULTIPLE_COILS ), Some( "Write Multiple Coils" ) );
+	assert_eq! ( get_function_name ( 0x7F ), None );
+}
+
+///	Returns the name of a public function code of the MODBUS specification.
+pub fn get_function_name ( function_code : u8 ) -> Option< &'static str >
+{
+	let reply : Option< &'static str >;
+
+	match function_code
+	{
+		FUNCTION_CODE_READ_COILS						=> { reply = Some( "Read Coils" ); }
+		FUNCTION_CODE_READ_DISCRETE_INPUTS				=> { reply = Some( "Read Discrete Inputs" ); }
+		FUNCTION_CODE_READ_HOLDING_REGISTERS			=> { reply = Some( "Read Holding Registers" ); }
+		FUNCTION_CODE_READ_INPUT_REGISTERS				=> { reply = Some( "Read Input Registers" ); }
+		FUNCTION_CODE_WRITE_SINGLE_COIL					=> { reply = Some( "Write Single Coil" ); }
+		FUNCTION_CODE_WRITE_SINGLE_REGISTER				=> { reply = Some( "Write Single Register" ); }
+		FUNCTION_CODE_READ_EXCEPTION_STATUS				=> { reply = Some( "Read Exception Status" ); }
+		FUNCTION_CODE_DIAGNOSTICS						=> { reply = Some( "Diagnostics" ); }
+		FUNCTION_CODE_GET_COMM_EVENT_COUNTER			=> { reply = Some( "Get Comm Event Counter" ); }
+		FUNCTION_CODE_GET_COMM_EVENT_LOG				=> { reply = Some( "Get Comm Event Log" ); }
+		FUNCTION_CODE_WRITE_MULTIPLE_COILS				=> { reply = Some( "Write Multiple Coils" ); }
+		FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS			=> { reply = Some( "Write Multiple Registers" ); }
+		FUNCTION_CODE_REPORT_SERVER_ID					=> { reply = Some( "Report Server ID" ); }
+		FUNCTION_CODE_READ_FILE_RECORD					=> { reply = Some( "Read File Record" ); }
+		FUNCTION_CODE_WRITE_FILE_RECORD					=> { reply = Some( "Write File Record" ); }
+		FUNCTION_CODE_MASK_WRITE_REGISTER				=> { reply = Some( "Mask Write Register" ); }
+		FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS		=> { reply = Some( "Read/Write Multiple Registers" ); }
+		FUNCTION_CODE_READ_FIFO_QUEUE					=> { reply = Some( "Read FIFO Queue" ); }
+		FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT	=> { reply = Some( "Encapsulated Interface Transport" ); }
+		_												=> { reply = None; }
+	}
+
+	return reply;
+}
@@ -0,0 +1,420 @@
+use std::fmt;
+use core::consts::*;
+use core::datatable::DataTable;
+use core::ethernet::EthernetMaster;
+use core::modbusreturn::*;
+#[cfg( feature = "json" )]
+use serde_json;
+#[cfg( test )]
+use core::testmaster::TestMaster;
+
+//	===============================================================================================
+
+const DISCOVERY_DEFAULT_LAST_ADDRESS : u16 = 9999;
+
+//	===============================================================================================
+
+/// The outcome of reading an address range.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum ProbeStatus
+{
+	Readable,
+	///	The device answered with ILLEGAL DATA ADDRESS (0x02).
+	IllegalAddress,
+	///	The device answered with another exception, like ILLEGAL FUNCTION for unsupported tables.
+	Exception( u8 ),
+	///	The device did not answer in time.
+	NoResponse
+}
+
+impl fmt::Display for ProbeStatus
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		match *self
+		{
+			ProbeStatus::Readable				=> { return write! ( f, "readable" ); }
+			ProbeStatus::IllegalAddress			=> { return write! ( f, "illegal_address" ); }
+			ProbeStatus::Exception( code )		=> { return write! ( f, "exception_{:02X}", code ); }
+			ProbeStatus::NoResponse				=> { return write! ( f, "no_response" ); }
+		}
+	}
+}
+
+//	===============================================================================================
+
+/// A range of addresses of a table with the same probe status.
+#[derive( Clone, Debug, Eq, PartialEq )]
+pub struct DiscoveredRange
+{
+	table : DataTable,
+	first_address : u16,
+	last_address : u16,
+	status : ProbeStatus
+}
+
+impl DiscoveredRange
+{
+	pub fn new ( table : DataTable, first_address : u16, last_address : u16, status : ProbeStatus ) -> DiscoveredRange
+	{
+		return DiscoveredRange
+		{
+			table : table,
+			first_address : first_address,
+			last_address : last_address,
+			status : status
+		};
+	}
+
+	pub fn get_table ( &self ) -> DataTable
+	{
+		return self.table;
+	}
+
+	pub fn get_first_address ( &self ) -> u16
+	{
+		return self.first_address;
+	}
+
+	pub fn get_last_address ( &self ) -> u16
+	{
+		return self.last_address;
+	}
+
+	///	Returns the number of addresses, up to 65536 for a whole table.
+	pub fn get_quantity ( &self ) -> u32
+	{
+		return self.last_address as u32 - self.first_address as u32 + 1;
+	}
+
+	pub fn get_status ( &self ) -> ProbeStatus
+	{
+		return self.status;
+	}
+}
+
+//	===============================================================================================
+
+/// The report of a discovery, ordered by table and address.
+#[derive( Clone, Debug, Default, Eq, PartialEq )]
+pub struct DiscoveredMap
+{
+	ranges : Vec< DiscoveredRange >,
+	request_count : usize
+}
+
+impl DiscoveredMap
+{
+	pub fn get_ranges ( &self ) -> &Vec< DiscoveredRange >
+	{
+		return &self.ranges;
+	}
+
+	///	Returns the ranges which could be read.
+	pub fn get_readable_ranges ( &self ) -> Vec< &DiscoveredRange >
+	{
+		return self.ranges.iter ().filter ( | range | range.status == ProbeStatus::Readable ).collect ();
+	}
+
+	///	Returns the number of read requests the discovery needed.
+	pub fn get_request_count ( &self ) -> usize
+	{
+		return self.request_count;
+	}
+
+	///	Returns the report as CSV with a header line.
+	pub fn to_csv ( &self ) -> String
+	{
+		let mut reply : String = "table,first_address,last_address,quantity,status\n".to_string ();
+
+		for range in &self.ranges
+		{
+			reply.push_str ( &format! ( "{},{},{},{},{}\n", range.table, range.first_address, range.last_address, range.get_quantity (), range.status ) );
+		}
+
+		return reply;
+	}
+
+	///	Returns the report as JSON array of objects with the fields of the CSV report.
+	#[cfg( feature = "json" )]
+	pub fn to_json ( &self ) -> String
+	{
+		let ranges : Vec< serde_json::Value > = self.ranges.iter ().map ( | range |
+		{
+			let mut object : serde_json::Map< String, serde_json::Value > = serde_json::Map::new ();
+
+			object.insert ( "table".to_string (), serde_json::Value::from ( range.table.to_string () ) );
+			object.insert ( "first_address".to_string (), serde_json::Value::from ( range.first_address ) );
+			object.insert ( "last_address".to_string (), serde_json::Value::from ( range.last_address ) );
+			object.insert ( "quantity".to_string (), serde_json::Value::from ( range.get_quantity () ) );
+			object.insert ( "status".to_string (), serde_json::Value::from ( range.status.to_string () ) );
+
+			serde_json::Value::Object( object )
+		} ).collect ();
+
+		return serde_json::Value::Array( ranges ).to_string ();
+	}
+
+	///	Adds the range or extends the last range if it continues it with the same status.
+	fn push ( &mut self, range : DiscoveredRange )
+	{
+		if let Some( last ) = self.ranges.last_mut ()
+		{
+			if last.table == range.table && last.status == range.status && last.last_address as u32 + 1 == range.first_address as u32
+			{
+				last.last_address = range.last_address;
+				return;
+			}
+		}
+
+		self.ranges.push ( range );
+	}
+}
+
+//	===============================================================================================
+
+/// Discovers the readable address ranges of a device. Each table is swept with
+/// reads of the maximum quantity; reads refused with ILLEGAL DATA ADDRESS are split
+/// in halves down to the resolution, which finds the exact boundaries for a resolution
+/// of one. Other exceptions and timeouts are reported for the whole read.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::core::discovery::Discovery;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let mut client = TcpClient::new("127.0.0.1");
+///
+/// if client.connect().is_ok()
+/// {
+///     let mut discovery = Discovery::new();
+///     discovery.set_address_range(0, 999);
+///
+///     print!("{}", discovery.discover(&mut client).to_csv());
+///
+///     client.disconnect();
+/// }
+/// ```
+#[derive( Clone, Debug )]
+pub struct Discovery
+{
+	tables : Vec< DataTable >,
+	first_address : u16,
+	last_address : u16,
+	resolution : u16
+}
+
+impl Default for Discovery
+{
+	fn default () -> Discovery
+	{
+		return Self::new ();
+	}
+}
+
+impl Discovery
+{
+	///	Creates a discovery of all four tables for the addresses 0 to 9999 with a resolution of one.
+	pub fn new () -> Discovery
+	{
+		return Discovery
+		{
+			tables : vec![ DataTable::Coils, DataTable::DiscreteInputs, DataTable::HoldingRegisters, DataTable::InputRegisters ],
+			first_address : 0x0000,
+			last_address : DISCOVERY_DEFAULT_LAST_ADDRESS,
+			resolution : 1
+		};
+	}
+
+	pub fn set_tables ( &mut self, tables : Vec< DataTable > )
+	{
+		self.tables = tables;
+	}
+
+	///	Sets the first and the last address to probe in each table.
+	pub fn set_address_range ( &mut self, first_address : u16, last_address : u16 )
+	{
+		self.first_address = first_address.min ( last_address );
+		self.last_address = first_address.max ( last_address );
+	}
+
+	///	Sets the size below which refused reads are not split any more.
+	/// Larger values need fewer requests on sparse maps but report coarser boundaries.
+	pub fn set_resolution ( &mut self, resolution : u16 )
+	{
+		self.resolution = resolution.max ( 1 );
+	}
+
+	pub fn discover< T : EthernetMaster > ( &self, master : &mut T ) -> DiscoveredMap
+	{
+		let mut reply : DiscoveredMap = DiscoveredMap::default ();
+
+		for table in &self.tables
+		{
+			let block_size : u32 = table.get_maximum_read_quantity () as u32;
+			let mut first_address : u32 = self.first_address as u32;
+
+			while first_address <= self.last_address as u32
+			{
+				let last_address : u32 = ( first_address + block_size - 1 ).min ( self.last_address as u32 );
+
+				self.probe_block ( master,
+								   *table,
+								   first_address as u16,
+								   last_address as u16,
+								   &mut reply );
+
+				//	A table the device does not support is not swept any further.
+				if reply.ranges.last ().map ( | range | range.status ) == Some( ProbeStatus::Exception( MODBUS_EXCEPTION_ILLEGAL_FUNCTION ) )
+				{
+					if last_address < self.last_address as u32
+					{
+						reply.push ( DiscoveredRange::new ( *table, last_address as u16 + 1, self.last_address, ProbeStatus::Exception( MODBUS_EXCEPTION_ILLEGAL_FUNCTION ) ) );
+					}
+
+					break;
+				}
+
+				first_address = last_address + 1;
+			}
+		}
+
+		return reply;
+	}
+
+	fn probe_block< T : EthernetMaster > ( &self, master : &mut T, table : DataTable, first_address : u16, last_address : u16, map : &mut DiscoveredMap )
+	{
+		let quantity : u16 = last_address - first_address + 1;
+		let status : ProbeStatus = probe_range ( master,
+												 table,
+												 first_address,
+												 quantity );
+
+		map.request_count += 1;
+
+		if status == ProbeStatus::IllegalAddress && quantity > self.resolution
+		{
+			let middle_address : u16 = first_address + ( quantity - 1 ) / 2;
+
+			self.probe_block ( master, table, first_address, middle_address, map );
+			self.probe_block ( master, table, middle_address + 1, last_address, map );
+		}
+		else
+		{
+			map.push ( DiscoveredRange::new ( table, first_address, last_address, status ) );
+		}
+	}
+}
+
+#[test]
+fn test_discovery ()
+{
+	let mut master : TestMaster = TestMaster::new ( vec![ 0; 300 ] );
+	let mut discovery : Discovery = Discovery::new ();
+
+	discovery.set_address_range ( 0, 999 );
+	discovery.set_tables ( vec![ DataTable::HoldingRegisters ] );
+
+	let map : DiscoveredMap = discovery.discover ( &mut master );
+
+	assert_eq! ( map.get_ranges (), &vec![ DiscoveredRange::new ( DataTable::HoldingRegisters, 0, 299, ProbeStatus::Readable ),
+										   DiscoveredRange::new ( DataTable::HoldingRegisters, 300, 999, ProbeStatus::IllegalAddress ) ] );
+	assert_eq! ( map.get_readable_ranges ().len (), 1 );
+	assert! ( map.get_request_count () < 1500 );
+
+	discovery.set_tables ( vec![ DataTable::Coils, DataTable::InputRegisters ] );
+	discovery.set_address_range ( 0, 4999 );
+
+	let map : DiscoveredMap = discovery.discover ( &mut master );
+
+	assert_eq! ( map.get_ranges (), &vec![ DiscoveredRange::new ( DataTable::Coils, 0, 4999, ProbeStatus::Readable ),
+										   DiscoveredRange::new ( DataTable::InputRegisters, 0, 4999, ProbeStatus::NoResponse ) ] );
+	assert_eq! ( map.to_csv ().lines ().nth ( 2 ), Some( "input_registers,0,4999,5000,no_response" ) );
+
+	discovery.set_tables ( vec![ DataTable::DiscreteInputs ] );
+	discovery.set_address_range ( 0, 99 );
+	discovery.set_resolution ( 10 );
+
+	let map : DiscoveredMap = discovery.discover ( &mut master );
+
+	assert_eq! ( map.get_ranges (), &vec![ DiscoveredRange::new ( DataTable::DiscreteInputs, 0, 99, ProbeStatus::IllegalAddress ) ] );
+	assert_eq! ( map.get_request_count (), 31 );
+}
+
+//	===============================================================================================
+
+///	Reads the range once and returns how the device answered.
+pub fn probe_range< T : EthernetMaster > ( master : &mut T, table : DataTable, address : u16, quantity : u16 ) -> ProbeStatus
+{
+	let reply : ProbeStatus;
+
+	match table
+	{
+		DataTable::Coils | DataTable::DiscreteInputs				=>
+		{
+			let response : ModbusReturnCoils;
+
+			if table == DataTable::Coils
+			{
+				response = master.read_coils ( address, quantity );
+			}
+			else
+			{
+				response = master.read_discrete_inputs ( address, quantity );
+			}
+
+			match response
+			{
+				ModbusReturnCoils::Good( _ )		=> { reply = ProbeStatus::Readable; }
+				ModbusReturnCoils::Bad( bad )		=> { reply = get_status_of_return_bad ( &bad ); }
+				ModbusReturnCoils::None				=> { reply = ProbeStatus::NoResponse; }
+			}
+		}
+		DataTable::HoldingRegisters | DataTable::InputRegisters	=>
+		{
+			let response : ModbusReturnRegisters;
+
+			if table == DataTable::HoldingRegisters
+			{
+				response = master.read_holding_registers ( address, quantity );
+			}
+			else
+			{
+				response = master.read_input_registers ( address, quantity );
+			}
+
+			match response
+			{
+				ModbusReturnRegisters::Good( _ )	=> { reply = ProbeStatus::Readable; }
+				ModbusReturnRegisters::Bad( bad )	=> { reply = get_status_of_return_bad ( &bad ); }
+				ModbusReturnRegisters::None			=> { reply = ProbeStatus::NoResponse; }
+			}
+		}
+	}
+
+	return reply;
+}
+
+///	Exception responses carry the function code with the exception flag,
+/// all other failures mean that no valid response arrived.
+fn get_status_of_return_bad ( bad : &ReturnBad ) -> ProbeStatus
+{
+	let reply : ProbeStatus;
+
+	if bad.get_error_code () & MODBUS_EXCEPTION_FLAG == 0
+	{
+		reply = ProbeStatus::NoResponse;
+	}
+	else if bad.get_exception_code () == MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS
+	{
+		reply = ProbeStatus::IllegalAddress;
+	}
+	else
+	{
+		reply = ProbeStatus::Exception( bad.get_exception_code () );
+	}
+
+	return reply;
+}
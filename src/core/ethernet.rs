@@ -1,6 +1,7 @@
 
 
-use core::modbusreturn::{ModbusReturnCoils, ModbusReturnRegisters};
+use core::consts::*;
+use core::modbusreturn::{ModbusReturnCoils, ModbusReturnRegisters, ReturnBad};
 
 
 /// MODBUS API (based on the technical protocol specification)
@@ -234,4 +235,100 @@ pub trait EthernetMaster
     /// }
     /// ```
     fn write_multiple_registers ( &mut self, starting_address : u16, register_values : Vec< u16 > ) -> ModbusReturnRegisters;    
+
+    ///	MODBUS Function Code 22 (0x16)
+    /// This function code is used to modify the contents of a specified
+    /// holding register using a combination of an AND mask, an OR mask,
+    /// and the register's current contents.
+    /// (Description from the MODBUS APPLICATION PROTOCOL SPECIFICATION V1.1b3)
+    /// 
+    /// The default implementation answers like a device without this function
+    /// code with an illegal function exception, `MasterAccess::write_register_bit`
+    /// then falls back to a read-modify-write.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// 
+    /// use modbus_iiot::tcp::master::*;
+    /// use modbus_iiot::core::ethernet::EthernetMaster;
+    /// 
+    /// let mut client = TcpClient::new("127.0.0.1");
+    ///
+    /// if let Err(message) = client.connect()
+    /// {
+    ///     println!("failure = {}", message);
+    /// }
+    /// else
+    /// {
+    ///     let response = client.mask_write_register(4, 0x00F2, 0x0025);
+    ///     println!("Response of mask_write_register: {:?}", response);
+    ///    
+    ///     client.disconnect ();    
+    /// }
+    /// ```
+    fn mask_write_register ( &mut self, _reference_address : u16, _and_mask : u16, _or_mask : u16 ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( FUNCTION_CODE_MASK_WRITE_REGISTER | MODBUS_EXCEPTION_FLAG,
+                                                                       MODBUS_EXCEPTION_ILLEGAL_FUNCTION ) );
+    }
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+struct MasterWithoutMaskWrite;
+
+#[cfg( test )]
+impl EthernetMaster for MasterWithoutMaskWrite
+{
+    fn read_coils ( &mut self, _starting_address : u16, _quantity_of_coils : u16 ) -> ModbusReturnCoils
+    {
+        return ModbusReturnCoils::None;
+    }
+
+    fn read_discrete_inputs ( &mut self, _starting_address : u16, _quantity_of_inputs : u16 ) -> ModbusReturnCoils
+    {
+        return ModbusReturnCoils::None;
+    }
+
+    fn read_holding_registers ( &mut self, _starting_address : u16, _quantity_of_registers : u16 ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::None;
+    }
+
+    fn read_input_registers ( &mut self, _starting_address : u16, _quantity_of_input_registers : u16 ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::None;
+    }
+
+    fn write_single_coil ( &mut self, _output_address : u16, _output_value : u16 ) -> ModbusReturnCoils
+    {
+        return ModbusReturnCoils::None;
+    }
+
+    fn write_single_register ( &mut self, _register_address : u16, _register_value : u16 ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::None;
+    }
+
+    fn write_multiple_coils ( &mut self, _starting_address : u16, _quantity_of_outputs : u16, _outputs_value : Vec< u8 > ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::None;
+    }
+
+    fn write_multiple_registers ( &mut self, _starting_address : u16, _register_values : Vec< u16 > ) -> ModbusReturnRegisters
+    {
+        return ModbusReturnRegisters::None;
+    }
+}
+
+#[test]
+fn test_mask_write_register_default ()
+{
+    let mut master : MasterWithoutMaskWrite = MasterWithoutMaskWrite;
+
+    let bad : ReturnBad = master.mask_write_register ( 4, 0x00F2, 0x0025 ).unwrap_bad ();
+    assert_eq! ( bad.get_error_code (), 0x96 );
+    assert_eq! ( bad.get_exception_code (), 0x01 );
 }
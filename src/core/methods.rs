@@ -1,8 +1,81 @@
-
+#![cfg_attr( test, allow( clippy::bool_assert_comparison, clippy::useless_vec ) )]
 
 use core::consts::*;
-use core::datatransformation::*;
 use core::modbustelegram::ModbusTelegram;
+use core::pdu::*;
+
+//	===============================================================================================
+
+#[test]
+fn test_create_request_mask_write_register ()
+{
+	let transaction_identifier : u16 = 0x00A0;
+	let unit_identifier : u8 = 0x01;
+	let reference_address : u16 = 0x0004;
+	let and_mask : u16 = 0x00F2;
+	let or_mask : u16 = 0x0025;
+
+	let result : Result< ModbusTelegram, String > = create_request_mask_write_register ( transaction_identifier,
+																						 unit_identifier,
+																						 reference_address,
+																						 and_mask,
+																						 or_mask );
+	assert! ( result.is_ok () );
+
+	let telegram : ModbusTelegram = result.unwrap ();
+	let function_code: Option< u8 > = telegram.get_function_code ();
+	assert! ( function_code.is_some () );
+	assert_eq! ( function_code.unwrap (), FUNCTION_CODE_MASK_WRITE_REGISTER );
+	assert_eq! ( telegram.get_expected_byte_count (), Some( 14 ) );
+
+	let telegram_bytes : Option< Vec< u8 > > = telegram.get_bytes ();
+	assert! ( telegram_bytes.is_some () );
+
+	let bytes : Vec< u8 > = telegram_bytes.unwrap ();
+	assert_eq! ( bytes.len (), 14 );
+	assert_eq! ( bytes[  0 ], 0x00 );	//	transaction_identifier
+	assert_eq! ( bytes[  1 ], 0xA0 );	//	transaction_identifier
+	assert_eq! ( bytes[  2 ], 0x00 );	//	protocol_identifier
+	assert_eq! ( bytes[  3 ], 0x00 );	//	protocol_identifier
+	assert_eq! ( bytes[  4 ], 0x00 );	//	length of all following bytes
+	assert_eq! ( bytes[  5 ], 0x08 );	//	length of all following bytes
+	assert_eq! ( bytes[  6 ], unit_identifier );
+	assert_eq! ( bytes[  7 ], FUNCTION_CODE_MASK_WRITE_REGISTER );
+	assert_eq! ( bytes[  8 ], 0x00 );	//	reference_address
+	assert_eq! ( bytes[  9 ], 0x04 );	//	reference_address
+	assert_eq! ( bytes[ 10 ], 0x00 );	//	and_mask
+	assert_eq! ( bytes[ 11 ], 0xF2 );	//	and_mask
+	assert_eq! ( bytes[ 12 ], 0x00 );	//	or_mask
+	assert_eq! ( bytes[ 13 ], 0x25 );	//	or_mask
+}
+
+pub fn create_request_mask_write_register ( transaction_identifier : u16, unit_identifier : u8, reference_address : u16, and_mask : u16, or_mask : u16 ) -> Result< ModbusTelegram, String >
+{
+	let reply : Result< ModbusTelegram, String >;
+
+	let parameter_verification : Result< bool, String > = verify_parameter_mask_write_register ();
+
+	match parameter_verification
+	{
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_mask_write_register ( reference_address,
+																			and_mask,
+																			or_mask );
+
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_MASK_WRITE_REGISTER,
+																			&payload,
+																			get_expected_byte_count_mask_write_register () );
+
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
+	}
+
+	return reply;
+}
 
 //	===============================================================================================
 
@@ -52,22 +125,22 @@ pub fn create_request_read_coils ( transaction_identifier : u16, unit_identifier
 	let parameter_verification : Result< bool, String > = verify_parameter_read_coils ( starting_address, 
 																						quantity_of_coils );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_read_coils ( starting_address, 
-															   quantity_of_coils );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_read_coils ( starting_address, 
+																   quantity_of_coils );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-										    							unit_identifier,
-																		FUNCTION_CODE_READ_COILS,
-																		&payload,
-																		get_expected_byte_count_read_coils ( quantity_of_coils ) );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+											    							unit_identifier,
+																			FUNCTION_CODE_READ_COILS,
+																			&payload,
+																			get_expected_byte_count_read_coils ( quantity_of_coils ) );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -120,22 +193,22 @@ pub fn create_request_read_discrete_inputs ( transaction_identifier : u16, unit_
 	let parameter_verification : Result< bool, String > = verify_parameter_read_discrete_inputs ( starting_address, 
 																								  quantity_of_inputs );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_read_discrete_inputs ( starting_address, 
-																		 quantity_of_inputs );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_read_discrete_inputs ( starting_address, 
+																			 quantity_of_inputs );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_READ_DISCRETE_INPUTS,
-																		&payload,
-																		get_expected_byte_count_read_discrete_inputs ( quantity_of_inputs ) );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_READ_DISCRETE_INPUTS,
+																			&payload,
+																			get_expected_byte_count_read_discrete_inputs ( quantity_of_inputs ) );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -188,22 +261,22 @@ pub fn create_request_read_holding_registers ( transaction_identifier : u16, uni
 	let parameter_verification : Result< bool, String > = verify_parameter_read_holding_registers ( starting_address, 
 																									quantity_of_registers );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_read_holding_registers ( starting_address, 
-																		   quantity_of_registers );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_read_holding_registers ( starting_address, 
+																			   quantity_of_registers );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_READ_HOLDING_REGISTERS,
-																		&payload,
-																		get_expected_byte_count_read_holding_registers ( quantity_of_registers ) );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_READ_HOLDING_REGISTERS,
+																			&payload,
+																			get_expected_byte_count_read_holding_registers ( quantity_of_registers ) );
 
-		reply = pack_telegram ( telegram );		
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );		
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -256,22 +329,22 @@ pub fn create_request_read_input_registers ( transaction_identifier : u16, unit_
 	let parameter_verification : Result< bool, String > = verify_parameter_read_input_registers ( starting_address, 
 																								  quantity_of_input_registers );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_read_input_registers ( starting_address, 
-																		 quantity_of_input_registers );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_read_input_registers ( starting_address, 
+																			 quantity_of_input_registers );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_READ_INPUT_REGISTERS,
-																		&payload,
-																		get_expected_byte_count_read_input_registers ( quantity_of_input_registers ) );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_READ_INPUT_REGISTERS,
+																			&payload,
+																			get_expected_byte_count_read_input_registers ( quantity_of_input_registers ) );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -330,23 +403,30 @@ pub fn create_request_write_multiple_coils ( transaction_identifier : u16, unit_
 	let parameter_verification : Result< bool, String > = verify_parameter_write_multiple_coils ( starting_address, 
 																								  quantity_of_outputs );
  
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_write_multiple_coils ( starting_address, 
-																		 quantity_of_outputs, 
-																		 &output_values );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_write_multiple_coils ( starting_address, 
+																			 quantity_of_outputs, 
+																			 &output_values );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_WRITE_MULTIPLE_COILS,
-																		&payload,
-																		get_expected_byte_count_write_multiple_coils () );
+			if payload.is_empty ()
+			{
+				reply = Err( "Error at parameter output_values - byte count does not match quantity_of_outputs.".to_string () );
+			}
+			else
+			{
+				let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																				unit_identifier,
+																				FUNCTION_CODE_WRITE_MULTIPLE_COILS,
+																				&payload,
+																				get_expected_byte_count_write_multiple_coils () );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+				reply = pack_telegram ( telegram );
+			}
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -409,22 +489,22 @@ pub fn create_request_write_multiple_registers ( transaction_identifier : u16, u
 	let parameter_verification : Result< bool, String > = verify_parameter_write_multiple_registers ( starting_address, 
 																									  quantity_of_registers );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_write_multiple_registers ( starting_address, 
-																			 &register_values );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_write_multiple_registers ( starting_address, 
+																				 &register_values );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS,
-																		&payload,
-																		get_expected_byte_count_write_multiple_registers () );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS,
+																			&payload,
+																			get_expected_byte_count_write_multiple_registers () );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -476,22 +556,22 @@ pub fn create_request_write_single_coil ( transaction_identifier : u16, unit_ide
 
 	let parameter_verification : Result< bool, String > = verify_parameter_write_single_coil ( output_value );
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_write_single_coil ( output_address, 
-																	  output_value );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_write_single_coil ( output_address, 
+																		  output_value );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_WRITE_SINGLE_COIL,
-																		&payload,
-																		get_expected_byte_count_write_single_coil () );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_WRITE_SINGLE_COIL,
+																			&payload,
+																			get_expected_byte_count_write_single_coil () );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -519,7 +599,7 @@ fn test_create_request_write_single_register ()
 	assert_eq! ( function_code.unwrap (), FUNCTION_CODE_WRITE_SINGLE_REGISTER );
 	
 	let telegram_bytes : Option< Vec< u8 > > = telegram.get_bytes ();
-	assert! ( telegram_bytes.is_some (), true );
+	assert! ( telegram_bytes.is_some () );
 
 	let bytes : Vec< u8 > = telegram_bytes.unwrap ();
 	assert_eq! ( bytes.len (), 12 );
@@ -543,22 +623,22 @@ pub fn create_request_write_single_register ( transaction_identifier : u16, unit
 
 	let parameter_verification : Result< bool, String > = verify_parameter_write_single_register ();
 
-	if parameter_verification.is_ok ()
+	match parameter_verification
 	{
-		let payload : Vec< u8 > = prepare_payload_write_single_register ( register_address, 
-																		  register_value );
+		Ok( _ )			=>
+		{
+			let payload : Vec< u8 > = prepare_payload_write_single_register ( register_address, 
+																			  register_value );
 
-		let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
-																		unit_identifier,
-																		FUNCTION_CODE_WRITE_SINGLE_REGISTER,
-																		&payload,
-																		get_expected_byte_count_write_single_register () );
+			let telegram : Option< ModbusTelegram > = ModbusTelegram::new ( transaction_identifier,
+																			unit_identifier,
+																			FUNCTION_CODE_WRITE_SINGLE_REGISTER,
+																			&payload,
+																			get_expected_byte_count_write_single_register () );
 
-		reply = pack_telegram ( telegram );
-	}
-	else
-	{
-		reply = Err( parameter_verification.unwrap_err () );
+			reply = pack_telegram ( telegram );
+		}
+		Err( message )	=> { reply = Err( message ); }
 	}
 
 	return reply;
@@ -566,33 +646,49 @@ pub fn create_request_write_single_register ( transaction_identifier : u16, unit
 
 //	===============================================================================================
 
+#[test]
+fn test_get_expected_byte_count_mask_write_register ()
+{
+	let result : u16 = get_expected_byte_count_mask_write_register ();
+	assert_eq! ( result, 14 );
+}
+
+fn get_expected_byte_count_mask_write_register () -> u16
+{
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + 7; // +7 for FunctionCode, 2 Bytes ReferenceAddress, 2 Bytes AndMask and 2 Bytes OrMask
+
+	return reply;
+}
+
+//	===============================================================================================
+
 #[test]
 fn test_get_expected_byte_count_read_coils ()
 {
-	let result_1 : u8 = get_expected_byte_count_read_coils ( 8 );
+	let result_1 : u16 = get_expected_byte_count_read_coils ( 8 );
 	assert_eq! ( result_1, 10 );
 
-	let result_2 : u8 = get_expected_byte_count_read_coils ( 16 );
+	let result_2 : u16 = get_expected_byte_count_read_coils ( 16 );
 	assert_eq! ( result_2, 11 );
 
-	let result_3 : u8 = get_expected_byte_count_read_coils ( 7 );
+	let result_3 : u16 = get_expected_byte_count_read_coils ( 7 );
 	assert_eq! ( result_3, 10 );
 
-	let result_4 : u8 = get_expected_byte_count_read_coils ( 19 );
+	let result_4 : u16 = get_expected_byte_count_read_coils ( 19 );
 	assert_eq! ( result_4, 12 );
 }
 
-fn get_expected_byte_count_read_coils ( quantity_of_coils : u16 ) -> u8
+fn get_expected_byte_count_read_coils ( quantity_of_coils : u16 ) -> u16
 {
-	let mut reply : u8 = MODBUS_HEADER_SIZE + 2;	// +2 for FunctionCode and ByteCount
+	let mut reply : u16 = MODBUS_HEADER_SIZE as u16 + 2;	// +2 for FunctionCode and ByteCount
 
 	if ( quantity_of_coils % 8 ) > 0
 	{
-		reply += ( ( quantity_of_coils / 8 ) + 1 ) as u8;
+		reply += ( quantity_of_coils / 8 ) + 1;
 	}
 	else
 	{
-		reply += ( quantity_of_coils / 8 ) as u8;
+		reply += quantity_of_coils / 8;
 	}
 
 	return reply;
@@ -603,30 +699,30 @@ fn get_expected_byte_count_read_coils ( quantity_of_coils : u16 ) -> u8
 #[test]
 fn test_get_expected_byte_count_read_discrete_inputs ()
 {
-	let result_1 : u8 = get_expected_byte_count_read_discrete_inputs ( 8 );
+	let result_1 : u16 = get_expected_byte_count_read_discrete_inputs ( 8 );
 	assert_eq! ( result_1, 10 );
 
-	let result_2 : u8 = get_expected_byte_count_read_discrete_inputs ( 16 );
+	let result_2 : u16 = get_expected_byte_count_read_discrete_inputs ( 16 );
 	assert_eq! ( result_2, 11 );
 
-	let result_3 : u8 = get_expected_byte_count_read_discrete_inputs ( 7 );
+	let result_3 : u16 = get_expected_byte_count_read_discrete_inputs ( 7 );
 	assert_eq! ( result_3, 10 );
 
-	let result_4 : u8 = get_expected_byte_count_read_discrete_inputs ( 19 );
+	let result_4 : u16 = get_expected_byte_count_read_discrete_inputs ( 19 );
 	assert_eq! ( result_4, 12 );
 }
 
-fn get_expected_byte_count_read_discrete_inputs ( quantity_of_inputs : u16 ) -> u8
+fn get_expected_byte_count_read_discrete_inputs ( quantity_of_inputs : u16 ) -> u16
 {
-	let mut reply : u8 = MODBUS_HEADER_SIZE + 2;	// +2 für FunctionCode und ByteCount
+	let mut reply : u16 = MODBUS_HEADER_SIZE as u16 + 2;	// +2 für FunctionCode und ByteCount
 
 	if ( quantity_of_inputs % 8 ) > 0
 	{
-		reply += ( ( quantity_of_inputs / 8 ) + 1 ) as u8;
+		reply += ( quantity_of_inputs / 8 ) + 1;
 	}
 	else
 	{
-		reply += ( quantity_of_inputs / 8 ) as u8;
+		reply += quantity_of_inputs / 8;
 	}
 
 	return reply;
@@ -637,13 +733,16 @@ fn get_expected_byte_count_read_discrete_inputs ( quantity_of_inputs : u16 ) ->
 #[test]
 fn test_get_expected_byte_count_read_holding_registers ()
 {
-	let result : u8 = get_expected_byte_count_read_holding_registers ( 20 );
+	let result : u16 = get_expected_byte_count_read_holding_registers ( 20 );
 	assert_eq! ( result, 49 );
+
+	let result_max : u16 = get_expected_byte_count_read_holding_registers ( 125 );
+	assert_eq! ( result_max, 259 );
 }
 
-fn get_expected_byte_count_read_holding_registers ( quantity_of_registers : u16 ) -> u8
+fn get_expected_byte_count_read_holding_registers ( quantity_of_registers : u16 ) -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + ( quantity_of_registers * 2 ) as u8 + 2; // +2 for FunctionCode and ByteCount
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + ( quantity_of_registers * 2 ) + 2; // +2 for FunctionCode and ByteCount
 
 	return reply;
 }
@@ -653,13 +752,13 @@ fn get_expected_byte_count_read_holding_registers ( quantity_of_registers : u16
 #[test]
 fn test_get_expected_byte_count_read_input_registers ()
 {
-	let result : u8 = get_expected_byte_count_read_input_registers ( 20 );
+	let result : u16 = get_expected_byte_count_read_input_registers ( 20 );
 	assert_eq! ( result, 49 );
 }
 
-fn get_expected_byte_count_read_input_registers ( quantity_of_input_registers : u16 ) -> u8
+fn get_expected_byte_count_read_input_registers ( quantity_of_input_registers : u16 ) -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + ( quantity_of_input_registers * 2 ) as u8 + 2; // +2 for FunctionCode and ByteCount
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + ( quantity_of_input_registers * 2 ) + 2; // +2 for FunctionCode and ByteCount
 
 	return reply;
 }
@@ -669,13 +768,13 @@ fn get_expected_byte_count_read_input_registers ( quantity_of_input_registers :
 #[test]
 fn test_get_expected_byte_count_write_multiple_coils ()
 {
-	let result : u8 =	get_expected_byte_count_write_multiple_coils ();
+	let result : u16 =	get_expected_byte_count_write_multiple_coils ();
 	assert_eq! ( result, 12 );
 }
 
-fn get_expected_byte_count_write_multiple_coils () -> u8
+fn get_expected_byte_count_write_multiple_coils () -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + 5; // +5 for FunctionCode, 2 Bytes StartingAddress and 2 Bytes QuantityOfOutputs
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + 5; // +5 for FunctionCode, 2 Bytes StartingAddress and 2 Bytes QuantityOfOutputs
 
 	return reply;
 }
@@ -685,13 +784,13 @@ fn get_expected_byte_count_write_multiple_coils () -> u8
 #[test]
 fn test_get_expected_byte_count_write_multiple_registers ()
 {
-	let result : u8 =	get_expected_byte_count_write_multiple_registers ();
+	let result : u16 =	get_expected_byte_count_write_multiple_registers ();
 	assert_eq! ( result, 12 );
 }
 
-fn get_expected_byte_count_write_multiple_registers () -> u8
+fn get_expected_byte_count_write_multiple_registers () -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + 5; // +5 for FunctionCode, 2 Bytes StartingAddress and 2 Bytes QuantityOfRegisters
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + 5; // +5 for FunctionCode, 2 Bytes StartingAddress and 2 Bytes QuantityOfRegisters
 
 	return reply;
 }
@@ -701,13 +800,13 @@ fn get_expected_byte_count_write_multiple_registers () -> u8
 #[test]
 fn test_get_expected_byte_count_write_single_coil ()
 {
-	let result : u8 = get_expected_byte_count_write_single_coil ();
+	let result : u16 = get_expected_byte_count_write_single_coil ();
 	assert_eq! ( result, 12 );
 }
 
-fn get_expected_byte_count_write_single_coil () -> u8
+fn get_expected_byte_count_write_single_coil () -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + 5; // +5 for FunctionCode, 2 Bytes OutputAddress and 2 Bytes OutputValue
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + 5; // +5 for FunctionCode, 2 Bytes OutputAddress and 2 Bytes OutputValue
 
 	return reply;
 }
@@ -717,13 +816,13 @@ fn get_expected_byte_count_write_single_coil () -> u8
 #[test]
 fn test_get_expected_byte_count_write_single_register ()
 {
-	let result : u8 = get_expected_byte_count_write_single_register ();
+	let result : u16 = get_expected_byte_count_write_single_register ();
 	assert_eq! ( result, 12 );
 }
 
-fn get_expected_byte_count_write_single_register () -> u8
+fn get_expected_byte_count_write_single_register () -> u16
 {
-	let reply : u8 = MODBUS_HEADER_SIZE + 5; // +5 for FunctionCode, 2 Bytes RegisterAddress and 2 Bytes RegisterValue
+	let reply : u16 = MODBUS_HEADER_SIZE as u16 + 5; // +5 for FunctionCode, 2 Bytes RegisterAddress and 2 Bytes RegisterValue
 
 	return reply;
 }
@@ -751,13 +850,36 @@ fn pack_telegram ( telegram : Option< ModbusTelegram > ) -> Result< ModbusTelegr
 {
 	let reply : Result< ModbusTelegram, String >;
 
-	if telegram.is_some ()
+	match telegram
 	{
-		reply = Ok( telegram.unwrap () );
+		Some( telegram )	=> { reply = Ok( telegram ); }
+		None				=> { reply = Err( "Error while creating telegram.".to_string () ); }
 	}
-	else
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_encode_payload ()
+{
+	let result_1 : Vec< u8 > = encode_payload ( &RequestPdu::ReadCoils { address : 0x00FF, quantity : 0x000A } );
+	assert_eq! ( result_1, vec![ 0x00, 0xFF, 0x00, 0x0A ] );
+
+	let result_2 : Vec< u8 > = encode_payload ( &RequestPdu::ReadCoils { address : 0x00FF, quantity : 0x0000 } );
+	assert_eq! ( result_2.len (), 0 );
+}
+
+///	Encodes a request PDU and returns the bytes following its function code,
+/// or no bytes if the request is invalid.
+fn encode_payload ( request : &RequestPdu ) -> Vec< u8 >
+{
+	let mut reply : Vec< u8 > = vec![];
+
+	if let Ok( bytes ) = request.to_bytes ()
 	{
-		reply = Err( "Error while creating telegram.".to_string () );
+		reply = bytes[ 1.. ].to_vec ();
 	}
 
 	return reply;
@@ -765,6 +887,22 @@ fn pack_telegram ( telegram : Option< ModbusTelegram > ) -> Result< ModbusTelegr
 
 //	===============================================================================================
 
+#[test]
+fn test_prepare_payload_mask_write_register ()
+{
+	let result : Vec< u8 > = prepare_payload_mask_write_register ( 0x0004,
+																   0x00F2,
+																   0x0025 );
+	assert_eq! ( result, vec![ 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25 ] );
+}
+
+fn prepare_payload_mask_write_register ( reference_address : u16, and_mask : u16, or_mask : u16 ) -> Vec< u8 >
+{
+	return encode_payload ( &RequestPdu::MaskWriteRegister { address : reference_address, and_mask : and_mask, or_mask : or_mask } );
+}
+
+//	===============================================================================================
+
 #[test]
 fn test_prepare_payload_read_coils ()
 {
@@ -779,14 +917,7 @@ fn test_prepare_payload_read_coils ()
 
 fn prepare_payload_read_coils ( starting_address : u16, quantity_of_coils : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address );
-	append_word_to_bytearray ( &mut reply, 
-							   quantity_of_coils );
-
-	return reply;
+	return encode_payload ( &RequestPdu::ReadCoils { address : starting_address, quantity : quantity_of_coils } );
 }
 
 //	===============================================================================================
@@ -805,14 +936,7 @@ fn test_prepare_payload_read_discrete_inputs ()
 
 fn prepare_payload_read_discrete_inputs ( starting_address : u16, quantity_of_inputs : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address );
-	append_word_to_bytearray ( &mut reply, 
-							   quantity_of_inputs );
-
-	return reply;
+	return encode_payload ( &RequestPdu::ReadDiscreteInputs { address : starting_address, quantity : quantity_of_inputs } );
 }
 
 //	===============================================================================================
@@ -831,14 +955,7 @@ fn test_prepare_payload_read_input_registers ()
 
 fn prepare_payload_read_input_registers ( starting_address : u16, quantity_of_input_registers : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address );
-	append_word_to_bytearray ( &mut reply, 
-							   quantity_of_input_registers );
-
-	return reply;	
+	return encode_payload ( &RequestPdu::ReadInputRegisters { address : starting_address, quantity : quantity_of_input_registers } );
 }
 
 //	===============================================================================================
@@ -857,14 +974,7 @@ fn test_prepare_payload_read_holding_registers ()
 
 fn prepare_payload_read_holding_registers ( starting_address : u16, quantity_of_registers : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-	
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address ); 
-	append_word_to_bytearray ( &mut reply, 
-							   quantity_of_registers );
-	
-	return reply;
+	return encode_payload ( &RequestPdu::ReadHoldingRegisters { address : starting_address, quantity : quantity_of_registers } );
 }
 
 //	===============================================================================================
@@ -887,20 +997,9 @@ fn test_prepare_payload_write_multiple_coils ()
 	assert_eq! ( result[ 6 ], 0xF0 );
 }
 
-fn prepare_payload_write_multiple_coils ( starting_address : u16, quantity_of_outputs : u16, output_values : &Vec< u8 > ) -> Vec< u8 >
+fn prepare_payload_write_multiple_coils ( starting_address : u16, quantity_of_outputs : u16, output_values : &[ u8 ] ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address );
-	append_word_to_bytearray ( &mut reply, 
-							   quantity_of_outputs );
-	append_byte_to_bytearray ( &mut reply, 
-							   output_values.len () as u8 );
-	append_bytearray_to_bytearray ( &mut reply, 
-									&output_values );
-
-	return reply;
+	return encode_payload ( &RequestPdu::WriteMultipleCoils { address : starting_address, values : Coils::Packed { bytes : output_values, quantity : quantity_of_outputs as usize } } );
 }
 
 //	===============================================================================================
@@ -928,22 +1027,9 @@ fn test_prepare_payload_write_multiple_registers ()
 	assert_eq! ( result[ 12 ], 0xCD );
 }
 
-fn prepare_payload_write_multiple_registers ( starting_address : u16, register_values : &Vec< u16 > ) -> Vec< u8 >
+fn prepare_payload_write_multiple_registers ( starting_address : u16, register_values : &[ u16 ] ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-	
-	append_word_to_bytearray ( &mut reply, 
-							   starting_address );
-	append_word_to_bytearray ( &mut reply, 
-							   register_values.len () as u16 );
-
-	let register_values_bytes : Vec< u8 > = transform_words_to_bytes ( &register_values );
-	append_byte_to_bytearray ( &mut reply, 
-							   register_values_bytes.len () as u8 );
-	append_bytearray_to_bytearray ( &mut reply, 
-									&register_values_bytes );
-
-	return reply;
+	return encode_payload ( &RequestPdu::WriteMultipleRegisters { address : starting_address, values : Registers::Words( register_values ) } );
 }
 
 //	===============================================================================================
@@ -971,14 +1057,7 @@ fn test_prepare_payload_write_single_coil ()
 
 fn prepare_payload_write_single_coil ( output_address : u16, output_value : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-	
-	append_word_to_bytearray ( &mut reply, 
-							   output_address );
-	append_word_to_bytearray ( &mut reply, 
-							   output_value );
-
-	return reply;
+	return encode_payload ( &RequestPdu::WriteSingleCoil { address : output_address, value : output_value == 0xFF00 } );
 }
 
 //	===============================================================================================
@@ -997,12 +1076,55 @@ fn test_prepare_payload_write_single_register ()
 
 fn prepare_payload_write_single_register ( register_address : u16, register_value : u16 ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
-	
-	append_word_to_bytearray ( &mut reply, 
-							   register_address );
-	append_word_to_bytearray ( &mut reply, 
-							   register_value );
+	return encode_payload ( &RequestPdu::WriteSingleRegister { address : register_address, value : register_value } );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_prepare_response_pdu ()
+{
+	let result : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_WRITE_SINGLE_REGISTER, 
+													&[ 0x00, 0x01, 0x00, 0x03 ] );
+	assert_eq! ( result, vec![ FUNCTION_CODE_WRITE_SINGLE_REGISTER, 0x00, 0x01, 0x00, 0x03 ] );
+}
+
+///	Prepends the function code to the payload of a response telegram,
+/// so it can be decoded as response PDU.
+fn prepare_response_pdu ( function_code : u8, payload : &[ u8 ] ) -> Vec< u8 >
+{
+	let mut reply : Vec< u8 > = vec![ function_code ];
+
+	reply.extend_from_slice ( payload );
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_prepare_response_mask_write_register ()
+{
+	let test_data : Vec< u8 > = vec![ 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25 ];
+
+	let result : Vec< u16 > = prepare_response_mask_write_register ( &test_data );
+	assert_eq! ( result, vec![ 0x0004, 0x00F2, 0x0025 ] );
+
+	let result_2 : Vec< u16 > = prepare_response_mask_write_register ( &vec![ 0x00, 0x04, 0x00, 0xF2 ] );
+	assert_eq! ( result_2.len (), 0 );
+}
+
+pub fn prepare_response_mask_write_register ( payload : &[ u8 ] ) -> Vec< u16 >
+{
+	let mut reply : Vec< u16 > = vec![];
+
+	let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_MASK_WRITE_REGISTER, 
+												  payload );
+
+	if let Ok( ResponsePdu::MaskWriteRegister { address, and_mask, or_mask } ) = ResponsePdu::decode ( &pdu )
+	{
+		reply = vec![ address, and_mask, or_mask ];
+	}
 
 	return reply;
 }
@@ -1038,38 +1160,60 @@ fn test_prepare_response_read_coils ()
 	assert_eq! ( result[ 18 ], true );
 }
 
-pub fn prepare_response_read_coils ( payload : &Vec< u8 >, coil_count : u16 ) -> Vec< bool >
+pub fn prepare_response_read_coils ( payload : &[ u8 ], coil_count : u16 ) -> Vec< bool >
 {
 	let mut reply : Vec< bool > = vec![];
 
-	if is_payload_read_coil_length_valid (&payload)
+	if is_payload_read_coil_length_valid ( payload )
 	{
-		if let Some( byte_count ) = extract_byte_from_bytearray ( &payload, 
-							 									  0 )
-		{
-			if let Some( coil_bytes ) = extract_bytes_from_bytearray ( &payload, 
-																	   1,
-																	   byte_count )
-			{
-				let mut byte_index : usize = 0;
-				let mut bit : u8 = 0;
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_READ_COILS, 
+														  payload );
 
-				for _ in 0..coil_count
+			if let Ok( ResponsePdu::ReadCoils { values } ) = ResponsePdu::decode ( &pdu )
+			{
+				if values.len () >= coil_count as usize
 				{
-					reply.push ( coil_bytes[ byte_index ] & ( 1 << bit ) != 0 );
-
-					if bit == 7
-					{
-						byte_index += 1;
-						bit = 0;
-					}
-					else
-					{
-						bit += 1;
-					}
+					reply = values.iter ().take ( coil_count as usize ).collect ();
 				}
 			}
+	}
+
+	return reply;
+}
+
+
+//	===============================================================================================
+
+#[test]
+fn test_prepare_register_bits ()
+{
+	let result : Vec< Vec< bool > > = prepare_register_bits ( &vec![ 0x8001, 0x0006 ] );
+	assert_eq! ( result.len (), 2 );
+	assert_eq! ( result[ 0 ].len (), 16 );
+	assert_eq! ( result[ 0 ][  0 ], true );
+	assert_eq! ( result[ 0 ][  1 ], false );
+	assert_eq! ( result[ 0 ][ 15 ], true );
+	assert_eq! ( result[ 1 ][  0 ], false );
+	assert_eq! ( result[ 1 ][  1 ], true );
+	assert_eq! ( result[ 1 ][  2 ], true );
+	assert_eq! ( result[ 1 ].iter ().filter ( | bit | **bit ).count (), 2 );
+}
+
+///	Unpacks each register into its 16 bits, starting with the least significant bit 0.
+pub fn prepare_register_bits ( registers : &Vec< u16 > ) -> Vec< Vec< bool > >
+{
+	let mut reply : Vec< Vec< bool > > = vec![];
+
+	for register in registers
+	{
+		let mut bits : Vec< bool > = vec![];
+
+		for bit in 0..16
+		{
+			bits.push ( register & ( 1 << bit ) != 0 );
 		}
+
+		reply.push ( bits );
 	}
 
 	return reply;
@@ -1077,6 +1221,56 @@ pub fn prepare_response_read_coils ( payload : &Vec< u8 >, coil_count : u16 ) ->
 
 //	===============================================================================================
 
+#[test]
+fn test_prepare_register_bit_masks ()
+{
+	assert_eq! ( prepare_register_bit_masks ( 0, true ), Some( ( 0xFFFE, 0x0001 ) ) );
+	assert_eq! ( prepare_register_bit_masks ( 15, false ), Some( ( 0x7FFF, 0x0000 ) ) );
+	assert_eq! ( prepare_register_bit_masks ( 16, true ), None );
+}
+
+///	Returns the AND and OR masks of a mask write register request
+/// which sets a single bit of a register to `value`.
+pub fn prepare_register_bit_masks ( bit : u8, value : bool ) -> Option< ( u16, u16 ) >
+{
+	let mut reply : Option< ( u16, u16 ) > = None;
+
+	if bit < 16
+	{
+		let bit_mask : u16 = 1 << bit;
+
+		if value
+		{
+			reply = Some( ( !bit_mask, bit_mask ) );
+		}
+		else
+		{
+			reply = Some( ( !bit_mask, 0x0000 ) );
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_apply_register_masks ()
+{
+	assert_eq! ( apply_register_masks ( 0x0012, 0x00F2, 0x0025 ), 0x0017 );
+	assert_eq! ( apply_register_masks ( 0x0000, 0xFFFE, 0x0001 ), 0x0001 );
+	assert_eq! ( apply_register_masks ( 0xFFFF, 0x7FFF, 0x0000 ), 0x7FFF );
+}
+
+///	Calculates the register content after a mask write register request,
+/// `( current AND and_mask ) OR ( or_mask AND ( NOT and_mask ) )`.
+pub fn apply_register_masks ( current_value : u16, and_mask : u16, or_mask : u16 ) -> u16
+{
+	return ( current_value & and_mask ) | ( or_mask & !and_mask );
+}
+
+//	===============================================================================================
+
 #[test]
 fn test_prepare_response_read_discrete_inputs ()
 {
@@ -1109,38 +1303,22 @@ fn test_prepare_response_read_discrete_inputs ()
 	assert_eq! ( result[ 21 ], true );
 }
 
-pub fn prepare_response_read_discrete_inputs ( payload : &Vec< u8 >, input_count : u16 ) -> Vec< bool >
+pub fn prepare_response_read_discrete_inputs ( payload : &[ u8 ], input_count : u16 ) -> Vec< bool >
 {
 	let mut reply : Vec< bool > = vec![];
 
-	if is_payload_read_coil_length_valid (&payload)
+	if is_payload_read_coil_length_valid ( payload )
 	{
-		if let Some( byte_count ) = extract_byte_from_bytearray ( &payload, 
-																  0 )
-		{
-			if let Some( input_bytes ) = extract_bytes_from_bytearray ( &payload, 
-																		1, 
-																		byte_count )
-			{
-				let mut byte_index : usize = 0;
-				let mut bit : u8 = 0;
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_READ_DISCRETE_INPUTS, 
+														  payload );
 
-				for _ in 0..input_count
+			if let Ok( ResponsePdu::ReadDiscreteInputs { values } ) = ResponsePdu::decode ( &pdu )
+			{
+				if values.len () >= input_count as usize
 				{
-					reply.push ( input_bytes[ byte_index ] & ( 1 << bit ) != 0 );
-
-					if bit == 7
-					{
-						byte_index += 1;
-						bit = 0;
-					}
-					else
-					{
-						bit += 1;
-					}
+					reply = values.iter ().take ( input_count as usize ).collect ();
 				}
 			}
-		}
 	}
 
 	return reply;
@@ -1160,25 +1338,19 @@ fn test_prepare_response_read_holding_registers ()
 	assert_eq! ( result[ 2 ], 0xFF00 );
 }
 
-pub fn prepare_response_read_holding_registers ( payload : &Vec< u8 > ) -> Vec< u16 >
+pub fn prepare_response_read_holding_registers ( payload : &[ u8 ] ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	if is_payload_read_register_length_valid (&payload)
+	if is_payload_read_register_length_valid ( payload )
 	{
-		if let Some( byte_count ) = extract_byte_from_bytearray ( &payload, 
-																  0 )
-		{
-			if let Some( register_values ) = extract_bytes_from_bytearray ( &payload, 
-																			1,
-																			byte_count )
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_READ_HOLDING_REGISTERS, 
+														  payload );
+
+			if let Ok( ResponsePdu::ReadHoldingRegisters { values } ) = ResponsePdu::decode ( &pdu )
 			{
-				let word_count : u8 = ( byte_count / 2 ) as u8;
-				reply = transform_bytes_to_words ( &register_values, 
-												   0,
-												   word_count );
+				reply = values.to_vec ();
 			}
-		}		
 	}
 
 	return reply;
@@ -1198,25 +1370,19 @@ fn test_prepare_response_read_input_registers ()
 	assert_eq! ( result[ 2 ], 0xFF00 );
 }
 
-pub fn prepare_response_read_input_registers ( payload : &Vec< u8 > ) -> Vec< u16 >
+pub fn prepare_response_read_input_registers ( payload : &[ u8 ] ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	if is_payload_read_register_length_valid (&payload)
+	if is_payload_read_register_length_valid ( payload )
 	{
-		if let Some( byte_count ) = extract_byte_from_bytearray ( &payload, 
-																  0 )
-		{
-			if let Some( register_values ) = extract_bytes_from_bytearray ( &payload, 
-																			1,
-																			byte_count )
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_READ_INPUT_REGISTERS, 
+														  payload );
+
+			if let Ok( ResponsePdu::ReadInputRegisters { values } ) = ResponsePdu::decode ( &pdu )
 			{
-				let words : u8 = ( byte_count / 2 ) as u8;
-				reply = transform_bytes_to_words ( &register_values, 
-												   0,
-												   words );
+				reply = values.to_vec ();
 			}
-		}		
 	}
 
 	return reply;
@@ -1235,15 +1401,19 @@ fn test_prepare_response_write_multiple_coils ()
 	assert_eq! ( result[ 1 ], 0x0003 );
 }
 
-pub fn prepare_response_write_multiple_coils ( payload : &Vec< u8 > ) -> Vec< u16 >
+pub fn prepare_response_write_multiple_coils ( payload : &[ u8 ] ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	if is_payload_write_length_valid ( &payload )
+	if is_payload_write_length_valid ( payload )
 	{
-		reply = transform_bytes_to_words ( &payload, 
-										   0,
-										   2 );
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_WRITE_MULTIPLE_COILS, 
+														  payload );
+
+			if let Ok( ResponsePdu::WriteMultipleCoils { address, quantity } ) = ResponsePdu::decode ( &pdu )
+			{
+				reply = vec![ address, quantity ];
+			}
 	}
 
 	return reply;
@@ -1262,23 +1432,19 @@ fn test_prepare_response_write_multiple_registers ()
 	assert_eq! ( result[ 1 ], 0x00FF );
 }
 
-pub fn prepare_response_write_multiple_registers ( payload : &Vec< u8 > ) -> Vec< u16 >
+pub fn prepare_response_write_multiple_registers ( payload : &[ u8 ] ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	if is_payload_write_length_valid ( &payload )
+	if is_payload_write_length_valid ( payload )
 	{
-		let option_address : Option< u16 > = extract_word_from_bytearray ( payload, 
-																		   0 );
-		let option_quantity : Option< u16 > = extract_word_from_bytearray ( payload, 
-																			2 );
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS, 
+														  payload );
 
-		if option_address.is_some () &&
-		   option_quantity.is_some ()
-		{
-			reply.push ( option_address.unwrap () );
-			reply.push ( option_quantity.unwrap () );
-		}
+			if let Ok( ResponsePdu::WriteMultipleRegisters { address, quantity } ) = ResponsePdu::decode ( &pdu )
+			{
+				reply = vec![ address, quantity ];
+			}
 	}
 
 	return reply;
@@ -1308,24 +1474,21 @@ fn test_prepare_response_write_single_coil ()
 	assert_eq! ( result_3[ 0 ], false );
 }
 
-pub fn prepare_response_write_single_coil ( payload : &Vec< u8 > ) -> Vec< bool >
+pub fn prepare_response_write_single_coil ( payload : &[ u8 ] ) -> Vec< bool >
 {
 	let mut reply : Vec< bool > = vec![];
 
-	if is_payload_write_length_valid ( &payload )
+	if is_payload_write_length_valid ( payload )
 	{
-		if let Some( word ) = extract_word_from_bytearray ( payload, 
-															2 )
-		{
-			if word == 0xFF00
-			{
-				reply.push ( true );
-			}
-			else
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_WRITE_SINGLE_COIL, 
+														  payload );
+
+			match ResponsePdu::decode ( &pdu )
 			{
-				reply.push ( false );
+				Ok( ResponsePdu::WriteSingleCoil { value, .. } )	=> { reply.push ( value ); }
+				Err( PduError::IllegalCoilValue( _ ) )				=> { reply.push ( false ); }
+				_													=> {}
 			}
-		}
 	}
 
 	return reply;
@@ -1344,15 +1507,19 @@ fn test_prepare_response_write_single_register ()
 	assert_eq! ( result[ 1 ], 0x0003 );
 }
 
-pub fn prepare_response_write_single_register ( payload : &Vec< u8 > ) -> Vec< u16 >
+pub fn prepare_response_write_single_register ( payload : &[ u8 ] ) -> Vec< u16 >
 {
 	let mut reply : Vec< u16 > = vec![];
 
-	if is_payload_write_length_valid ( &payload )
+	if is_payload_write_length_valid ( payload )
 	{
-		reply = transform_bytes_to_words ( &payload, 
-										   0,
-										   2 );
+			let pdu : Vec< u8 > = prepare_response_pdu ( FUNCTION_CODE_WRITE_SINGLE_REGISTER, 
+														  payload );
+
+			if let Ok( ResponsePdu::WriteSingleRegister { address, value } ) = ResponsePdu::decode ( &pdu )
+			{
+				reply = vec![ address, value ];
+			}
 	}
 
 	return reply;
@@ -1369,7 +1536,7 @@ fn test_is_payload_read_coil_length_valid ()
 	assert_eq!( is_payload_read_coil_length_valid ( &test_read_data ), false );
 }
 
-fn is_payload_read_coil_length_valid ( payload: &Vec< u8 > ) -> bool 
+fn is_payload_read_coil_length_valid ( payload : &[ u8 ] ) -> bool
 {
 	return payload.len () >= MODBUS_READ_COIL_MINIMUM_PAYLOAD_LENGTH;
 }
@@ -1385,7 +1552,7 @@ fn test_is_payload_read_register_length_valid ()
 	assert_eq!( is_payload_read_register_length_valid ( &test_read_data ), false );
 }
 
-fn is_payload_read_register_length_valid ( payload: &Vec< u8 > ) -> bool 
+fn is_payload_read_register_length_valid ( payload : &[ u8 ] ) -> bool
 {
 	return payload.len () >= MODBUS_READ_REGISTER_MINIMUM_PAYLOAD_LENGTH;
 }
@@ -1401,7 +1568,7 @@ fn test_is_payload_write_length_valid ()
 	assert_eq!( is_payload_write_length_valid ( &test_write_data ), false );	
 }
 
-fn is_payload_write_length_valid ( payload: &Vec< u8 > ) -> bool 
+fn is_payload_write_length_valid ( payload : &[ u8 ] ) -> bool
 {
 	return payload.len () == MODBUS_WRITE_MINIMUM_PAYLOAD_LENGTH;
 }
@@ -1420,18 +1587,7 @@ fn test_is_start_and_quantity_ok ()
 
 fn is_start_and_quantity_ok ( start : u16, quantity : u16 ) -> bool
 {
-	let reply : bool;
-
-	if ( start as u32 + quantity as u32 ) <= 0x0000FFFF
-	{
-		reply = true;
-	}
-	else
-	{
-		reply = false;
-	}
-
-	return reply;
+	return ( start as u32 + quantity as u32 ) <= 0x0000FFFF;
 }
 
 //	===============================================================================================
@@ -1448,18 +1604,21 @@ fn test_is_value_in_range ()
 
 fn is_value_in_range ( value : u16, min_value : u16, max_value : u16 ) -> bool
 {
-	let reply : bool;
+	return value >= min_value && value <= max_value;
+}
 
-	if value >= min_value && value <= max_value
-	{
-		reply = true;
-	}
-	else
-	{
-		reply = false;
-	}
+//	===============================================================================================
 
-	return reply;
+#[test]
+fn test_verify_parameter_mask_write_register ()
+{
+	let result_1 : Result< bool, String > = verify_parameter_mask_write_register ();
+	assert! ( result_1.is_ok () );
+}
+
+fn verify_parameter_mask_write_register () -> Result< bool, String >
+{
+	return Ok( true );
 }
 
 //	===============================================================================================
@@ -1,9 +1,20 @@
 
 
-pub mod consts;
+pub mod batchreader;
+pub mod datatable;
 pub mod datatransformation;
+pub mod decoder;
+pub mod discovery;
 pub mod ethernet;
 pub mod methods;
 pub mod modbusreturn;
 pub mod modbustelegram;
+pub mod scaling;
+pub mod statistics;
+#[cfg( test )]
+pub mod testmaster;
 pub mod timehandling;
+pub mod transport;
+
+pub use protocol::consts;
+pub use protocol::pdu;
@@ -5,6 +5,7 @@ use std::fmt;
 //  ===============================================================================================
 
 #[derive( Debug )] 
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
 pub enum ModbusReturnCoils
 {
     Bad( ReturnBad ),
@@ -101,10 +102,10 @@ fn test_modbus_return_coils_on_bad ()
 {
     let result : ModbusReturnCoils = ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( 0x01, 
                                                                                          0x01 ) );
-    assert_eq! ( result.is_none (), false );
-    assert_eq! ( result.is_some (), true );
-    assert_eq! ( result.is_bad (), true );
-    assert_eq! ( result.is_good (), false );
+    assert! ( !result.is_none () );
+    assert! ( result.is_some () );
+    assert! ( result.is_bad () );
+    assert! ( !result.is_good () );
 
     let result_data : ReturnBad = result.unwrap_bad ();
     assert_eq! ( result_data.get_error_code (), 0x01 ); 
@@ -117,10 +118,10 @@ fn test_modbus_return_coils_on_good ()
 
     let result : ModbusReturnCoils = ModbusReturnCoils::Good( ReturnGood::new ( test_data,
                                                                                 250 ) );
-    assert_eq! ( result.is_none (), false );
-    assert_eq! ( result.is_some (), true );
-    assert_eq! ( result.is_bad (), false );
-    assert_eq! ( result.is_good (), true );
+    assert! ( !result.is_none () );
+    assert! ( result.is_some () );
+    assert! ( !result.is_bad () );
+    assert! ( result.is_good () );
 
     let mut result_good : ReturnGood< bool > = result.unwrap_good ();
     assert_eq! ( result_good.get_duration_in_milliseconds (), 250 );
@@ -133,10 +134,10 @@ fn test_modbus_return_coils_on_good ()
 fn test_modbus_return_coils_on_none ()
 {
     let result : ModbusReturnCoils = ModbusReturnCoils::None;
-    assert_eq! ( result.is_none (), true );
-    assert_eq! ( result.is_some (), false );
-    assert_eq! ( result.is_bad (), false );
-    assert_eq! ( result.is_good (), false );
+    assert! ( result.is_none () );
+    assert! ( !result.is_some () );
+    assert! ( !result.is_bad () );
+    assert! ( !result.is_good () );
 }
 
 //	===============================================================================================
@@ -174,6 +175,7 @@ impl fmt::Display for ModbusReturnCoils
 //	===============================================================================================
 
 #[derive( Debug )] 
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
 pub enum ModbusReturnRegisters
 {
     Bad( ReturnBad ),
@@ -270,10 +272,10 @@ fn test_modbus_return_registers_on_bad ()
 {
     let result : ModbusReturnRegisters = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( 0x01, 
                                                                                                  0x01 ) );
-    assert_eq! ( result.is_none (), false );
-    assert_eq! ( result.is_some (), true );
-    assert_eq! ( result.is_bad (), true );
-    assert_eq! ( result.is_good (), false );
+    assert! ( !result.is_none () );
+    assert! ( result.is_some () );
+    assert! ( result.is_bad () );
+    assert! ( !result.is_good () );
 
     let result_data : ReturnBad = result.unwrap_bad ();
     assert_eq! ( result_data.get_error_code (), 0x01 ); 
@@ -286,10 +288,10 @@ fn test_modbus_return_registers_on_good ()
 
     let result : ModbusReturnRegisters = ModbusReturnRegisters::Good( ReturnGood::new ( test_data,
                                                                                         250 ) );
-    assert_eq! ( result.is_none (), false );
-    assert_eq! ( result.is_some (), true );
-    assert_eq! ( result.is_bad (), false );
-    assert_eq! ( result.is_good (), true );
+    assert! ( !result.is_none () );
+    assert! ( result.is_some () );
+    assert! ( !result.is_bad () );
+    assert! ( result.is_good () );
 
     let mut result_good : ReturnGood< u16 > = result.unwrap_good ();
     assert_eq! ( result_good.get_duration_in_milliseconds (), 250 );
@@ -302,10 +304,10 @@ fn test_modbus_return_registers_on_good ()
 fn test_modbus_return_registers_on_none ()
 {
     let result : ModbusReturnRegisters = ModbusReturnRegisters::None;
-    assert_eq! ( result.is_none (), true );
-    assert_eq! ( result.is_some (), false );
-    assert_eq! ( result.is_bad (), false );
-    assert_eq! ( result.is_good (), false );
+    assert! ( result.is_none () );
+    assert! ( !result.is_some () );
+    assert! ( !result.is_bad () );
+    assert! ( !result.is_good () );
 }
 
 //	===============================================================================================
@@ -343,6 +345,7 @@ impl fmt::Display for ModbusReturnRegisters
 //	===============================================================================================
 
 #[derive( Debug )] 
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
 pub struct ReturnBad
 {
     error_code : Option< u8 >,
@@ -380,9 +383,9 @@ impl ReturnBad
     {
         let mut reply : u8 = 0x00;
 
-        if self.error_code.is_some ()
+        if let Some( error_code ) = self.error_code
         {
-            reply = self.error_code.unwrap ();            
+            reply = error_code;
         }
 
         return reply;
@@ -392,9 +395,9 @@ impl ReturnBad
     {
         let mut reply : u8 = 0x00;
 
-        if self.exception_code.is_some ()
+        if let Some( exception_code ) = self.exception_code
         {
-            reply = self.exception_code.unwrap ();            
+            reply = exception_code;
         }
 
         return reply;
@@ -404,9 +407,9 @@ impl ReturnBad
     {
         let mut reply : String = "Err".to_string ();
 
-        if self.message.is_some ()
+        if let Some( message ) = self.message
         {
-            reply = self.message.unwrap ();
+            reply = message;
         }
 
         return reply;
@@ -420,7 +423,7 @@ fn test_return_bad_by_new_with_codes ()
                                                          0x01 );
     assert_eq! ( result.get_error_code (), 0x01 );
     assert_eq! ( result.get_exception_code (), 0x01 );
-    assert! ( result.get_message ().len () > 0 );
+    assert! ( !result.get_message ().is_empty () );
 }
 
 #[test]
@@ -449,9 +452,13 @@ fn test_get_message_of_exception_code ()
 
     let result_4 : Option< String > = get_message_of_exception_code ( 0x04 );
     assert! ( result_4.is_some () );
+
+    let result_5 : Option< String > = get_message_of_exception_code ( 0x0B );
+    assert! ( result_5.unwrap ().contains ( "GATEWAY" ) );
 }
 
-fn get_message_of_exception_code ( code : u8 ) -> Option< String >
+///	Returns the message of a MODBUS exception code.
+pub fn get_message_of_exception_code ( code : u8 ) -> Option< String >
 {
     let reply : Option< String >;
  
@@ -462,6 +469,8 @@ fn get_message_of_exception_code ( code : u8 ) -> Option< String >
             0x02 => "Exception Code 02 - ILLEGAL DATA ADDRESS".to_string (),
             0x03 => "Exception Code 03 - ILLEGAL DATA VALUE".to_string (),
             0x04 => "Exception Code 04 - SERVER DEVICE FAILURE".to_string (),
+            0x0A => "Exception Code 0A - GATEWAY PATH UNAVAILABLE".to_string (),
+            0x0B => "Exception Code 0B - GATEWAY TARGET DEVICE FAILED TO RESPOND".to_string (),
             _ => "Unknown Exception Code".to_string ()
         };
 
@@ -473,6 +482,7 @@ fn get_message_of_exception_code ( code : u8 ) -> Option< String >
 //	===============================================================================================
 
 #[derive( Debug )] 
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
 pub struct ReturnGood< T >
 {
     data : Option< Vec< T > >,
@@ -512,9 +522,9 @@ impl< T > ReturnGood< T >
     {
         let reply : u64;
 
-        if self.duration_in_milliseconds.is_some ()
+        if let Some( duration_in_milliseconds ) = self.duration_in_milliseconds
         {
-            reply = *self.duration_in_milliseconds.as_ref ().unwrap ();
+            reply = duration_in_milliseconds;
         }
         else
         {
@@ -539,3 +549,25 @@ fn test_return_good ()
     assert_eq! ( result_data[ 0 ], 0xFF00 );
     assert_eq! ( result_data[ 1 ], 0x00FF );
 }
+
+//	===============================================================================================
+
+#[cfg( all( test, feature = "json" ) )]
+use serde_json;
+
+#[cfg( feature = "json" )]
+#[test]
+fn test_return_serialization ()
+{
+    let good : ModbusReturnRegisters = ModbusReturnRegisters::Good( ReturnGood::new ( vec![ 230, 7 ], 4 ) );
+    let good_text : String = serde_json::to_string ( &good ).unwrap ();
+    assert_eq! ( good_text, r#"{"Good":{"data":[230,7],"duration_in_milliseconds":4}}"# );
+    assert_eq! ( serde_json::from_str::< ModbusReturnRegisters > ( &good_text ).unwrap ().unwrap_good ().get_data (), vec![ 230, 7 ] );
+
+    let bad : ModbusReturnCoils = ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( 0x81, 0x02 ) );
+    let bad_text : String = serde_json::to_string ( &bad ).unwrap ();
+    assert_eq! ( bad_text, r#"{"Bad":{"error_code":129,"exception_code":2,"message":"Exception Code 02 - ILLEGAL DATA ADDRESS"}}"# );
+    assert_eq! ( serde_json::from_str::< ModbusReturnCoils > ( &bad_text ).unwrap ().unwrap_bad ().get_exception_code (), 0x02 );
+
+    assert_eq! ( serde_json::to_string ( &ModbusReturnCoils::None ).unwrap (), r#""None""# );
+}
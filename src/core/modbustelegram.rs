@@ -2,21 +2,26 @@
 
 use core::consts::*;
 use core::datatransformation::*;
+#[cfg( feature = "serde" )]
+use std::convert::TryFrom;
+#[cfg( all( test, feature = "json" ) )]
+use serde_json;
 
 //	===============================================================================================
 
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ), serde( try_from = "ModbusTelegramFields" ) )]
 pub struct ModbusTelegram
 {
 	transaction_identifier : u16,
 	unit_identifier : u8,
 	function_code : u8,
 	payload : Vec< u8 >,
-	expected_bytes : u8
+	expected_bytes : u16
 }
 
 impl ModbusTelegram
 {
-	pub fn new ( transaction_identifier : u16, unit_identifier : u8, function_code : u8, payload : &Vec< u8 >, expected_bytes : u8 ) -> Option< ModbusTelegram >
+	pub fn new ( transaction_identifier : u16, unit_identifier : u8, function_code : u8, payload : &[ u8 ], expected_bytes : u16 ) -> Option< ModbusTelegram >
 	{
 		let reply : Option< ModbusTelegram >;
 
@@ -28,7 +33,7 @@ impl ModbusTelegram
 					transaction_identifier : transaction_identifier,
 					unit_identifier : unit_identifier,
 					function_code : function_code,
-					payload : payload.clone (),
+					payload : payload.to_vec (),
 					expected_bytes : expected_bytes
 				}
 			);
@@ -41,34 +46,34 @@ impl ModbusTelegram
 		return reply;
 	}
 
-	pub fn new_from_bytes ( bytes : &Vec< u8 > ) -> Option< ModbusTelegram >
+	pub fn new_from_bytes ( bytes : &[ u8 ] ) -> Option< ModbusTelegram >
 	{
 		let reply : Option< ModbusTelegram >;
 
-		if bytes.len () > 9
+		if bytes.len () > 8
 		{
-			let response_transaction_identifier : Option< u16 > = extract_word_from_bytearray ( &bytes, 
+			let response_transaction_identifier : Option< u16 > = extract_word_from_bytearray ( bytes, 
 																								0 );
-			let response_unit_identifier : Option< u8 > = extract_byte_from_bytearray ( &bytes, 
+			let response_unit_identifier : Option< u8 > = extract_byte_from_bytearray ( bytes, 
 																						6 );
-			let response_function_code : Option< u8 > =	extract_byte_from_bytearray ( &bytes, 
+			let response_function_code : Option< u8 > =	extract_byte_from_bytearray ( bytes, 
 																					  7 );
 			let function_code : u8 = response_function_code.unwrap ();
 			let response_payload : Option< Vec< u8 > > = extract_payload_by_function_code ( function_code, 
-																							&bytes );
+																							bytes );
 
-			if response_transaction_identifier.is_some () &&
-			   response_unit_identifier.is_some () &&
-			   response_payload.is_some ()
+			if let ( Some( transaction_identifier ), Some( unit_identifier ), Some( payload ) ) = ( response_transaction_identifier,
+																										  response_unit_identifier,
+																										  response_payload )
 			{
 				reply =	Some(
 					ModbusTelegram
 					{
-						transaction_identifier : response_transaction_identifier.unwrap (),
-						unit_identifier : response_unit_identifier.unwrap (),
+						transaction_identifier : transaction_identifier,
+						unit_identifier : unit_identifier,
 						function_code :	function_code,
-						payload : response_payload.unwrap (),
-						expected_bytes : 0x00
+						payload : payload,
+						expected_bytes : 0x0000
 					}
 				);
 			}
@@ -107,11 +112,24 @@ impl ModbusTelegram
 		return Some( reply );
 	}
 
-	pub fn get_expected_byte_count ( &self ) -> Option< u8 >
+	///	Returns the exception code of an exception response.
+	pub fn get_exception_code ( &self ) -> Option< u8 >
 	{
-		let reply : Option< u8 >;
+		let mut reply : Option< u8 > = None;
 
-		if self.expected_bytes > MODBUS_HEADER_SIZE
+		if self.function_code & MODBUS_EXCEPTION_FLAG != 0
+		{
+			reply = self.payload.first ().cloned ();
+		}
+
+		return reply;
+	}
+
+	pub fn get_expected_byte_count ( &self ) -> Option< u16 >
+	{
+		let reply : Option< u16 >;
+
+		if self.expected_bytes > MODBUS_HEADER_SIZE as u16
 		{
 			reply = Some( self.expected_bytes );			
 		}
@@ -143,11 +161,50 @@ impl ModbusTelegram
 	{
 		return Some( self.payload.clone () );
 	}
+
+	pub fn get_transaction_identifier ( &self ) -> u16
+	{
+		return self.transaction_identifier;
+	}
+
+	pub fn get_unit_identifier ( &self ) -> u8
+	{
+		return self.unit_identifier;
+	}
+}
+
+///	Deserialized telegrams are created by `ModbusTelegram::new`, which rejects
+/// a transaction identifier or function code of 0.
+#[cfg( feature = "serde" )]
+#[derive( Deserialize )]
+struct ModbusTelegramFields
+{
+	transaction_identifier : u16,
+	unit_identifier : u8,
+	function_code : u8,
+	payload : Vec< u8 >,
+	expected_bytes : u16
+}
+
+#[cfg( feature = "serde" )]
+impl TryFrom< ModbusTelegramFields > for ModbusTelegram
+{
+	type Error = String;
+
+	fn try_from ( fields : ModbusTelegramFields ) -> Result< ModbusTelegram, String >
+	{
+		return ModbusTelegram::new ( fields.transaction_identifier,
+									 fields.unit_identifier,
+									 fields.function_code,
+									 &fields.payload,
+									 fields.expected_bytes ).ok_or ( "invalid transaction identifier or function code".to_string () );
+	}
 }
 
 //	===============================================================================================
 
 #[test]
+#[allow( clippy::vec_init_then_push )]
 fn test_extract_payload_by_function_code ()
 {
 	let mut test_data_1 : Vec< u8 > = vec![];
@@ -205,22 +262,74 @@ fn test_extract_payload_by_function_code ()
 	assert_eq! ( result_bytes_2[ 1 ], 0x00 );
 	assert_eq! ( result_bytes_2[ 2 ], 0x00 );
 	assert_eq! ( result_bytes_2[ 3 ], 0x10 );	
+
+	let test_data_3 : Vec< u8 > = vec![ 0x00, 0xA0, 0x00, 0x00, 0x00, 0x03, 0x01, 0x96, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00 ];
+
+	let result_data_3 : Option< Vec< u8 > > = extract_payload_by_function_code ( 0x96,
+																				 &test_data_3 );
+	assert_eq! ( result_data_3, Some( vec![ 0x01 ] ) );
+}
+
+#[test]
+fn test_new_from_bytes_with_exception ()
+{
+	let test_data : Vec< u8 > = vec![ 0x00, 0xA0, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02 ];
+
+	let result : Option< ModbusTelegram > = ModbusTelegram::new_from_bytes ( &test_data );
+	assert! ( result.is_some () );
+
+	let telegram : ModbusTelegram = result.unwrap ();
+	assert_eq! ( telegram.get_function_code (), Some( 0x83 ) );
+	assert_eq! ( telegram.get_exception_code (), Some( 0x02 ) );
+
+	let test_data_2 : Vec< u8 > = vec![ 0x00, 0xA0, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x01, 0x00, 0x03 ];
+	assert_eq! ( ModbusTelegram::new_from_bytes ( &test_data_2 ).unwrap ().get_exception_code (), None );
+}
+
+#[cfg( feature = "json" )]
+#[test]
+fn test_telegram_deserialization ()
+{
+	let text : &str = r#"{"transaction_identifier":1,"unit_identifier":1,"function_code":3,"payload":[0,0,0,1],"expected_bytes":11}"#;
+
+	let telegram : ModbusTelegram = serde_json::from_str ( text ).unwrap ();
+	assert_eq! ( serde_json::to_string ( &telegram ).unwrap (), text );
+
+	assert! ( serde_json::from_str::< ModbusTelegram > ( &text.replace ( "\"transaction_identifier\":1", "\"transaction_identifier\":0" ) ).is_err () );
+	assert! ( serde_json::from_str::< ModbusTelegram > ( &text.replace ( "\"function_code\":3", "\"function_code\":0" ) ).is_err () );
 }
 
-fn extract_payload_by_function_code ( function_code : u8, bytes : &Vec< u8 > ) -> Option< Vec< u8 > >
+#[test]
+fn test_new_from_bytes_minimum_length ()
+{
+	let exception : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x96, 0x01 ];
+	let telegram : Option< ModbusTelegram > = ModbusTelegram::new_from_bytes ( &exception );
+	assert_eq! ( telegram.map ( | telegram | telegram.get_exception_code () ), Some( Some( 0x01 ) ) );
+
+	let truncated : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x96 ];
+	assert! ( ModbusTelegram::new_from_bytes ( &truncated ).is_none () );
+
+	let empty_read : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x03, 0x00 ];
+	assert_eq! ( ModbusTelegram::new_from_bytes ( &empty_read ).map ( | telegram | telegram.get_payload () ), Some( Some( vec![ 0x00 ] ) ) );
+}
+
+fn extract_payload_by_function_code ( function_code : u8, bytes : &[ u8 ] ) -> Option< Vec< u8 > >
 {
 	let reply : Option< Vec< u8 > >;
 
 	match function_code
 	{
-		0x01	=> { reply = extract_payload_with_byte_count ( &bytes ); }
-		0x02	=> { reply = extract_payload_with_byte_count ( &bytes ); }
-		0x03	=> { reply = extract_payload_with_byte_count ( &bytes ); }
-		0x04	=> { reply = extract_payload_with_byte_count ( &bytes ); }
-		0x05	=> { reply = extract_payload_without_byte_count ( &bytes ); }
-		0x06	=> { reply = extract_payload_without_byte_count ( &bytes ); }
-		0x0F	=> { reply = extract_payload_without_byte_count ( &bytes ); }
-		0x10	=> { reply = extract_payload_without_byte_count ( &bytes ); }
+		0x01	=> { reply = extract_payload_with_byte_count ( bytes ); }
+		0x02	=> { reply = extract_payload_with_byte_count ( bytes ); }
+		0x03	=> { reply = extract_payload_with_byte_count ( bytes ); }
+		0x04	=> { reply = extract_payload_with_byte_count ( bytes ); }
+		0x05	=> { reply = extract_payload_without_byte_count ( bytes ); }
+		0x06	=> { reply = extract_payload_without_byte_count ( bytes ); }
+		0x0F	=> { reply = extract_payload_without_byte_count ( bytes ); }
+		0x10	=> { reply = extract_payload_without_byte_count ( bytes ); }
+		0x16	=> { reply = extract_payload_without_byte_count ( bytes ); }
+		code if code & MODBUS_EXCEPTION_FLAG != 0
+				=> { reply = extract_bytes_from_bytearray ( bytes, 8, 1 ); }
 		_		=> { reply = None; }
 	}
 
@@ -230,6 +339,7 @@ fn extract_payload_by_function_code ( function_code : u8, bytes : &Vec< u8 > ) -
 //	===============================================================================================
 
 #[test]
+#[allow( clippy::vec_init_then_push )]
 fn test_extract_payload_with_byte_count ()
 {
 	let mut test_data : Vec< u8 > = vec![];
@@ -264,17 +374,18 @@ fn test_extract_payload_with_byte_count ()
 	assert_eq! ( result_bytes[ 6 ], 0x00 );
 }
 
-fn extract_payload_with_byte_count ( bytes : &Vec< u8 > ) -> Option< Vec< u8 > >
+fn extract_payload_with_byte_count ( bytes : &[ u8 ] ) -> Option< Vec< u8 > >
 {
 	let reply : Option< Vec< u8 > >;
 
-	let byte_count : Option< u8 > = extract_byte_from_bytearray ( &bytes, 
+	let byte_count : Option< u8 > = extract_byte_from_bytearray ( bytes, 
 																  8 );
-	let count : u8 = byte_count.unwrap () + 1;
 
-	reply = extract_bytes_from_bytearray ( &bytes, 
-										   8, 
-										   count );
+	match byte_count
+	{
+		Some( count ) if count < 0xFF	=> { reply = extract_bytes_from_bytearray ( bytes, 8, count + 1 ); }
+		_								=> { reply = None; }
+	}
 
 	return reply;
 }
@@ -282,6 +393,7 @@ fn extract_payload_with_byte_count ( bytes : &Vec< u8 > ) -> Option< Vec< u8 > >
 //	===============================================================================================
 
 #[test]
+#[allow( clippy::vec_init_then_push )]
 fn test_extract_payload_without_byte_count ()
 {
 	let mut test_data : Vec< u8 > = vec![];
@@ -310,13 +422,13 @@ fn test_extract_payload_without_byte_count ()
 	assert_eq! ( result_bytes[ 3 ], 0x10 );
 }
 
-fn extract_payload_without_byte_count ( bytes : &Vec< u8 > ) -> Option< Vec< u8 > >
+fn extract_payload_without_byte_count ( bytes : &[ u8 ] ) -> Option< Vec< u8 > >
 {
 	let reply : Option< Vec< u8 > >;
 
 	let byte_count : u8 = bytes.len () as u8 - MODBUS_HEADER_SIZE - 1; // -1 for FunctionCode
 
-	reply = extract_bytes_from_bytearray ( &bytes, 
+	reply = extract_bytes_from_bytearray ( bytes, 
 										   8, 
 										   byte_count );
 
@@ -0,0 +1,255 @@
+const SCALING_ROUNDING_FACTOR : f64 = 1_000_000.0;
+
+//	===============================================================================================
+
+/// Linear conversion between raw register values and engineering values
+/// `engineering value = raw value * gain + offset`.
+///
+/// Read values are clamped to the engineering range, written values
+/// outside of it are refused before a request is built.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::core::scaling::Scaling;
+///
+/// let mut temperature = Scaling::new_signed(0.1, 0.0);
+/// temperature.set_range(-40.0, 125.0).unwrap();
+///
+/// assert_eq!(temperature.scale_register(0xFF9C), -10.0);
+/// assert_eq!(temperature.unscale_register(21.5), Ok(215));
+/// assert!(temperature.unscale_register(130.0).is_err());
+/// ```
+#[derive( Clone, Copy, Debug, PartialEq )]
+pub struct Scaling
+{
+	gain : f64,
+	offset : f64,
+	signed : bool,
+	minimum : f64,
+	maximum : f64
+}
+
+impl Scaling
+{
+	///	Creates a scaling for unsigned raw values without a range limit.
+	pub fn new ( gain : f64, offset : f64 ) -> Scaling
+	{
+		return Scaling
+		{
+			gain : gain,
+			offset : offset,
+			signed : false,
+			minimum : f64::NEG_INFINITY,
+			maximum : f64::INFINITY
+		};
+	}
+
+	///	Creates a scaling which reads single registers as two's complement `i16`.
+	pub fn new_signed ( gain : f64, offset : f64 ) -> Scaling
+	{
+		let mut reply : Scaling = Scaling::new ( gain,
+												 offset );
+		reply.signed = true;
+
+		return reply;
+	}
+
+	///	Creates a scaling which leaves the values unchanged.
+	pub fn new_identity () -> Scaling
+	{
+		return Scaling::new ( 1.0,
+							  0.0 );
+	}
+
+	///	Sets the engineering range, infinite limits leave a side open.
+	pub fn set_range ( &mut self, minimum : f64, maximum : f64 ) -> Result< (), String >
+	{
+		if minimum.is_nan () || maximum.is_nan () || minimum > maximum
+		{
+			return Err( format! ( "invalid range {} to {}", minimum, maximum ) );
+		}
+
+		self.minimum = minimum;
+		self.maximum = maximum;
+
+		return Ok( () );
+	}
+
+	pub fn get_gain ( &self ) -> f64
+	{
+		return self.gain;
+	}
+
+	pub fn get_maximum ( &self ) -> f64
+	{
+		return self.maximum;
+	}
+
+	pub fn get_minimum ( &self ) -> f64
+	{
+		return self.minimum;
+	}
+
+	pub fn get_offset ( &self ) -> f64
+	{
+		return self.offset;
+	}
+
+	pub fn is_signed ( &self ) -> bool
+	{
+		return self.signed;
+	}
+
+	///	Converts a raw number into the engineering value, clamped to the range.
+	pub fn scale_value ( &self, raw_value : f64 ) -> f64
+	{
+		let value : f64 = raw_value * self.gain + self.offset;
+
+		return value.max ( self.minimum ).min ( self.maximum );
+	}
+
+	///	Converts an engineering value back into a raw number. The result is
+	/// rounded to 6 decimal places, so gains like 0.1 which have no exact binary
+	/// representation do not turn 2.35 into 23.499999999999996.
+	pub fn unscale_value ( &self, value : f64 ) -> Result< f64, String >
+	{
+		let reply : Result< f64, String >;
+
+		if value.is_nan ()
+		{
+			reply = Err( "value is not a number".to_string () );
+		}
+		else if value < self.minimum || value > self.maximum
+		{
+			reply = Err( format! ( "value {} is out of the range {} to {}", value, self.minimum, self.maximum ) );
+		}
+		else if self.gain == 0.0
+		{
+			reply = Err( "a gain of 0 can not be inverted".to_string () );
+		}
+		else
+		{
+			let raw_value : f64 = ( value - self.offset ) / self.gain;
+
+			reply = Ok( ( raw_value * SCALING_ROUNDING_FACTOR ).round () / SCALING_ROUNDING_FACTOR );
+		}
+
+		return reply;
+	}
+
+	///	Converts a single register into the engineering value.
+	pub fn scale_register ( &self, register : u16 ) -> f64
+	{
+		let raw_value : f64;
+
+		if self.signed
+		{
+			raw_value = register as i16 as f64;
+		}
+		else
+		{
+			raw_value = register as f64;
+		}
+
+		return self.scale_value ( raw_value );
+	}
+
+	///	Converts an engineering value into a single register. Halves are
+	/// rounded away from zero, raw values which do not fit are refused.
+	pub fn unscale_register ( &self, value : f64 ) -> Result< u16, String >
+	{
+		let reply : Result< u16, String >;
+
+		let raw_value : f64 = self.unscale_value ( value )?.round ();
+
+		if self.signed && ( -32768.0..=32767.0 ).contains ( &raw_value )
+		{
+			reply = Ok( raw_value as i16 as u16 );
+		}
+		else if !self.signed && ( 0.0..=65535.0 ).contains ( &raw_value )
+		{
+			reply = Ok( raw_value as u16 );
+		}
+		else
+		{
+			reply = Err( format! ( "value {} does not fit into a register", value ) );
+		}
+
+		return reply;
+	}
+
+	pub fn scale_registers ( &self, registers : &[ u16 ] ) -> Vec< f64 >
+	{
+		return registers.iter ().map ( | register | self.scale_register ( *register ) ).collect ();
+	}
+
+	///	Converts all values or none if one of them is refused.
+	pub fn unscale_registers ( &self, values : &[ f64 ] ) -> Result< Vec< u16 >, String >
+	{
+		return values.iter ().map ( | value | self.unscale_register ( *value ) ).collect ();
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_scale_register ()
+{
+	let mut scaling : Scaling = Scaling::new_signed ( 0.1,
+													  -40.0 );
+	assert_eq! ( scaling.scale_register ( 650 ), 25.0 );
+	assert_eq! ( scaling.scale_register ( 0xFF9C ), -50.0 );
+
+	scaling.set_range ( -45.0, 80.0 ).unwrap ();
+	assert_eq! ( scaling.scale_register ( 0xFF9C ), -45.0 );
+	assert_eq! ( scaling.scale_register ( 1500 ), 80.0 );
+
+	let unsigned : Scaling = Scaling::new ( 0.5,
+											0.0 );
+	assert_eq! ( unsigned.scale_register ( 0xFF9C ), 32718.0 );
+	assert_eq! ( unsigned.scale_registers ( &[ 2, 4 ] ), vec![ 1.0, 2.0 ] );
+
+	assert! ( scaling.set_range ( 10.0, 0.0 ).is_err () );
+	assert! ( scaling.set_range ( f64::NAN, 0.0 ).is_err () );
+}
+
+#[test]
+fn test_unscale_register_rounding ()
+{
+	let scaling : Scaling = Scaling::new_signed ( 0.1,
+												  0.0 );
+	assert_eq! ( scaling.unscale_register ( 2.34 ), Ok( 23 ) );
+	assert_eq! ( scaling.unscale_register ( 2.35 ), Ok( 24 ) );
+	assert_eq! ( scaling.unscale_register ( 2.36 ), Ok( 24 ) );
+	assert_eq! ( scaling.unscale_register ( -2.35 ), Ok( 0xFFE8 ) );
+	assert_eq! ( scaling.unscale_register ( -0.04 ), Ok( 0 ) );
+
+	let unsigned : Scaling = Scaling::new ( 1.0,
+											0.0 );
+	assert_eq! ( unsigned.unscale_register ( 12.4 ), Ok( 12 ) );
+	assert_eq! ( unsigned.unscale_register ( 12.5 ), Ok( 13 ) );
+	assert_eq! ( unsigned.unscale_register ( 65535.4 ), Ok( 65535 ) );
+	assert! ( unsigned.unscale_register ( 65535.5 ).is_err () );
+	assert! ( unsigned.unscale_register ( -0.5 ).is_err () );
+	assert_eq! ( unsigned.unscale_register ( -0.4 ), Ok( 0 ) );
+}
+
+#[test]
+fn test_unscale_register_validation ()
+{
+	let mut scaling : Scaling = Scaling::new ( 0.01,
+											   0.0 );
+	scaling.set_range ( 0.0, 100.0 ).unwrap ();
+
+	assert_eq! ( scaling.unscale_register ( 100.0 ), Ok( 10000 ) );
+	assert! ( scaling.unscale_register ( 100.01 ).is_err () );
+	assert! ( scaling.unscale_register ( -0.01 ).is_err () );
+	assert! ( scaling.unscale_register ( f64::NAN ).is_err () );
+	assert! ( scaling.unscale_registers ( &[ 1.0, 200.0 ] ).is_err () );
+	assert_eq! ( scaling.unscale_registers ( &[ 1.0, 2.0 ] ), Ok( vec![ 100, 200 ] ) );
+
+	assert! ( Scaling::new ( 0.0, 1.0 ).unscale_register ( 1.0 ).is_err () );
+	assert! ( Scaling::new ( 0.001, 0.0 ).unscale_register ( 70.0 ).is_err () );
+}
@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+//	===============================================================================================
+
+///	The number of latest latencies kept for the percentiles.
+const STATISTICS_LATENCY_SAMPLES : usize = 1024;
+
+//	===============================================================================================
+
+/// The cause of a failed request.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum ErrorCategory
+{
+	///	The device did not answer in time.
+	Timeout,
+	///	The device answered with an exception code.
+	Exception( u8 ),
+	///	The response was no valid frame.
+	Framing,
+	///	The connection failed or was closed.
+	Connection
+}
+
+//	===============================================================================================
+
+/// Counters and latencies of the requests of a client. A client returns a copy
+/// as snapshot, which stays unchanged by the following requests.
+#[derive( Clone, Debug, Default, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub struct ClientStatistics
+{
+	requests : BTreeMap< u8, u64 >,
+	responses : u64,
+	timeouts : u64,
+	exceptions : BTreeMap< u8, u64 >,
+	framing_errors : u64,
+	connection_errors : u64,
+	bytes_sent : u64,
+	bytes_received : u64,
+	reconnects : u64,
+	latency_count : u64,
+	latency_sum : Duration,
+	minimum_latency : Option< Duration >,
+	maximum_latency : Option< Duration >,
+	latest_latencies : VecDeque< Duration >
+}
+
+impl ClientStatistics
+{
+	pub fn new () -> ClientStatistics
+	{
+		return ClientStatistics::default ();
+	}
+
+	///	Counts a request frame of the function code.
+	pub fn record_request ( &mut self, function_code : u8, bytes : usize )
+	{
+		*self.requests.entry ( function_code ).or_insert ( 0 ) += 1;
+		self.bytes_sent += bytes as u64;
+	}
+
+	///	Counts a response frame and its latency since the request, exception responses included.
+	pub fn record_response ( &mut self, bytes : usize, latency : Duration )
+	{
+		self.responses += 1;
+		self.bytes_received += bytes as u64;
+		self.latency_count += 1;
+		self.latency_sum += latency;
+		self.minimum_latency = Some( self.minimum_latency.map_or ( latency, | minimum | minimum.min ( latency ) ) );
+		self.maximum_latency = Some( self.maximum_latency.map_or ( latency, | maximum | maximum.max ( latency ) ) );
+
+		if self.latest_latencies.len () == STATISTICS_LATENCY_SAMPLES
+		{
+			self.latest_latencies.pop_front ();
+		}
+
+		self.latest_latencies.push_back ( latency );
+	}
+
+	pub fn record_error ( &mut self, category : ErrorCategory )
+	{
+		match category
+		{
+			ErrorCategory::Timeout				=> { self.timeouts += 1; }
+			ErrorCategory::Exception( code )	=> { *self.exceptions.entry ( code ).or_insert ( 0 ) += 1; }
+			ErrorCategory::Framing				=> { self.framing_errors += 1; }
+			ErrorCategory::Connection			=> { self.connection_errors += 1; }
+		}
+	}
+
+	pub fn record_reconnect ( &mut self )
+	{
+		self.reconnects += 1;
+	}
+
+	///	Sets all counters back to zero.
+	pub fn reset ( &mut self )
+	{
+		*self = ClientStatistics::default ();
+	}
+
+	pub fn get_request_count ( &self ) -> u64
+	{
+		return self.requests.values ().sum ();
+	}
+
+	///	Returns the number of requests by function code.
+	pub fn get_requests_per_function_code ( &self ) -> &BTreeMap< u8, u64 >
+	{
+		return &self.requests;
+	}
+
+	pub fn get_response_count ( &self ) -> u64
+	{
+		return self.responses;
+	}
+
+	///	Returns the number of errors of the category, for `Exception` of the given code.
+	pub fn get_error_count ( &self, category : ErrorCategory ) -> u64
+	{
+		let reply : u64;
+
+		match category
+		{
+			ErrorCategory::Timeout				=> { reply = self.timeouts; }
+			ErrorCategory::Exception( code )	=> { reply = self.exceptions.get ( &code ).cloned ().unwrap_or ( 0 ); }
+			ErrorCategory::Framing				=> { reply = self.framing_errors; }
+			ErrorCategory::Connection			=> { reply = self.connection_errors; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the number of exception responses by exception code.
+	pub fn get_exceptions_per_code ( &self ) -> &BTreeMap< u8, u64 >
+	{
+		return &self.exceptions;
+	}
+
+	///	Returns the number of all errors, exception responses included.
+	pub fn get_total_error_count ( &self ) -> u64
+	{
+		return self.timeouts + self.exceptions.values ().sum::< u64 > () + self.framing_errors + self.connection_errors;
+	}
+
+	pub fn get_bytes_sent ( &self ) -> u64
+	{
+		return self.bytes_sent;
+	}
+
+	pub fn get_bytes_received ( &self ) -> u64
+	{
+		return self.bytes_received;
+	}
+
+	pub fn get_reconnect_count ( &self ) -> u64
+	{
+		return self.reconnects;
+	}
+
+	pub fn get_minimum_latency ( &self ) -> Option< Duration >
+	{
+		return self.minimum_latency;
+	}
+
+	pub fn get_average_latency ( &self ) -> Option< Duration >
+	{
+		if self.latency_count == 0
+		{
+			return None;
+		}
+
+		return Some( Duration::from_nanos ( ( self.latency_sum.as_nanos () / self.latency_count as u128 ) as u64 ) );
+	}
+
+	pub fn get_maximum_latency ( &self ) -> Option< Duration >
+	{
+		return self.maximum_latency;
+	}
+
+	///	Returns the sum of the latencies of all responses.
+	pub fn get_latency_sum ( &self ) -> Duration
+	{
+		return self.latency_sum;
+	}
+
+	///	Returns the latency below or at which the percentage (0 to 100) of the
+	/// latest 1024 responses arrived, by the nearest rank.
+	pub fn get_latency_percentile ( &self, percentage : f64 ) -> Option< Duration >
+	{
+		if self.latest_latencies.is_empty ()
+		{
+			return None;
+		}
+
+		let mut latencies : Vec< Duration > = self.latest_latencies.iter ().cloned ().collect ();
+
+		latencies.sort ();
+
+		let rank : usize = ( percentage.clamp ( 0.0, 100.0 ) / 100.0 * latencies.len () as f64 ).ceil () as usize;
+
+		return Some( latencies[ rank.max ( 1 ) - 1 ] );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_client_statistics ()
+{
+	let mut statistics : ClientStatistics = ClientStatistics::new ();
+
+	assert_eq! ( statistics.get_average_latency (), None );
+	assert_eq! ( statistics.get_latency_percentile ( 50.0 ), None );
+
+	for milliseconds in 1..=100
+	{
+		statistics.record_request ( 0x03, 12 );
+		statistics.record_response ( 13, Duration::from_millis ( milliseconds ) );
+	}
+
+	statistics.record_request ( 0x06, 12 );
+	statistics.record_error ( ErrorCategory::Timeout );
+	statistics.record_request ( 0x03, 12 );
+	statistics.record_response ( 9, Duration::from_millis ( 50 ) );
+	statistics.record_error ( ErrorCategory::Exception( 0x02 ) );
+	statistics.record_reconnect ();
+
+	assert_eq! ( statistics.get_request_count (), 102 );
+	assert_eq! ( statistics.get_requests_per_function_code ().get ( &0x03 ), Some( &101 ) );
+	assert_eq! ( statistics.get_response_count (), 101 );
+	assert_eq! ( statistics.get_error_count ( ErrorCategory::Timeout ), 1 );
+	assert_eq! ( statistics.get_error_count ( ErrorCategory::Exception( 0x02 ) ), 1 );
+	assert_eq! ( statistics.get_error_count ( ErrorCategory::Exception( 0x01 ) ), 0 );
+	assert_eq! ( statistics.get_total_error_count (), 2 );
+	assert_eq! ( ( statistics.get_bytes_sent (), statistics.get_bytes_received () ), ( 1224, 1309 ) );
+	assert_eq! ( statistics.get_reconnect_count (), 1 );
+	assert_eq! ( statistics.get_minimum_latency (), Some( Duration::from_millis ( 1 ) ) );
+	assert_eq! ( statistics.get_maximum_latency (), Some( Duration::from_millis ( 100 ) ) );
+	assert_eq! ( statistics.get_average_latency (), Some( Duration::from_nanos ( 50_495_049 ) ) );
+	assert_eq! ( statistics.get_latency_sum (), Duration::from_millis ( 5100 ) );
+	assert_eq! ( statistics.get_latency_percentile ( 0.0 ), Some( Duration::from_millis ( 1 ) ) );
+	assert_eq! ( statistics.get_latency_percentile ( 99.0 ), Some( Duration::from_millis ( 99 ) ) );
+	assert_eq! ( statistics.get_latency_percentile ( 100.0 ), Some( Duration::from_millis ( 100 ) ) );
+
+	statistics.reset ();
+
+	assert_eq! ( statistics, ClientStatistics::new () );
+}
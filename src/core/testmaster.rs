@@ -0,0 +1,173 @@
+use core::consts::*;
+use core::ethernet::EthernetMaster;
+use core::modbusreturn::*;
+use tcp::masteraccess::*;
+
+//	===============================================================================================
+
+/// An `EthernetMaster` without I/O for unit tests. Coils always read as on,
+/// discrete inputs answer with an exception and input registers with none.
+/// Mask writes answer with an illegal function exception unless `mask_write_supported`.
+pub struct TestMaster
+{
+	pub registers : Vec< u16 >,
+	pub requests : Vec< ( u8, u16, u16 ) >,
+	pub mask_write_supported : bool
+}
+
+impl TestMaster
+{
+	pub fn new ( registers : Vec< u16 > ) -> TestMaster
+	{
+		return TestMaster
+		{
+			registers : registers,
+			requests : vec![],
+			mask_write_supported : true
+		};
+	}
+}
+
+impl EthernetMaster for TestMaster
+{
+	fn read_coils ( &mut self, starting_address : u16, quantity_of_coils : u16 ) -> ModbusReturnCoils
+	{
+		self.requests.push ( ( FUNCTION_CODE_READ_COILS, starting_address, quantity_of_coils ) );
+		return ModbusReturnCoils::Good( ReturnGood::new ( vec![ true; quantity_of_coils as usize ], 0 ) );
+	}
+
+	fn read_discrete_inputs ( &mut self, starting_address : u16, quantity_of_inputs : u16 ) -> ModbusReturnCoils
+	{
+		self.requests.push ( ( FUNCTION_CODE_READ_DISCRETE_INPUTS, starting_address, quantity_of_inputs ) );
+		return ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( 0x82, 0x02 ) );
+	}
+
+	fn read_holding_registers ( &mut self, starting_address : u16, quantity_of_registers : u16 ) -> ModbusReturnRegisters
+	{
+		self.requests.push ( ( FUNCTION_CODE_READ_HOLDING_REGISTERS, starting_address, quantity_of_registers ) );
+
+		let begin : usize = starting_address as usize;
+		let end : usize = begin + quantity_of_registers as usize;
+
+		if end > self.registers.len ()
+		{
+			return ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( 0x83, 0x02 ) );
+		}
+
+		return ModbusReturnRegisters::Good( ReturnGood::new ( self.registers[ begin..end ].to_vec (), 0 ) );
+	}
+
+	fn read_input_registers ( &mut self, _starting_address : u16, _quantity_of_input_registers : u16 ) -> ModbusReturnRegisters
+	{
+		return ModbusReturnRegisters::None;
+	}
+
+	fn write_single_coil ( &mut self, _output_address : u16, _output_value : u16 ) -> ModbusReturnCoils
+	{
+		return ModbusReturnCoils::None;
+	}
+
+	fn write_single_register ( &mut self, register_address : u16, register_value : u16 ) -> ModbusReturnRegisters
+	{
+		self.requests.push ( ( FUNCTION_CODE_WRITE_SINGLE_REGISTER, register_address, 1 ) );
+		self.registers[ register_address as usize ] = register_value;
+
+		return ModbusReturnRegisters::Good( ReturnGood::new ( vec![ register_address, register_value ], 0 ) );
+	}
+
+	fn write_multiple_coils ( &mut self, _starting_address : u16, _quantity_of_outputs : u16, _outputs_value : Vec< u8 > ) -> ModbusReturnRegisters
+	{
+		return ModbusReturnRegisters::None;
+	}
+
+	fn write_multiple_registers ( &mut self, _starting_address : u16, _register_values : Vec< u16 > ) -> ModbusReturnRegisters
+	{
+		return ModbusReturnRegisters::None;
+	}
+
+	fn mask_write_register ( &mut self, reference_address : u16, and_mask : u16, or_mask : u16 ) -> ModbusReturnRegisters
+	{
+		self.requests.push ( ( FUNCTION_CODE_MASK_WRITE_REGISTER, reference_address, 1 ) );
+
+		if !self.mask_write_supported
+		{
+			return ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( 0x96, 0x01 ) );
+		}
+
+		let register : &mut u16 = &mut self.registers[ reference_address as usize ];
+		*register = ( *register & and_mask ) | ( or_mask & !and_mask );
+
+		return ModbusReturnRegisters::Good( ReturnGood::new ( vec![ reference_address, and_mask, or_mask ], 0 ) );
+	}
+}
+
+//	===============================================================================================
+
+/// A `MasterAccess` without I/O for unit tests. Reads and writes go to the
+/// coils and registers, discrete inputs and multiple coils always fail.
+pub struct TestAccess
+{
+	pub registers : Vec< u16 >,
+	pub coils : Vec< CoilValue >
+}
+
+impl TestAccess
+{
+	pub fn new ( registers : Vec< u16 >, coils : Vec< CoilValue > ) -> TestAccess
+	{
+		return TestAccess
+		{
+			registers : registers,
+			coils : coils
+		};
+	}
+}
+
+impl MasterAccess for TestAccess
+{
+	fn read_coils ( &mut self, address : u16, quantity : u16 ) -> Vec< CoilValue >
+	{
+		return self.coils[ address as usize..( address + quantity ) as usize ].to_vec ();
+	}
+
+	fn read_discrete_inputs ( &mut self, _address : u16, _quantity : u16 ) -> Vec< CoilValue >
+	{
+		return vec![];
+	}
+
+	fn read_holding_registers ( &mut self, address : u16, quantity : u16 ) -> Vec< u16 >
+	{
+		return self.registers[ address as usize..( address + quantity ) as usize ].to_vec ();
+	}
+
+	fn read_input_registers ( &mut self, address : u16, quantity : u16 ) -> Vec< u16 >
+	{
+		return self.read_holding_registers ( address, quantity );
+	}
+
+	fn write_single_coil ( &mut self, address : u16, value : CoilValue ) -> bool
+	{
+		self.coils[ address as usize ] = value;
+		return true;
+	}
+
+	fn write_single_register ( &mut self, address : u16, value : u16 ) -> bool
+	{
+		self.registers[ address as usize ] = value;
+		return true;
+	}
+
+	fn write_multiple_coils ( &mut self, _address : u16, _values : Vec< CoilValue > ) -> bool
+	{
+		return false;
+	}
+
+	fn write_multiple_registers ( &mut self, address : u16, values : Vec< u16 > ) -> bool
+	{
+		for ( index, value ) in values.iter ().enumerate ()
+		{
+			self.registers[ address as usize + index ] = *value;
+		}
+		return true;
+	}
+}
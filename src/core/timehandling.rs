@@ -9,6 +9,14 @@ pub struct Timestamp
     time : SystemTime
 }
 
+impl Default for Timestamp
+{
+    fn default () -> Timestamp
+    {
+        return Self::new ();
+    }
+}
+
 impl Timestamp
 {
     pub fn new () -> Timestamp
@@ -42,5 +50,5 @@ fn test_compute_milliseconds ( )
 
 fn compute_milliseconds ( duration : &Duration ) -> u64
 {
-    return ( duration.as_secs () * 1000 ) + ( ( duration.subsec_nanos () / 1000000 ) as u64 );
+    return ( duration.as_secs () * 1000 ) + ( duration.subsec_millis () as u64 );
 }
@@ -0,0 +1,51 @@
+use core::consts::*;
+
+//	===============================================================================================
+
+/// The connection of a master to a device, which frames request PDUs
+/// and receives their responses. Each transport gets the operations of
+/// `EthernetMaster` and `MasterAccess` (see `tcp::master`).
+pub trait TelegramTransport
+{
+	///	Returns the unit identifier of the device.
+	fn get_unit_identifier ( &self ) -> u8;
+
+	///	Sends the request PDU (function code and data) to the device and returns the response PDU,
+	/// Err if the device did not answer or the response frame is invalid.
+	fn process_pdu ( &mut self, request : &[ u8 ] ) -> Result< Vec< u8 >, String >;
+
+	///	Returns false after the device refused a mask write register request.
+	fn is_mask_write_supported ( &self ) -> bool;
+
+	fn set_mask_write_supported ( &mut self, supported : bool );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_count_up_last_transaction_id ()
+{
+	let test_data_1 : u16 = 0x0001;
+	let result_data_1 : u16 = count_up_last_transaction_id ( test_data_1 );
+	assert_eq! ( result_data_1, 0x0002 );
+
+	let test_data_2 : u16 = 0xFFFF;
+	let result_data_2 : u16 = count_up_last_transaction_id ( test_data_2 );
+	assert_eq! ( result_data_2, 0x0001 );
+}
+
+pub fn count_up_last_transaction_id ( last_transaction_id : u16 ) -> u16
+{
+	let reply : u16;
+
+	if last_transaction_id == 0xFFFF
+	{
+		reply = MODBUS_TRANSACTION_ID_INITIALIZER;
+	}
+	else
+	{
+		reply = last_transaction_id + 1;
+	}
+
+	return reply;
+}
@@ -1,5 +1,49 @@
+#![cfg_attr( not( feature = "std" ), no_std )]
+#![allow( clippy::needless_late_init,
+		  clippy::needless_return,
+		  clippy::redundant_field_names,
+		  clippy::tabs_in_doc_comments )]
 
+#[cfg( not( feature = "std" ) )]
+extern crate core as std;
+#[cfg( feature = "serde" )]
+#[macro_use]
+extern crate serde_derive;
+#[cfg( feature = "json" )]
+extern crate serde_json;
+#[cfg( feature = "tls" )]
+extern crate rustls;
+#[cfg( feature = "log" )]
+extern crate log;
 
+#[cfg( feature = "std" )]
+#[macro_use]
+mod logging;
+
+#[cfg( feature = "std" )]
+pub mod capture;
+#[cfg( feature = "std" )]
 pub mod core;
+#[cfg( feature = "metrics" )]
+pub mod metrics;
+#[cfg( feature = "mqtt" )]
+pub mod mqtt;
+#[cfg( feature = "std" )]
 pub mod network;
+#[cfg( feature = "std" )]
+pub mod poller;
+pub mod protocol;
+#[cfg( feature = "std" )]
+pub mod rtu;
+#[cfg( feature = "std" )]
+pub mod server;
+#[cfg( feature = "std" )]
+pub mod sim;
+#[cfg( feature = "std" )]
+pub mod tags;
+#[cfg( feature = "std" )]
 pub mod tcp;
+#[cfg( feature = "tls" )]
+pub mod tls;
+#[cfg( feature = "std" )]
+pub mod udp;
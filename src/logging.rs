@@ -0,0 +1,58 @@
+//	Crate internal logging. With the feature `log` the events go to the `log` facade,
+//	without the arguments are only type checked and never evaluated.
+
+#[cfg( feature = "log" )]
+macro_rules! log_event
+{
+	( $level : ident, $( $argument : tt )+ ) => { ::log::$level! ( $( $argument )+ ) };
+}
+
+#[cfg( not( feature = "log" ) )]
+macro_rules! log_event
+{
+	( $level : ident, $( $argument : tt )+ ) => { if false { let _ = format_args! ( $( $argument )+ ); } };
+}
+
+//	===============================================================================================
+
+#[cfg( all( test, feature = "log" ) )]
+use std::sync::Mutex;
+
+#[cfg( all( test, feature = "log" ) )]
+static LOGGED_EVENTS : Mutex< Vec< String > > = Mutex::new ( Vec::new () );
+
+#[cfg( all( test, feature = "log" ) )]
+struct TestLogger;
+
+#[cfg( all( test, feature = "log" ) )]
+impl ::log::Log for TestLogger
+{
+	fn enabled ( &self, _metadata : &::log::Metadata ) -> bool
+	{
+		return true;
+	}
+
+	fn log ( &self, record : &::log::Record )
+	{
+		LOGGED_EVENTS.lock ().unwrap ().push ( format! ( "{} {}", record.level (), record.args () ) );
+	}
+
+	fn flush ( &self )
+	{
+	}
+}
+
+#[cfg( all( test, feature = "log" ) )]
+static TEST_LOGGER : TestLogger = TestLogger;
+
+#[cfg( feature = "log" )]
+#[test]
+fn test_log_event ()
+{
+	let _ = ::log::set_logger ( &TEST_LOGGER );
+	::log::set_max_level ( ::log::LevelFilter::Trace );
+
+	log_event! ( debug, "request of function 0x{:02X}", 3 );
+
+	assert! ( LOGGED_EVENTS.lock ().unwrap ().contains ( &"DEBUG request of function 0x03".to_string () ) );
+}
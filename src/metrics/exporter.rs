@@ -0,0 +1,235 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use metrics::registry::*;
+use server::tcpserver::TcpServer;
+
+//	===============================================================================================
+
+const EXPORTER_READ_TIMEOUT : u64 = 1000;
+const EXPORTER_MAXIMUM_REQUEST_SIZE : usize = 8192;
+const EXPORTER_METRICS_PATH : &str = "/metrics";
+
+//	===============================================================================================
+
+/// An HTTP server which answers `GET /metrics` with the metrics of the registry,
+/// in the OpenMetrics format if the scraper accepts it and in the Prometheus
+/// text format otherwise. Each connection is served by its own thread and
+/// closed after the response, the exporter stops when it is dropped.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::metrics::exporter::MetricsExporter;
+/// use modbus_iiot::metrics::registry::MetricsRegistry;
+///
+/// let registry = MetricsRegistry::new();
+/// let exporter = MetricsExporter::start("127.0.0.1:9502", registry.clone()).unwrap();
+///
+/// println!("scrape http://{}/metrics", exporter.get_local_address());
+/// ```
+pub struct MetricsExporter
+{
+	server : TcpServer
+}
+
+impl MetricsExporter
+{
+	///	Starts serving the registry on the address, e.g. `0.0.0.0:9502`.
+	pub fn start ( address : &str, registry : MetricsRegistry ) -> Result< MetricsExporter, String >
+	{
+		let server : TcpServer = TcpServer::start_with_connection_handler ( address,
+																			move | mut stream : TcpStream | serve_scrape ( &mut stream,
+																														   &registry ) )?;
+
+		return Ok( MetricsExporter
+		{
+			server : server
+		} );
+	}
+
+	///	Returns the address the exporter listens on, useful with port 0.
+	pub fn get_local_address ( &self ) -> SocketAddr
+	{
+		return self.server.get_local_address ();
+	}
+
+	///	Stops accepting and closes all connections.
+	pub fn stop ( &mut self )
+	{
+		self.server.stop ();
+	}
+}
+
+//	===============================================================================================
+
+fn serve_scrape ( stream : &mut TcpStream, registry : &MetricsRegistry )
+{
+	let _ = stream.set_read_timeout ( Some( Duration::from_millis ( EXPORTER_READ_TIMEOUT ) ) );
+
+	if let Ok( request ) = read_http_request ( stream )
+	{
+		let response : Vec< u8 > = create_http_response ( &request,
+														  registry );
+
+		let _ = stream.write_all ( &response );
+		let _ = stream.flush ();
+	}
+}
+
+///	Reads the request line and the headers, a request body is not expected.
+fn read_http_request< T : Read > ( stream : &mut T ) -> Result< String, String >
+{
+	let mut request : Vec< u8 > = vec![];
+	let mut buffer : [ u8; 512 ] = [ 0; 512 ];
+
+	while !request.windows ( 4 ).any ( | window | window == b"\r\n\r\n" )
+	{
+		if request.len () > EXPORTER_MAXIMUM_REQUEST_SIZE
+		{
+			return Err( "request too large".to_string () );
+		}
+
+		let length : usize = stream.read ( &mut buffer ).map_err ( | error | error.to_string () )?;
+
+		if length == 0
+		{
+			return Err( "connection closed".to_string () );
+		}
+
+		request.extend_from_slice ( &buffer[ 0..length ] );
+	}
+
+	return Ok( String::from_utf8_lossy ( &request ).into_owned () );
+}
+
+#[test]
+fn test_create_http_response ()
+{
+	let registry : MetricsRegistry = MetricsRegistry::new ();
+
+	let metrics : String = String::from_utf8 ( create_http_response ( "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n", &registry ) ).unwrap ();
+	assert! ( metrics.starts_with ( "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\n" ) );
+	assert! ( metrics.contains ( "# TYPE modbus_tag_value gauge\n" ) );
+
+	let open_metrics : String = String::from_utf8 ( create_http_response ( "GET /metrics?x=1 HTTP/1.1\r\naccept: application/openmetrics-text;version=1.0.0\r\n\r\n", &registry ) ).unwrap ();
+	assert! ( open_metrics.contains ( "Content-Type: application/openmetrics-text; version=1.0.0; charset=utf-8\r\n" ) );
+	assert! ( open_metrics.ends_with ( "# EOF\n" ) );
+
+	let not_found : String = String::from_utf8 ( create_http_response ( "GET / HTTP/1.1\r\n\r\n", &registry ) ).unwrap ();
+	assert! ( not_found.starts_with ( "HTTP/1.1 404 Not Found\r\n" ) );
+
+	let not_allowed : String = String::from_utf8 ( create_http_response ( "POST /metrics HTTP/1.1\r\n\r\n", &registry ) ).unwrap ();
+	assert! ( not_allowed.starts_with ( "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET\r\n" ) );
+}
+
+fn create_http_response ( request : &str, registry : &MetricsRegistry ) -> Vec< u8 >
+{
+	let mut lines = request.lines ();
+	let mut request_line = lines.next ().unwrap_or ( "" ).split_whitespace ();
+	let method : &str = request_line.next ().unwrap_or ( "" );
+	let path : &str = request_line.next ().unwrap_or ( "" ).split ( '?' ).next ().unwrap_or ( "" );
+
+	let mut accept : &str = "";
+
+	for line in lines
+	{
+		if let Some( ( name, value ) ) = line.split_once ( ':' )
+		{
+			if name.trim ().eq_ignore_ascii_case ( "accept" )
+			{
+				accept = value.trim ();
+			}
+		}
+	}
+
+	let reply : String;
+
+	if path != EXPORTER_METRICS_PATH
+	{
+		reply = format_http_response ( "404 Not Found",
+									   "",
+									   "text/plain; charset=utf-8",
+									   "not found\n" );
+	}
+	else if method != "GET"
+	{
+		reply = format_http_response ( "405 Method Not Allowed",
+									   "Allow: GET\r\n",
+									   "text/plain; charset=utf-8",
+									   "method not allowed\n" );
+	}
+	else
+	{
+		let format : ExpositionFormat = ExpositionFormat::from_accept_header ( accept );
+
+		reply = format_http_response ( "200 OK",
+									   "",
+									   format.get_content_type (),
+									   &registry.render ( format ) );
+	}
+
+	return reply.into_bytes ();
+}
+
+fn format_http_response ( status : &str, headers : &str, content_type : &str, body : &str ) -> String
+{
+	return format! ( "HTTP/1.1 {}\r\n{}Content-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					 status,
+					 headers,
+					 content_type,
+					 body.len (),
+					 body );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::batchreader::BatchTag;
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use poller::pollingjob::PollingJob;
+#[cfg( test )]
+use poller::scheduler::Poller;
+#[cfg( test )]
+use server::datastore::DataStore;
+#[cfg( test )]
+use tcp::master::TcpClient;
+
+#[test]
+fn test_metrics_exporter ()
+{
+	let store : DataStore = DataStore::new_dense ( 10 );
+	let server : TcpServer = TcpServer::start ( "127.0.0.1:0", store.clone () ).unwrap ();
+	let mut client : TcpClient = TcpClient::new_with_port ( "127.0.0.1", server.get_local_address ().port () );
+
+	client.connect ().unwrap ();
+	store.set_registers ( DataTable::HoldingRegisters, 2, &[ 230 ] ).unwrap ();
+
+	let mut job : PollingJob = PollingJob::new ( "temperatures",
+												 Duration::from_secs ( 3600 ) );
+	job.add_tag ( BatchTag::new_from_reference ( 40003, 1 ).unwrap () );
+
+	let mut poller : Poller< TcpClient > = Poller::new ( client );
+	poller.add_job ( job );
+
+	let registry : MetricsRegistry = MetricsRegistry::new ();
+	poller.poll ();
+	registry.update_poller ( &mut poller );
+
+	let exporter : MetricsExporter = MetricsExporter::start ( "127.0.0.1:0", registry ).unwrap ();
+	let mut stream : TcpStream = TcpStream::connect ( exporter.get_local_address () ).unwrap ();
+	let mut response : String = String::new ();
+
+	stream.write_all ( b"GET /metrics HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n" ).unwrap ();
+	stream.read_to_string ( &mut response ).unwrap ();
+
+	assert! ( response.starts_with ( "HTTP/1.1 200 OK\r\n" ) );
+	assert! ( response.contains ( "\nmodbus_tag_value{polling_job=\"temperatures\",table=\"holding_registers\",address=\"2\"} 230\n" ) );
+	assert! ( response.contains ( "\nmodbus_client_connected 1\n" ) );
+	assert! ( response.contains ( "\nmodbus_client_requests_total{function_code=\"3\"} 1\n" ) );
+	assert! ( response.contains ( "\nmodbus_client_errors_total{category=\"timeout\"} 0\n" ) );
+	assert! ( response.contains ( "\nmodbus_client_latency_seconds_count 1\n" ) );
+}
@@ -0,0 +1,4 @@
+
+
+pub mod exporter;
+pub mod registry;
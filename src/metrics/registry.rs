@@ -0,0 +1,506 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use core::batchreader::*;
+use core::statistics::*;
+use poller::pollingjob::PollingJob;
+use poller::scheduler::Poller;
+use tcp::master::TcpClient;
+
+//	===============================================================================================
+
+/// The text format of the exposed metrics.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum ExpositionFormat
+{
+	///	The Prometheus text format 0.0.4.
+	Prometheus,
+	///	The OpenMetrics text format 1.0.0.
+	OpenMetrics
+}
+
+impl ExpositionFormat
+{
+	///	Returns the format asked for by the `Accept` header of a scrape.
+	pub fn from_accept_header ( accept : &str ) -> ExpositionFormat
+	{
+		let reply : ExpositionFormat;
+
+		if accept.contains ( "application/openmetrics-text" )
+		{
+			reply = ExpositionFormat::OpenMetrics;
+		}
+		else
+		{
+			reply = ExpositionFormat::Prometheus;
+		}
+
+		return reply;
+	}
+
+	pub fn get_content_type ( &self ) -> &'static str
+	{
+		let reply : &'static str;
+
+		match *self
+		{
+			ExpositionFormat::Prometheus	=> { reply = "text/plain; version=0.0.4; charset=utf-8"; }
+			ExpositionFormat::OpenMetrics	=> { reply = "application/openmetrics-text; version=1.0.0; charset=utf-8"; }
+		}
+
+		return reply;
+	}
+}
+
+//	===============================================================================================
+
+struct JobSnapshot
+{
+	name : String,
+	tags : Vec< BatchTag >,
+	values : Vec< Option< BatchValue > >,
+	poll_count : u64,
+	overrun_count : u64,
+	last_duration : Duration
+}
+
+struct ClientSnapshot
+{
+	statistics : ClientStatistics,
+	connected : bool
+}
+
+#[derive( Default )]
+struct RegistryState
+{
+	jobs : Vec< JobSnapshot >,
+	client : Option< ClientSnapshot >
+}
+
+//	===============================================================================================
+
+/// The latest values of the polling jobs and the health of their client,
+/// rendered as metrics for each scrape. Clones share the same values, so
+/// the polling thread updates the registry which the `MetricsExporter` serves.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::sync::atomic::AtomicBool;
+/// use std::time::Duration;
+/// use modbus_iiot::core::batchreader::BatchTag;
+/// use modbus_iiot::metrics::exporter::MetricsExporter;
+/// use modbus_iiot::metrics::registry::MetricsRegistry;
+/// use modbus_iiot::poller::pollingjob::PollingJob;
+/// use modbus_iiot::poller::scheduler::Poller;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let mut client = TcpClient::new("192.168.0.10");
+/// client.connect().unwrap();
+///
+/// let mut job = PollingJob::new("temperatures", Duration::from_secs(1));
+/// job.add_tag(BatchTag::new_from_reference(30001, 4).unwrap());
+///
+/// let mut poller = Poller::new(client);
+/// poller.add_job(job);
+///
+/// let registry = MetricsRegistry::new();
+/// let _exporter = MetricsExporter::start("127.0.0.1:9502", registry.clone()).unwrap();
+///
+/// let running = AtomicBool::new(true);
+/// registry.run_poller(&mut poller, &running);
+/// ```
+#[derive( Clone, Default )]
+pub struct MetricsRegistry
+{
+	state : Arc< Mutex< RegistryState > >
+}
+
+impl MetricsRegistry
+{
+	pub fn new () -> MetricsRegistry
+	{
+		return MetricsRegistry::default ();
+	}
+
+	///	Takes the last values and poll counters of the jobs.
+	pub fn update_jobs ( &self, jobs : &[ PollingJob ] )
+	{
+		let snapshots : Vec< JobSnapshot > = jobs.iter ()
+												 .map ( | job | JobSnapshot
+														{
+															name : job.get_name ().to_string (),
+															tags : job.get_tags ().clone (),
+															values : job.get_values ().clone (),
+															poll_count : job.get_poll_count (),
+															overrun_count : job.get_overrun_count (),
+															last_duration : job.get_last_duration ()
+														} )
+												 .collect ();
+
+		self.lock_state ().jobs = snapshots;
+	}
+
+	///	Takes the statistics and the connection state of the client.
+	pub fn update_client ( &self, client : &TcpClient )
+	{
+		self.lock_state ().client = Some( ClientSnapshot
+										  {
+											  statistics : client.get_statistics (),
+											  connected : client.is_connected ()
+										  } );
+	}
+
+	///	Takes the jobs and the client of the poller.
+	pub fn update_poller ( &self, poller : &mut Poller< TcpClient > )
+	{
+		self.update_jobs ( poller.get_jobs () );
+		self.update_client ( poller.get_master () );
+	}
+
+	///	Polls like `Poller::run` until `running` is set to false
+	/// and updates the registry after each poll.
+	pub fn run_poller ( &self, poller : &mut Poller< TcpClient >, running : &AtomicBool )
+	{
+		while running.load ( Ordering::SeqCst )
+		{
+			let sleep_time : Duration = poller.poll ();
+
+			self.update_poller ( poller );
+
+			if sleep_time > Duration::from_millis ( 0 )
+			{
+				thread::sleep ( sleep_time );
+			}
+		}
+	}
+
+	///	Renders all metrics in the text format.
+	pub fn render ( &self, format : ExpositionFormat ) -> String
+	{
+		let state : MutexGuard< '_, RegistryState > = self.lock_state ();
+		let mut writer : MetricsWriter = MetricsWriter::new ( format );
+
+		render_tag_values ( &mut writer,
+							&state.jobs );
+		render_jobs ( &mut writer,
+					  &state.jobs );
+
+		if let Some( ref client ) = state.client
+		{
+			render_client ( &mut writer,
+							client );
+		}
+
+		return writer.finish ();
+	}
+
+	fn lock_state ( &self ) -> MutexGuard< '_, RegistryState >
+	{
+		return self.state.lock ().unwrap_or_else ( | error | error.into_inner () );
+	}
+}
+
+//	===============================================================================================
+
+fn render_tag_values ( writer : &mut MetricsWriter, jobs : &Vec< JobSnapshot > )
+{
+	writer.add_family ( "modbus_tag_value",
+						"gauge",
+						"Last polled value of a register or bit." );
+
+	for job in jobs
+	{
+		for ( tag, value ) in job.tags.iter ().zip ( job.values.iter () )
+		{
+			let values : Vec< f64 >;
+
+			match *value
+			{
+				Some( BatchValue::Registers( ref registers ) )	=> { values = registers.iter ().map ( | register | *register as f64 ).collect (); }
+				Some( BatchValue::Coils( ref coils ) )			=> { values = coils.iter ().map ( | coil | if *coil { 1.0 } else { 0.0 } ).collect (); }
+				_												=> { values = vec![]; }
+			}
+
+			for ( offset, value ) in values.into_iter ().enumerate ()
+			{
+				writer.add_sample ( "modbus_tag_value",
+									&get_tag_labels ( &job.name, tag, offset ),
+									value );
+			}
+		}
+	}
+
+	writer.add_family ( "modbus_tag_good",
+						"gauge",
+						"1 if the last read of the tag succeeded, 0 otherwise." );
+
+	for job in jobs
+	{
+		for ( tag, value ) in job.tags.iter ().zip ( job.values.iter () )
+		{
+			if let Some( ref value ) = *value
+			{
+				writer.add_sample ( "modbus_tag_good",
+									&get_tag_labels ( &job.name, tag, 0 ),
+									if value.is_bad () { 0.0 } else { 1.0 } );
+			}
+		}
+	}
+}
+
+fn get_tag_labels ( job_name : &str, tag : &BatchTag, offset : usize ) -> Vec< ( &'static str, String ) >
+{
+	return vec![ ( "polling_job", job_name.to_string () ),
+				 ( "table", tag.get_table ().to_string () ),
+				 ( "address", ( tag.get_address () as usize + offset ).to_string () ) ];
+}
+
+fn render_jobs ( writer : &mut MetricsWriter, jobs : &Vec< JobSnapshot > )
+{
+	writer.add_family ( "modbus_poll",
+						"counter",
+						"Number of polls of the job." );
+
+	for job in jobs
+	{
+		writer.add_sample ( "modbus_poll_total",
+							&[ ( "polling_job", job.name.clone () ) ],
+							job.poll_count as f64 );
+	}
+
+	writer.add_family ( "modbus_poll_overrun",
+						"counter",
+						"Number of polls which missed the following deadline." );
+
+	for job in jobs
+	{
+		writer.add_sample ( "modbus_poll_overrun_total",
+							&[ ( "polling_job", job.name.clone () ) ],
+							job.overrun_count as f64 );
+	}
+
+	writer.add_family ( "modbus_poll_duration_seconds",
+						"gauge",
+						"Duration of the last poll of the job." );
+
+	for job in jobs
+	{
+		writer.add_sample ( "modbus_poll_duration_seconds",
+							&[ ( "polling_job", job.name.clone () ) ],
+							job.last_duration.as_secs_f64 () );
+	}
+}
+
+fn render_client ( writer : &mut MetricsWriter, client : &ClientSnapshot )
+{
+	let statistics : &ClientStatistics = &client.statistics;
+
+	writer.add_family ( "modbus_client_connected",
+						"gauge",
+						"1 while the client is connected to the device." );
+	writer.add_sample ( "modbus_client_connected",
+						&[],
+						if client.connected { 1.0 } else { 0.0 } );
+
+	writer.add_family ( "modbus_client_requests",
+						"counter",
+						"Number of requests by function code." );
+
+	for ( function_code, count ) in statistics.get_requests_per_function_code ()
+	{
+		writer.add_sample ( "modbus_client_requests_total",
+							&[ ( "function_code", function_code.to_string () ) ],
+							*count as f64 );
+	}
+
+	writer.add_family ( "modbus_client_responses",
+						"counter",
+						"Number of responses, exception responses included." );
+	writer.add_sample ( "modbus_client_responses_total",
+						&[],
+						statistics.get_response_count () as f64 );
+
+	writer.add_family ( "modbus_client_errors",
+						"counter",
+						"Number of failed requests by category, exception responses by code." );
+
+	for ( category, category_name ) in [ ( ErrorCategory::Timeout, "timeout" ),
+										 ( ErrorCategory::Framing, "framing" ),
+										 ( ErrorCategory::Connection, "connection" ) ].iter ()
+	{
+		writer.add_sample ( "modbus_client_errors_total",
+							&[ ( "category", category_name.to_string () ) ],
+							statistics.get_error_count ( *category ) as f64 );
+	}
+
+	for ( code, count ) in statistics.get_exceptions_per_code ()
+	{
+		writer.add_sample ( "modbus_client_errors_total",
+							&[ ( "category", "exception".to_string () ),
+							   ( "code", code.to_string () ) ],
+							*count as f64 );
+	}
+
+	writer.add_family ( "modbus_client_reconnects",
+						"counter",
+						"Number of connections after the first one." );
+	writer.add_sample ( "modbus_client_reconnects_total",
+						&[],
+						statistics.get_reconnect_count () as f64 );
+
+	writer.add_family ( "modbus_client_sent_bytes",
+						"counter",
+						"Number of bytes of the sent frames." );
+	writer.add_sample ( "modbus_client_sent_bytes_total",
+						&[],
+						statistics.get_bytes_sent () as f64 );
+
+	writer.add_family ( "modbus_client_received_bytes",
+						"counter",
+						"Number of bytes of the received frames." );
+	writer.add_sample ( "modbus_client_received_bytes_total",
+						&[],
+						statistics.get_bytes_received () as f64 );
+
+	writer.add_family ( "modbus_client_latency_seconds",
+						"summary",
+						"Time from a request to its response, quantiles of the latest 1024 responses." );
+
+	for quantile in [ 0.5, 0.9, 0.99 ].iter ()
+	{
+		if let Some( latency ) = statistics.get_latency_percentile ( quantile * 100.0 )
+		{
+			writer.add_sample ( "modbus_client_latency_seconds",
+								&[ ( "quantile", quantile.to_string () ) ],
+								latency.as_secs_f64 () );
+		}
+	}
+
+	writer.add_sample ( "modbus_client_latency_seconds_sum",
+						&[],
+						statistics.get_latency_sum ().as_secs_f64 () );
+	writer.add_sample ( "modbus_client_latency_seconds_count",
+						&[],
+						statistics.get_response_count () as f64 );
+}
+
+//	===============================================================================================
+
+///	Writes metric families and their samples in one of the text formats.
+struct MetricsWriter
+{
+	format : ExpositionFormat,
+	text : String
+}
+
+impl MetricsWriter
+{
+	fn new ( format : ExpositionFormat ) -> MetricsWriter
+	{
+		return MetricsWriter
+		{
+			format : format,
+			text : String::new ()
+		};
+	}
+
+	///	Starts a family, counters are named without the `_total` suffix of their samples.
+	fn add_family ( &mut self, name : &str, metric_type : &str, help : &str )
+	{
+		let family_name : String;
+
+		if metric_type == "counter" && self.format == ExpositionFormat::Prometheus
+		{
+			family_name = format! ( "{}_total", name );
+		}
+		else
+		{
+			family_name = name.to_string ();
+		}
+
+		self.text.push_str ( &format! ( "# HELP {} {}\n# TYPE {} {}\n", family_name, help, family_name, metric_type ) );
+	}
+
+	fn add_sample ( &mut self, name : &str, labels : &[ ( &str, String ) ], value : f64 )
+	{
+		self.text.push_str ( name );
+
+		if !labels.is_empty ()
+		{
+			let pairs : Vec< String > = labels.iter ()
+											  .map ( | &( label, ref label_value ) | format! ( "{}=\"{}\"", label, escape_label_value ( label_value ) ) )
+											  .collect ();
+
+			self.text.push_str ( &format! ( "{{{}}}", pairs.join ( "," ) ) );
+		}
+
+		self.text.push_str ( &format! ( " {}\n", value ) );
+	}
+
+	fn finish ( mut self ) -> String
+	{
+		if self.format == ExpositionFormat::OpenMetrics
+		{
+			self.text.push_str ( "# EOF\n" );
+		}
+
+		return self.text;
+	}
+}
+
+#[test]
+fn test_escape_label_value ()
+{
+	assert_eq! ( escape_label_value ( "fast io" ), "fast io" );
+	assert_eq! ( escape_label_value ( "a\\b\"c\nd" ), "a\\\\b\\\"c\\nd" );
+}
+
+fn escape_label_value ( value : &str ) -> String
+{
+	return value.replace ( '\\', "\\\\" )
+				.replace ( '"', "\\\"" )
+				.replace ( '\n', "\\n" );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::testmaster::TestMaster;
+
+#[test]
+fn test_metrics_registry_render ()
+{
+	let mut job : PollingJob = PollingJob::new ( "fast io",
+												 Duration::from_millis ( 0 ) );
+	job.add_tag ( BatchTag::new_from_reference ( 40001, 2 ).unwrap () );
+	job.add_tag ( BatchTag::new_from_reference ( 1, 1 ).unwrap () );
+	job.add_tag ( BatchTag::new_from_reference ( 10001, 1 ).unwrap () );
+
+	let mut poller : Poller< TestMaster > = Poller::new ( TestMaster::new ( vec![ 230, 7 ] ) );
+	poller.add_job ( job );
+	poller.poll ();
+
+	let registry : MetricsRegistry = MetricsRegistry::new ();
+	registry.update_jobs ( poller.get_jobs () );
+
+	let text : String = registry.render ( ExpositionFormat::Prometheus );
+
+	assert! ( text.contains ( "# TYPE modbus_tag_value gauge\n" ) );
+	assert! ( text.contains ( "modbus_tag_value{polling_job=\"fast io\",table=\"holding_registers\",address=\"0\"} 230\n" ) );
+	assert! ( text.contains ( "modbus_tag_value{polling_job=\"fast io\",table=\"holding_registers\",address=\"1\"} 7\n" ) );
+	assert! ( text.contains ( "modbus_tag_value{polling_job=\"fast io\",table=\"coils\",address=\"0\"} 1\n" ) );
+	assert! ( !text.contains ( "modbus_tag_value{polling_job=\"fast io\",table=\"discrete_inputs\"" ) );
+	assert! ( text.contains ( "modbus_tag_good{polling_job=\"fast io\",table=\"holding_registers\",address=\"0\"} 1\n" ) );
+	assert! ( text.contains ( "modbus_tag_good{polling_job=\"fast io\",table=\"discrete_inputs\",address=\"0\"} 0\n" ) );
+	assert! ( text.contains ( "# TYPE modbus_poll_total counter\nmodbus_poll_total{polling_job=\"fast io\"} 1\n" ) );
+	assert! ( !text.contains ( "modbus_client_" ) );
+	assert! ( !text.contains ( "# EOF" ) );
+
+	let open_metrics : String = registry.render ( ExpositionFormat::OpenMetrics );
+
+	assert! ( open_metrics.contains ( "# TYPE modbus_poll counter\nmodbus_poll_total{polling_job=\"fast io\"} 1\n" ) );
+	assert! ( open_metrics.ends_with ( "# EOF\n" ) );
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use serde_json;
+use capture::hexlog::format_timestamp;
+use core::transport::TelegramTransport;
+use mqtt::client::*;
+use tags::tag::*;
+use tags::tagdatabase::TagDatabase;
+use tcp::master::TcpClient;
+
+//	===============================================================================================
+
+const BRIDGE_DEFAULT_INTERVAL : u64 = 1000;
+const BRIDGE_RECEIVE_INTERVAL : u64 = 100;
+
+//	===============================================================================================
+
+/// Polls the tags of a database with a `TcpClient` and publishes each changed
+/// value as retained JSON message to `<device>/<unit identifier>/<tag>`, e.g.
+/// `{"value":21.5,"unit":"°C","timestamp":"2026-10-19T08:15:02.125Z"}`.
+/// A failed read publishes `null` as value with an `error` text.
+///
+/// Messages to `<device>/<unit identifier>/<tag>/set` write the tag, their
+/// payload is a number, `true`/`false` or an object with a `value`. The tag has
+/// to be writable and the value valid for its data type and range, the outcome
+/// is published to `.../set/result` as `{"ok":true}` or `{"ok":false,"error":"..."}`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::sync::atomic::AtomicBool;
+/// use modbus_iiot::mqtt::bridge::MqttBridge;
+/// use modbus_iiot::mqtt::client::MqttClient;
+/// use modbus_iiot::tags::tagdatabase::TagDatabase;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let database = TagDatabase::load_csv_file("registers.csv").unwrap();
+/// let mut client = TcpClient::new("192.168.0.10");
+/// let mut mqtt = MqttClient::new("127.0.0.1", 1883, "boiler-bridge");
+///
+/// client.connect().unwrap();
+/// mqtt.connect().unwrap();
+///
+/// let mut bridge = MqttBridge::new(client, database, mqtt, "boiler");
+/// let running = AtomicBool::new(true);
+///
+/// if let Err(message) = bridge.run(&running)
+/// {
+///     println!("failure = {}", message);
+/// }
+/// ```
+pub struct MqttBridge
+{
+	client : TcpClient,
+	database : TagDatabase,
+	mqtt : MqttClient,
+	topic_prefix : String,
+	interval : Duration,
+	published_values : HashMap< String, Result< TagValue, String > >
+}
+
+impl MqttBridge
+{
+	///	Creates a bridge of the connected clients, the topics start with the device name
+	/// and the unit identifier of the `TcpClient`.
+	pub fn new ( client : TcpClient, database : TagDatabase, mqtt : MqttClient, device : &str ) -> MqttBridge
+	{
+		let topic_prefix : String = format! ( "{}/{}", device, client.get_unit_identifier () );
+
+		return MqttBridge
+		{
+			client : client,
+			database : database,
+			mqtt : mqtt,
+			topic_prefix : topic_prefix,
+			interval : Duration::from_millis ( BRIDGE_DEFAULT_INTERVAL ),
+			published_values : HashMap::new ()
+		};
+	}
+
+	///	Sets the interval of reading all tags (1 s by default).
+	pub fn set_interval ( &mut self, interval : Duration )
+	{
+		self.interval = interval;
+	}
+
+	pub fn get_topic_prefix ( &self ) -> &str
+	{
+		return &self.topic_prefix;
+	}
+
+	pub fn get_client ( &mut self ) -> &mut TcpClient
+	{
+		return &mut self.client;
+	}
+
+	pub fn get_mqtt_client ( &mut self ) -> &mut MqttClient
+	{
+		return &mut self.mqtt;
+	}
+
+	///	Subscribes to the set topics of all tags.
+	pub fn subscribe ( &mut self ) -> Result< (), String >
+	{
+		let topic_filter : String = format! ( "{}/+/set", self.topic_prefix );
+
+		return self.mqtt.subscribe ( &topic_filter );
+	}
+
+	///	Reads all tags and publishes the changed values, returns the number of published values.
+	pub fn poll_tags ( &mut self ) -> Result< usize, String >
+	{
+		let mut reply : usize = 0;
+
+		let names : Vec< String > = self.database.get_tags ().iter ().map ( | tag | tag.get_name ().to_string () ).collect ();
+
+		for name in names
+		{
+			if self.publish_tag ( &name )?
+			{
+				reply += 1;
+			}
+		}
+
+		return Ok( reply );
+	}
+
+	///	Writes the value of a message to a set topic and publishes the result.
+	/// Returns Err if the message was no valid write request.
+	pub fn process_message ( &mut self, message : &MqttMessage ) -> Result< (), String >
+	{
+		let name : &str = message.get_topic ()
+								 .strip_prefix ( &self.topic_prefix )
+								 .and_then ( | topic | topic.strip_prefix ( '/' ) )
+								 .and_then ( | topic | topic.strip_suffix ( "/set" ) )
+								 .ok_or ( format! ( "unexpected topic {}", message.get_topic () ) )?;
+
+		let result : Result< (), String > = parse_set_payload ( message.get_payload () )
+												.and_then ( | value | self.database.write_tag ( &mut self.client,
+																								name,
+																								value ) );
+
+		let mut object : serde_json::Map< String, serde_json::Value > = serde_json::Map::new ();
+
+		object.insert ( "ok".to_string (), serde_json::Value::from ( result.is_ok () ) );
+
+		match result
+		{
+			Ok( _ )				=> { log_event! ( info, "wrote tag {} from {}", name, message.get_topic () ); }
+			Err( ref error )	=>
+			{
+				log_event! ( warn, "writing tag {} from {} failed: {}", name, message.get_topic (), error );
+				object.insert ( "error".to_string (), serde_json::Value::from ( error.clone () ) );
+			}
+		}
+
+		let name : String = name.to_string ();
+
+		self.mqtt.publish ( &format! ( "{}/result", message.get_topic () ),
+							serde_json::Value::Object( object ).to_string ().as_bytes (),
+							false )?;
+
+		if result.is_ok ()
+		{
+			self.publish_tag ( &name )?;
+		}
+
+		return result;
+	}
+
+	///	Polls the tags at the interval and processes set messages until `running` is set to false.
+	/// Returns Err if the connection to the broker failed.
+	pub fn run ( &mut self, running : &AtomicBool ) -> Result< (), String >
+	{
+		self.subscribe ()?;
+
+		let mut next_poll : Instant = Instant::now ();
+
+		while running.load ( Ordering::SeqCst )
+		{
+			let now : Instant = Instant::now ();
+
+			if now >= next_poll
+			{
+				self.poll_tags ()?;
+
+				next_poll += self.interval;
+
+				if next_poll < now
+				{
+					next_poll = now + self.interval;
+				}
+			}
+
+			let wait_time : Duration = next_poll.saturating_duration_since ( Instant::now () );
+
+			if let Some( message ) = self.mqtt.receive ( wait_time.min ( Duration::from_millis ( BRIDGE_RECEIVE_INTERVAL ) ) )?
+			{
+				let _ = self.process_message ( &message );
+			}
+		}
+
+		return Ok( () );
+	}
+
+	///	Reads the tag and publishes its value if it changed, returns true if published.
+	fn publish_tag ( &mut self, name : &str ) -> Result< bool, String >
+	{
+		let value : Result< TagValue, String > = self.database.read_tag ( &mut self.client,
+																		   name );
+
+		if self.published_values.get ( name ) == Some( &value )
+		{
+			return Ok( false );
+		}
+
+		let unit : String = self.database.get_tag ( name ).map ( | tag | tag.get_unit ().to_string () ).unwrap_or_default ();
+		let payload : String = create_value_payload ( &value,
+													  &unit,
+													  SystemTime::now () );
+
+		self.mqtt.publish ( &format! ( "{}/{}", self.topic_prefix, name ),
+							payload.as_bytes (),
+							true )?;
+		self.published_values.insert ( name.to_string (),
+										value );
+
+		return Ok( true );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_create_value_payload ()
+{
+	let time : SystemTime = ::std::time::UNIX_EPOCH + Duration::from_millis ( 1_760_861_702_125 );
+
+	assert_eq! ( create_value_payload ( &Ok( TagValue::Number( 21.5 ) ), "°C", time ),
+				 "{\"timestamp\":\"2025-10-19T08:15:02.125Z\",\"unit\":\"°C\",\"value\":21.5}" );
+	assert_eq! ( create_value_payload ( &Ok( TagValue::Bool( true ) ), "", time ),
+				 "{\"timestamp\":\"2025-10-19T08:15:02.125Z\",\"value\":true}" );
+	assert_eq! ( create_value_payload ( &Err( "reading tag pump failed".to_string () ), "", time ),
+				 "{\"error\":\"reading tag pump failed\",\"timestamp\":\"2025-10-19T08:15:02.125Z\",\"value\":null}" );
+}
+
+fn create_value_payload ( value : &Result< TagValue, String >, unit : &str, time : SystemTime ) -> String
+{
+	let mut object : serde_json::Map< String, serde_json::Value > = serde_json::Map::new ();
+
+	match *value
+	{
+		Ok( TagValue::Bool( value ) )	=> { object.insert ( "value".to_string (), serde_json::Value::from ( value ) ); }
+		Ok( TagValue::Number( value ) )	=> { object.insert ( "value".to_string (), serde_json::Value::from ( value ) ); }
+		Err( ref message )				=>
+		{
+			object.insert ( "value".to_string (), serde_json::Value::Null );
+			object.insert ( "error".to_string (), serde_json::Value::from ( message.clone () ) );
+		}
+	}
+
+	if !unit.is_empty ()
+	{
+		object.insert ( "unit".to_string (), serde_json::Value::from ( unit ) );
+	}
+
+	object.insert ( "timestamp".to_string (), serde_json::Value::from ( format_timestamp ( time ) ) );
+
+	return serde_json::Value::Object( object ).to_string ();
+}
+
+#[test]
+fn test_parse_set_payload ()
+{
+	assert_eq! ( parse_set_payload ( b"21.5" ), Ok( TagValue::Number( 21.5 ) ) );
+	assert_eq! ( parse_set_payload ( b" false " ), Ok( TagValue::Bool( false ) ) );
+	assert_eq! ( parse_set_payload ( b"{\"value\":-3}" ), Ok( TagValue::Number( -3.0 ) ) );
+	assert! ( parse_set_payload ( b"\"on\"" ).is_err () );
+	assert! ( parse_set_payload ( b"{\"setpoint\":1}" ).is_err () );
+	assert! ( parse_set_payload ( b"21,5" ).is_err () );
+}
+
+fn parse_set_payload ( payload : &[ u8 ] ) -> Result< TagValue, String >
+{
+	let reply : Result< TagValue, String >;
+
+	let json : serde_json::Value = serde_json::from_slice ( payload ).map_err ( | error | format! ( "invalid JSON: {}", error ) )?;
+	let value : &serde_json::Value = json.get ( "value" ).unwrap_or ( &json );
+
+	match *value
+	{
+		serde_json::Value::Bool( value )					=> { reply = Ok( TagValue::Bool( value ) ); }
+		serde_json::Value::Number( ref value )				=> { reply = value.as_f64 ().map ( TagValue::Number ).ok_or ( "invalid number".to_string () ); }
+		_													=> { reply = Err( "the value has to be a number or a bool".to_string () ); }
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use mqtt::testbroker::TestBroker;
+#[cfg( test )]
+use server::datastore::DataStore;
+#[cfg( test )]
+use server::tcpserver::TcpServer;
+#[cfg( test )]
+use tags::datatype::DataType;
+
+#[test]
+fn test_mqtt_bridge ()
+{
+	let store : DataStore = DataStore::new_dense ( 10 );
+	let server : TcpServer = TcpServer::start ( "127.0.0.1:0", store.clone () ).unwrap ();
+	let broker : TestBroker = TestBroker::start ();
+
+	let mut client : TcpClient = TcpClient::new_with_port_and_unitid ( "127.0.0.1", server.get_local_address ().port (), 3 );
+	let mut mqtt : MqttClient = MqttClient::new ( "127.0.0.1", broker.get_port (), "bridge" );
+
+	client.connect ().unwrap ();
+	mqtt.connect ().unwrap ();
+
+	let mut setpoint : Tag = Tag::new ( "setpoint", DataTable::HoldingRegisters, 0, DataType::Int16 );
+	setpoint.set_scaling ( 0.1, 0.0 );
+	setpoint.set_range ( 0.0, 50.0 ).unwrap ();
+	setpoint.set_unit ( "°C" );
+
+	let mut database : TagDatabase = TagDatabase::new ();
+	database.add_tag ( setpoint ).unwrap ();
+	database.add_tag ( Tag::new ( "status", DataTable::InputRegisters, 1, DataType::UInt16 ) ).unwrap ();
+	database.add_tag ( Tag::new ( "missing", DataTable::HoldingRegisters, 20, DataType::UInt16 ) ).unwrap ();
+
+	store.set_registers ( DataTable::HoldingRegisters, 0, &[ 215 ] ).unwrap ();
+
+	let mut bridge : MqttBridge = MqttBridge::new ( client, database, mqtt, "boiler" );
+
+	assert_eq! ( bridge.get_topic_prefix (), "boiler/3" );
+
+	bridge.subscribe ().unwrap ();
+
+	assert_eq! ( bridge.poll_tags (), Ok( 3 ) );
+	assert_eq! ( bridge.poll_tags (), Ok( 0 ) );
+
+	let messages : Vec< ( String, Vec< u8 > ) > = broker.wait_for_messages ( 3 );
+	let setpoint_message : serde_json::Value = serde_json::from_slice ( &messages[ 0 ].1 ).unwrap ();
+	let missing_message : serde_json::Value = serde_json::from_slice ( &messages[ 2 ].1 ).unwrap ();
+
+	assert_eq! ( broker.get_subscriptions (), vec![ "boiler/3/+/set".to_string () ] );
+	assert_eq! ( messages.len (), 3 );
+	assert_eq! ( messages[ 0 ].0, "boiler/3/setpoint" );
+	assert_eq! ( setpoint_message[ "value" ], serde_json::Value::from ( 21.5 ) );
+	assert_eq! ( setpoint_message[ "unit" ], serde_json::Value::from ( "°C" ) );
+	assert_eq! ( messages[ 2 ].0, "boiler/3/missing" );
+	assert_eq! ( missing_message[ "value" ], serde_json::Value::Null );
+
+	broker.clear_messages ();
+	broker.publish ( "boiler/3/setpoint/set", b"{\"value\":22.5}" );
+
+	let set_message : MqttMessage = bridge.get_mqtt_client ().receive ( Duration::from_secs ( 2 ) ).unwrap ().unwrap ();
+
+	assert_eq! ( bridge.process_message ( &set_message ), Ok( () ) );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 0, 1 ), Some( vec![ 225 ] ) );
+
+	let messages : Vec< ( String, Vec< u8 > ) > = broker.wait_for_messages ( 2 );
+
+	assert_eq! ( messages[ 0 ], ( "boiler/3/setpoint/set/result".to_string (), b"{\"ok\":true}".to_vec () ) );
+	assert_eq! ( messages[ 1 ].0, "boiler/3/setpoint" );
+
+	broker.clear_messages ();
+
+	let out_of_range : MqttMessage = MqttMessage::new ( "boiler/3/setpoint/set", b"90" );
+	let read_only : MqttMessage = MqttMessage::new ( "boiler/3/status/set", b"1" );
+	let other_device : MqttMessage = MqttMessage::new ( "pump/3/setpoint/set", b"1" );
+
+	assert! ( bridge.process_message ( &out_of_range ).is_err () );
+	assert_eq! ( bridge.process_message ( &read_only ), Err( "tag status is read only".to_string () ) );
+	assert! ( bridge.process_message ( &other_device ).is_err () );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 0, 1 ), Some( vec![ 225 ] ) );
+
+	let messages : Vec< ( String, Vec< u8 > ) > = broker.wait_for_messages ( 2 );
+
+	assert_eq! ( messages.len (), 2 );
+	assert_eq! ( messages[ 1 ], ( "boiler/3/status/set/result".to_string (), b"{\"error\":\"tag status is read only\",\"ok\":false}".to_vec () ) );
+}
@@ -0,0 +1,386 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::time::{Duration, Instant};
+use mqtt::packet::*;
+use network::common::create_tcp_stream_with_timeout;
+
+//	===============================================================================================
+
+const MQTT_DEFAULT_KEEP_ALIVE : u64 = 60;
+const MQTT_DEFAULT_TIMEOUT : u64 = 5000;
+
+//	===============================================================================================
+
+/// A message received on a subscribed topic.
+#[derive( Clone, Debug, PartialEq )]
+pub struct MqttMessage
+{
+	topic : String,
+	payload : Vec< u8 >
+}
+
+impl MqttMessage
+{
+	pub fn new ( topic : &str, payload : &[ u8 ] ) -> MqttMessage
+	{
+		return MqttMessage
+		{
+			topic : topic.to_string (),
+			payload : payload.to_vec ()
+		};
+	}
+
+	pub fn get_topic ( &self ) -> &str
+	{
+		return &self.topic;
+	}
+
+	pub fn get_payload ( &self ) -> &Vec< u8 >
+	{
+		return &self.payload;
+	}
+}
+
+//	===============================================================================================
+
+/// A minimal MQTT 3.1.1 client over TCP, which publishes and subscribes with
+/// QoS 0 and a clean session. Pings are sent by `receive` after the keep
+/// alive interval without other packets.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::time::Duration;
+/// use modbus_iiot::mqtt::client::MqttClient;
+///
+/// let mut client = MqttClient::new("127.0.0.1", 1883, "modbus-bridge");
+///
+/// if client.connect().is_ok()
+/// {
+///     client.publish("plant/1/temperature", b"21.5", true).unwrap();
+///     client.subscribe("plant/1/+/set").unwrap();
+///
+///     if let Ok(Some(message)) = client.receive(Duration::from_secs(1))
+///     {
+///         println!("{} = {:?}", message.get_topic(), message.get_payload());
+///     }
+///
+///     client.disconnect();
+/// }
+/// ```
+pub struct MqttClient
+{
+	address : String,
+	port : u16,
+	client_id : String,
+	credentials : Option< ( String, String ) >,
+	keep_alive : Duration,
+	timeout : Duration,
+	stream : Option< TcpStream >,
+	last_packet_id : u16,
+	last_sent : Instant,
+	pending_messages : VecDeque< MqttMessage >
+}
+
+impl MqttClient
+{
+	///	Creates a client for the broker at the address and port, e.g. `MQTT_DEFAULT_PORT`.
+	pub fn new ( address : &str, port : u16, client_id : &str ) -> MqttClient
+	{
+		return MqttClient
+		{
+			address : address.to_string (),
+			port : port,
+			client_id : client_id.to_string (),
+			credentials : None,
+			keep_alive : Duration::from_secs ( MQTT_DEFAULT_KEEP_ALIVE ),
+			timeout : Duration::from_millis ( MQTT_DEFAULT_TIMEOUT ),
+			stream : None,
+			last_packet_id : 0,
+			last_sent : Instant::now (),
+			pending_messages : VecDeque::new ()
+		};
+	}
+
+	///	Sets the user name and password sent with the next `connect`.
+	pub fn set_credentials ( &mut self, username : &str, password : &str )
+	{
+		self.credentials = Some( ( username.to_string (), password.to_string () ) );
+	}
+
+	///	Sets the keep alive interval (60 s by default), which applies from the next `connect`.
+	pub fn set_keep_alive ( &mut self, keep_alive : Duration )
+	{
+		self.keep_alive = keep_alive;
+	}
+
+	///	Sets how long to wait for the connection and the acknowledgements of the broker (5 s by default).
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	pub fn is_connected ( &self ) -> bool
+	{
+		return self.stream.is_some ();
+	}
+
+	///	Opens the connection and waits for the acknowledgement of the broker.
+	pub fn connect ( &mut self ) -> Result< (), String >
+	{
+		let stream : TcpStream = create_tcp_stream_with_timeout ( &self.address,
+																  self.port,
+																  Some( self.timeout ) )?;
+
+		let _ = stream.set_nodelay ( true );
+
+		self.stream = Some( stream );
+		self.pending_messages.clear ();
+
+		let connect : MqttPacket = MqttPacket::Connect
+		{
+			client_id : self.client_id.clone (),
+			keep_alive : self.keep_alive.as_secs ().min ( u16::MAX as u64 ) as u16,
+			credentials : self.credentials.clone ()
+		};
+
+		self.send ( &connect )?;
+
+		match self.read_packet ( self.timeout )?
+		{
+			Some( MqttPacket::ConnAck { return_code : 0, .. } )	=> { log_event! ( info, "connected to broker {}:{}", self.address, self.port ); }
+			Some( MqttPacket::ConnAck { return_code, .. } )		=> { return self.fail ( format! ( "broker refused the connection with code {}", return_code ) ); }
+			_													=> { return self.fail ( "no connection acknowledgement".to_string () ); }
+		}
+
+		return Ok( () );
+	}
+
+	///	Sends a disconnect and closes the connection if it is open.
+	pub fn disconnect ( &mut self ) -> bool
+	{
+		let mut reply : bool = false;
+
+		if self.stream.is_some ()
+		{
+			let _ = self.send ( &MqttPacket::Disconnect );
+
+			if let Some( stream ) = self.stream.take ()
+			{
+				reply = stream.shutdown ( Shutdown::Both ).is_ok ();
+			}
+		}
+
+		return reply;
+	}
+
+	pub fn publish ( &mut self, topic : &str, payload : &[ u8 ], retain : bool ) -> Result< (), String >
+	{
+		return self.send ( &MqttPacket::Publish
+						   {
+							   topic : topic.to_string (),
+							   payload : payload.to_vec (),
+							   retain : retain
+						   } );
+	}
+
+	///	Subscribes to the topic filter, which may contain the wildcards `+` and `#`,
+	/// and waits for the acknowledgement. Messages arriving meanwhile are kept for `receive`.
+	pub fn subscribe ( &mut self, topic_filter : &str ) -> Result< (), String >
+	{
+		self.last_packet_id = self.last_packet_id.wrapping_add ( 1 ).max ( 1 );
+
+		let packet_id : u16 = self.last_packet_id;
+
+		self.send ( &MqttPacket::Subscribe
+					{
+						packet_id : packet_id,
+						topic_filters : vec![ topic_filter.to_string () ]
+					} )?;
+
+		let deadline : Instant = Instant::now () + self.timeout;
+
+		loop
+		{
+			let now : Instant = Instant::now ();
+
+			if now >= deadline
+			{
+				return Err( format! ( "no subscription acknowledgement for {}", topic_filter ) );
+			}
+
+			match self.read_packet ( deadline - now )?
+			{
+				Some( MqttPacket::SubAck { packet_id : acknowledged_id, ref return_codes } ) if acknowledged_id == packet_id =>
+				{
+					if return_codes.contains ( &0x80 )
+					{
+						return Err( format! ( "broker refused the subscription to {}", topic_filter ) );
+					}
+
+					return Ok( () );
+				}
+				Some( MqttPacket::Publish { topic, payload, .. } ) =>
+				{
+					self.pending_messages.push_back ( MqttMessage::new ( &topic, &payload ) );
+				}
+				_ => {}
+			}
+		}
+	}
+
+	///	Waits up to the timeout for a message of the subscriptions, `None` if none arrived.
+	pub fn receive ( &mut self, timeout : Duration ) -> Result< Option< MqttMessage >, String >
+	{
+		if let Some( message ) = self.pending_messages.pop_front ()
+		{
+			return Ok( Some( message ) );
+		}
+
+		if self.keep_alive > Duration::from_millis ( 0 ) && self.last_sent.elapsed () >= self.keep_alive
+		{
+			self.send ( &MqttPacket::PingReq )?;
+		}
+
+		let mut reply : Option< MqttMessage > = None;
+
+		let wait_time : Duration;
+
+		if self.keep_alive > Duration::from_millis ( 0 )
+		{
+			wait_time = timeout.min ( self.keep_alive );
+		}
+		else
+		{
+			wait_time = timeout;
+		}
+
+		if let Some( MqttPacket::Publish { topic, payload, .. } ) = self.read_packet ( wait_time )?
+		{
+			reply = Some( MqttMessage::new ( &topic, &payload ) );
+		}
+
+		return Ok( reply );
+	}
+
+	fn send ( &mut self, packet : &MqttPacket ) -> Result< (), String >
+	{
+		let result : io::Result< () >;
+
+		match self.stream
+		{
+			Some( ref mut stream )	=> { result = stream.write_all ( &packet.to_bytes () ); }
+			None					=> { return Err( "not connected".to_string () ); }
+		}
+
+		if let Err( error ) = result
+		{
+			return self.fail ( error.to_string () );
+		}
+
+		self.last_sent = Instant::now ();
+
+		return Ok( () );
+	}
+
+	///	Waits up to the timeout for the first byte of a packet, then reads the whole packet.
+	fn read_packet ( &mut self, timeout : Duration ) -> Result< Option< MqttPacket >, String >
+	{
+		let result : Result< Option< MqttPacket >, String >;
+
+		match self.stream
+		{
+			Some( ref mut stream )	=> { result = read_packet_with_timeout ( stream, timeout, self.timeout ); }
+			None					=> { return Err( "not connected".to_string () ); }
+		}
+
+		if let Err( message ) = result
+		{
+			return self.fail ( message );
+		}
+
+		return result;
+	}
+
+	///	Closes the connection after an error, the client has to `connect` again.
+	fn fail< T > ( &mut self, message : String ) -> Result< T, String >
+	{
+		log_event! ( warn, "connection to broker {}:{} failed: {}", self.address, self.port, message );
+
+		if let Some( stream ) = self.stream.take ()
+		{
+			let _ = stream.shutdown ( Shutdown::Both );
+		}
+
+		return Err( message );
+	}
+}
+
+impl Drop for MqttClient
+{
+	fn drop ( &mut self )
+	{
+		self.disconnect ();
+	}
+}
+
+//	===============================================================================================
+
+fn read_packet_with_timeout ( stream : &mut TcpStream, timeout : Duration, packet_timeout : Duration ) -> Result< Option< MqttPacket >, String >
+{
+	let mut first_byte : [ u8; 1 ] = [ 0 ];
+
+	stream.set_read_timeout ( Some( timeout.max ( Duration::from_millis ( 1 ) ) ) ).map_err ( | error | error.to_string () )?;
+
+	match stream.read ( &mut first_byte )
+	{
+		Ok( 0 )	=> { return Err( "connection closed by broker".to_string () ); }
+		Ok( _ )	=> {}
+		Err( ref error ) if error.kind () == io::ErrorKind::WouldBlock || error.kind () == io::ErrorKind::TimedOut =>
+		{
+			return Ok( None );
+		}
+		Err( error ) => { return Err( error.to_string () ); }
+	}
+
+	stream.set_read_timeout ( Some( packet_timeout ) ).map_err ( | error | error.to_string () )?;
+
+	return Ok( Some( MqttPacket::read_after_first_byte ( stream, first_byte[ 0 ] )? ) );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use mqtt::testbroker::TestBroker;
+
+#[test]
+fn test_mqtt_client ()
+{
+	let broker : TestBroker = TestBroker::start ();
+	let mut client : MqttClient = MqttClient::new ( "127.0.0.1", broker.get_port (), "test" );
+
+	assert! ( client.publish ( "a", b"1", false ).is_err () );
+
+	client.set_credentials ( "user", "secret" );
+	client.connect ().unwrap ();
+
+	assert! ( client.is_connected () );
+	assert_eq! ( broker.get_client_ids (), vec![ "test".to_string () ] );
+
+	client.subscribe ( "plant/+/set" ).unwrap ();
+	client.publish ( "plant/state", b"{\"value\":1}", true ).unwrap ();
+	broker.publish ( "plant/pump/set", b"true" );
+
+	let message : MqttMessage = client.receive ( Duration::from_secs ( 2 ) ).unwrap ().unwrap ();
+	assert_eq! ( message.get_topic (), "plant/pump/set" );
+	assert_eq! ( message.get_payload (), &b"true".to_vec () );
+	assert_eq! ( client.receive ( Duration::from_millis ( 10 ) ), Ok( None ) );
+
+	assert_eq! ( broker.wait_for_messages ( 1 ), vec![ ( "plant/state".to_string (), b"{\"value\":1}".to_vec () ) ] );
+	assert_eq! ( broker.get_subscriptions (), vec![ "plant/+/set".to_string () ] );
+
+	assert! ( client.disconnect () );
+	assert! ( !client.is_connected () );
+}
@@ -0,0 +1,7 @@
+
+
+pub mod bridge;
+pub mod client;
+pub mod packet;
+#[cfg( test )]
+pub mod testbroker;
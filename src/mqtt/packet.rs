@@ -0,0 +1,369 @@
+use std::io::Read;
+
+//	===============================================================================================
+
+pub const MQTT_DEFAULT_PORT : u16 = 1883;
+
+const MQTT_PROTOCOL_NAME : &str = "MQTT";
+const MQTT_PROTOCOL_LEVEL : u8 = 4;
+const MQTT_CONNECT_CLEAN_SESSION : u8 = 0x02;
+const MQTT_CONNECT_PASSWORD : u8 = 0x40;
+const MQTT_CONNECT_USERNAME : u8 = 0x80;
+const MQTT_MAXIMUM_REMAINING_LENGTH : usize = 268_435_455;
+
+//	===============================================================================================
+
+/// The MQTT 3.1.1 control packets used by the client, publishing and
+/// subscribing is done with QoS 0 only.
+#[derive( Clone, Debug, PartialEq )]
+pub enum MqttPacket
+{
+	Connect
+	{
+		client_id : String,
+		keep_alive : u16,
+		credentials : Option< ( String, String ) >
+	},
+	ConnAck
+	{
+		session_present : bool,
+		return_code : u8
+	},
+	Publish
+	{
+		topic : String,
+		payload : Vec< u8 >,
+		retain : bool
+	},
+	Subscribe
+	{
+		packet_id : u16,
+		topic_filters : Vec< String >
+	},
+	SubAck
+	{
+		packet_id : u16,
+		return_codes : Vec< u8 >
+	},
+	PingReq,
+	PingResp,
+	Disconnect
+}
+
+impl MqttPacket
+{
+	///	Encodes the packet with its fixed header.
+	pub fn to_bytes ( &self ) -> Vec< u8 >
+	{
+		let packet_type : u8;
+		let mut body : Vec< u8 > = vec![];
+
+		match *self
+		{
+			MqttPacket::Connect { ref client_id, keep_alive, ref credentials } =>
+			{
+				let mut flags : u8 = MQTT_CONNECT_CLEAN_SESSION;
+
+				if credentials.is_some ()
+				{
+					flags |= MQTT_CONNECT_USERNAME | MQTT_CONNECT_PASSWORD;
+				}
+
+				packet_type = 0x10;
+				append_string ( &mut body, MQTT_PROTOCOL_NAME );
+				body.push ( MQTT_PROTOCOL_LEVEL );
+				body.push ( flags );
+				body.extend_from_slice ( &keep_alive.to_be_bytes () );
+				append_string ( &mut body, client_id );
+
+				if let Some( ( ref username, ref password ) ) = *credentials
+				{
+					append_string ( &mut body, username );
+					append_string ( &mut body, password );
+				}
+			}
+			MqttPacket::ConnAck { session_present, return_code } =>
+			{
+				packet_type = 0x20;
+				body.push ( session_present as u8 );
+				body.push ( return_code );
+			}
+			MqttPacket::Publish { ref topic, ref payload, retain } =>
+			{
+				packet_type = 0x30 | retain as u8;
+				append_string ( &mut body, topic );
+				body.extend_from_slice ( payload );
+			}
+			MqttPacket::Subscribe { packet_id, ref topic_filters } =>
+			{
+				packet_type = 0x82;
+				body.extend_from_slice ( &packet_id.to_be_bytes () );
+
+				for topic_filter in topic_filters
+				{
+					append_string ( &mut body, topic_filter );
+					body.push ( 0 );
+				}
+			}
+			MqttPacket::SubAck { packet_id, ref return_codes } =>
+			{
+				packet_type = 0x90;
+				body.extend_from_slice ( &packet_id.to_be_bytes () );
+				body.extend_from_slice ( return_codes );
+			}
+			MqttPacket::PingReq		=> { packet_type = 0xC0; }
+			MqttPacket::PingResp	=> { packet_type = 0xD0; }
+			MqttPacket::Disconnect	=> { packet_type = 0xE0; }
+		}
+
+		let mut reply : Vec< u8 > = vec![ packet_type ];
+
+		append_remaining_length ( &mut reply,
+								  body.len () );
+		reply.append ( &mut body );
+
+		return reply;
+	}
+
+	///	Reads one packet from the stream. Publish packets of QoS 1 and 2 are
+	/// returned without their packet identifier.
+	pub fn read_from< T : Read > ( stream : &mut T ) -> Result< MqttPacket, String >
+	{
+		let mut first_byte : [ u8; 1 ] = [ 0 ];
+
+		stream.read_exact ( &mut first_byte ).map_err ( | error | error.to_string () )?;
+
+		return Self::read_after_first_byte ( stream,
+											 first_byte[ 0 ] );
+	}
+
+	///	Reads the rest of a packet whose first byte was already read.
+	pub fn read_after_first_byte< T : Read > ( stream : &mut T, first_byte : u8 ) -> Result< MqttPacket, String >
+	{
+		let length : usize = read_remaining_length ( stream )?;
+		let mut body : Vec< u8 > = vec![ 0; length ];
+
+		stream.read_exact ( &mut body ).map_err ( | error | error.to_string () )?;
+
+		return Self::decode ( first_byte,
+							  &body );
+	}
+
+	fn decode ( first_byte : u8, body : &[ u8 ] ) -> Result< MqttPacket, String >
+	{
+		let reply : MqttPacket;
+		let mut position : usize = 0;
+
+		match first_byte >> 4
+		{
+			1 =>
+			{
+				let protocol_name : String = read_string ( body, &mut position )?;
+
+				if protocol_name != MQTT_PROTOCOL_NAME || body.len () < position + 4
+				{
+					return Err( format! ( "unsupported protocol {}", protocol_name ) );
+				}
+
+				let flags : u8 = body[ position + 1 ];
+				let keep_alive : u16 = u16::from_be_bytes ( [ body[ position + 2 ], body[ position + 3 ] ] );
+
+				position += 4;
+
+				let client_id : String = read_string ( body, &mut position )?;
+				let mut credentials : Option< ( String, String ) > = None;
+
+				if flags & MQTT_CONNECT_USERNAME != 0
+				{
+					let username : String = read_string ( body, &mut position )?;
+					let password : String = read_string ( body, &mut position ).unwrap_or_default ();
+
+					credentials = Some( ( username, password ) );
+				}
+
+				reply = MqttPacket::Connect { client_id : client_id, keep_alive : keep_alive, credentials : credentials };
+			}
+			2 if body.len () == 2 =>
+			{
+				reply = MqttPacket::ConnAck { session_present : body[ 0 ] & 0x01 != 0, return_code : body[ 1 ] };
+			}
+			3 =>
+			{
+				let topic : String = read_string ( body, &mut position )?;
+
+				if ( first_byte >> 1 ) & 0x03 != 0
+				{
+					position += 2;
+				}
+
+				if position > body.len ()
+				{
+					return Err( "truncated publish packet".to_string () );
+				}
+
+				reply = MqttPacket::Publish { topic : topic, payload : body[ position.. ].to_vec (), retain : first_byte & 0x01 != 0 };
+			}
+			8 if body.len () >= 2 =>
+			{
+				let mut topic_filters : Vec< String > = vec![];
+
+				position = 2;
+
+				while position < body.len ()
+				{
+					topic_filters.push ( read_string ( body, &mut position )? );
+					position += 1;
+				}
+
+				reply = MqttPacket::Subscribe { packet_id : u16::from_be_bytes ( [ body[ 0 ], body[ 1 ] ] ), topic_filters : topic_filters };
+			}
+			9 if body.len () >= 2 =>
+			{
+				reply = MqttPacket::SubAck { packet_id : u16::from_be_bytes ( [ body[ 0 ], body[ 1 ] ] ), return_codes : body[ 2.. ].to_vec () };
+			}
+			12	=> { reply = MqttPacket::PingReq; }
+			13	=> { reply = MqttPacket::PingResp; }
+			14	=> { reply = MqttPacket::Disconnect; }
+			_	=> { return Err( format! ( "unsupported packet 0x{:02X}", first_byte ) ); }
+		}
+
+		return Ok( reply );
+	}
+}
+
+#[test]
+fn test_mqtt_packet_encoding ()
+{
+	let connect : MqttPacket = MqttPacket::Connect { client_id : "plc".to_string (), keep_alive : 60, credentials : None };
+	assert_eq! ( connect.to_bytes (), vec![ 0x10, 0x0F, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0x02, 0x00, 0x3C, 0x00, 0x03, b'p', b'l', b'c' ] );
+
+	let publish : MqttPacket = MqttPacket::Publish { topic : "a/b".to_string (), payload : b"1".to_vec (), retain : true };
+	assert_eq! ( publish.to_bytes (), vec![ 0x31, 0x06, 0x00, 0x03, b'a', b'/', b'b', b'1' ] );
+
+	let subscribe : MqttPacket = MqttPacket::Subscribe { packet_id : 1, topic_filters : vec![ "a/+/set".to_string () ] };
+	assert_eq! ( subscribe.to_bytes (), vec![ 0x82, 0x0C, 0x00, 0x01, 0x00, 0x07, b'a', b'/', b'+', b'/', b's', b'e', b't', 0x00 ] );
+
+	assert_eq! ( MqttPacket::PingReq.to_bytes (), vec![ 0xC0, 0x00 ] );
+	assert_eq! ( MqttPacket::Disconnect.to_bytes (), vec![ 0xE0, 0x00 ] );
+}
+
+#[test]
+fn test_mqtt_packet_decoding ()
+{
+	let packets : Vec< MqttPacket > = vec![ MqttPacket::Connect { client_id : "plc".to_string (), keep_alive : 30, credentials : Some( ( "user".to_string (), "secret".to_string () ) ) },
+											MqttPacket::ConnAck { session_present : false, return_code : 0 },
+											MqttPacket::Publish { topic : "plant/1/temperature".to_string (), payload : vec![ 0; 200 ], retain : false },
+											MqttPacket::Subscribe { packet_id : 7, topic_filters : vec![ "a/#".to_string (), "b".to_string () ] },
+											MqttPacket::SubAck { packet_id : 7, return_codes : vec![ 0, 0x80 ] },
+											MqttPacket::PingResp ];
+
+	for packet in packets
+	{
+		let bytes : Vec< u8 > = packet.to_bytes ();
+
+		assert_eq! ( MqttPacket::read_from ( &mut &bytes[ .. ] ), Ok( packet ) );
+	}
+
+	let qos_1_publish : Vec< u8 > = vec![ 0x32, 0x06, 0x00, 0x01, b'a', 0x00, 0x05, b'x' ];
+	assert_eq! ( MqttPacket::read_from ( &mut &qos_1_publish[ .. ] ), Ok( MqttPacket::Publish { topic : "a".to_string (), payload : b"x".to_vec (), retain : false } ) );
+
+	assert! ( MqttPacket::read_from ( &mut &[ 0x30, 0x05, 0x00, 0x09, b'a' ][ .. ] ).is_err () );
+	assert! ( MqttPacket::read_from ( &mut &[ 0xF0, 0x00 ][ .. ] ).is_err () );
+}
+
+//	===============================================================================================
+
+fn append_string ( bytes : &mut Vec< u8 >, text : &str )
+{
+	bytes.extend_from_slice ( &( text.len () as u16 ).to_be_bytes () );
+	bytes.extend_from_slice ( text.as_bytes () );
+}
+
+fn read_string ( bytes : &[ u8 ], position : &mut usize ) -> Result< String, String >
+{
+	if bytes.len () < *position + 2
+	{
+		return Err( "truncated string".to_string () );
+	}
+
+	let length : usize = u16::from_be_bytes ( [ bytes[ *position ], bytes[ *position + 1 ] ] ) as usize;
+	let start : usize = *position + 2;
+
+	if bytes.len () < start + length
+	{
+		return Err( "truncated string".to_string () );
+	}
+
+	*position = start + length;
+
+	return String::from_utf8 ( bytes[ start..start + length ].to_vec () ).map_err ( | error | error.to_string () );
+}
+
+#[test]
+fn test_remaining_length ()
+{
+	for length in [ 0, 127, 128, 16_383, 16_384, 2_097_152 ].iter ()
+	{
+		let mut bytes : Vec< u8 > = vec![];
+
+		append_remaining_length ( &mut bytes, *length );
+
+		assert_eq! ( read_remaining_length ( &mut &bytes[ .. ] ), Ok( *length ) );
+	}
+
+	let mut bytes : Vec< u8 > = vec![];
+	append_remaining_length ( &mut bytes, 321 );
+	assert_eq! ( bytes, vec![ 0xC1, 0x02 ] );
+}
+
+fn append_remaining_length ( bytes : &mut Vec< u8 >, length : usize )
+{
+	let mut remaining : usize = length;
+
+	loop
+	{
+		let mut byte : u8 = ( remaining % 128 ) as u8;
+
+		remaining /= 128;
+
+		if remaining > 0
+		{
+			byte |= 0x80;
+		}
+
+		bytes.push ( byte );
+
+		if remaining == 0
+		{
+			break;
+		}
+	}
+}
+
+fn read_remaining_length< T : Read > ( stream : &mut T ) -> Result< usize, String >
+{
+	let mut reply : usize = 0;
+	let mut multiplier : usize = 1;
+	let mut byte : [ u8; 1 ] = [ 0 ];
+
+	loop
+	{
+		stream.read_exact ( &mut byte ).map_err ( | error | error.to_string () )?;
+
+		reply += ( byte[ 0 ] & 0x7F ) as usize * multiplier;
+
+		if byte[ 0 ] & 0x80 == 0
+		{
+			break;
+		}
+
+		multiplier *= 128;
+
+		if multiplier > MQTT_MAXIMUM_REMAINING_LENGTH
+		{
+			return Err( "invalid remaining length".to_string () );
+		}
+	}
+
+	return Ok( reply );
+}
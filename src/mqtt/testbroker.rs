@@ -0,0 +1,170 @@
+use std::io::Write;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use mqtt::packet::MqttPacket;
+
+//	===============================================================================================
+
+#[derive( Default )]
+struct BrokerState
+{
+	client_ids : Vec< String >,
+	subscriptions : Vec< String >,
+	messages : Vec< ( String, Vec< u8 > ) >,
+	connection : Option< TcpStream >
+}
+
+/// A broker stand-in for unit tests on a free local port. It accepts one
+/// client at a time, records its publications and subscriptions and forwards
+/// messages to it which match one of its subscriptions.
+pub struct TestBroker
+{
+	port : u16,
+	state : Arc< Mutex< BrokerState > >
+}
+
+impl TestBroker
+{
+	pub fn start () -> TestBroker
+	{
+		let listener : TcpListener = TcpListener::bind ( "127.0.0.1:0" ).unwrap ();
+		let port : u16 = listener.local_addr ().unwrap ().port ();
+		let state : Arc< Mutex< BrokerState > > = Arc::new ( Mutex::new ( BrokerState::default () ) );
+		let thread_state : Arc< Mutex< BrokerState > > = state.clone ();
+
+		thread::spawn ( move ||
+		{
+			for stream in listener.incoming ().flatten ()
+			{
+				serve_client ( stream,
+							   &thread_state );
+			}
+		} );
+
+		return TestBroker
+		{
+			port : port,
+			state : state
+		};
+	}
+
+	pub fn get_port ( &self ) -> u16
+	{
+		return self.port;
+	}
+
+	pub fn get_client_ids ( &self ) -> Vec< String >
+	{
+		return self.state.lock ().unwrap ().client_ids.clone ();
+	}
+
+	pub fn get_subscriptions ( &self ) -> Vec< String >
+	{
+		return self.state.lock ().unwrap ().subscriptions.clone ();
+	}
+
+	///	Waits up to two seconds until the client published the count of messages and returns all.
+	pub fn wait_for_messages ( &self, count : usize ) -> Vec< ( String, Vec< u8 > ) >
+	{
+		let deadline : Instant = Instant::now () + Duration::from_secs ( 2 );
+
+		while self.state.lock ().unwrap ().messages.len () < count && Instant::now () < deadline
+		{
+			thread::sleep ( Duration::from_millis ( 5 ) );
+		}
+
+		return self.state.lock ().unwrap ().messages.clone ();
+	}
+
+	pub fn clear_messages ( &self )
+	{
+		self.state.lock ().unwrap ().messages.clear ();
+	}
+
+	///	Sends the message to the client if it matches one of its subscriptions.
+	pub fn publish ( &self, topic : &str, payload : &[ u8 ] )
+	{
+		forward_message ( &mut self.state.lock ().unwrap (),
+						  topic,
+						  payload );
+	}
+}
+
+fn serve_client ( mut stream : TcpStream, state : &Mutex< BrokerState > )
+{
+	state.lock ().unwrap ().connection = stream.try_clone ().ok ();
+
+	while let Ok( packet ) = MqttPacket::read_from ( &mut stream )
+	{
+		let mut state = state.lock ().unwrap ();
+		let response : Option< MqttPacket >;
+
+		match packet
+		{
+			MqttPacket::Connect { client_id, .. } =>
+			{
+				state.client_ids.push ( client_id );
+				response = Some( MqttPacket::ConnAck { session_present : false, return_code : 0 } );
+			}
+			MqttPacket::Subscribe { packet_id, topic_filters } =>
+			{
+				response = Some( MqttPacket::SubAck { packet_id : packet_id, return_codes : vec![ 0; topic_filters.len () ] } );
+				state.subscriptions.extend ( topic_filters );
+			}
+			MqttPacket::Publish { topic, payload, .. } =>
+			{
+				forward_message ( &mut state,
+								  &topic,
+								  &payload );
+				state.messages.push ( ( topic, payload ) );
+				response = None;
+			}
+			MqttPacket::PingReq	=> { response = Some( MqttPacket::PingResp ); }
+			_					=> { break; }
+		}
+
+		if let Some( response ) = response
+		{
+			let _ = stream.write_all ( &response.to_bytes () );
+		}
+	}
+
+	let _ = stream.shutdown ( Shutdown::Both );
+	state.lock ().unwrap ().subscriptions.clear ();
+}
+
+fn forward_message ( state : &mut BrokerState, topic : &str, payload : &[ u8 ] )
+{
+	if state.subscriptions.iter ().any ( | filter | is_topic_matching ( filter, topic ) )
+	{
+		if let Some( ref mut connection ) = state.connection
+		{
+			let publish : MqttPacket = MqttPacket::Publish { topic : topic.to_string (), payload : payload.to_vec (), retain : false };
+
+			let _ = connection.write_all ( &publish.to_bytes () );
+		}
+	}
+}
+
+fn is_topic_matching ( filter : &str, topic : &str ) -> bool
+{
+	let filter_levels : Vec< &str > = filter.split ( '/' ).collect ();
+	let topic_levels : Vec< &str > = topic.split ( '/' ).collect ();
+
+	for ( index, level ) in filter_levels.iter ().enumerate ()
+	{
+		if *level == "#"
+		{
+			return true;
+		}
+
+		if index >= topic_levels.len () || ( *level != "+" && *level != topic_levels[ index ] )
+		{
+			return false;
+		}
+	}
+
+	return filter_levels.len () == topic_levels.len ();
+}
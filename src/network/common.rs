@@ -1,34 +1,42 @@
 
 
-use std::error::Error;
 use std::io;
 use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
 use network::socket::parse_network_address;
 
 //	===============================================================================================
 
 pub fn create_tcp_stream ( ip_address : &str, port : u16 ) -> Result< TcpStream, String >
+{
+	return create_tcp_stream_with_timeout ( ip_address,
+											port,
+											None );
+}
+
+///	Connects like `create_tcp_stream`, but gives up after the timeout if there is one.
+pub fn create_tcp_stream_with_timeout ( ip_address : &str, port : u16, timeout : Option< Duration > ) -> Result< TcpStream, String >
 {	
 	let reply : Result< TcpStream, String >;
 
 	let address_result : Result< SocketAddr, String > = parse_network_address ( ip_address, 
 																				port );
 
-	if address_result.is_ok ()
+	match address_result
 	{
-		let connection_result : io::Result< TcpStream > = TcpStream::connect ( address_result.unwrap () );
-		if connection_result.is_ok ()
+		Ok( address )		=>
 		{
-			reply = Ok( connection_result.unwrap () );
-		}
-		else
-		{			
-			reply = Err( connection_result.unwrap_err ().description ().to_owned () );
+			let connection_result : io::Result< TcpStream >;
+
+			match timeout
+			{
+				Some( timeout )	=> { connection_result = TcpStream::connect_timeout ( &address, timeout ); }
+				None			=> { connection_result = TcpStream::connect ( address ); }
+			}
+
+			reply = connection_result.map_err ( | error | error.to_string () );
 		}
-	}
-	else
-	{
-		reply = Err( address_result.unwrap_err () );
+		Err( message )		=> { reply = Err( message ); }
 	}
 
 	return reply;		
@@ -1,6 +1,5 @@
 
 
-use std::error::Error;
 use std::net::{AddrParseError, IpAddr, SocketAddr};
 use std::str::FromStr;
 
@@ -70,15 +69,8 @@ pub fn parse_network_address ( address_string : &str, default_port : u16 ) -> Re
         {
             let ip : Result< IpAddr, String > = parse_ip_address ( address_string );
 
-            if ip.is_ok ()
-            {
-                reply = Ok( SocketAddr::new ( ip.unwrap (),
-                                              default_port ) );
-            }
-            else
-            {
-                reply = Err( ip.unwrap_err () );
-            }
+            reply = ip.map ( | ip | SocketAddr::new ( ip,
+                                                      default_port ) );
         }
     }
 
@@ -126,14 +118,7 @@ fn parse_ip_address ( address_string : &str ) -> Result< IpAddr, String >
     {
         let result : Result< IpAddr, AddrParseError > = IpAddr::from_str ( address_string );
 
-        if result.is_ok ()
-        {
-            reply = Ok( result.unwrap () );
-        }
-        else
-        {
-            reply = Err( result.unwrap_err ().description ().to_owned () );
-        }
+        reply = result.map_err ( | error | error.to_string () );
     }
 
     return reply;
@@ -188,14 +173,7 @@ fn parse_socket_address ( address_string : &str ) -> Result< SocketAddr, String
     {
         let result : Result< SocketAddr, AddrParseError > = SocketAddr::from_str ( address_string );
 
-        if result.is_ok ()
-        {
-            reply = Ok( result.unwrap () );
-        }
-        else
-        {
-            reply = Err( result.unwrap_err ().description ().to_owned () );
-        }
+        reply = result.map_err ( | error | error.to_string () );
     }
 
     return reply;
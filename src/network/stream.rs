@@ -0,0 +1,71 @@
+use std::io;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream};
+#[cfg( feature = "tls" )]
+use rustls::{ClientConnection, StreamOwned};
+
+//	===============================================================================================
+
+/// The connection of a client, plain TCP or with the feature `tls` TLS over TCP.
+pub enum NetworkStream
+{
+	Tcp( TcpStream ),
+	#[cfg( feature = "tls" )]
+	Tls( Box< StreamOwned< ClientConnection, TcpStream > > )
+}
+
+impl NetworkStream
+{
+	///	Closes the connection, TLS connections send a close notification before.
+	pub fn shutdown ( &mut self ) -> io::Result< () >
+	{
+		match *self
+		{
+			NetworkStream::Tcp( ref stream )	=> { return stream.shutdown ( Shutdown::Both ); }
+			#[cfg( feature = "tls" )]
+			NetworkStream::Tls( ref mut stream )	=>
+			{
+				stream.conn.send_close_notify ();
+				let _ = stream.conn.complete_io ( &mut stream.sock );
+
+				return stream.sock.shutdown ( Shutdown::Both );
+			}
+		}
+	}
+}
+
+impl Read for NetworkStream
+{
+	fn read ( &mut self, buffer : &mut [ u8 ] ) -> io::Result< usize >
+	{
+		match *self
+		{
+			NetworkStream::Tcp( ref mut stream )	=> { return stream.read ( buffer ); }
+			#[cfg( feature = "tls" )]
+			NetworkStream::Tls( ref mut stream )	=> { return stream.read ( buffer ); }
+		}
+	}
+}
+
+impl Write for NetworkStream
+{
+	fn write ( &mut self, buffer : &[ u8 ] ) -> io::Result< usize >
+	{
+		match *self
+		{
+			NetworkStream::Tcp( ref mut stream )	=> { return stream.write ( buffer ); }
+			#[cfg( feature = "tls" )]
+			NetworkStream::Tls( ref mut stream )	=> { return stream.write ( buffer ); }
+		}
+	}
+
+	fn flush ( &mut self ) -> io::Result< () >
+	{
+		match *self
+		{
+			NetworkStream::Tcp( ref mut stream )	=> { return stream.flush (); }
+			#[cfg( feature = "tls" )]
+			NetworkStream::Tls( ref mut stream )	=> { return stream.flush (); }
+		}
+	}
+}
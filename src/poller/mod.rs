@@ -0,0 +1,4 @@
+
+
+pub mod pollingjob;
+pub mod scheduler;
@@ -0,0 +1,389 @@
+use std::time::{Duration, Instant};
+use core::batchreader::*;
+use core::ethernet::EthernetMaster;
+#[cfg( all( test, feature = "json" ) )]
+use core::consts::MODBUS_MAXIMUM_PDU_SIZE;
+#[cfg( all( test, feature = "json" ) )]
+use core::datatable::DataTable;
+#[cfg( all( test, feature = "json" ) )]
+use serde_json;
+
+//	===============================================================================================
+
+/// A change of a polled tag, reported by the `Poller`.
+/// The previous value is `None` on the first successful or failed read.
+#[derive( Clone, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub struct PollingEvent
+{
+	job_name : String,
+	tag_index : usize,
+	tag : BatchTag,
+	value : BatchValue,
+	previous_value : Option< BatchValue >
+}
+
+impl PollingEvent
+{
+	pub fn get_job_name ( &self ) -> &str
+	{
+		return &self.job_name;
+	}
+
+	pub fn get_previous_value ( &self ) -> Option< &BatchValue >
+	{
+		return self.previous_value.as_ref ();
+	}
+
+	pub fn get_tag ( &self ) -> BatchTag
+	{
+		return self.tag;
+	}
+
+	pub fn get_tag_index ( &self ) -> usize
+	{
+		return self.tag_index;
+	}
+
+	pub fn get_value ( &self ) -> &BatchValue
+	{
+		return &self.value;
+	}
+}
+
+//	===============================================================================================
+
+/// A named group of tags which is read every `interval`.
+/// The tags of a job are read with coalesced requests of a `BatchReader`.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use std::time::Duration;
+/// use modbus_iiot::core::batchreader::BatchTag;
+/// use modbus_iiot::poller::pollingjob::PollingJob;
+///
+/// let mut job = PollingJob::new("fast io", Duration::from_millis(100));
+/// job.add_tag(BatchTag::new_from_reference(1, 16).unwrap());
+/// job.add_tag(BatchTag::new_from_reference(30001, 1).unwrap());
+/// job.set_deadband(5);
+/// ```
+pub struct PollingJob
+{
+	name : String,
+	interval : Duration,
+	deadband : u16,
+	reader : BatchReader,
+	reported_values : Vec< Option< BatchValue > >,
+	next_deadline : Option< Instant >,
+	overrun_count : u64,
+	poll_count : u64,
+	last_duration : Duration
+}
+
+impl PollingJob
+{
+	pub fn new ( name : &str, interval : Duration ) -> PollingJob
+	{
+		return Self::new_with_configuration ( name,
+											  interval,
+											  BatchConfiguration::new () );
+	}
+
+	pub fn new_with_configuration ( name : &str, interval : Duration, configuration : BatchConfiguration ) -> PollingJob
+	{
+		return PollingJob
+		{
+			name : name.to_string (),
+			interval : interval,
+			deadband : 0,
+			reader : BatchReader::new ( configuration ),
+			reported_values : vec![],
+			next_deadline : None,
+			overrun_count : 0,
+			poll_count : 0,
+			last_duration : Duration::from_millis ( 0 )
+		};
+	}
+
+	///	Adds a tag and returns its index, which is reported in each `PollingEvent`.
+	pub fn add_tag ( &mut self, tag : BatchTag ) -> usize
+	{
+		self.reported_values.push ( None );
+
+		return self.reader.add_tag ( tag );
+	}
+
+	///	Tags of a single register are only reported as changed if the unsigned value
+	/// differs by more than the deadband from the last reported value. The registers
+	/// of larger tags have no known data type and word order, so any change is reported.
+	pub fn set_deadband ( &mut self, deadband : u16 )
+	{
+		self.deadband = deadband;
+	}
+
+	pub fn get_deadband ( &self ) -> u16
+	{
+		return self.deadband;
+	}
+
+	pub fn get_interval ( &self ) -> Duration
+	{
+		return self.interval;
+	}
+
+	///	Duration of the last poll of this job.
+	pub fn get_last_duration ( &self ) -> Duration
+	{
+		return self.last_duration;
+	}
+
+	pub fn get_name ( &self ) -> &str
+	{
+		return &self.name;
+	}
+
+	///	The deadline at which the job is polled next, `None` before the first poll.
+	pub fn get_next_deadline ( &self ) -> Option< Instant >
+	{
+		return self.next_deadline;
+	}
+
+	///	Number of polls which started or finished after the following deadline.
+	pub fn get_overrun_count ( &self ) -> u64
+	{
+		return self.overrun_count;
+	}
+
+	pub fn get_poll_count ( &self ) -> u64
+	{
+		return self.poll_count;
+	}
+
+	pub fn get_tags ( &self ) -> &Vec< BatchTag >
+	{
+		return self.reader.get_tags ();
+	}
+
+	///	Returns the last reported value of each tag.
+	pub fn get_values ( &self ) -> &Vec< Option< BatchValue > >
+	{
+		return &self.reported_values;
+	}
+
+	pub fn is_due ( &self, now : Instant ) -> bool
+	{
+		let reply : bool;
+
+		match self.next_deadline
+		{
+			Some( deadline )	=> { reply = now >= deadline; }
+			None				=> { reply = true; }
+		}
+
+		return reply;
+	}
+
+	///	Reads all tags of the job, schedules the next deadline
+	/// and returns the events of all changed tags.
+	pub fn poll< T : EthernetMaster > ( &mut self, master : &mut T, now : Instant ) -> Vec< PollingEvent >
+	{
+		let mut reply : Vec< PollingEvent > = vec![];
+
+		let start_time : Instant = Instant::now ();
+		let values : Vec< BatchValue > = self.reader.read ( master );
+		let end_time : Instant = Instant::now ();
+
+		self.poll_count += 1;
+		self.last_duration = end_time.duration_since ( start_time );
+		self.schedule_next_deadline ( now,
+									  end_time );
+
+		for ( index, value ) in values.into_iter ().enumerate ()
+		{
+			let changed : bool;
+
+			match self.reported_values[ index ]
+			{
+				Some( ref previous )	=> { changed = is_value_changed ( previous, &value, self.deadband ); }
+				None					=> { changed = true; }
+			}
+
+			if changed
+			{
+				let previous_value : Option< BatchValue > = self.reported_values[ index ].take ();
+
+				reply.push ( PollingEvent
+							 {
+								 job_name : self.name.clone (),
+								 tag_index : index,
+								 tag : self.reader.get_tags ()[ index ],
+								 value : value.clone (),
+								 previous_value : previous_value
+							 } );
+
+				self.reported_values[ index ] = Some( value );
+			}
+		}
+
+		return reply;
+	}
+
+	fn schedule_next_deadline ( &mut self, now : Instant, end_time : Instant )
+	{
+		let mut deadline : Instant;
+
+		match self.next_deadline
+		{
+			Some( last_deadline )	=> { deadline = last_deadline + self.interval; }
+			None					=> { deadline = now + self.interval; }
+		}
+
+		if end_time > deadline
+		{
+			if self.interval > Duration::from_millis ( 0 )
+			{
+				self.overrun_count += 1;
+
+				while deadline <= end_time
+				{
+					deadline += self.interval;
+				}
+			}
+			else
+			{
+				deadline = end_time;
+			}
+		}
+
+		self.next_deadline = Some( deadline );
+	}
+}
+
+//	===============================================================================================
+
+/// The settings of a `PollingJob`, e.g. loaded from a configuration file.
+/// Without the feature `serde` the fields are set directly.
+#[derive( Clone, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub struct PollingJobConfiguration
+{
+	pub name : String,
+	pub interval_in_milliseconds : u64,
+	#[cfg_attr( feature = "serde", serde( default ) )]
+	pub deadband : u16,
+	#[cfg_attr( feature = "serde", serde( default = "BatchConfiguration::new" ) )]
+	pub batch_configuration : BatchConfiguration,
+	pub tags : Vec< BatchTag >
+}
+
+impl PollingJobConfiguration
+{
+	pub fn create_job ( &self ) -> PollingJob
+	{
+		let mut reply : PollingJob = PollingJob::new_with_configuration ( &self.name,
+																		  Duration::from_millis ( self.interval_in_milliseconds ),
+																		  self.batch_configuration );
+
+		reply.set_deadband ( self.deadband );
+
+		for tag in self.tags.iter ()
+		{
+			reply.add_tag ( *tag );
+		}
+
+		return reply;
+	}
+}
+
+#[cfg( feature = "json" )]
+#[test]
+fn test_polling_job_configuration ()
+{
+	let text : &str = r#"{ "name" : "fast io", "interval_in_milliseconds" : 250, "tags" : [ { "table" : "Coils", "address" : 0, "quantity" : 16 } ] }"#;
+
+	let configuration : PollingJobConfiguration = serde_json::from_str ( text ).unwrap ();
+	let job : PollingJob = configuration.create_job ();
+
+	assert_eq! ( job.get_name (), "fast io" );
+	assert_eq! ( job.get_interval (), Duration::from_millis ( 250 ) );
+	assert_eq! ( job.get_deadband (), 0 );
+	assert_eq! ( job.get_tags (), &vec![ BatchTag::new ( DataTable::Coils, 0, 16 ).unwrap () ] );
+	assert_eq! ( configuration.batch_configuration, BatchConfiguration::new () );
+
+	let text : &str = r#"{ "name" : "x", "interval_in_milliseconds" : 1, "batch_configuration" : { "maximum_gap" : 0, "maximum_pdu_size" : 1000 }, "tags" : [ { "table" : "InputRegisters", "address" : 7, "quantity" : 0 } ] }"#;
+
+	let configuration : PollingJobConfiguration = serde_json::from_str ( text ).unwrap ();
+
+	assert_eq! ( configuration.tags, vec![ BatchTag::new ( DataTable::InputRegisters, 7, 1 ).unwrap () ] );
+	assert! ( serde_json::from_str::< BatchTag > ( r#"{ "table" : "InputRegisters", "address" : 65535, "quantity" : 2 }"# ).is_err () );
+	assert_eq! ( configuration.batch_configuration, BatchConfiguration::new_with_limits ( 0, MODBUS_MAXIMUM_PDU_SIZE ) );
+
+	let event : PollingEvent = PollingEvent
+	{
+		job_name : "fast io".to_string (),
+		tag_index : 0,
+		tag : BatchTag::new ( DataTable::HoldingRegisters, 4, 1 ).unwrap (),
+		value : BatchValue::Registers( vec![ 230 ] ),
+		previous_value : Some( BatchValue::Bad( "timeout".to_string () ) )
+	};
+	let event_text : String = serde_json::to_string ( &event ).unwrap ();
+
+	assert_eq! ( event_text, r#"{"job_name":"fast io","tag_index":0,"tag":{"table":"HoldingRegisters","address":4,"quantity":1},"value":{"Registers":[230]},"previous_value":{"Bad":"timeout"}}"# );
+	assert_eq! ( serde_json::from_str::< PollingEvent > ( &event_text ).unwrap (), event );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_is_value_changed ()
+{
+	let registers_1 : BatchValue = BatchValue::Registers( vec![ 100 ] );
+	let registers_2 : BatchValue = BatchValue::Registers( vec![ 103 ] );
+	let registers_3 : BatchValue = BatchValue::Registers( vec![ 94 ] );
+	let coils_1 : BatchValue = BatchValue::Coils( vec![ true, false ] );
+	let coils_2 : BatchValue = BatchValue::Coils( vec![ true, true ] );
+	let bad_1 : BatchValue = BatchValue::Bad( "timeout".to_string () );
+
+	assert! ( !is_value_changed ( &registers_1, &registers_1, 0 ) );
+	assert! ( is_value_changed ( &registers_1, &registers_2, 0 ) );
+	assert! ( !is_value_changed ( &registers_1, &registers_2, 3 ) );
+	assert! ( is_value_changed ( &registers_1, &registers_3, 5 ) );
+	assert! ( !is_value_changed ( &coils_1, &coils_1, 10 ) );
+	assert! ( is_value_changed ( &coils_1, &coils_2, 10 ) );
+	assert! ( !is_value_changed ( &bad_1, &bad_1, 0 ) );
+	assert! ( is_value_changed ( &registers_1, &bad_1, 100 ) );
+	assert! ( is_value_changed ( &bad_1, &registers_1, 100 ) );
+
+	let register_1 : BatchValue = BatchValue::Registers( vec![ 0xFFFF ] );
+	let register_2 : BatchValue = BatchValue::Registers( vec![ 0xFFFB ] );
+	assert! ( !is_value_changed ( &register_1, &register_2, 4 ) );
+	assert! ( is_value_changed ( &register_1, &register_2, 3 ) );
+
+	let counter_1 : BatchValue = BatchValue::Registers( vec![ 0x0001, 0xFFFF ] );
+	let counter_2 : BatchValue = BatchValue::Registers( vec![ 0x0002, 0x0000 ] );
+	let counter_3 : BatchValue = BatchValue::Registers( vec![ 0x0003, 0x0000 ] );
+	assert! ( is_value_changed ( &counter_1, &counter_2, 5 ) );
+	assert! ( is_value_changed ( &counter_2, &counter_3, 5 ) );
+	assert! ( !is_value_changed ( &counter_3, &counter_3, 5 ) );
+}
+
+fn is_value_changed ( previous : &BatchValue, current : &BatchValue, deadband : u16 ) -> bool
+{
+	let reply : bool;
+
+	match ( previous, current )
+	{
+		( BatchValue::Registers( old_values ), BatchValue::Registers( new_values ) ) if old_values.len () == 1 && new_values.len () == 1 =>
+		{
+			reply = ( old_values[ 0 ] as i32 - new_values[ 0 ] as i32 ).abs () > deadband as i32;
+		}
+		_ =>
+		{
+			reply = previous != current;
+		}
+	}
+
+	return reply;
+}
@@ -0,0 +1,286 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use core::ethernet::EthernetMaster;
+use poller::pollingjob::*;
+
+//	===============================================================================================
+
+const POLLER_IDLE_INTERVAL : u64 = 100;
+
+//	===============================================================================================
+
+pub type PollingCallback = Box< dyn FnMut ( &PollingEvent ) + Send >;
+
+//	===============================================================================================
+
+/// Runs a set of `PollingJob`s against a master at their configured intervals
+/// and reports changed values to callbacks and channels.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::sync::atomic::AtomicBool;
+/// use std::time::Duration;
+/// use modbus_iiot::core::batchreader::BatchTag;
+/// use modbus_iiot::poller::pollingjob::PollingJob;
+/// use modbus_iiot::poller::scheduler::Poller;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let mut client = TcpClient::new("127.0.0.1");
+///
+/// if client.connect().is_ok()
+/// {
+///     let mut fast_io = PollingJob::new("fast io", Duration::from_millis(100));
+///     fast_io.add_tag(BatchTag::new_from_reference(1, 16).unwrap());
+///
+///     let mut levels = PollingJob::new("levels", Duration::from_secs(10));
+///     levels.add_tag(BatchTag::new_from_reference(40001, 1).unwrap());
+///     levels.set_deadband(10);
+///
+///     let mut poller = Poller::new(client);
+///     poller.add_job(fast_io);
+///     poller.add_job(levels);
+///     poller.add_callback(|event| println!("{} changed: {:?}", event.get_job_name(), event.get_value()));
+///
+///     let running = AtomicBool::new(true);
+///     poller.run(&running);
+/// }
+/// ```
+pub struct Poller< T : EthernetMaster >
+{
+	master : T,
+	jobs : Vec< PollingJob >,
+	callbacks : Vec< PollingCallback >,
+	senders : Vec< Sender< PollingEvent > >
+}
+
+impl< T : EthernetMaster > Poller< T >
+{
+	pub fn new ( master : T ) -> Poller< T >
+	{
+		return Poller
+		{
+			master : master,
+			jobs : vec![],
+			callbacks : vec![],
+			senders : vec![]
+		};
+	}
+
+	///	Adds a job and returns its index.
+	pub fn add_job ( &mut self, job : PollingJob ) -> usize
+	{
+		self.jobs.push ( job );
+
+		return self.jobs.len () - 1;
+	}
+
+	///	Registers a callback which is invoked for every changed value.
+	pub fn add_callback< F > ( &mut self, callback : F ) where F : FnMut ( &PollingEvent ) + Send + 'static
+	{
+		self.callbacks.push ( Box::new ( callback ) );
+	}
+
+	///	Returns a receiver which gets every changed value.
+	pub fn subscribe ( &mut self ) -> Receiver< PollingEvent >
+	{
+		let ( sender, receiver ) = channel ();
+
+		self.senders.push ( sender );
+
+		return receiver;
+	}
+
+	pub fn get_job ( &self, index : usize ) -> Option< &PollingJob >
+	{
+		return self.jobs.get ( index );
+	}
+
+	pub fn get_jobs ( &self ) -> &Vec< PollingJob >
+	{
+		return &self.jobs;
+	}
+
+	pub fn get_master ( &mut self ) -> &mut T
+	{
+		return &mut self.master;
+	}
+
+	///	Stops polling and hands back the master.
+	pub fn into_master ( self ) -> T
+	{
+		return self.master;
+	}
+
+	///	Polls every job which is due and returns the time until the next deadline.
+	pub fn poll ( &mut self ) -> Duration
+	{
+		let now : Instant = Instant::now ();
+		let mut events : Vec< PollingEvent > = vec![];
+
+		for job in self.jobs.iter_mut ()
+		{
+			if job.is_due ( now )
+			{
+				events.append ( &mut job.poll ( &mut self.master,
+												now ) );
+			}
+		}
+
+		for event in events
+		{
+			self.dispatch_event ( event );
+		}
+
+		return get_time_until_next_deadline ( &self.jobs,
+											  Instant::now () );
+	}
+
+	///	Polls the jobs until `running` is set to false.
+	pub fn run ( &mut self, running : &AtomicBool )
+	{
+		while running.load ( Ordering::SeqCst )
+		{
+			let sleep_time : Duration = self.poll ();
+
+			if sleep_time > Duration::from_millis ( 0 )
+			{
+				thread::sleep ( sleep_time );
+			}
+		}
+	}
+
+	fn dispatch_event ( &mut self, event : PollingEvent )
+	{
+		for callback in self.callbacks.iter_mut ()
+		{
+			callback ( &event );
+		}
+
+		self.senders.retain ( | sender | sender.send ( event.clone () ).is_ok () );
+	}
+}
+
+//	===============================================================================================
+
+fn get_time_until_next_deadline ( jobs : &Vec< PollingJob >, now : Instant ) -> Duration
+{
+	let mut reply : Duration = Duration::from_millis ( POLLER_IDLE_INTERVAL );
+
+	for job in jobs
+	{
+		let time : Duration;
+
+		match job.get_next_deadline ()
+		{
+			Some( deadline ) if deadline > now	=> { time = deadline.duration_since ( now ); }
+			_									=> { time = Duration::from_millis ( 0 ); }
+		}
+
+		if time < reply
+		{
+			reply = time;
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use std::sync::Arc;
+#[cfg( test )]
+use std::sync::atomic::AtomicUsize;
+#[cfg( test )]
+use core::batchreader::*;
+#[cfg( test )]
+use core::testmaster::TestMaster;
+
+#[test]
+fn test_poller_reports_changes ()
+{
+	let mut job : PollingJob = PollingJob::new ( "counters",
+												 Duration::from_millis ( 0 ) );
+	job.add_tag ( BatchTag::new_from_reference ( 40001, 1 ).unwrap () );
+	job.add_tag ( BatchTag::new_from_reference ( 40003, 1 ).unwrap () );
+	job.set_deadband ( 5 );
+
+	let mut poller : Poller< TestMaster > = Poller::new ( TestMaster::new ( vec![ 100, 0, 200 ] ) );
+	let job_index : usize = poller.add_job ( job );
+	let receiver : Receiver< PollingEvent > = poller.subscribe ();
+
+	poller.poll ();
+	let events_1 : Vec< PollingEvent > = receiver.try_iter ().collect ();
+	assert_eq! ( events_1.len (), 2 );
+	assert_eq! ( events_1[ 0 ].get_job_name (), "counters" );
+	assert_eq! ( events_1[ 0 ].get_value (), &BatchValue::Registers( vec![ 100 ] ) );
+	assert! ( events_1[ 0 ].get_previous_value ().is_none () );
+
+	poller.get_master ().registers[ 0 ] = 104;
+	poller.get_master ().registers[ 2 ] = 210;
+	poller.poll ();
+	let events_2 : Vec< PollingEvent > = receiver.try_iter ().collect ();
+	assert_eq! ( events_2.len (), 1 );
+	assert_eq! ( events_2[ 0 ].get_tag_index (), 1 );
+	assert_eq! ( events_2[ 0 ].get_value (), &BatchValue::Registers( vec![ 210 ] ) );
+	assert_eq! ( events_2[ 0 ].get_previous_value (), Some( &BatchValue::Registers( vec![ 200 ] ) ) );
+
+	poller.poll ();
+	assert_eq! ( receiver.try_iter ().count (), 0 );
+	assert_eq! ( poller.get_job ( job_index ).unwrap ().get_poll_count (), 3 );
+}
+
+#[test]
+fn test_poller_respects_intervals ()
+{
+	let mut fast_job : PollingJob = PollingJob::new ( "fast",
+													  Duration::from_millis ( 0 ) );
+	fast_job.add_tag ( BatchTag::new_from_reference ( 40001, 1 ).unwrap () );
+
+	let mut slow_job : PollingJob = PollingJob::new ( "slow",
+													  Duration::from_secs ( 3600 ) );
+	slow_job.add_tag ( BatchTag::new_from_reference ( 40002, 1 ).unwrap () );
+
+	let mut poller : Poller< TestMaster > = Poller::new ( TestMaster::new ( vec![ 1, 2 ] ) );
+	let fast_index : usize = poller.add_job ( fast_job );
+	let slow_index : usize = poller.add_job ( slow_job );
+
+	let counter : Arc< AtomicUsize > = Arc::new ( AtomicUsize::new ( 0 ) );
+	let callback_counter : Arc< AtomicUsize > = counter.clone ();
+	poller.add_callback ( move | _ | { callback_counter.fetch_add ( 1, Ordering::SeqCst ); } );
+
+	for _ in 0..3
+	{
+		poller.poll ();
+	}
+
+	assert_eq! ( poller.get_job ( fast_index ).unwrap ().get_poll_count (), 3 );
+	assert_eq! ( poller.get_job ( slow_index ).unwrap ().get_poll_count (), 1 );
+	assert_eq! ( counter.load ( Ordering::SeqCst ), 2 );
+	assert! ( poller.poll () <= Duration::from_millis ( POLLER_IDLE_INTERVAL ) );
+}
+
+#[test]
+fn test_polling_job_counts_overruns ()
+{
+	let mut job : PollingJob = PollingJob::new ( "overrun",
+												 Duration::from_millis ( 10 ) );
+	job.add_tag ( BatchTag::new_from_reference ( 40001, 1 ).unwrap () );
+
+	let mut master : TestMaster = TestMaster::new ( vec![ 1 ] );
+	let start : Instant = Instant::now ();
+
+	job.poll ( &mut master,
+			   start );
+	assert_eq! ( job.get_overrun_count (), 0 );
+
+	thread::sleep ( Duration::from_millis ( 35 ) );
+	job.poll ( &mut master,
+			   Instant::now () );
+	assert_eq! ( job.get_overrun_count (), 1 );
+	assert! ( job.get_next_deadline ().unwrap () > Instant::now () );
+}
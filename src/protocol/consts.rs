@@ -0,0 +1,52 @@
+pub const FUNCTION_CODE_DIAGNOSTICS : u8 = 0x08;
+pub const FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT : u8 = 0x2B;
+pub const FUNCTION_CODE_GET_COMM_EVENT_COUNTER : u8 = 0x0B;
+pub const FUNCTION_CODE_GET_COMM_EVENT_LOG : u8 = 0x0C;
+pub const FUNCTION_CODE_MASK_WRITE_REGISTER : u8 = 0x16;
+pub const FUNCTION_CODE_READ_COILS : u8 = 0x01;
+pub const FUNCTION_CODE_READ_DISCRETE_INPUTS : u8 = 0x02;
+pub const FUNCTION_CODE_READ_EXCEPTION_STATUS : u8 = 0x07;
+pub const FUNCTION_CODE_READ_FIFO_QUEUE : u8 = 0x18;
+pub const FUNCTION_CODE_READ_FILE_RECORD : u8 = 0x14;
+pub const FUNCTION_CODE_READ_HOLDING_REGISTERS : u8 = 0x03;
+pub const FUNCTION_CODE_READ_INPUT_REGISTERS : u8 = 0x04;
+pub const FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS : u8 = 0x17;
+pub const FUNCTION_CODE_REPORT_SERVER_ID : u8 = 0x11;
+pub const FUNCTION_CODE_WRITE_FILE_RECORD : u8 = 0x15;
+pub const FUNCTION_CODE_WRITE_MULTIPLE_COILS : u8 = 0x0F;
+pub const FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS : u8 = 0x10;
+pub const FUNCTION_CODE_WRITE_SINGLE_COIL : u8 = 0x05;
+pub const FUNCTION_CODE_WRITE_SINGLE_REGISTER : u8 = 0x06;
+
+pub const MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION : u8 = 0x0E;
+
+pub const MODBUS_EXCEPTION_FLAG : u8 = 0x80;
+pub const MODBUS_EXCEPTION_GATEWAY_PATH_UNAVAILABLE : u8 = 0x0A;
+pub const MODBUS_EXCEPTION_GATEWAY_TARGET_FAILED_TO_RESPOND : u8 = 0x0B;
+pub const MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS : u8 = 0x02;
+pub const MODBUS_EXCEPTION_ILLEGAL_DATA_VALUE : u8 = 0x03;
+pub const MODBUS_EXCEPTION_ILLEGAL_FUNCTION : u8 = 0x01;
+pub const MODBUS_EXCEPTION_SERVER_DEVICE_FAILURE : u8 = 0x04;
+
+pub const MODBUS_DEFAULT_UNIT_IDENTIFIER : u8 = 0x01;
+pub const MODBUS_FUNCTION_CODE_LENGTH : u16 = 0x0001;
+pub const MODBUS_HEADER_SIZE : u8 = 0x07;
+pub const MODBUS_MAXIMUM_PDU_SIZE : u16 = 253;
+pub const MODBUS_PROTOCOL_IDENTIFIER_TCP : u16 = 0x0000;
+pub const MODBUS_RTU_CRC_LENGTH : usize = 2;
+pub const MODBUS_TCP_PORT : u16 = 502;
+pub const MODBUS_TLS_PORT : u16 = 802;
+pub const MODBUS_TRANSACTION_ID_INITIALIZER : u16 = 0x0001;
+pub const MODBUS_UNIT_IDENTIFIER_LENGTH : u16 = 0x0001;
+
+pub const MODBUS_MASK_WRITE_PAYLOAD_LENGTH : usize = 6;
+pub const MODBUS_MAXIMUM_FIFO_COUNT : u16 = 0x001F;
+pub const MODBUS_MAXIMUM_QUANTITY_OF_READ_BITS : u16 = 0x07D0;
+pub const MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS : u16 = 0x007D;
+pub const MODBUS_MAXIMUM_QUANTITY_OF_WRITE_BITS : u16 = 0x07B0;
+pub const MODBUS_MAXIMUM_QUANTITY_OF_READ_WRITE_REGISTERS : u16 = 0x0079;
+pub const MODBUS_MAXIMUM_QUANTITY_OF_WRITE_REGISTERS : u16 = 0x007B;
+pub const MODBUS_READ_FILE_SUB_REQUEST_LENGTH : usize = 7;
+pub const MODBUS_READ_COIL_MINIMUM_PAYLOAD_LENGTH : usize = 2;
+pub const MODBUS_READ_REGISTER_MINIMUM_PAYLOAD_LENGTH : usize = 3;
+pub const MODBUS_WRITE_MINIMUM_PAYLOAD_LENGTH : usize = 4;
\ No newline at end of file
@@ -0,0 +1,4 @@
+
+
+pub mod consts;
+pub mod pdu;
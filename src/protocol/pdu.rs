@@ -0,0 +1,1551 @@
+use std::fmt;
+use protocol::consts::*;
+
+//	===============================================================================================
+
+///	Errors of encoding and decoding PDUs.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum PduError
+{
+	AddressOverflow { address : u16, quantity : usize },
+	BufferTooSmall { required : usize, available : usize },
+	ByteCountMismatch( u16 ),
+	IllegalCoilValue( u16 ),
+	IllegalQuantity { quantity : usize, maximum : u16 },
+	IllegalValue( u8 ),
+	OddByteCount( u8 ),
+	TooLarge( usize ),
+	TrailingBytes( usize ),
+	Truncated( usize ),
+	UnsupportedFunctionCode( u8 )
+}
+
+impl fmt::Display for PduError
+{
+	fn fmt ( &self, formatter : &mut fmt::Formatter ) -> fmt::Result
+	{
+		match *self
+		{
+			PduError::AddressOverflow { address, quantity }	=> { return write! ( formatter, "{} values from address {} exceed the address range", quantity, address ); }
+			PduError::BufferTooSmall { required, available }	=> { return write! ( formatter, "buffer of {} bytes too small for {} bytes", available, required ); }
+			PduError::ByteCountMismatch( byte_count )			=> { return write! ( formatter, "byte count {} does not match quantity", byte_count ); }
+			PduError::IllegalCoilValue( value )					=> { return write! ( formatter, "illegal coil value {:#06X}", value ); }
+			PduError::IllegalQuantity { quantity, maximum }		=> { return write! ( formatter, "quantity {} not between 1 and {}", quantity, maximum ); }
+			PduError::IllegalValue( value )						=> { return write! ( formatter, "illegal value {:#04X}", value ); }
+			PduError::OddByteCount( byte_count )				=> { return write! ( formatter, "odd byte count {} for registers", byte_count ); }
+			PduError::TooLarge( length )						=> { return write! ( formatter, "pdu of {} bytes exceeds the maximum size", length ); }
+			PduError::TrailingBytes( count )					=> { return write! ( formatter, "{} unexpected bytes after pdu", count ); }
+			PduError::Truncated( length )						=> { return write! ( formatter, "pdu of {} bytes truncated", length ); }
+			PduError::UnsupportedFunctionCode( function_code )	=> { return write! ( formatter, "unsupported function code 0x{:02X}", function_code ); }
+		}
+	}
+}
+
+#[cfg( feature = "std" )]
+impl From< PduError > for String
+{
+	fn from ( error : PduError ) -> String
+	{
+		return error.to_string ();
+	}
+}
+
+//	===============================================================================================
+
+///	Coil or discrete input values, either as `bool` slice or packed
+/// into bytes like on the wire, with the first value in the lowest bit.
+#[derive( Clone, Copy, Debug )]
+pub enum Coils< 'a >
+{
+	Unpacked( &'a [ bool ] ),
+	Packed { bytes : &'a [ u8 ], quantity : usize }
+}
+
+impl< 'a > Coils< 'a >
+{
+	pub fn len ( &self ) -> usize
+	{
+		match *self
+		{
+			Coils::Unpacked( values )			=> { return values.len (); }
+			Coils::Packed { quantity, .. }		=> { return quantity; }
+		}
+	}
+
+	pub fn is_empty ( &self ) -> bool
+	{
+		return self.len () == 0;
+	}
+
+	pub fn get ( &self, index : usize ) -> Option< bool >
+	{
+		if index >= self.len ()
+		{
+			return None;
+		}
+
+		match *self
+		{
+			Coils::Unpacked( values )			=> { return Some( values[ index ] ); }
+			Coils::Packed { bytes, .. }			=> { return Some( bytes[ index / 8 ] & ( 1 << ( index % 8 ) ) != 0 ); }
+		}
+	}
+
+	pub fn iter ( &self ) -> impl Iterator< Item = bool > + 'a
+	{
+		let coils : Coils< 'a > = *self;
+
+		return ( 0..coils.len () ).map ( move | index | coils.get ( index ).unwrap_or ( false ) );
+	}
+
+	#[cfg( feature = "std" )]
+	pub fn to_vec ( &self ) -> Vec< bool >
+	{
+		return self.iter ().collect ();
+	}
+}
+
+impl< 'a, 'b > PartialEq< Coils< 'b > > for Coils< 'a >
+{
+	fn eq ( &self, other : &Coils< 'b > ) -> bool
+	{
+		return self.len () == other.len () && self.iter ().eq ( other.iter () );
+	}
+}
+
+impl< 'a > Eq for Coils< 'a > {}
+
+//	===============================================================================================
+
+///	Register values, either as words or as big-endian bytes like on the wire.
+#[derive( Clone, Copy, Debug )]
+pub enum Registers< 'a >
+{
+	Words( &'a [ u16 ] ),
+	Bytes( &'a [ u8 ] )
+}
+
+impl< 'a > Registers< 'a >
+{
+	pub fn len ( &self ) -> usize
+	{
+		match *self
+		{
+			Registers::Words( values )		=> { return values.len (); }
+			Registers::Bytes( bytes )		=> { return bytes.len () / 2; }
+		}
+	}
+
+	pub fn is_empty ( &self ) -> bool
+	{
+		return self.len () == 0;
+	}
+
+	pub fn get ( &self, index : usize ) -> Option< u16 >
+	{
+		if index >= self.len ()
+		{
+			return None;
+		}
+
+		match *self
+		{
+			Registers::Words( values )		=> { return Some( values[ index ] ); }
+			Registers::Bytes( bytes )		=> { return Some( ( bytes[ index * 2 ] as u16 ) << 8 | bytes[ index * 2 + 1 ] as u16 ); }
+		}
+	}
+
+	pub fn iter ( &self ) -> impl Iterator< Item = u16 > + 'a
+	{
+		let registers : Registers< 'a > = *self;
+
+		return ( 0..registers.len () ).map ( move | index | registers.get ( index ).unwrap_or ( 0 ) );
+	}
+
+	#[cfg( feature = "std" )]
+	pub fn to_vec ( &self ) -> Vec< u16 >
+	{
+		return self.iter ().collect ();
+	}
+}
+
+impl< 'a, 'b > PartialEq< Registers< 'b > > for Registers< 'a >
+{
+	fn eq ( &self, other : &Registers< 'b > ) -> bool
+	{
+		return self.len () == other.len () && self.iter ().eq ( other.iter () );
+	}
+}
+
+impl< 'a > Eq for Registers< 'a > {}
+
+//	===============================================================================================
+
+///	The objects of a read device identification response like on the wire,
+/// each with its id, its length and its value.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub struct DeviceObjects< 'a >
+{
+	bytes : &'a [ u8 ],
+	count : usize
+}
+
+impl< 'a > DeviceObjects< 'a >
+{
+	///	Creates the objects of their encoded bytes, which have to end with the last object.
+	pub fn new ( bytes : &'a [ u8 ] ) -> Result< DeviceObjects< 'a >, PduError >
+	{
+		let mut position : usize = 0;
+		let mut count : usize = 0;
+
+		while position < bytes.len ()
+		{
+			if position + 2 > bytes.len ()
+			{
+				return Err( PduError::Truncated( bytes.len () ) );
+			}
+
+			position += 2 + bytes[ position + 1 ] as usize;
+			count += 1;
+		}
+
+		if position > bytes.len ()
+		{
+			return Err( PduError::Truncated( bytes.len () ) );
+		}
+
+		return Ok( DeviceObjects
+				   {
+					   bytes : bytes,
+					   count : count
+				   } );
+	}
+
+	pub fn len ( &self ) -> usize
+	{
+		return self.count;
+	}
+
+	pub fn is_empty ( &self ) -> bool
+	{
+		return self.count == 0;
+	}
+
+	pub fn as_bytes ( &self ) -> &'a [ u8 ]
+	{
+		return self.bytes;
+	}
+
+	///	Returns the id and the value of the objects.
+	pub fn iter ( &self ) -> impl Iterator< Item = ( u8, &'a [ u8 ] ) > + 'a
+	{
+		let bytes : &'a [ u8 ] = self.bytes;
+		let mut position : usize = 0;
+
+		return ( 0..self.count ).map ( move | _ |
+		{
+			let length : usize = bytes[ position + 1 ] as usize;
+			let object : ( u8, &'a [ u8 ] ) = ( bytes[ position ], &bytes[ position + 2..position + 2 + length ] );
+
+			position += 2 + length;
+
+			return object;
+		} );
+	}
+}
+
+//	===============================================================================================
+
+///	A request PDU (function code and data) independent of any transport.
+/// Values borrow from the caller or from the decoded bytes, so no allocation
+/// is needed. Function codes without a variant of their own are kept as `Custom`.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum RequestPdu< 'a >
+{
+	ReadCoils { address : u16, quantity : u16 },
+	ReadDiscreteInputs { address : u16, quantity : u16 },
+	ReadHoldingRegisters { address : u16, quantity : u16 },
+	ReadInputRegisters { address : u16, quantity : u16 },
+	WriteSingleCoil { address : u16, value : bool },
+	WriteSingleRegister { address : u16, value : u16 },
+	ReadExceptionStatus,
+	Diagnostics { sub_function : u16, data : &'a [ u8 ] },
+	GetCommEventCounter,
+	GetCommEventLog,
+	WriteMultipleCoils { address : u16, values : Coils< 'a > },
+	WriteMultipleRegisters { address : u16, values : Registers< 'a > },
+	ReportServerId,
+	///	The sub-requests of 7 bytes each (reference type, file number, record number and record length).
+	ReadFileRecord { sub_requests : &'a [ u8 ] },
+	///	The sub-requests, each with its reference type, file number, record number, record length and record data.
+	WriteFileRecord { sub_requests : &'a [ u8 ] },
+	MaskWriteRegister { address : u16, and_mask : u16, or_mask : u16 },
+	ReadWriteMultipleRegisters { read_address : u16, read_quantity : u16, write_address : u16, values : Registers< 'a > },
+	ReadFifoQueue { address : u16 },
+	ReadDeviceIdentification { read_device_id_code : u8, object_id : u8 },
+	Custom { function_code : u8, data : &'a [ u8 ] }
+}
+
+impl< 'a > RequestPdu< 'a >
+{
+	pub fn get_function_code ( &self ) -> u8
+	{
+		let reply : u8;
+
+		match *self
+		{
+			RequestPdu::ReadCoils { .. }					=> { reply = FUNCTION_CODE_READ_COILS; }
+			RequestPdu::ReadDiscreteInputs { .. }			=> { reply = FUNCTION_CODE_READ_DISCRETE_INPUTS; }
+			RequestPdu::ReadHoldingRegisters { .. }			=> { reply = FUNCTION_CODE_READ_HOLDING_REGISTERS; }
+			RequestPdu::ReadInputRegisters { .. }			=> { reply = FUNCTION_CODE_READ_INPUT_REGISTERS; }
+			RequestPdu::WriteSingleCoil { .. }				=> { reply = FUNCTION_CODE_WRITE_SINGLE_COIL; }
+			RequestPdu::WriteSingleRegister { .. }			=> { reply = FUNCTION_CODE_WRITE_SINGLE_REGISTER; }
+			RequestPdu::ReadExceptionStatus					=> { reply = FUNCTION_CODE_READ_EXCEPTION_STATUS; }
+			RequestPdu::Diagnostics { .. }					=> { reply = FUNCTION_CODE_DIAGNOSTICS; }
+			RequestPdu::GetCommEventCounter					=> { reply = FUNCTION_CODE_GET_COMM_EVENT_COUNTER; }
+			RequestPdu::GetCommEventLog						=> { reply = FUNCTION_CODE_GET_COMM_EVENT_LOG; }
+			RequestPdu::WriteMultipleCoils { .. }			=> { reply = FUNCTION_CODE_WRITE_MULTIPLE_COILS; }
+			RequestPdu::WriteMultipleRegisters { .. }		=> { reply = FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS; }
+			RequestPdu::ReportServerId						=> { reply = FUNCTION_CODE_REPORT_SERVER_ID; }
+			RequestPdu::ReadFileRecord { .. }				=> { reply = FUNCTION_CODE_READ_FILE_RECORD; }
+			RequestPdu::WriteFileRecord { .. }				=> { reply = FUNCTION_CODE_WRITE_FILE_RECORD; }
+			RequestPdu::MaskWriteRegister { .. }			=> { reply = FUNCTION_CODE_MASK_WRITE_REGISTER; }
+			RequestPdu::ReadWriteMultipleRegisters { .. }	=> { reply = FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS; }
+			RequestPdu::ReadFifoQueue { .. }				=> { reply = FUNCTION_CODE_READ_FIFO_QUEUE; }
+			RequestPdu::ReadDeviceIdentification { .. }		=> { reply = FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT; }
+			RequestPdu::Custom { function_code, .. }		=> { reply = function_code; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the number of bytes `encode` writes.
+	pub fn get_encoded_length ( &self ) -> usize
+	{
+		let reply : usize;
+
+		match *self
+		{
+			RequestPdu::ReadExceptionStatus |
+			RequestPdu::GetCommEventCounter |
+			RequestPdu::GetCommEventLog |
+			RequestPdu::ReportServerId									=> { reply = 1; }
+			RequestPdu::Diagnostics { data, .. }						=> { reply = 3 + data.len (); }
+			RequestPdu::WriteMultipleCoils { ref values, .. }			=> { reply = 6 + values.len ().div_ceil ( 8 ); }
+			RequestPdu::WriteMultipleRegisters { ref values, .. }		=> { reply = 6 + values.len () * 2; }
+			RequestPdu::ReadFileRecord { sub_requests } |
+			RequestPdu::WriteFileRecord { sub_requests }				=> { reply = 2 + sub_requests.len (); }
+			RequestPdu::MaskWriteRegister { .. }						=> { reply = 1 + MODBUS_MASK_WRITE_PAYLOAD_LENGTH; }
+			RequestPdu::ReadWriteMultipleRegisters { ref values, .. }	=> { reply = 10 + values.len () * 2; }
+			RequestPdu::ReadFifoQueue { .. }							=> { reply = 3; }
+			RequestPdu::ReadDeviceIdentification { .. }					=> { reply = 4; }
+			RequestPdu::Custom { data, .. }								=> { reply = 1 + data.len (); }
+			_															=> { reply = 5; }
+		}
+
+		return reply;
+	}
+
+	///	Writes the PDU to the start of the buffer and returns the number of bytes written.
+	/// Fails if the buffer is too small or the request breaks the protocol limits.
+	pub fn encode ( &self, buffer : &mut [ u8 ] ) -> Result< usize, PduError >
+	{
+		let length : usize = self.get_encoded_length ();
+		let mut writer : PduWriter = PduWriter::new ( buffer,
+													  length )?;
+
+		writer.put_u8 ( self.get_function_code () );
+
+		match *self
+		{
+			RequestPdu::ReadCoils { address, quantity } |
+			RequestPdu::ReadDiscreteInputs { address, quantity }	=>
+			{
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_BITS )?;
+				verify_address_range ( address, quantity as usize )?;
+				writer.put_u16 ( address );
+				writer.put_u16 ( quantity );
+			}
+			RequestPdu::ReadHoldingRegisters { address, quantity } |
+			RequestPdu::ReadInputRegisters { address, quantity }	=>
+			{
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS )?;
+				verify_address_range ( address, quantity as usize )?;
+				writer.put_u16 ( address );
+				writer.put_u16 ( quantity );
+			}
+			RequestPdu::WriteSingleCoil { address, value }			=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( encode_coil_value ( value ) );
+			}
+			RequestPdu::WriteSingleRegister { address, value }		=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( value );
+			}
+			RequestPdu::ReadExceptionStatus |
+			RequestPdu::GetCommEventCounter |
+			RequestPdu::GetCommEventLog |
+			RequestPdu::ReportServerId								=> {}
+			RequestPdu::Diagnostics { sub_function, data }			=>
+			{
+				writer.put_u16 ( sub_function );
+				writer.put_bytes ( data );
+			}
+			RequestPdu::WriteMultipleCoils { address, ref values }	=>
+			{
+				verify_quantity ( values.len (), MODBUS_MAXIMUM_QUANTITY_OF_WRITE_BITS )?;
+				verify_address_range ( address, values.len () )?;
+				verify_coils ( values )?;
+				writer.put_u16 ( address );
+				writer.put_u16 ( values.len () as u16 );
+				writer.put_u8 ( ( length - 6 ) as u8 );
+				writer.put_coils ( values );
+			}
+			RequestPdu::WriteMultipleRegisters { address, ref values }	=>
+			{
+				verify_quantity ( values.len (), MODBUS_MAXIMUM_QUANTITY_OF_WRITE_REGISTERS )?;
+				verify_address_range ( address, values.len () )?;
+				writer.put_u16 ( address );
+				writer.put_u16 ( values.len () as u16 );
+				writer.put_u8 ( ( length - 6 ) as u8 );
+				writer.put_registers ( values );
+			}
+			RequestPdu::ReadFileRecord { sub_requests }				=>
+			{
+				verify_read_file_sub_requests ( sub_requests )?;
+				writer.put_u8 ( sub_requests.len () as u8 );
+				writer.put_bytes ( sub_requests );
+			}
+			RequestPdu::WriteFileRecord { sub_requests }			=>
+			{
+				writer.put_u8 ( sub_requests.len () as u8 );
+				writer.put_bytes ( sub_requests );
+			}
+			RequestPdu::MaskWriteRegister { address, and_mask, or_mask }	=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( and_mask );
+				writer.put_u16 ( or_mask );
+			}
+			RequestPdu::ReadWriteMultipleRegisters { read_address, read_quantity, write_address, ref values }	=>
+			{
+				verify_quantity ( read_quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS )?;
+				verify_quantity ( values.len (), MODBUS_MAXIMUM_QUANTITY_OF_READ_WRITE_REGISTERS )?;
+				verify_address_range ( read_address, read_quantity as usize )?;
+				verify_address_range ( write_address, values.len () )?;
+				writer.put_u16 ( read_address );
+				writer.put_u16 ( read_quantity );
+				writer.put_u16 ( write_address );
+				writer.put_u16 ( values.len () as u16 );
+				writer.put_u8 ( ( length - 10 ) as u8 );
+				writer.put_registers ( values );
+			}
+			RequestPdu::ReadFifoQueue { address }					=>
+			{
+				writer.put_u16 ( address );
+			}
+			RequestPdu::ReadDeviceIdentification { read_device_id_code, object_id }	=>
+			{
+				writer.put_u8 ( MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION );
+				writer.put_u8 ( read_device_id_code );
+				writer.put_u8 ( object_id );
+			}
+			RequestPdu::Custom { data, .. }							=>
+			{
+				writer.put_bytes ( data );
+			}
+		}
+
+		return Ok( writer.position );
+	}
+
+	///	Returns the encoded PDU.
+	#[cfg( feature = "std" )]
+	pub fn to_bytes ( &self ) -> Result< Vec< u8 >, String >
+	{
+		let mut reply : Vec< u8 > = vec![ 0; self.get_encoded_length () ];
+
+		let length : usize = self.encode ( &mut reply )?;
+		reply.truncate ( length );
+
+		return Ok( reply );
+	}
+
+	///	Decodes a request PDU and checks it against the protocol limits.
+	/// Unknown function codes are returned as `Custom`, like encapsulated
+	/// interface transports other than read device identification.
+	pub fn decode ( pdu : &'a [ u8 ] ) -> Result< RequestPdu< 'a >, PduError >
+	{
+		let reply : RequestPdu< 'a >;
+
+		let mut reader : PduReader< 'a > = PduReader::new ( pdu );
+		let function_code : u8 = reader.get_u8 ()?;
+
+		match function_code
+		{
+			FUNCTION_CODE_READ_COILS |
+			FUNCTION_CODE_READ_DISCRETE_INPUTS			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let quantity : u16 = reader.get_u16 ()?;
+
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_BITS )?;
+				verify_address_range ( address, quantity as usize )?;
+
+				if function_code == FUNCTION_CODE_READ_COILS
+				{
+					reply = RequestPdu::ReadCoils { address : address, quantity : quantity };
+				}
+				else
+				{
+					reply = RequestPdu::ReadDiscreteInputs { address : address, quantity : quantity };
+				}
+			}
+			FUNCTION_CODE_READ_HOLDING_REGISTERS |
+			FUNCTION_CODE_READ_INPUT_REGISTERS			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let quantity : u16 = reader.get_u16 ()?;
+
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS )?;
+				verify_address_range ( address, quantity as usize )?;
+
+				if function_code == FUNCTION_CODE_READ_HOLDING_REGISTERS
+				{
+					reply = RequestPdu::ReadHoldingRegisters { address : address, quantity : quantity };
+				}
+				else
+				{
+					reply = RequestPdu::ReadInputRegisters { address : address, quantity : quantity };
+				}
+			}
+			FUNCTION_CODE_WRITE_SINGLE_COIL				=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let value : bool = decode_coil_value ( reader.get_u16 ()? )?;
+
+				reply = RequestPdu::WriteSingleCoil { address : address, value : value };
+			}
+			FUNCTION_CODE_WRITE_SINGLE_REGISTER			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let value : u16 = reader.get_u16 ()?;
+
+				reply = RequestPdu::WriteSingleRegister { address : address, value : value };
+			}
+			FUNCTION_CODE_READ_EXCEPTION_STATUS			=> { reply = RequestPdu::ReadExceptionStatus; }
+			FUNCTION_CODE_DIAGNOSTICS					=>
+			{
+				let sub_function : u16 = reader.get_u16 ()?;
+
+				reply = RequestPdu::Diagnostics { sub_function : sub_function, data : reader.get_remaining () };
+			}
+			FUNCTION_CODE_GET_COMM_EVENT_COUNTER		=> { reply = RequestPdu::GetCommEventCounter; }
+			FUNCTION_CODE_GET_COMM_EVENT_LOG			=> { reply = RequestPdu::GetCommEventLog; }
+			FUNCTION_CODE_WRITE_MULTIPLE_COILS			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let quantity : u16 = reader.get_u16 ()?;
+
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_WRITE_BITS )?;
+				verify_address_range ( address, quantity as usize )?;
+
+				let byte_count : usize = reader.get_byte_count ( ( quantity as usize ).div_ceil ( 8 ) )?;
+				let bytes : &'a [ u8 ] = reader.get_bytes ( byte_count )?;
+
+				reply = RequestPdu::WriteMultipleCoils { address : address, values : Coils::Packed { bytes : bytes, quantity : quantity as usize } };
+			}
+			FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS		=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let quantity : u16 = reader.get_u16 ()?;
+
+				verify_quantity ( quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_WRITE_REGISTERS )?;
+				verify_address_range ( address, quantity as usize )?;
+
+				let byte_count : usize = reader.get_byte_count ( quantity as usize * 2 )?;
+				let bytes : &'a [ u8 ] = reader.get_bytes ( byte_count )?;
+
+				reply = RequestPdu::WriteMultipleRegisters { address : address, values : Registers::Bytes( bytes ) };
+			}
+			FUNCTION_CODE_REPORT_SERVER_ID				=> { reply = RequestPdu::ReportServerId; }
+			FUNCTION_CODE_READ_FILE_RECORD				=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+				let sub_requests : &'a [ u8 ] = reader.get_bytes ( byte_count as usize )?;
+
+				verify_read_file_sub_requests ( sub_requests )?;
+
+				reply = RequestPdu::ReadFileRecord { sub_requests : sub_requests };
+			}
+			FUNCTION_CODE_WRITE_FILE_RECORD				=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+
+				reply = RequestPdu::WriteFileRecord { sub_requests : reader.get_bytes ( byte_count as usize )? };
+			}
+			FUNCTION_CODE_MASK_WRITE_REGISTER			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let and_mask : u16 = reader.get_u16 ()?;
+				let or_mask : u16 = reader.get_u16 ()?;
+
+				reply = RequestPdu::MaskWriteRegister { address : address, and_mask : and_mask, or_mask : or_mask };
+			}
+			FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS	=>
+			{
+				let read_address : u16 = reader.get_u16 ()?;
+				let read_quantity : u16 = reader.get_u16 ()?;
+				let write_address : u16 = reader.get_u16 ()?;
+				let write_quantity : u16 = reader.get_u16 ()?;
+
+				verify_quantity ( read_quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_REGISTERS )?;
+				verify_quantity ( write_quantity as usize, MODBUS_MAXIMUM_QUANTITY_OF_READ_WRITE_REGISTERS )?;
+				verify_address_range ( read_address, read_quantity as usize )?;
+				verify_address_range ( write_address, write_quantity as usize )?;
+
+				let byte_count : usize = reader.get_byte_count ( write_quantity as usize * 2 )?;
+				let bytes : &'a [ u8 ] = reader.get_bytes ( byte_count )?;
+
+				reply = RequestPdu::ReadWriteMultipleRegisters { read_address : read_address, read_quantity : read_quantity, write_address : write_address, values : Registers::Bytes( bytes ) };
+			}
+			FUNCTION_CODE_READ_FIFO_QUEUE				=> { reply = RequestPdu::ReadFifoQueue { address : reader.get_u16 ()? }; }
+			FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT if pdu.get ( 1 ) == Some( &MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION )	=>
+			{
+				reader.get_u8 ()?;
+
+				let read_device_id_code : u8 = reader.get_u8 ()?;
+				let object_id : u8 = reader.get_u8 ()?;
+
+				reply = RequestPdu::ReadDeviceIdentification { read_device_id_code : read_device_id_code, object_id : object_id };
+			}
+			_											=>
+			{
+				reply = RequestPdu::Custom { function_code : function_code, data : reader.get_remaining () };
+			}
+		}
+
+		reader.finish ()?;
+
+		return Ok( reply );
+	}
+}
+
+//	===============================================================================================
+
+///	A response PDU independent of any transport. Read coils and discrete inputs
+/// contain all bits of the response bytes, so their length is a multiple of eight.
+#[derive( Clone, Copy, Debug, Eq, PartialEq )]
+pub enum ResponsePdu< 'a >
+{
+	ReadCoils { values : Coils< 'a > },
+	ReadDiscreteInputs { values : Coils< 'a > },
+	ReadHoldingRegisters { values : Registers< 'a > },
+	ReadInputRegisters { values : Registers< 'a > },
+	WriteSingleCoil { address : u16, value : bool },
+	WriteSingleRegister { address : u16, value : u16 },
+	ReadExceptionStatus { status : u8 },
+	Diagnostics { sub_function : u16, data : &'a [ u8 ] },
+	GetCommEventCounter { status : u16, event_count : u16 },
+	GetCommEventLog { status : u16, event_count : u16, message_count : u16, events : &'a [ u8 ] },
+	WriteMultipleCoils { address : u16, quantity : u16 },
+	WriteMultipleRegisters { address : u16, quantity : u16 },
+	///	The server id, the run indicator and the additional data, which depend on the device.
+	ReportServerId { data : &'a [ u8 ] },
+	///	The sub-responses, each with its length, reference type and record data.
+	ReadFileRecord { sub_responses : &'a [ u8 ] },
+	WriteFileRecord { sub_requests : &'a [ u8 ] },
+	MaskWriteRegister { address : u16, and_mask : u16, or_mask : u16 },
+	ReadWriteMultipleRegisters { values : Registers< 'a > },
+	ReadFifoQueue { values : Registers< 'a > },
+	ReadDeviceIdentification { read_device_id_code : u8, conformity_level : u8, more_follows : bool, next_object_id : u8, objects : DeviceObjects< 'a > },
+	Exception { function_code : u8, exception_code : u8 },
+	Custom { function_code : u8, data : &'a [ u8 ] }
+}
+
+impl< 'a > ResponsePdu< 'a >
+{
+	///	Returns the function code of the response, with the exception flag for exceptions.
+	pub fn get_function_code ( &self ) -> u8
+	{
+		let reply : u8;
+
+		match *self
+		{
+			ResponsePdu::ReadCoils { .. }					=> { reply = FUNCTION_CODE_READ_COILS; }
+			ResponsePdu::ReadDiscreteInputs { .. }			=> { reply = FUNCTION_CODE_READ_DISCRETE_INPUTS; }
+			ResponsePdu::ReadHoldingRegisters { .. }		=> { reply = FUNCTION_CODE_READ_HOLDING_REGISTERS; }
+			ResponsePdu::ReadInputRegisters { .. }			=> { reply = FUNCTION_CODE_READ_INPUT_REGISTERS; }
+			ResponsePdu::WriteSingleCoil { .. }				=> { reply = FUNCTION_CODE_WRITE_SINGLE_COIL; }
+			ResponsePdu::WriteSingleRegister { .. }			=> { reply = FUNCTION_CODE_WRITE_SINGLE_REGISTER; }
+			ResponsePdu::ReadExceptionStatus { .. }			=> { reply = FUNCTION_CODE_READ_EXCEPTION_STATUS; }
+			ResponsePdu::Diagnostics { .. }					=> { reply = FUNCTION_CODE_DIAGNOSTICS; }
+			ResponsePdu::GetCommEventCounter { .. }			=> { reply = FUNCTION_CODE_GET_COMM_EVENT_COUNTER; }
+			ResponsePdu::GetCommEventLog { .. }				=> { reply = FUNCTION_CODE_GET_COMM_EVENT_LOG; }
+			ResponsePdu::WriteMultipleCoils { .. }			=> { reply = FUNCTION_CODE_WRITE_MULTIPLE_COILS; }
+			ResponsePdu::WriteMultipleRegisters { .. }		=> { reply = FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS; }
+			ResponsePdu::ReportServerId { .. }				=> { reply = FUNCTION_CODE_REPORT_SERVER_ID; }
+			ResponsePdu::ReadFileRecord { .. }				=> { reply = FUNCTION_CODE_READ_FILE_RECORD; }
+			ResponsePdu::WriteFileRecord { .. }				=> { reply = FUNCTION_CODE_WRITE_FILE_RECORD; }
+			ResponsePdu::MaskWriteRegister { .. }			=> { reply = FUNCTION_CODE_MASK_WRITE_REGISTER; }
+			ResponsePdu::ReadWriteMultipleRegisters { .. }	=> { reply = FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS; }
+			ResponsePdu::ReadFifoQueue { .. }				=> { reply = FUNCTION_CODE_READ_FIFO_QUEUE; }
+			ResponsePdu::ReadDeviceIdentification { .. }	=> { reply = FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT; }
+			ResponsePdu::Exception { function_code, .. }	=> { reply = function_code | MODBUS_EXCEPTION_FLAG; }
+			ResponsePdu::Custom { function_code, .. }		=> { reply = function_code; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the number of bytes `encode` writes.
+	pub fn get_encoded_length ( &self ) -> usize
+	{
+		let reply : usize;
+
+		match *self
+		{
+			ResponsePdu::ReadCoils { ref values } |
+			ResponsePdu::ReadDiscreteInputs { ref values }				=> { reply = 2 + values.len ().div_ceil ( 8 ); }
+			ResponsePdu::ReadHoldingRegisters { ref values } |
+			ResponsePdu::ReadInputRegisters { ref values } |
+			ResponsePdu::ReadWriteMultipleRegisters { ref values }		=> { reply = 2 + values.len () * 2; }
+			ResponsePdu::ReadExceptionStatus { .. } |
+			ResponsePdu::Exception { .. }								=> { reply = 2; }
+			ResponsePdu::Diagnostics { data, .. }						=> { reply = 3 + data.len (); }
+			ResponsePdu::GetCommEventLog { events, .. }					=> { reply = 8 + events.len (); }
+			ResponsePdu::ReportServerId { data : bytes } |
+			ResponsePdu::ReadFileRecord { sub_responses : bytes } |
+			ResponsePdu::WriteFileRecord { sub_requests : bytes }		=> { reply = 2 + bytes.len (); }
+			ResponsePdu::MaskWriteRegister { .. }						=> { reply = 1 + MODBUS_MASK_WRITE_PAYLOAD_LENGTH; }
+			ResponsePdu::ReadFifoQueue { ref values }					=> { reply = 5 + values.len () * 2; }
+			ResponsePdu::ReadDeviceIdentification { ref objects, .. }	=> { reply = 7 + objects.as_bytes ().len (); }
+			ResponsePdu::Custom { data, .. }							=> { reply = 1 + data.len (); }
+			_															=> { reply = 5; }
+		}
+
+		return reply;
+	}
+
+	///	Writes the PDU to the start of the buffer and returns the number of bytes written.
+	/// Fails if the buffer is too small or the response breaks the protocol limits.
+	pub fn encode ( &self, buffer : &mut [ u8 ] ) -> Result< usize, PduError >
+	{
+		let length : usize = self.get_encoded_length ();
+		let mut writer : PduWriter = PduWriter::new ( buffer,
+													  length )?;
+
+		writer.put_u8 ( self.get_function_code () );
+
+		match *self
+		{
+			ResponsePdu::ReadCoils { ref values } |
+			ResponsePdu::ReadDiscreteInputs { ref values }				=>
+			{
+				verify_coils ( values )?;
+				writer.put_u8 ( ( length - 2 ) as u8 );
+				writer.put_coils ( values );
+			}
+			ResponsePdu::ReadHoldingRegisters { ref values } |
+			ResponsePdu::ReadInputRegisters { ref values } |
+			ResponsePdu::ReadWriteMultipleRegisters { ref values }		=>
+			{
+				writer.put_u8 ( ( length - 2 ) as u8 );
+				writer.put_registers ( values );
+			}
+			ResponsePdu::WriteSingleCoil { address, value }				=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( encode_coil_value ( value ) );
+			}
+			ResponsePdu::WriteSingleRegister { address, value }			=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( value );
+			}
+			ResponsePdu::ReadExceptionStatus { status }					=>
+			{
+				writer.put_u8 ( status );
+			}
+			ResponsePdu::Diagnostics { sub_function, data }				=>
+			{
+				writer.put_u16 ( sub_function );
+				writer.put_bytes ( data );
+			}
+			ResponsePdu::GetCommEventCounter { status, event_count }	=>
+			{
+				writer.put_u16 ( status );
+				writer.put_u16 ( event_count );
+			}
+			ResponsePdu::GetCommEventLog { status, event_count, message_count, events }	=>
+			{
+				writer.put_u8 ( ( length - 2 ) as u8 );
+				writer.put_u16 ( status );
+				writer.put_u16 ( event_count );
+				writer.put_u16 ( message_count );
+				writer.put_bytes ( events );
+			}
+			ResponsePdu::WriteMultipleCoils { address, quantity } |
+			ResponsePdu::WriteMultipleRegisters { address, quantity }	=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( quantity );
+			}
+			ResponsePdu::ReportServerId { data : bytes } |
+			ResponsePdu::ReadFileRecord { sub_responses : bytes } |
+			ResponsePdu::WriteFileRecord { sub_requests : bytes }		=>
+			{
+				writer.put_u8 ( bytes.len () as u8 );
+				writer.put_bytes ( bytes );
+			}
+			ResponsePdu::MaskWriteRegister { address, and_mask, or_mask }	=>
+			{
+				writer.put_u16 ( address );
+				writer.put_u16 ( and_mask );
+				writer.put_u16 ( or_mask );
+			}
+			ResponsePdu::ReadFifoQueue { ref values }					=>
+			{
+				verify_quantity_with_zero ( values.len (), MODBUS_MAXIMUM_FIFO_COUNT )?;
+				writer.put_u16 ( ( length - 3 ) as u16 );
+				writer.put_u16 ( values.len () as u16 );
+				writer.put_registers ( values );
+			}
+			ResponsePdu::ReadDeviceIdentification { read_device_id_code, conformity_level, more_follows, next_object_id, ref objects }	=>
+			{
+				writer.put_u8 ( MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION );
+				writer.put_u8 ( read_device_id_code );
+				writer.put_u8 ( conformity_level );
+				writer.put_u8 ( if more_follows { 0xFF } else { 0x00 } );
+				writer.put_u8 ( next_object_id );
+				writer.put_u8 ( objects.len () as u8 );
+				writer.put_bytes ( objects.as_bytes () );
+			}
+			ResponsePdu::Exception { exception_code, .. }				=>
+			{
+				writer.put_u8 ( exception_code );
+			}
+			ResponsePdu::Custom { data, .. }							=>
+			{
+				writer.put_bytes ( data );
+			}
+		}
+
+		return Ok( writer.position );
+	}
+
+	///	Returns the encoded PDU.
+	#[cfg( feature = "std" )]
+	pub fn to_bytes ( &self ) -> Result< Vec< u8 >, String >
+	{
+		let mut reply : Vec< u8 > = vec![ 0; self.get_encoded_length () ];
+
+		let length : usize = self.encode ( &mut reply )?;
+		reply.truncate ( length );
+
+		return Ok( reply );
+	}
+
+	///	Decodes a response PDU. Function codes with the exception flag are
+	/// returned as `Exception`, unknown function codes as `Custom`.
+	pub fn decode ( pdu : &'a [ u8 ] ) -> Result< ResponsePdu< 'a >, PduError >
+	{
+		let reply : ResponsePdu< 'a >;
+
+		let mut reader : PduReader< 'a > = PduReader::new ( pdu );
+		let function_code : u8 = reader.get_u8 ()?;
+
+		match function_code
+		{
+			FUNCTION_CODE_READ_COILS |
+			FUNCTION_CODE_READ_DISCRETE_INPUTS			=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+				let bytes : &'a [ u8 ] = reader.get_bytes ( byte_count as usize )?;
+				let values : Coils< 'a > = Coils::Packed { bytes : bytes, quantity : bytes.len () * 8 };
+
+				if function_code == FUNCTION_CODE_READ_COILS
+				{
+					reply = ResponsePdu::ReadCoils { values : values };
+				}
+				else
+				{
+					reply = ResponsePdu::ReadDiscreteInputs { values : values };
+				}
+			}
+			FUNCTION_CODE_READ_HOLDING_REGISTERS |
+			FUNCTION_CODE_READ_INPUT_REGISTERS |
+			FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS	=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+
+				if byte_count % 2 != 0
+				{
+					return Err( PduError::OddByteCount( byte_count ) );
+				}
+
+				let values : Registers< 'a > = Registers::Bytes( reader.get_bytes ( byte_count as usize )? );
+
+				match function_code
+				{
+					FUNCTION_CODE_READ_HOLDING_REGISTERS	=> { reply = ResponsePdu::ReadHoldingRegisters { values : values }; }
+					FUNCTION_CODE_READ_INPUT_REGISTERS		=> { reply = ResponsePdu::ReadInputRegisters { values : values }; }
+					_										=> { reply = ResponsePdu::ReadWriteMultipleRegisters { values : values }; }
+				}
+			}
+			FUNCTION_CODE_WRITE_SINGLE_COIL				=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let value : bool = decode_coil_value ( reader.get_u16 ()? )?;
+
+				reply = ResponsePdu::WriteSingleCoil { address : address, value : value };
+			}
+			FUNCTION_CODE_WRITE_SINGLE_REGISTER			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let value : u16 = reader.get_u16 ()?;
+
+				reply = ResponsePdu::WriteSingleRegister { address : address, value : value };
+			}
+			FUNCTION_CODE_READ_EXCEPTION_STATUS			=> { reply = ResponsePdu::ReadExceptionStatus { status : reader.get_u8 ()? }; }
+			FUNCTION_CODE_DIAGNOSTICS					=>
+			{
+				let sub_function : u16 = reader.get_u16 ()?;
+
+				reply = ResponsePdu::Diagnostics { sub_function : sub_function, data : reader.get_remaining () };
+			}
+			FUNCTION_CODE_GET_COMM_EVENT_COUNTER		=>
+			{
+				let status : u16 = reader.get_u16 ()?;
+				let event_count : u16 = reader.get_u16 ()?;
+
+				reply = ResponsePdu::GetCommEventCounter { status : status, event_count : event_count };
+			}
+			FUNCTION_CODE_GET_COMM_EVENT_LOG			=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+
+				if byte_count < 6
+				{
+					return Err( PduError::ByteCountMismatch( byte_count as u16 ) );
+				}
+
+				let status : u16 = reader.get_u16 ()?;
+				let event_count : u16 = reader.get_u16 ()?;
+				let message_count : u16 = reader.get_u16 ()?;
+				let events : &'a [ u8 ] = reader.get_bytes ( byte_count as usize - 6 )?;
+
+				reply = ResponsePdu::GetCommEventLog { status : status, event_count : event_count, message_count : message_count, events : events };
+			}
+			FUNCTION_CODE_WRITE_MULTIPLE_COILS |
+			FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS		=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let quantity : u16 = reader.get_u16 ()?;
+
+				if function_code == FUNCTION_CODE_WRITE_MULTIPLE_COILS
+				{
+					reply = ResponsePdu::WriteMultipleCoils { address : address, quantity : quantity };
+				}
+				else
+				{
+					reply = ResponsePdu::WriteMultipleRegisters { address : address, quantity : quantity };
+				}
+			}
+			FUNCTION_CODE_REPORT_SERVER_ID |
+			FUNCTION_CODE_READ_FILE_RECORD |
+			FUNCTION_CODE_WRITE_FILE_RECORD				=>
+			{
+				let byte_count : u8 = reader.get_u8 ()?;
+				let bytes : &'a [ u8 ] = reader.get_bytes ( byte_count as usize )?;
+
+				match function_code
+				{
+					FUNCTION_CODE_REPORT_SERVER_ID			=> { reply = ResponsePdu::ReportServerId { data : bytes }; }
+					FUNCTION_CODE_READ_FILE_RECORD			=> { reply = ResponsePdu::ReadFileRecord { sub_responses : bytes }; }
+					_										=> { reply = ResponsePdu::WriteFileRecord { sub_requests : bytes }; }
+				}
+			}
+			FUNCTION_CODE_MASK_WRITE_REGISTER			=>
+			{
+				let address : u16 = reader.get_u16 ()?;
+				let and_mask : u16 = reader.get_u16 ()?;
+				let or_mask : u16 = reader.get_u16 ()?;
+
+				reply = ResponsePdu::MaskWriteRegister { address : address, and_mask : and_mask, or_mask : or_mask };
+			}
+			FUNCTION_CODE_READ_FIFO_QUEUE				=>
+			{
+				let byte_count : u16 = reader.get_u16 ()?;
+				let fifo_count : u16 = reader.get_u16 ()?;
+
+				verify_quantity_with_zero ( fifo_count as usize, MODBUS_MAXIMUM_FIFO_COUNT )?;
+
+				if byte_count != 2 + fifo_count * 2
+				{
+					return Err( PduError::ByteCountMismatch( byte_count ) );
+				}
+
+				reply = ResponsePdu::ReadFifoQueue { values : Registers::Bytes( reader.get_bytes ( fifo_count as usize * 2 )? ) };
+			}
+			FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT if pdu.get ( 1 ) == Some( &MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION )	=>
+			{
+				reader.get_u8 ()?;
+
+				let read_device_id_code : u8 = reader.get_u8 ()?;
+				let conformity_level : u8 = reader.get_u8 ()?;
+				let more_follows : bool;
+
+				match reader.get_u8 ()?
+				{
+					0xFF	=> { more_follows = true; }
+					0x00	=> { more_follows = false; }
+					value	=> { return Err( PduError::IllegalValue( value ) ); }
+				}
+
+				let next_object_id : u8 = reader.get_u8 ()?;
+				let number_of_objects : u8 = reader.get_u8 ()?;
+				let objects : DeviceObjects< 'a > = DeviceObjects::new ( reader.get_remaining () )?;
+
+				if objects.len () != number_of_objects as usize
+				{
+					return Err( PduError::IllegalQuantity { quantity : objects.len (), maximum : number_of_objects as u16 } );
+				}
+
+				reply = ResponsePdu::ReadDeviceIdentification { read_device_id_code : read_device_id_code, conformity_level : conformity_level, more_follows : more_follows, next_object_id : next_object_id, objects : objects };
+			}
+			_ if function_code & MODBUS_EXCEPTION_FLAG != 0	=>
+			{
+				let exception_code : u8 = reader.get_u8 ()?;
+
+				reply = ResponsePdu::Exception { function_code : function_code & !MODBUS_EXCEPTION_FLAG, exception_code : exception_code };
+			}
+			_											=>
+			{
+				reply = ResponsePdu::Custom { function_code : function_code, data : reader.get_remaining () };
+			}
+		}
+
+		reader.finish ()?;
+
+		return Ok( reply );
+	}
+
+	///	Copies the response PDU into the buffer and decodes it from there,
+	/// so the response can outlive the bytes it was received in.
+	pub fn decode_into ( pdu : &[ u8 ], buffer : &'a mut [ u8 ] ) -> Result< ResponsePdu< 'a >, PduError >
+	{
+		if pdu.len () > buffer.len ()
+		{
+			return Err( PduError::BufferTooSmall { required : pdu.len (), available : buffer.len () } );
+		}
+
+		buffer[ 0..pdu.len () ].copy_from_slice ( pdu );
+
+		return ResponsePdu::decode ( &buffer[ 0..pdu.len () ] );
+	}
+
+	///	Returns the length of the response PDU starting with the bytes as far as they
+	/// tell it. While more bytes are needed to know the length, it exceeds their number,
+	/// so readers of a byte stream read up to the returned length until it stays the same.
+	/// Diagnostics responses are expected with two bytes of data, like most of them.
+	pub fn get_length ( bytes : &[ u8 ] ) -> Result< usize, PduError >
+	{
+		let reply : usize;
+
+		let function_code : u8;
+
+		match bytes.first ()
+		{
+			Some( value )	=> { function_code = *value; }
+			None			=> { return Ok( 1 ); }
+		}
+
+		match function_code
+		{
+			FUNCTION_CODE_READ_COILS |
+			FUNCTION_CODE_READ_DISCRETE_INPUTS |
+			FUNCTION_CODE_READ_HOLDING_REGISTERS |
+			FUNCTION_CODE_READ_INPUT_REGISTERS |
+			FUNCTION_CODE_GET_COMM_EVENT_LOG |
+			FUNCTION_CODE_REPORT_SERVER_ID |
+			FUNCTION_CODE_READ_FILE_RECORD |
+			FUNCTION_CODE_WRITE_FILE_RECORD |
+			FUNCTION_CODE_READ_WRITE_MULTIPLE_REGISTERS	=>
+			{
+				match bytes.get ( 1 )
+				{
+					Some( byte_count )	=> { reply = 2 + *byte_count as usize; }
+					None				=> { reply = 2; }
+				}
+			}
+			FUNCTION_CODE_WRITE_SINGLE_COIL |
+			FUNCTION_CODE_WRITE_SINGLE_REGISTER |
+			FUNCTION_CODE_DIAGNOSTICS |
+			FUNCTION_CODE_GET_COMM_EVENT_COUNTER |
+			FUNCTION_CODE_WRITE_MULTIPLE_COILS |
+			FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS		=> { reply = 5; }
+			FUNCTION_CODE_READ_EXCEPTION_STATUS			=> { reply = 2; }
+			FUNCTION_CODE_MASK_WRITE_REGISTER			=> { reply = 1 + MODBUS_MASK_WRITE_PAYLOAD_LENGTH; }
+			FUNCTION_CODE_READ_FIFO_QUEUE				=>
+			{
+				if bytes.len () < 3
+				{
+					reply = 3;
+				}
+				else
+				{
+					reply = 3 + ( ( bytes[ 1 ] as usize ) << 8 | bytes[ 2 ] as usize );
+				}
+			}
+			FUNCTION_CODE_ENCAPSULATED_INTERFACE_TRANSPORT	=>
+			{
+				reply = get_device_identification_length ( bytes )?;
+			}
+			_ if function_code & MODBUS_EXCEPTION_FLAG != 0	=> { reply = 2; }
+			_											=> { return Err( PduError::UnsupportedFunctionCode( function_code ) ); }
+		}
+
+		if reply > MODBUS_MAXIMUM_PDU_SIZE as usize
+		{
+			return Err( PduError::TooLarge( reply ) );
+		}
+
+		return Ok( reply );
+	}
+}
+
+///	Returns the length of a read device identification response as far as the bytes tell it.
+fn get_device_identification_length ( bytes : &[ u8 ] ) -> Result< usize, PduError >
+{
+	const OBJECTS_START : usize = 7;
+
+	match bytes.get ( 1 )
+	{
+		Some( &MODBUS_MEI_TYPE_READ_DEVICE_IDENTIFICATION )	=> {}
+		Some( _ )											=> { return Err( PduError::UnsupportedFunctionCode( bytes[ 0 ] ) ); }
+		None												=> { return Ok( 2 ); }
+	}
+
+	if bytes.len () < OBJECTS_START
+	{
+		return Ok( OBJECTS_START );
+	}
+
+	let mut position : usize = OBJECTS_START;
+
+	for _ in 0..bytes[ OBJECTS_START - 1 ]
+	{
+		if bytes.len () < position + 2
+		{
+			return Ok( position + 2 );
+		}
+
+		position += 2 + bytes[ position + 1 ] as usize;
+	}
+
+	return Ok( position );
+}
+
+//	===============================================================================================
+
+struct PduWriter< 'a >
+{
+	buffer : &'a mut [ u8 ],
+	position : usize
+}
+
+impl< 'a > PduWriter< 'a >
+{
+	///	Checks that `length` bytes fit into the buffer and the PDU size,
+	/// so the put methods do not need to.
+	fn new ( buffer : &'a mut [ u8 ], length : usize ) -> Result< PduWriter< 'a >, PduError >
+	{
+		if length > MODBUS_MAXIMUM_PDU_SIZE as usize
+		{
+			return Err( PduError::TooLarge( length ) );
+		}
+
+		if length > buffer.len ()
+		{
+			return Err( PduError::BufferTooSmall { required : length, available : buffer.len () } );
+		}
+
+		return Ok( PduWriter
+				   {
+					   buffer : buffer,
+					   position : 0
+				   } );
+	}
+
+	fn put_u8 ( &mut self, value : u8 )
+	{
+		self.buffer[ self.position ] = value;
+		self.position += 1;
+	}
+
+	fn put_u16 ( &mut self, value : u16 )
+	{
+		self.put_u8 ( ( value >> 8 ) as u8 );
+		self.put_u8 ( value as u8 );
+	}
+
+	fn put_bytes ( &mut self, values : &[ u8 ] )
+	{
+		for value in values
+		{
+			self.put_u8 ( *value );
+		}
+	}
+
+	fn put_coils ( &mut self, values : &Coils )
+	{
+		for byte_index in 0..values.len ().div_ceil ( 8 )
+		{
+			let mut byte : u8 = 0x00;
+
+			for bit in 0..8
+			{
+				if values.get ( byte_index * 8 + bit ) == Some( true )
+				{
+					byte |= 1 << bit;
+				}
+			}
+
+			self.put_u8 ( byte );
+		}
+	}
+
+	fn put_registers ( &mut self, values : &Registers )
+	{
+		for value in values.iter ()
+		{
+			self.put_u16 ( value );
+		}
+	}
+}
+
+//	===============================================================================================
+
+struct PduReader< 'a >
+{
+	data : &'a [ u8 ],
+	position : usize
+}
+
+impl< 'a > PduReader< 'a >
+{
+	fn new ( data : &'a [ u8 ] ) -> PduReader< 'a >
+	{
+		return PduReader
+		{
+			data : data,
+			position : 0
+		};
+	}
+
+	fn get_bytes ( &mut self, count : usize ) -> Result< &'a [ u8 ], PduError >
+	{
+		if self.position + count > self.data.len ()
+		{
+			return Err( PduError::Truncated( self.data.len () ) );
+		}
+
+		let reply : &'a [ u8 ] = &self.data[ self.position..self.position + count ];
+		self.position += count;
+
+		return Ok( reply );
+	}
+
+	fn get_u8 ( &mut self ) -> Result< u8, PduError >
+	{
+		return Ok( self.get_bytes ( 1 )?[ 0 ] );
+	}
+
+	fn get_u16 ( &mut self ) -> Result< u16, PduError >
+	{
+		let bytes : &[ u8 ] = self.get_bytes ( 2 )?;
+
+		return Ok( ( bytes[ 0 ] as u16 ) << 8 | bytes[ 1 ] as u16 );
+	}
+
+	///	Reads the byte count of a write request, which has to match the quantity.
+	fn get_byte_count ( &mut self, expected_byte_count : usize ) -> Result< usize, PduError >
+	{
+		let byte_count : u8 = self.get_u8 ()?;
+
+		if byte_count as usize != expected_byte_count
+		{
+			return Err( PduError::ByteCountMismatch( byte_count as u16 ) );
+		}
+
+		return Ok( expected_byte_count );
+	}
+
+	fn get_remaining ( &mut self ) -> &'a [ u8 ]
+	{
+		let reply : &'a [ u8 ] = &self.data[ self.position.. ];
+		self.position = self.data.len ();
+
+		return reply;
+	}
+
+	fn finish ( &self ) -> Result< (), PduError >
+	{
+		if self.position != self.data.len ()
+		{
+			return Err( PduError::TrailingBytes( self.data.len () - self.position ) );
+		}
+
+		return Ok( () );
+	}
+}
+
+//	===============================================================================================
+
+fn verify_quantity ( quantity : usize, maximum_quantity : u16 ) -> Result< (), PduError >
+{
+	if quantity == 0 || quantity > maximum_quantity as usize
+	{
+		return Err( PduError::IllegalQuantity { quantity : quantity, maximum : maximum_quantity } );
+	}
+
+	return Ok( () );
+}
+
+///	Like `verify_quantity` for quantities which may be 0, e.g. of an empty FIFO queue.
+fn verify_quantity_with_zero ( quantity : usize, maximum_quantity : u16 ) -> Result< (), PduError >
+{
+	if quantity > maximum_quantity as usize
+	{
+		return Err( PduError::IllegalQuantity { quantity : quantity, maximum : maximum_quantity } );
+	}
+
+	return Ok( () );
+}
+
+///	Checks that the last address of the values is 65535 at most.
+fn verify_address_range ( address : u16, quantity : usize ) -> Result< (), PduError >
+{
+	if address as usize + quantity > 0x10000
+	{
+		return Err( PduError::AddressOverflow { address : address, quantity : quantity } );
+	}
+
+	return Ok( () );
+}
+
+///	Checks that packed coils have a byte for each eight values.
+fn verify_coils ( values : &Coils ) -> Result< (), PduError >
+{
+	if let Coils::Packed { bytes, quantity } = *values
+	{
+		if bytes.len () != quantity.div_ceil ( 8 )
+		{
+			return Err( PduError::ByteCountMismatch( bytes.len () as u16 ) );
+		}
+	}
+
+	return Ok( () );
+}
+
+fn verify_read_file_sub_requests ( sub_requests : &[ u8 ] ) -> Result< (), PduError >
+{
+	if sub_requests.is_empty () || sub_requests.len () % MODBUS_READ_FILE_SUB_REQUEST_LENGTH != 0
+	{
+		return Err( PduError::ByteCountMismatch( sub_requests.len () as u16 ) );
+	}
+
+	return Ok( () );
+}
+
+fn encode_coil_value ( value : bool ) -> u16
+{
+	if value
+	{
+		return 0xFF00;
+	}
+
+	return 0x0000;
+}
+
+fn decode_coil_value ( value : u16 ) -> Result< bool, PduError >
+{
+	match value
+	{
+		0xFF00	=> { return Ok( true ); }
+		0x0000	=> { return Ok( false ); }
+		_		=> { return Err( PduError::IllegalCoilValue( value ) ); }
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_request_pdu_encode_and_decode ()
+{
+	let coils : [ bool; 10 ] = [ true, false, true, true, false, false, true, true, true, false ];
+	let registers : [ u16; 2 ] = [ 0x000A, 0x0102 ];
+
+	let requests : [ ( RequestPdu, &[ u8 ] ); 12 ] =
+	[
+		( RequestPdu::ReadHoldingRegisters { address : 0x006B, quantity : 3 }, &[ 0x03, 0x00, 0x6B, 0x00, 0x03 ] ),
+		( RequestPdu::WriteSingleCoil { address : 0x00AC, value : true }, &[ 0x05, 0x00, 0xAC, 0xFF, 0x00 ] ),
+		( RequestPdu::ReadExceptionStatus, &[ 0x07 ] ),
+		( RequestPdu::Diagnostics { sub_function : 0x0000, data : &[ 0xA5, 0x37 ] }, &[ 0x08, 0x00, 0x00, 0xA5, 0x37 ] ),
+		( RequestPdu::WriteMultipleCoils { address : 0x0013, values : Coils::Unpacked( &coils ) },
+		  &[ 0x0F, 0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01 ] ),
+		( RequestPdu::WriteMultipleRegisters { address : 0x0001, values : Registers::Words( &registers ) },
+		  &[ 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02 ] ),
+		( RequestPdu::ReadFileRecord { sub_requests : &[ 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02 ] },
+		  &[ 0x14, 0x07, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02 ] ),
+		( RequestPdu::MaskWriteRegister { address : 0x0004, and_mask : 0x00F2, or_mask : 0x0025 },
+		  &[ 0x16, 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25 ] ),
+		( RequestPdu::ReadWriteMultipleRegisters { read_address : 0x0003, read_quantity : 6, write_address : 0x000E, values : Registers::Words( &[ 0x00FF, 0x00FF, 0x00FF ] ) },
+		  &[ 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x03, 0x06, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF ] ),
+		( RequestPdu::ReadFifoQueue { address : 0x04DE }, &[ 0x18, 0x04, 0xDE ] ),
+		( RequestPdu::ReadDeviceIdentification { read_device_id_code : 0x01, object_id : 0x00 }, &[ 0x2B, 0x0E, 0x01, 0x00 ] ),
+		( RequestPdu::Custom { function_code : 0x2B, data : &[ 0x0D, 0x00 ] }, &[ 0x2B, 0x0D, 0x00 ] )
+	];
+
+	for &( request, bytes ) in requests.iter ()
+	{
+		let mut buffer : [ u8; MODBUS_MAXIMUM_PDU_SIZE as usize ] = [ 0; MODBUS_MAXIMUM_PDU_SIZE as usize ];
+		let length : usize = request.encode ( &mut buffer ).unwrap ();
+
+		assert_eq! ( &buffer[ 0..length ], bytes );
+		assert_eq! ( RequestPdu::decode ( bytes ), Ok( request ) );
+	}
+
+	assert_eq! ( RequestPdu::ReadCoils { address : 0, quantity : 8 }.encode ( &mut [ 0; 4 ] ),
+				 Err( PduError::BufferTooSmall { required : 5, available : 4 } ) );
+	assert_eq! ( RequestPdu::ReadCoils { address : 0, quantity : 0 }.encode ( &mut [ 0; 5 ] ),
+				 Err( PduError::IllegalQuantity { quantity : 0, maximum : MODBUS_MAXIMUM_QUANTITY_OF_READ_BITS } ) );
+	assert_eq! ( RequestPdu::WriteMultipleRegisters { address : 0, values : Registers::Words( &[ 0; 124 ] ) }.encode ( &mut [ 0; 254 ] ),
+				 Err( PduError::TooLarge( 254 ) ) );
+	assert_eq! ( RequestPdu::ReadHoldingRegisters { address : 0xFFFF, quantity : 2 }.encode ( &mut [ 0; 5 ] ),
+				 Err( PduError::AddressOverflow { address : 0xFFFF, quantity : 2 } ) );
+	assert_eq! ( RequestPdu::ReadHoldingRegisters { address : 0xFFFF, quantity : 1 }.encode ( &mut [ 0; 5 ] ), Ok( 5 ) );
+	assert_eq! ( RequestPdu::WriteMultipleCoils { address : 0, values : Coils::Packed { bytes : &[ 0xFF ], quantity : 9 } }.encode ( &mut [ 0; 8 ] ),
+				 Err( PduError::ByteCountMismatch( 1 ) ) );
+	assert_eq! ( RequestPdu::ReadFileRecord { sub_requests : &[ 0x06, 0x00 ] }.encode ( &mut [ 0; 4 ] ),
+				 Err( PduError::ByteCountMismatch( 2 ) ) );
+}
+
+#[test]
+fn test_request_pdu_decode_errors ()
+{
+	assert_eq! ( RequestPdu::decode ( &[] ), Err( PduError::Truncated( 0 ) ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x03, 0x00, 0x00, 0x00 ] ), Err( PduError::Truncated( 4 ) ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x03, 0x00, 0x00, 0x00, 0x7E ] ), Err( PduError::IllegalQuantity { quantity : 126, maximum : 125 } ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x03, 0x00, 0x00, 0x00, 0x01, 0x00 ] ), Err( PduError::TrailingBytes( 1 ) ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x05, 0x00, 0x00, 0x12, 0x34 ] ), Err( PduError::IllegalCoilValue( 0x1234 ) ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x10, 0x00, 0x00, 0x00, 0x01, 0x03, 0x00, 0x01 ] ), Err( PduError::ByteCountMismatch( 3 ) ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x03, 0xFF, 0xFF, 0x00, 0x02 ] ), Err( PduError::AddressOverflow { address : 0xFFFF, quantity : 2 } ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x0F, 0xFF, 0xFF, 0x00, 0x02, 0x01, 0x03 ] ), Err( PduError::AddressOverflow { address : 0xFFFF, quantity : 2 } ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x10, 0xFF, 0xFF, 0x00, 0x02, 0x04, 0x00, 0x01, 0x00, 0x02 ] ), Err( PduError::AddressOverflow { address : 0xFFFF, quantity : 2 } ) );
+	assert_eq! ( RequestPdu::decode ( &[ 0x17, 0x00, 0x00, 0x00, 0x01, 0xFF, 0xFF, 0x00, 0x02, 0x04, 0x00, 0x01, 0x00, 0x02 ] ),
+				 Err( PduError::AddressOverflow { address : 0xFFFF, quantity : 2 } ) );
+	#[cfg( feature = "std" )]
+	assert_eq! ( String::from ( PduError::ByteCountMismatch( 3 ) ), "byte count 3 does not match quantity" );
+}
+
+#[test]
+fn test_response_pdu_encode_and_decode ()
+{
+	let coils : [ bool; 16 ] = [ true, false, true, true, false, false, true, true, true, false, false, false, false, false, false, false ];
+
+	let responses : [ ( ResponsePdu, &[ u8 ] ); 10 ] =
+	[
+		( ResponsePdu::ReadCoils { values : Coils::Unpacked( &coils ) }, &[ 0x01, 0x02, 0xCD, 0x01 ] ),
+		( ResponsePdu::ReadInputRegisters { values : Registers::Words( &[ 0x000A ] ) }, &[ 0x04, 0x02, 0x00, 0x0A ] ),
+		( ResponsePdu::ReadExceptionStatus { status : 0x6D }, &[ 0x07, 0x6D ] ),
+		( ResponsePdu::GetCommEventCounter { status : 0xFFFF, event_count : 0x0108 }, &[ 0x0B, 0xFF, 0xFF, 0x01, 0x08 ] ),
+		( ResponsePdu::GetCommEventLog { status : 0x0000, event_count : 0x0108, message_count : 0x0121, events : &[ 0x20, 0x00 ] },
+		  &[ 0x0C, 0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20, 0x00 ] ),
+		( ResponsePdu::WriteMultipleRegisters { address : 0x0001, quantity : 2 }, &[ 0x10, 0x00, 0x01, 0x00, 0x02 ] ),
+		( ResponsePdu::ReadWriteMultipleRegisters { values : Registers::Words( &[ 0x00FE, 0x0ACD ] ) }, &[ 0x17, 0x04, 0x00, 0xFE, 0x0A, 0xCD ] ),
+		( ResponsePdu::ReadFifoQueue { values : Registers::Words( &[ 0x01B8, 0x1284 ] ) }, &[ 0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84 ] ),
+		( ResponsePdu::ReadDeviceIdentification { read_device_id_code : 0x01, conformity_level : 0x01, more_follows : false, next_object_id : 0x00,
+												  objects : DeviceObjects::new ( &[ 0x00, 0x03, 0x41, 0x42, 0x43, 0x01, 0x01, 0x58 ] ).unwrap () },
+		  &[ 0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x02, 0x00, 0x03, 0x41, 0x42, 0x43, 0x01, 0x01, 0x58 ] ),
+		( ResponsePdu::Exception { function_code : 0x03, exception_code : 0x02 }, &[ 0x83, 0x02 ] )
+	];
+
+	for &( response, bytes ) in responses.iter ()
+	{
+		let mut buffer : [ u8; MODBUS_MAXIMUM_PDU_SIZE as usize ] = [ 0; MODBUS_MAXIMUM_PDU_SIZE as usize ];
+		let length : usize = response.encode ( &mut buffer ).unwrap ();
+
+		assert_eq! ( &buffer[ 0..length ], bytes );
+		assert_eq! ( ResponsePdu::decode ( bytes ), Ok( response ) );
+	}
+
+	match ResponsePdu::decode ( &[ 0x03, 0x04, 0x00, 0x0A, 0x01, 0x02 ] )
+	{
+		Ok( ResponsePdu::ReadHoldingRegisters { values } )	=> { assert_eq! ( values, Registers::Words( &[ 0x000A, 0x0102 ] ) ); }
+		other												=> { panic! ( "unexpected response {:?}", other ); }
+	}
+
+	assert_eq! ( ResponsePdu::decode ( &[ 0x03, 0x03, 0x00, 0x0A, 0x00 ] ), Err( PduError::OddByteCount( 3 ) ) );
+	assert_eq! ( ResponsePdu::decode ( &[ 0x03, 0x04, 0x00, 0x0A ] ), Err( PduError::Truncated( 4 ) ) );
+	assert_eq! ( ResponsePdu::decode ( &[ 0x83 ] ), Err( PduError::Truncated( 1 ) ) );
+	assert_eq! ( ResponsePdu::decode ( &[ 0x18, 0x00, 0x04, 0x00, 0x02, 0x01, 0xB8 ] ), Err( PduError::ByteCountMismatch( 4 ) ) );
+	assert_eq! ( ResponsePdu::decode ( &[ 0x2B, 0x0E, 0x01, 0x01, 0x12, 0x00, 0x00 ] ), Err( PduError::IllegalValue( 0x12 ) ) );
+	assert_eq! ( ResponsePdu::decode ( &[ 0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x03, 0x41 ] ), Err( PduError::Truncated( 3 ) ) );
+}
+
+#[test]
+fn test_device_objects ()
+{
+	let objects : DeviceObjects = DeviceObjects::new ( &[ 0x00, 0x03, 0x41, 0x42, 0x43, 0x01, 0x00 ] ).unwrap ();
+	let mut iterator = objects.iter ();
+
+	assert_eq! ( objects.len (), 2 );
+	assert_eq! ( iterator.next (), Some( ( 0x00, &b"ABC"[ .. ] ) ) );
+	assert_eq! ( iterator.next (), Some( ( 0x01, &b""[ .. ] ) ) );
+	assert_eq! ( iterator.next (), None );
+	assert! ( DeviceObjects::new ( &[] ).unwrap ().is_empty () );
+	assert_eq! ( DeviceObjects::new ( &[ 0x00 ] ), Err( PduError::Truncated( 1 ) ) );
+}
+
+#[test]
+fn test_response_pdu_get_length ()
+{
+	let responses : [ &[ u8 ]; 6 ] =
+	[
+		&[ 0x03, 0x04, 0x00, 0x0A, 0x01, 0x02 ],
+		&[ 0x06, 0x00, 0x01, 0x00, 0x03 ],
+		&[ 0x16, 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25 ],
+		&[ 0x18, 0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84 ],
+		&[ 0x2B, 0x0E, 0x01, 0x01, 0x00, 0x00, 0x02, 0x00, 0x03, 0x41, 0x42, 0x43, 0x01, 0x01, 0x58 ],
+		&[ 0x83, 0x02 ]
+	];
+
+	for response in responses.iter ()
+	{
+		let mut length : usize = 0;
+
+		while ResponsePdu::get_length ( &response[ 0..length ] ) != Ok( length )
+		{
+			length = ResponsePdu::get_length ( &response[ 0..length ] ).unwrap ();
+		}
+
+		assert_eq! ( length, response.len () );
+	}
+
+	assert_eq! ( ResponsePdu::get_length ( &[ 0x41 ] ), Err( PduError::UnsupportedFunctionCode( 0x41 ) ) );
+	assert_eq! ( ResponsePdu::get_length ( &[ 0x2B, 0x0D ] ), Err( PduError::UnsupportedFunctionCode( 0x2B ) ) );
+	assert_eq! ( ResponsePdu::get_length ( &[ 0x18, 0x01, 0x00 ] ), Err( PduError::TooLarge( 259 ) ) );
+}
+
+#[test]
+fn test_response_pdu_decode_into ()
+{
+	let mut buffer : [ u8; 4 ] = [ 0; 4 ];
+
+	assert_eq! ( ResponsePdu::decode_into ( &[ 0x83, 0x02 ], &mut buffer ), Ok( ResponsePdu::Exception { function_code : 0x03, exception_code : 0x02 } ) );
+	assert_eq! ( ResponsePdu::decode_into ( &[ 0x06, 0x00, 0x01, 0x00, 0x03 ], &mut buffer ), Err( PduError::BufferTooSmall { required : 5, available : 4 } ) );
+}
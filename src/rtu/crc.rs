@@ -0,0 +1,80 @@
+use core::consts::*;
+
+//	===============================================================================================
+
+const CRC_INITIAL_VALUE : u16 = 0xFFFF;
+const CRC_POLYNOMIAL : u16 = 0xA001;
+
+//	===============================================================================================
+
+#[test]
+fn test_calculate_crc ()
+{
+	assert_eq! ( calculate_crc ( &[ 0x01, 0x03, 0x00, 0x00, 0x00, 0x0A ] ), 0xCDC5 );
+	assert_eq! ( calculate_crc ( &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03 ] ), 0x9B9A );
+	assert_eq! ( calculate_crc ( &[] ), 0xFFFF );
+}
+
+///	Calculates the CRC-16 of a RTU frame, which is sent low byte first.
+pub fn calculate_crc ( bytes : &[ u8 ] ) -> u16
+{
+	let mut crc : u16 = CRC_INITIAL_VALUE;
+
+	for byte in bytes
+	{
+		crc ^= *byte as u16;
+
+		for _ in 0..8
+		{
+			if crc & 0x0001 != 0
+			{
+				crc = ( crc >> 1 ) ^ CRC_POLYNOMIAL;
+			}
+			else
+			{
+				crc >>= 1;
+			}
+		}
+	}
+
+	return crc;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_append_and_verify_crc ()
+{
+	let mut frame : Vec< u8 > = vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x0A ];
+
+	append_crc ( &mut frame );
+	assert_eq! ( frame, vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD ] );
+	assert! ( verify_crc ( &frame ) );
+
+	frame[ 5 ] = 0x0B;
+	assert! ( !verify_crc ( &frame ) );
+	assert! ( !verify_crc ( &[ 0xFF, 0xFF ] ) );
+}
+
+///	Appends the CRC to a RTU frame.
+pub fn append_crc ( frame : &mut Vec< u8 > )
+{
+	let crc : u16 = calculate_crc ( frame );
+
+	frame.push ( crc as u8 );
+	frame.push ( ( crc >> 8 ) as u8 );
+}
+
+///	Returns true if the frame ends with the CRC of the bytes before.
+pub fn verify_crc ( frame : &[ u8 ] ) -> bool
+{
+	if frame.len () <= MODBUS_RTU_CRC_LENGTH
+	{
+		return false;
+	}
+
+	let data_length : usize = frame.len () - MODBUS_RTU_CRC_LENGTH;
+	let crc : u16 = calculate_crc ( &frame[ 0..data_length ] );
+
+	return frame[ data_length ] == crc as u8 && frame[ data_length + 1 ] == ( crc >> 8 ) as u8;
+}
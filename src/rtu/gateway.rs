@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use core::consts::*;
+use core::datatransformation::*;
+use rtu::master::{RtuMaster, RtuPort};
+use server::requesthandler::create_exception_response;
+use server::tcpserver::*;
+
+//	===============================================================================================
+
+/// A MODBUS TCP to RTU gateway. Requests are routed by their unit identifier
+/// to a slave on one of the serial lines, the lines are used by one request
+/// at a time. Unknown unit identifiers answer with exception 0x0A, slaves not
+/// answering in time (or with a broken frame) with exception 0x0B.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::net::TcpStream;
+/// use modbus_iiot::rtu::gateway::Gateway;
+/// use modbus_iiot::rtu::master::RtuMaster;
+///
+/// // any RtuPort, e.g. a serial port or a TCP stream to a device server
+/// let port = TcpStream::connect("192.168.0.10:4001").unwrap();
+///
+/// let mut gateway = Gateway::new();
+/// let bus = gateway.add_bus(RtuMaster::new(port));
+/// gateway.add_route(1, bus, 17).unwrap();
+///
+/// let _server = gateway.start("0.0.0.0:502").unwrap();
+/// ```
+pub struct Gateway< T >
+{
+	buses : Vec< Arc< Mutex< RtuMaster< T > > > >,
+	routes : BTreeMap< u8, ( usize, u8 ) >
+}
+
+impl< T > Clone for Gateway< T >
+{
+	fn clone ( &self ) -> Gateway< T >
+	{
+		return Gateway
+		{
+			buses : self.buses.clone (),
+			routes : self.routes.clone ()
+		};
+	}
+}
+
+impl< T : RtuPort > Default for Gateway< T >
+{
+	fn default () -> Gateway< T >
+	{
+		return Self::new ();
+	}
+}
+
+impl< T : RtuPort > Gateway< T >
+{
+	pub fn new () -> Gateway< T >
+	{
+		return Gateway
+		{
+			buses : vec![],
+			routes : BTreeMap::new ()
+		};
+	}
+
+	///	Adds a serial line and returns its index for the routes.
+	pub fn add_bus ( &mut self, master : RtuMaster< T > ) -> usize
+	{
+		self.buses.push ( Arc::new ( Mutex::new ( master ) ) );
+
+		return self.buses.len () - 1;
+	}
+
+	///	Routes requests for the unit identifier to the slave address on the bus.
+	pub fn add_route ( &mut self, unit_identifier : u8, bus : usize, slave_address : u8 ) -> Result< (), String >
+	{
+		if bus >= self.buses.len ()
+		{
+			return Err( format! ( "bus {} does not exist", bus ) );
+		}
+
+		if slave_address == 0 || slave_address > 247
+		{
+			return Err( format! ( "slave address {} is out of 1 to 247", slave_address ) );
+		}
+
+		self.routes.insert ( unit_identifier,
+							 ( bus, slave_address ) );
+
+		return Ok( () );
+	}
+
+	///	Forwards a request frame (MBAP header and PDU) and returns the response
+	/// frame with the same transaction identifier, `None` for other protocols.
+	pub fn process_frame ( &self, frame : &[ u8 ] ) -> Option< Vec< u8 > >
+	{
+		let header_size : usize = MODBUS_HEADER_SIZE as usize;
+
+		if frame.len () <= header_size || transform_bytes_to_word ( frame, 2 ) != MODBUS_PROTOCOL_IDENTIFIER_TCP
+		{
+			return None;
+		}
+
+		let function_code : u8 = frame[ header_size ];
+		let pdu : Vec< u8 >;
+
+		match self.routes.get ( &frame[ 6 ] )
+		{
+			Some( &( bus, slave_address ) ) =>
+			{
+				let response : Result< Vec< u8 >, String > = self.buses[ bus ].lock ()
+																			  .unwrap_or_else ( | error | error.into_inner () )
+																			  .send_pdu ( slave_address,
+																						  &frame[ header_size.. ] );
+
+				match response
+				{
+					Ok( response_pdu )	=> { pdu = response_pdu; }
+					Err( _ )			=> { pdu = create_exception_response ( function_code, MODBUS_EXCEPTION_GATEWAY_TARGET_FAILED_TO_RESPOND ); }
+				}
+			}
+			None =>
+			{
+				pdu = create_exception_response ( function_code,
+												  MODBUS_EXCEPTION_GATEWAY_PATH_UNAVAILABLE );
+			}
+		}
+
+		return Some( create_response_frame ( frame,
+											 &pdu ) );
+	}
+}
+
+impl< T : RtuPort + Send + 'static > Gateway< T >
+{
+	///	Starts a MODBUS TCP server on the address forwarding all requests.
+	pub fn start ( self, address : &str ) -> Result< TcpServer, String >
+	{
+		return TcpServer::start_with_frame_handler ( address,
+													 move | frame : &[ u8 ] | self.process_frame ( frame ) );
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use rtu::testport::TestPort;
+#[cfg( test )]
+use sim::device::SimDevice;
+
+#[test]
+fn test_gateway_process_frame ()
+{
+	let device : SimDevice = SimDevice::new_with_size ( 10 );
+	device.set_registers ( DataTable::HoldingRegisters, 0, &[ 0x1234 ] ).unwrap ();
+
+	let mut gateway : Gateway< TestPort > = Gateway::new ();
+	let bus : usize = gateway.add_bus ( RtuMaster::new ( TestPort::new ( 0x11, device ) ) );
+
+	gateway.add_route ( 1, bus, 0x11 ).unwrap ();
+	gateway.add_route ( 2, bus, 0x12 ).unwrap ();
+	assert! ( gateway.add_route ( 3, 1, 0x11 ).is_err () );
+	assert! ( gateway.add_route ( 3, bus, 0 ).is_err () );
+
+	assert_eq! ( gateway.process_frame ( &[ 0xAB, 0xCD, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x01 ] ),
+				 Some( vec![ 0xAB, 0xCD, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34 ] ) );
+	assert_eq! ( gateway.process_frame ( &[ 0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x0A, 0x00, 0x01 ] ),
+				 Some( vec![ 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02 ] ) );
+	assert_eq! ( gateway.process_frame ( &[ 0x00, 0x03, 0x00, 0x00, 0x00, 0x06, 0x02, 0x03, 0x00, 0x00, 0x00, 0x01 ] ),
+				 Some( vec![ 0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0x02, 0x83, 0x0B ] ) );
+	assert_eq! ( gateway.process_frame ( &[ 0x00, 0x04, 0x00, 0x00, 0x00, 0x06, 0x05, 0x03, 0x00, 0x00, 0x00, 0x01 ] ),
+				 Some( vec![ 0x00, 0x04, 0x00, 0x00, 0x00, 0x03, 0x05, 0x83, 0x0A ] ) );
+	assert_eq! ( gateway.process_frame ( &[ 0x00, 0x05, 0x00, 0x01, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x01 ] ), None );
+}
@@ -0,0 +1,318 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use capture::hexlog::format_hex;
+use capture::observer::*;
+use core::consts::*;
+use core::pdu::*;
+use rtu::crc::*;
+
+//	===============================================================================================
+
+const RTU_DEFAULT_SILENT_INTERVAL : u64 = 5;
+const RTU_DEFAULT_TIMEOUT : u64 = 500;
+const RTU_MAXIMUM_FRAME_SIZE : usize = 256;
+
+//	===============================================================================================
+
+/// The serial line of a `RtuMaster`, a port whose read timeout can be changed.
+/// Serial ports implement it with their timeout setter.
+pub trait RtuPort : Read + Write
+{
+	///	Sets the time a read waits for the next byte.
+	fn set_timeout ( &mut self, timeout : Duration ) -> Result< (), String >;
+}
+
+impl RtuPort for TcpStream
+{
+	fn set_timeout ( &mut self, timeout : Duration ) -> Result< (), String >
+	{
+		return self.set_read_timeout ( Some( timeout ) ).map_err ( | error | error.to_string () );
+	}
+}
+
+//	===============================================================================================
+
+/// A MODBUS RTU master on a serial line. Before each request and after each
+/// failed response the master drops the bytes on the line until it is silent
+/// for the silent interval, so late responses and the rest of broken frames
+/// do not end up in the next response.
+pub struct RtuMaster< T >
+{
+	port : T,
+	frame_observer : Option< BoxedFrameObserver >,
+	silent_interval : Duration,
+	timeout : Duration
+}
+
+impl< T : RtuPort > RtuMaster< T >
+{
+	pub fn new ( port : T ) -> RtuMaster< T >
+	{
+		return RtuMaster
+		{
+			port : port,
+			frame_observer : None,
+			silent_interval : Duration::from_millis ( RTU_DEFAULT_SILENT_INTERVAL ),
+			timeout : Duration::from_millis ( RTU_DEFAULT_TIMEOUT )
+		};
+	}
+
+	///	Sets the time without bytes after which the line counts as silent (5 ms by default),
+	/// at least 3.5 characters of the baud rate.
+	pub fn set_silent_interval ( &mut self, silent_interval : Duration )
+	{
+		self.silent_interval = silent_interval;
+	}
+
+	///	Sets the time to wait for the bytes of a response (500 ms by default).
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	pub fn get_port ( &mut self ) -> &mut T
+	{
+		return &mut self.port;
+	}
+
+	///	Sets the observer which gets every frame sent to and received from the slaves,
+	/// e.g. a `capture::pcap::PcapWriter` with `PcapLinkType::Rtu`.
+	pub fn set_frame_observer ( &mut self, observer : Option< BoxedFrameObserver > )
+	{
+		self.frame_observer = observer;
+	}
+
+	///	Sends the request PDU (function code and data) to the slave and returns the response PDU.
+	/// Exception responses are returned like all other responses.
+	pub fn send_pdu ( &mut self, slave_address : u8, pdu : &[ u8 ] ) -> Result< Vec< u8 >, String >
+	{
+		let request : Vec< u8 > = create_rtu_frame ( slave_address,
+													 pdu );
+
+		log_event! ( debug, "request of function 0x{:02X} to slave {}", pdu.first ().cloned ().unwrap_or ( 0 ), slave_address );
+		log_event! ( trace, "sent {}", format_hex ( &request ) );
+
+		notify_observer ( &mut self.frame_observer,
+						  FrameDirection::Sent,
+						  &request );
+
+		discard_received_bytes ( &mut self.port,
+								 self.silent_interval );
+
+		self.port.set_timeout ( self.timeout )?;
+		self.port.write_all ( &request ).map_err ( | error | error.to_string () )?;
+		self.port.flush ().map_err ( | error | error.to_string () )?;
+
+		let response : Vec< u8 >;
+
+		match read_rtu_response ( &mut self.port )
+		{
+			Ok( frame )		=> { response = frame; }
+			Err( message )	=>
+			{
+				log_event! ( warn, "no valid response of slave {}: {}", slave_address, message );
+				discard_received_bytes ( &mut self.port,
+										 self.silent_interval );
+				return Err( message );
+			}
+		}
+
+		log_event! ( trace, "received {}", format_hex ( &response ) );
+
+		notify_observer ( &mut self.frame_observer,
+						  FrameDirection::Received,
+						  &response );
+
+		if response[ 0 ] != slave_address
+		{
+			log_event! ( warn, "response of slave {} instead of {}", response[ 0 ], slave_address );
+			discard_received_bytes ( &mut self.port,
+									 self.silent_interval );
+			return Err( format! ( "response of slave {} instead of {}", response[ 0 ], slave_address ) );
+		}
+
+		return Ok( response[ 1..response.len () - MODBUS_RTU_CRC_LENGTH ].to_vec () );
+	}
+
+	///	Sends a request to the slave and returns the response decoded from the buffer.
+	/// The buffer has to hold the whole response PDU.
+	pub fn send_request< 'a > ( &mut self, slave_address : u8, request : &RequestPdu, buffer : &'a mut [ u8 ] ) -> Result< ResponsePdu< 'a >, String >
+	{
+		let pdu : Vec< u8 > = request.to_bytes ()?;
+		let response : Vec< u8 > = self.send_pdu ( slave_address,
+												   &pdu )?;
+
+		return Ok( ResponsePdu::decode_into ( &response,
+											  buffer )? );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_create_rtu_frame ()
+{
+	assert_eq! ( create_rtu_frame ( 0x01, &[ 0x03, 0x00, 0x00, 0x00, 0x0A ] ), vec![ 0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD ] );
+}
+
+///	Creates a RTU frame of the address, the PDU and the CRC.
+pub fn create_rtu_frame ( address : u8, pdu : &[ u8 ] ) -> Vec< u8 >
+{
+	let mut reply : Vec< u8 > = vec![ address ];
+
+	reply.extend_from_slice ( pdu );
+	append_crc ( &mut reply );
+
+	return reply;
+}
+
+//	===============================================================================================
+
+///	Drops bytes until none arrives within the silent interval,
+/// e.g. a late response or the rest of a broken frame.
+fn discard_received_bytes< T : RtuPort > ( port : &mut T, silent_interval : Duration )
+{
+	let mut buffer : [ u8; RTU_MAXIMUM_FRAME_SIZE ] = [ 0x00; RTU_MAXIMUM_FRAME_SIZE ];
+
+	if port.set_timeout ( silent_interval ).is_ok ()
+	{
+		while let Ok( length ) = port.read ( &mut buffer )
+		{
+			if length == 0
+			{
+				break;
+			}
+
+			log_event! ( debug, "discarded {} bytes", length );
+		}
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_read_rtu_response ()
+{
+	let mut response_1 : &[ u8 ] = &[ 0x01, 0x03, 0x04, 0x00, 0x01, 0x00, 0x02, 0x2A, 0x32, 0xFF ];
+	assert_eq! ( read_rtu_response ( &mut response_1 ), Ok( vec![ 0x01, 0x03, 0x04, 0x00, 0x01, 0x00, 0x02, 0x2A, 0x32 ] ) );
+	assert_eq! ( response_1, &[ 0xFF ] );
+
+	let mut response_2 : &[ u8 ] = &[ 0x01, 0x83, 0x02, 0xC0, 0xF1 ];
+	assert_eq! ( read_rtu_response ( &mut response_2 ), Ok( vec![ 0x01, 0x83, 0x02, 0xC0, 0xF1 ] ) );
+
+	let mut response_3 : &[ u8 ] = &[ 0x01, 0x83, 0x02, 0xC0, 0xF2 ];
+	assert! ( read_rtu_response ( &mut response_3 ).is_err () );
+
+	let mut response_4 : &[ u8 ] = &[ 0x01, 0x06, 0x00, 0x01 ];
+	assert! ( read_rtu_response ( &mut response_4 ).is_err () );
+
+	let mut response_5 : &[ u8 ] = &[ 0x01, 0x2B, 0x0E, 0x01 ];
+	assert! ( read_rtu_response ( &mut response_5 ).is_err () );
+
+	let mut response_6 : &[ u8 ] = &[ 0x01, 0x07, 0x6D, 0xE3, 0xDD ];
+	assert_eq! ( read_rtu_response ( &mut response_6 ), Ok( vec![ 0x01, 0x07, 0x6D, 0xE3, 0xDD ] ) );
+
+	let mut response_7 : &[ u8 ] = &[ 0x01, 0x41, 0x00 ];
+	assert_eq! ( read_rtu_response ( &mut response_7 ), Err( "unsupported function code 0x41".to_string () ) );
+}
+
+///	Reads a response frame and verifies its CRC. The length of the frame
+/// follows from the response PDU as far as it has been read (see `ResponsePdu::get_length`).
+pub fn read_rtu_response< T : Read > ( port : &mut T ) -> Result< Vec< u8 >, String >
+{
+	let mut reply : Vec< u8 > = vec![ 0x00; 1 ];
+
+	port.read_exact ( &mut reply ).map_err ( | error | error.to_string () )?;
+
+	loop
+	{
+		let length : usize = ResponsePdu::get_length ( &reply[ 1.. ] )? + 1;
+
+		if length == reply.len ()
+		{
+			break;
+		}
+
+		let mut remaining : Vec< u8 > = vec![ 0x00; length - reply.len () ];
+
+		port.read_exact ( &mut remaining ).map_err ( | error | error.to_string () )?;
+		reply.append ( &mut remaining );
+	}
+
+	let mut crc : Vec< u8 > = vec![ 0x00; MODBUS_RTU_CRC_LENGTH ];
+
+	port.read_exact ( &mut crc ).map_err ( | error | error.to_string () )?;
+	reply.append ( &mut crc );
+
+	if !verify_crc ( &reply )
+	{
+		return Err( "CRC error".to_string () );
+	}
+
+	return Ok( reply );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use capture::hexlog::HexLogWriter;
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use rtu::testport::TestPort;
+#[cfg( test )]
+use sim::device::SimDevice;
+#[cfg( test )]
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_rtu_master_send_request ()
+{
+	let mut master : RtuMaster< TestPort > = RtuMaster::new ( TestPort::new ( 0x11, SimDevice::new_with_size ( 10 ) ) );
+
+	assert_eq! ( master.send_pdu ( 0x11, &[ 0x06, 0x00, 0x01, 0x00, 0x03 ] ), Ok( vec![ 0x06, 0x00, 0x01, 0x00, 0x03 ] ) );
+	assert_eq! ( master.get_port ().requests[ 0 ], vec![ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B ] );
+	assert_eq! ( master.get_port ().device.get_registers ( DataTable::HoldingRegisters, 1, 1 ), Some( vec![ 3 ] ) );
+
+	assert_eq! ( master.send_pdu ( 0x11, &[ 0x03, 0x00, 0x0A, 0x00, 0x01 ] ), Ok( vec![ 0x83, 0x02 ] ) );
+	assert! ( master.send_pdu ( 0x12, &[ 0x03, 0x00, 0x00, 0x00, 0x01 ] ).is_err () );
+
+	let mut buffer : [ u8; 8 ] = [ 0; 8 ];
+
+	assert_eq! ( master.send_request ( 0x11, &RequestPdu::ReadHoldingRegisters { address : 0, quantity : 2 }, &mut buffer ),
+				 Ok( ResponsePdu::ReadHoldingRegisters { values : Registers::Words( &[ 0, 3 ] ) } ) );
+	assert! ( master.send_request ( 0x11, &RequestPdu::ReadHoldingRegisters { address : 0, quantity : 4 }, &mut buffer ).is_err () );
+}
+
+#[test]
+fn test_rtu_master_discards_late_and_garbled_responses ()
+{
+	let mut master : RtuMaster< TestPort > = RtuMaster::new ( TestPort::new ( 0x11, SimDevice::new_with_size ( 10 ) ) );
+
+	master.get_port ().delayed_reads = 2;
+	assert! ( master.send_pdu ( 0x11, &[ 0x06, 0x00, 0x01, 0x00, 0x03 ] ).is_err () );
+	assert_eq! ( master.send_pdu ( 0x11, &[ 0x03, 0x00, 0x00, 0x00, 0x02 ] ), Ok( vec![ 0x03, 0x04, 0x00, 0x00, 0x00, 0x03 ] ) );
+
+	master.get_port ().noise = vec![ 0x11, 0x00 ];
+	assert! ( master.send_pdu ( 0x11, &[ 0x06, 0x00, 0x01, 0x00, 0x04 ] ).is_err () );
+	assert_eq! ( master.send_pdu ( 0x11, &[ 0x03, 0x00, 0x01, 0x00, 0x01 ] ), Ok( vec![ 0x03, 0x02, 0x00, 0x04 ] ) );
+}
+
+#[test]
+fn test_rtu_master_frame_observer ()
+{
+	let log : Arc< Mutex< HexLogWriter< Vec< u8 > > > > = Arc::new ( Mutex::new ( HexLogWriter::new ( vec![] ) ) );
+	let mut master : RtuMaster< TestPort > = RtuMaster::new ( TestPort::new ( 0x11, SimDevice::new_with_size ( 10 ) ) );
+
+	master.set_frame_observer ( Some( Box::new ( log.clone () ) ) );
+
+	assert! ( master.send_pdu ( 0x11, &[ 0x06, 0x00, 0x01, 0x00, 0x03 ] ).is_ok () );
+
+	let text : String = String::from_utf8 ( log.lock ().unwrap ().get_writer ().clone () ).unwrap ();
+	let lines : Vec< &str > = text.lines ().collect ();
+
+	assert_eq! ( lines.len (), 2 );
+	assert! ( lines[ 0 ].ends_with ( "Z TX 11 06 00 01 00 03 9A 9B" ) );
+	assert! ( lines[ 1 ].ends_with ( "Z RX 11 06 00 01 00 03 9A 9B" ) );
+}
@@ -0,0 +1,9 @@
+
+
+pub mod crc;
+pub mod gateway;
+pub mod master;
+pub mod slave;
+pub mod tcpclient;
+#[cfg( test )]
+pub mod testport;
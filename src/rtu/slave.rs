@@ -0,0 +1,258 @@
+use std::io::{ErrorKind, Read, Write};
+use core::consts::*;
+use rtu::crc::verify_crc;
+use rtu::master::create_rtu_frame;
+use server::datamodel::DataModel;
+use server::requesthandler::process_request;
+
+//	===============================================================================================
+
+const RTU_BROADCAST_ADDRESS : u8 = 0x00;
+const RTU_MAXIMUM_FRAME_SIZE : usize = 256;
+const RTU_MAXIMUM_SLAVE_ADDRESS : u8 = 247;
+
+//	===============================================================================================
+
+/// A MODBUS RTU slave on a serial line answering requests from a data model.
+/// The read timeout of the port is the silent interval between frames: a read
+/// timing out after some bytes ends the frame. Frames with a wrong CRC or for
+/// other slaves are ignored, writes to the broadcast address 0 are executed
+/// without a response.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::net::TcpStream;
+/// use std::time::Duration;
+/// use modbus_iiot::rtu::slave::RtuSlave;
+/// use modbus_iiot::server::datastore::DataStore;
+///
+/// // any Read + Write port with a short read timeout, e.g. a serial port
+/// let port = TcpStream::connect("192.168.0.10:4001").unwrap();
+/// port.set_read_timeout(Some(Duration::from_millis(5))).unwrap();
+///
+/// let mut slave = RtuSlave::new(port, 17, DataStore::new_dense(100)).unwrap();
+/// println!("stopped: {}", slave.serve());
+/// ```
+pub struct RtuSlave< T, M >
+{
+	port : T,
+	slave_address : u8,
+	model : M
+}
+
+impl< T : Read + Write, M : DataModel > RtuSlave< T, M >
+{
+	///	Creates a slave with the address 1 to 247.
+	pub fn new ( port : T, slave_address : u8, model : M ) -> Result< RtuSlave< T, M >, String >
+	{
+		if slave_address == RTU_BROADCAST_ADDRESS || slave_address > RTU_MAXIMUM_SLAVE_ADDRESS
+		{
+			return Err( format! ( "slave address {} is out of 1 to 247", slave_address ) );
+		}
+
+		return Ok( RtuSlave
+		{
+			port : port,
+			slave_address : slave_address,
+			model : model
+		} );
+	}
+
+	pub fn get_model ( &mut self ) -> &mut M
+	{
+		return &mut self.model;
+	}
+
+	pub fn get_port ( &mut self ) -> &mut T
+	{
+		return &mut self.port;
+	}
+
+	///	Answers frames until the port fails or is closed and returns the reason.
+	pub fn serve ( &mut self ) -> String
+	{
+		loop
+		{
+			if let Err( message ) = self.serve_frame ()
+			{
+				return message;
+			}
+		}
+	}
+
+	///	Waits for the next frame and answers it if it is a request for this slave.
+	pub fn serve_frame ( &mut self ) -> Result< (), String >
+	{
+		let frame : Vec< u8 > = read_frame ( &mut self.port )?;
+
+		if let Some( response ) = process_frame ( &mut self.model,
+												  self.slave_address,
+												  &frame )
+		{
+			self.port.write_all ( &response ).map_err ( | error | error.to_string () )?;
+			self.port.flush ().map_err ( | error | error.to_string () )?;
+		}
+
+		return Ok( () );
+	}
+}
+
+//	===============================================================================================
+
+///	Reads bytes until the read after the first bytes times out.
+fn read_frame< T : Read > ( port : &mut T ) -> Result< Vec< u8 >, String >
+{
+	let mut reply : Vec< u8 > = vec![];
+	let mut buffer : [ u8; RTU_MAXIMUM_FRAME_SIZE ] = [ 0x00; RTU_MAXIMUM_FRAME_SIZE ];
+
+	loop
+	{
+		match port.read ( &mut buffer )
+		{
+			Ok( 0 ) if reply.is_empty ()	=> { return Err( "port closed".to_string () ); }
+			Ok( 0 )							=> { return Ok( reply ); }
+			Ok( length )					=> { reply.extend_from_slice ( &buffer[ 0..length ] ); }
+			Err( ref error ) if error.kind () == ErrorKind::WouldBlock || error.kind () == ErrorKind::TimedOut =>
+			{
+				if !reply.is_empty ()
+				{
+					return Ok( reply );
+				}
+			}
+			Err( ref error ) if error.kind () == ErrorKind::Interrupted => {}
+			Err( error )					=> { return Err( error.to_string () ); }
+		}
+	}
+}
+
+#[test]
+fn test_process_frame ()
+{
+	let mut device : SimDevice = SimDevice::new_with_size ( 10 );
+
+	assert_eq! ( process_frame ( &mut device, 0x11, &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B ] ), Some( vec![ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B ] ) );
+	assert_eq! ( process_frame ( &mut device, 0x11, &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x04, 0x9A, 0x9B ] ), None );
+	assert_eq! ( process_frame ( &mut device, 0x12, &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x04, 0xDB, 0x59 ] ), None );
+	assert_eq! ( device.get_registers ( DataTable::HoldingRegisters, 1, 1 ), Some( vec![ 3 ] ) );
+
+	assert_eq! ( process_frame ( &mut device, 0x11, &create_rtu_frame ( 0x00, &[ 0x06, 0x00, 0x01, 0x00, 0x05 ] ) ), None );
+	assert_eq! ( device.get_registers ( DataTable::HoldingRegisters, 1, 1 ), Some( vec![ 5 ] ) );
+	assert_eq! ( process_frame ( &mut device, 0x11, &create_rtu_frame ( 0x00, &[ 0x06, 0x00, 0x0A, 0x00, 0x05 ] ) ), None );
+	assert_eq! ( process_frame ( &mut device, 0x11, &create_rtu_frame ( 0x00, &[ 0x03, 0x00, 0x00, 0x00, 0x01 ] ) ), None );
+	assert_eq! ( process_frame ( &mut device, 0x11, &[ 0x11 ] ), None );
+}
+
+///	Returns the response frame to a request frame, `None` if the
+/// frame is invalid, for another slave or a broadcast.
+fn process_frame< M : DataModel > ( model : &mut M, slave_address : u8, frame : &[ u8 ] ) -> Option< Vec< u8 > >
+{
+	let mut reply : Option< Vec< u8 > > = None;
+
+	if frame.len () > MODBUS_RTU_CRC_LENGTH + 1 && verify_crc ( frame )
+	{
+		let pdu : &[ u8 ] = &frame[ 1..frame.len () - MODBUS_RTU_CRC_LENGTH ];
+
+		if frame[ 0 ] == slave_address
+		{
+			reply = Some( create_rtu_frame ( slave_address,
+											 &process_request ( model,
+																pdu ) ) );
+		}
+		else if frame[ 0 ] == RTU_BROADCAST_ADDRESS && is_write_function_code ( pdu[ 0 ] )
+		{
+			process_request ( model,
+							  pdu );
+		}
+	}
+
+	return reply;
+}
+
+fn is_write_function_code ( function_code : u8 ) -> bool
+{
+	return function_code == FUNCTION_CODE_WRITE_SINGLE_COIL ||
+		   function_code == FUNCTION_CODE_WRITE_SINGLE_REGISTER ||
+		   function_code == FUNCTION_CODE_WRITE_MULTIPLE_COILS ||
+		   function_code == FUNCTION_CODE_WRITE_MULTIPLE_REGISTERS ||
+		   function_code == FUNCTION_CODE_MASK_WRITE_REGISTER;
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use std::collections::VecDeque;
+#[cfg( test )]
+use std::io;
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use sim::device::SimDevice;
+
+///	A serial line for unit tests passing one chunk per read, an empty
+/// chunk times out. The line is closed after the last chunk.
+#[cfg( test )]
+struct TestLine
+{
+	incoming : VecDeque< Vec< u8 > >,
+	outgoing : Vec< u8 >
+}
+
+#[cfg( test )]
+impl Read for TestLine
+{
+	fn read ( &mut self, buffer : &mut [ u8 ] ) -> io::Result< usize >
+	{
+		match self.incoming.pop_front ()
+		{
+			Some( ref chunk ) if chunk.is_empty ()	=> { return Err( io::Error::new ( io::ErrorKind::TimedOut, "timed out" ) ); }
+			Some( chunk )							=>
+			{
+				buffer[ 0..chunk.len () ].copy_from_slice ( &chunk );
+				return Ok( chunk.len () );
+			}
+			None									=> { return Ok( 0 ); }
+		}
+	}
+}
+
+#[cfg( test )]
+impl Write for TestLine
+{
+	fn write ( &mut self, buffer : &[ u8 ] ) -> io::Result< usize >
+	{
+		self.outgoing.extend_from_slice ( buffer );
+		return Ok( buffer.len () );
+	}
+
+	fn flush ( &mut self ) -> io::Result< () >
+	{
+		return Ok( () );
+	}
+}
+
+#[test]
+fn test_rtu_slave_serve ()
+{
+	let request : Vec< u8 > = create_rtu_frame ( 0x11, &[ 0x03, 0x00, 0x00, 0x00, 0x02 ] );
+	let line : TestLine = TestLine
+	{
+		incoming : vec![ vec![],
+						 request[ 0..3 ].to_vec (), request[ 3.. ].to_vec (), vec![],
+						 create_rtu_frame ( 0x00, &[ 0x10, 0x00, 0x00, 0x00, 0x02, 0x04, 0x00, 0x01, 0x00, 0x02 ] ), vec![],
+						 create_rtu_frame ( 0x05, &[ 0x03, 0x00, 0x00, 0x00, 0x02 ] ), vec![],
+						 request.clone (), vec![] ].into_iter ().collect (),
+		outgoing : vec![]
+	};
+
+	let mut slave : RtuSlave< TestLine, SimDevice > = RtuSlave::new ( line, 0x11, SimDevice::new_with_size ( 10 ) ).unwrap ();
+
+	assert_eq! ( slave.serve (), "port closed" );
+	assert_eq! ( slave.get_port ().outgoing,
+				 [ create_rtu_frame ( 0x11, &[ 0x03, 0x04, 0x00, 0x00, 0x00, 0x00 ] ),
+				   create_rtu_frame ( 0x11, &[ 0x03, 0x04, 0x00, 0x01, 0x00, 0x02 ] ) ].concat () );
+
+	assert! ( RtuSlave::new ( TestLine { incoming : VecDeque::new (), outgoing : vec![] }, 0, SimDevice::new () ).is_err () );
+	assert! ( RtuSlave::new ( TestLine { incoming : VecDeque::new (), outgoing : vec![] }, 248, SimDevice::new () ).is_err () );
+}
@@ -0,0 +1,294 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::time::Duration;
+use core::consts::*;
+use core::transport::*;
+use network::common::create_tcp_stream;
+use rtu::crc::verify_crc;
+use rtu::master::create_rtu_frame;
+
+//	===============================================================================================
+
+const RTU_DEFAULT_SILENT_INTERVAL : u64 = 20;
+const RTU_DEFAULT_TIMEOUT : u64 = 500;
+const RTU_MAXIMUM_FRAME_SIZE : usize = 256;
+
+//	===============================================================================================
+
+/// A MODBUS RTU master over a TCP connection, e.g. to a serial device server
+/// in raw mode. Requests are sent as RTU frames (slave address, PDU and CRC),
+/// a response ends when no byte arrives within the silent interval.
+/// The master operations are the same as of `TcpClient`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::rtu::tcpclient::RtuOverTcpClient;
+/// use modbus_iiot::tcp::masteraccess::MasterAccess;
+///
+/// // slave 17 behind the serial device server
+/// let mut client = RtuOverTcpClient::new_with_port_and_unitid("192.168.0.30", 4001, 17);
+///
+/// if client.connect().is_ok()
+/// {
+///     println!("HR 0 = {:?}", client.read_holding_registers(0, 1));
+///     client.disconnect();
+/// }
+/// ```
+pub struct RtuOverTcpClient
+{
+	address : String,
+	port : u16,
+	stream : Option< TcpStream >,
+	unit_identifier : u8,
+	mask_write_supported : bool,
+	silent_interval : Duration,
+	timeout : Duration
+}
+
+impl RtuOverTcpClient
+{
+	/// Creates a new `RtuOverTcpClient` with the IPv4 or IPv6 address and the port.
+	pub fn new_with_port ( address : &str, port : u16 ) -> RtuOverTcpClient
+	{
+		return Self::new_with_port_and_unitid ( address,
+												port,
+												MODBUS_DEFAULT_UNIT_IDENTIFIER );
+	}
+
+	/// Creates a new `RtuOverTcpClient` with the IPv4 or IPv6 address,
+	/// the port and the address of the slave.
+	pub fn new_with_port_and_unitid ( address : &str, port : u16, unit_id : u8 ) -> RtuOverTcpClient
+	{
+		return RtuOverTcpClient
+		{
+			address : address.to_string (),
+			port : port,
+			stream : None,
+			unit_identifier : unit_id,
+			mask_write_supported : true,
+			silent_interval : Duration::from_millis ( RTU_DEFAULT_SILENT_INTERVAL ),
+			timeout : Duration::from_millis ( RTU_DEFAULT_TIMEOUT )
+		};
+	}
+
+	///	Sets the time without bytes which ends a response (20 ms by default). It has
+	/// to cover the delays of the network, not only the 3.5 characters of the serial line.
+	pub fn set_silent_interval ( &mut self, silent_interval : Duration )
+	{
+		self.silent_interval = silent_interval;
+	}
+
+	///	Sets the time to wait for the first byte of a response (500 ms by default).
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	///	Opens the connection to the device server.
+	pub fn connect ( &mut self ) -> Result< (), String >
+	{
+		let connection : TcpStream = create_tcp_stream ( &self.address,
+														 self.port )?;
+
+		let _ = connection.set_write_timeout ( Some( self.timeout ) );
+		let _ = connection.set_nodelay ( true );
+
+		self.stream = Some( connection );
+
+		return Ok( () );
+	}
+
+	///	Closes the connection if it is open.
+	pub fn disconnect ( &mut self ) -> bool
+	{
+		let mut reply : bool = false;
+
+		if let Some( connection ) = self.stream.take ()
+		{
+			reply = connection.shutdown ( Shutdown::Both ).is_ok ();
+		}
+
+		return reply;
+	}
+}
+
+impl TelegramTransport for RtuOverTcpClient
+{
+	fn get_unit_identifier ( &self ) -> u8
+	{
+		return self.unit_identifier;
+	}
+
+	fn process_pdu ( &mut self, request : &[ u8 ] ) -> Result< Vec< u8 >, String >
+	{
+		let stream : &mut TcpStream = self.stream.as_mut ().ok_or ( "not connected".to_string () )?;
+		let request_frame : Vec< u8 > = create_rtu_frame ( self.unit_identifier,
+														   request );
+
+		discard_received_bytes ( stream );
+
+		stream.write_all ( &request_frame ).map_err ( | error | error.to_string () )?;
+
+		let response_frame : Vec< u8 > = read_frame_by_silent_interval ( stream,
+																		 self.timeout,
+																		 self.silent_interval )?;
+
+		return get_pdu_of_rtu_frame ( &response_frame,
+									  self.unit_identifier ).ok_or ( "invalid rtu frame".to_string () );
+	}
+
+	fn is_mask_write_supported ( &self ) -> bool
+	{
+		return self.mask_write_supported;
+	}
+
+	fn set_mask_write_supported ( &mut self, supported : bool )
+	{
+		self.mask_write_supported = supported;
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_get_pdu_of_rtu_frame ()
+{
+	assert_eq! ( get_pdu_of_rtu_frame ( &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B ], 0x11 ), Some( vec![ 0x06, 0x00, 0x01, 0x00, 0x03 ] ) );
+	assert! ( get_pdu_of_rtu_frame ( &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9C ], 0x11 ).is_none () );
+	assert! ( get_pdu_of_rtu_frame ( &[ 0x11, 0x06, 0x00, 0x01, 0x00, 0x03, 0x9A, 0x9B ], 0x12 ).is_none () );
+}
+
+///	Verifies the CRC and the slave address of a RTU frame and returns its PDU.
+fn get_pdu_of_rtu_frame ( frame : &[ u8 ], slave_address : u8 ) -> Option< Vec< u8 > >
+{
+	let mut reply : Option< Vec< u8 > > = None;
+
+	if verify_crc ( frame ) && frame[ 0 ] == slave_address
+	{
+		reply = Some( frame[ 1..frame.len () - MODBUS_RTU_CRC_LENGTH ].to_vec () );
+	}
+
+	return reply;
+}
+
+///	Drops bytes left over from earlier responses.
+fn discard_received_bytes ( stream : &mut TcpStream )
+{
+	let mut buffer : [ u8; RTU_MAXIMUM_FRAME_SIZE ] = [ 0x00; RTU_MAXIMUM_FRAME_SIZE ];
+
+	if stream.set_nonblocking ( true ).is_ok ()
+	{
+		while let Ok( length ) = stream.read ( &mut buffer )
+		{
+			if length == 0
+			{
+				break;
+			}
+		}
+
+		let _ = stream.set_nonblocking ( false );
+	}
+}
+
+///	Waits up to the timeout for the first byte and reads until no byte
+/// arrives within the silent interval.
+fn read_frame_by_silent_interval ( stream : &mut TcpStream, timeout : Duration, silent_interval : Duration ) -> Result< Vec< u8 >, String >
+{
+	let mut reply : Vec< u8 > = vec![];
+	let mut buffer : [ u8; RTU_MAXIMUM_FRAME_SIZE ] = [ 0x00; RTU_MAXIMUM_FRAME_SIZE ];
+
+	stream.set_read_timeout ( Some( timeout ) ).map_err ( | error | error.to_string () )?;
+
+	loop
+	{
+		match stream.read ( &mut buffer )
+		{
+			Ok( 0 )			=> { return Err( "connection closed".to_string () ); }
+			Ok( length )	=> { reply.extend_from_slice ( &buffer[ 0..length ] ); }
+			Err( ref error ) if !reply.is_empty () && ( error.kind () == ErrorKind::WouldBlock || error.kind () == ErrorKind::TimedOut ) =>
+			{
+				return Ok( reply );
+			}
+			Err( error )	=> { return Err( error.to_string () ); }
+		}
+
+		if reply.len () > RTU_MAXIMUM_FRAME_SIZE
+		{
+			return Err( "frame is too long".to_string () );
+		}
+
+		stream.set_read_timeout ( Some( silent_interval ) ).map_err ( | error | error.to_string () )?;
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use std::net::TcpListener;
+#[cfg( test )]
+use std::thread;
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use sim::device::SimDevice;
+#[cfg( test )]
+use tcp::masteraccess::*;
+
+///	Starts a serial device server with a slave, which sends each
+/// response in two parts with a pause shorter than the silent interval.
+#[cfg( test )]
+fn start_test_device_server ( slave_address : u8, mut device : SimDevice ) -> u16
+{
+	let listener : TcpListener = TcpListener::bind ( "127.0.0.1:0" ).unwrap ();
+	let port : u16 = listener.local_addr ().unwrap ().port ();
+
+	thread::spawn ( move ||
+	{
+		let ( mut stream, _ ) = listener.accept ().unwrap ();
+		let _ = stream.set_nodelay ( true );
+		let mut buffer : [ u8; RTU_MAXIMUM_FRAME_SIZE ] = [ 0x00; RTU_MAXIMUM_FRAME_SIZE ];
+
+		while let Ok( length ) = stream.read ( &mut buffer )
+		{
+			if length == 0 || buffer[ 0 ] != slave_address || !verify_crc ( &buffer[ 0..length ] )
+			{
+				continue;
+			}
+
+			let pdu : Vec< u8 > = device.process_request ( &buffer[ 1..length - MODBUS_RTU_CRC_LENGTH ] );
+			let response : Vec< u8 > = create_rtu_frame ( slave_address, &pdu );
+
+			let _ = stream.write_all ( &response[ 0..3 ] );
+			thread::sleep ( Duration::from_millis ( 5 ) );
+			let _ = stream.write_all ( &response[ 3.. ] );
+		}
+	} );
+
+	return port;
+}
+
+#[test]
+fn test_rtu_over_tcp_client ()
+{
+	let device : SimDevice = SimDevice::new_with_size ( 10 );
+	device.set_registers ( DataTable::InputRegisters, 0, &[ 7, 8, 9 ] ).unwrap ();
+
+	let port : u16 = start_test_device_server ( 0x11, device );
+	let mut client : RtuOverTcpClient = RtuOverTcpClient::new_with_port_and_unitid ( "127.0.0.1", port, 0x11 );
+
+	client.set_timeout ( Duration::from_millis ( 200 ) );
+	client.connect ().unwrap ();
+
+	assert_eq! ( client.read_input_registers ( 0, 3 ), vec![ 7, 8, 9 ] );
+	assert! ( client.write_single_coil ( 2, CoilValue::On ) );
+	assert_eq! ( client.read_coils ( 0, 3 ), vec![ CoilValue::Off, CoilValue::Off, CoilValue::On ] );
+	assert! ( client.write_multiple_registers ( 0, vec![ 0x1234, 0x5678 ] ) );
+	assert_eq! ( client.read_holding_registers ( 0, 2 ), vec![ 0x1234, 0x5678 ] );
+	assert! ( client.read_holding_registers ( 9, 2 ).is_empty () );
+
+	client.unit_identifier = 0x12;
+	assert! ( client.read_holding_registers ( 0, 1 ).is_empty () );
+	assert! ( client.disconnect () );
+}
@@ -0,0 +1,94 @@
+use std::io;
+use std::io::{Read, Write};
+use std::time::Duration;
+use rtu::crc::verify_crc;
+use rtu::master::{create_rtu_frame, RtuPort};
+use sim::device::SimDevice;
+
+//	===============================================================================================
+
+/// A serial line for unit tests with a simulated slave answering requests
+/// to its address. Reads without a pending response time out, like the
+/// first `delayed_reads` reads of a pending response of a slow slave.
+/// The `noise` is sent ahead of the next response.
+pub struct TestPort
+{
+	pub address : u8,
+	pub device : SimDevice,
+	pub requests : Vec< Vec< u8 > >,
+	pub delayed_reads : usize,
+	pub noise : Vec< u8 >,
+	response : Vec< u8 >
+}
+
+impl TestPort
+{
+	pub fn new ( address : u8, device : SimDevice ) -> TestPort
+	{
+		return TestPort
+		{
+			address : address,
+			device : device,
+			requests : vec![],
+			delayed_reads : 0,
+			noise : vec![],
+			response : vec![]
+		};
+	}
+}
+
+impl Read for TestPort
+{
+	fn read ( &mut self, buffer : &mut [ u8 ] ) -> io::Result< usize >
+	{
+		if self.response.is_empty ()
+		{
+			return Err( io::Error::new ( io::ErrorKind::TimedOut, "timed out" ) );
+		}
+
+		if self.delayed_reads > 0
+		{
+			self.delayed_reads -= 1;
+			return Err( io::Error::new ( io::ErrorKind::TimedOut, "timed out" ) );
+		}
+
+		let length : usize = buffer.len ().min ( self.response.len () );
+
+		buffer[ 0..length ].copy_from_slice ( &self.response[ 0..length ] );
+		self.response.drain ( 0..length );
+
+		return Ok( length );
+	}
+}
+
+impl Write for TestPort
+{
+	fn write ( &mut self, buffer : &[ u8 ] ) -> io::Result< usize >
+	{
+		self.requests.push ( buffer.to_vec () );
+
+		if verify_crc ( buffer ) && buffer[ 0 ] == self.address
+		{
+			let pdu : Vec< u8 > = self.device.process_request ( &buffer[ 1..buffer.len () - 2 ] );
+
+			self.response.append ( &mut self.noise );
+			self.response.append ( &mut create_rtu_frame ( self.address,
+														   &pdu ) );
+		}
+
+		return Ok( buffer.len () );
+	}
+
+	fn flush ( &mut self ) -> io::Result< () >
+	{
+		return Ok( () );
+	}
+}
+
+impl RtuPort for TestPort
+{
+	fn set_timeout ( &mut self, _timeout : Duration ) -> Result< (), String >
+	{
+		return Ok( () );
+	}
+}
@@ -0,0 +1,67 @@
+use core::datatable::DataTable;
+
+//	===============================================================================================
+
+/// The data of a server as seen by a master. Errors are MODBUS exception codes,
+/// which are sent back in the exception response.
+pub trait DataModel
+{
+	///	Reads coils or discrete inputs.
+	fn read_bits ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< bool >, u8 >;
+
+	///	Reads holding or input registers.
+	fn read_registers ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< u16 >, u8 >;
+
+	///	Writes coils.
+	fn write_bits ( &mut self, address : u16, values : &[ bool ] ) -> Result< (), u8 >;
+
+	///	Writes holding registers.
+	fn write_registers ( &mut self, address : u16, values : &[ u16 ] ) -> Result< (), u8 >;
+}
+
+//	===============================================================================================
+
+/// The values written by a master.
+#[derive( Clone, Debug, PartialEq )]
+pub enum WriteValues
+{
+	Bits( Vec< bool > ),
+	Registers( Vec< u16 > )
+}
+
+/// A change of coils or holding registers by a master.
+#[derive( Clone, Debug, PartialEq )]
+pub struct WriteEvent
+{
+	table : DataTable,
+	address : u16,
+	values : WriteValues
+}
+
+impl WriteEvent
+{
+	pub fn new ( table : DataTable, address : u16, values : WriteValues ) -> WriteEvent
+	{
+		return WriteEvent
+		{
+			table : table,
+			address : address,
+			values : values
+		};
+	}
+
+	pub fn get_address ( &self ) -> u16
+	{
+		return self.address;
+	}
+
+	pub fn get_table ( &self ) -> DataTable
+	{
+		return self.table;
+	}
+
+	pub fn get_values ( &self ) -> &WriteValues
+	{
+		return &self.values;
+	}
+}
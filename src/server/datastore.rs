@@ -0,0 +1,487 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use core::consts::*;
+use core::datatable::DataTable;
+use server::datamodel::*;
+
+//	===============================================================================================
+
+const DATASTORE_MAXIMUM_SIZE : usize = 0x10000;
+
+//	===============================================================================================
+
+///	Called after a master changed coils or holding registers.
+pub type WriteCallback = Box< dyn Fn ( &WriteEvent ) + Send >;
+
+//	===============================================================================================
+
+/// The protection of an address range against writes of a master.
+#[derive( Clone, Copy, Debug, PartialEq )]
+pub enum Protection
+{
+	///	Writes answer with an illegal data address exception.
+	ReadOnly,
+	///	Writes answer with an illegal data value exception.
+	WriteProtected
+}
+
+impl Protection
+{
+	pub fn get_exception_code ( &self ) -> u8
+	{
+		let reply : u8;
+
+		match *self
+		{
+			Protection::ReadOnly		=> { reply = MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS; }
+			Protection::WriteProtected	=> { reply = MODBUS_EXCEPTION_ILLEGAL_DATA_VALUE; }
+		}
+
+		return reply;
+	}
+}
+
+/// Protects `quantity` addresses of a table starting at `address`.
+#[derive( Clone, Copy, Debug, PartialEq )]
+pub struct AccessRule
+{
+	table : DataTable,
+	address : u16,
+	quantity : u16,
+	protection : Protection
+}
+
+impl AccessRule
+{
+	pub fn new ( table : DataTable, address : u16, quantity : u16, protection : Protection ) -> AccessRule
+	{
+		return AccessRule
+		{
+			table : table,
+			address : address,
+			quantity : quantity,
+			protection : protection
+		};
+	}
+
+	pub fn get_protection ( &self ) -> Protection
+	{
+		return self.protection;
+	}
+
+	///	Returns true if the rule covers any of the addresses.
+	pub fn is_overlapping ( &self, table : DataTable, address : u16, quantity : usize ) -> bool
+	{
+		let rule_begin : usize = self.address as usize;
+		let rule_end : usize = rule_begin + self.quantity as usize;
+		let begin : usize = address as usize;
+
+		return self.table == table && begin < rule_end && rule_begin < begin + quantity;
+	}
+}
+
+//	===============================================================================================
+
+enum Bank< T >
+{
+	Dense( Vec< T > ),
+	Sparse( BTreeMap< u16, T > )
+}
+
+impl< T : Copy + Default > Bank< T >
+{
+	fn new ( size : Option< usize > ) -> Bank< T >
+	{
+		let reply : Bank< T >;
+
+		match size
+		{
+			Some( size )	=> { reply = Bank::Dense( vec![ T::default (); size.min ( DATASTORE_MAXIMUM_SIZE ) ] ); }
+			None			=> { reply = Bank::Sparse( BTreeMap::new () ); }
+		}
+
+		return reply;
+	}
+
+	fn get ( &self, address : u16, quantity : u16 ) -> Option< Vec< T > >
+	{
+		let begin : usize = address as usize;
+		let end : usize = begin + quantity as usize;
+
+		if end > DATASTORE_MAXIMUM_SIZE
+		{
+			return None;
+		}
+
+		match *self
+		{
+			Bank::Dense( ref values )	=> { return values.get ( begin..end ).map ( | slice | slice.to_vec () ); }
+			Bank::Sparse( ref values )	=> { return ( begin..end ).map ( | index | values.get ( &( index as u16 ) ).cloned () )
+																   .collect (); }
+		}
+	}
+
+	///	Sets the values, sparse banks only accept defined addresses unless `define` is set.
+	fn set ( &mut self, address : u16, values : &[ T ], define : bool ) -> bool
+	{
+		let begin : usize = address as usize;
+		let end : usize = begin + values.len ();
+
+		if end > DATASTORE_MAXIMUM_SIZE
+		{
+			return false;
+		}
+
+		match *self
+		{
+			Bank::Dense( ref mut bank )		=>
+			{
+				if end > bank.len ()
+				{
+					return false;
+				}
+
+				bank[ begin..end ].copy_from_slice ( values );
+			}
+			Bank::Sparse( ref mut bank )	=>
+			{
+				if !define && ( begin..end ).any ( | index | !bank.contains_key ( &( index as u16 ) ) )
+				{
+					return false;
+				}
+
+				for ( index, value ) in values.iter ().enumerate ()
+				{
+					bank.insert ( ( begin + index ) as u16,
+								  *value );
+				}
+			}
+		}
+
+		return true;
+	}
+}
+
+struct DataBanks
+{
+	coils : Bank< bool >,
+	discrete_inputs : Bank< bool >,
+	holding_registers : Bank< u16 >,
+	input_registers : Bank< u16 >,
+	rules : Vec< AccessRule >
+}
+
+impl DataBanks
+{
+	fn get_bit_bank ( &mut self, table : DataTable ) -> Option< &mut Bank< bool > >
+	{
+		match table
+		{
+			DataTable::Coils			=> { return Some( &mut self.coils ); }
+			DataTable::DiscreteInputs	=> { return Some( &mut self.discrete_inputs ); }
+			_							=> { return None; }
+		}
+	}
+
+	fn get_register_bank ( &mut self, table : DataTable ) -> Option< &mut Bank< u16 > >
+	{
+		match table
+		{
+			DataTable::HoldingRegisters	=> { return Some( &mut self.holding_registers ); }
+			DataTable::InputRegisters	=> { return Some( &mut self.input_registers ); }
+			_							=> { return None; }
+		}
+	}
+
+	///	Returns the exception code of the first rule protecting the addresses.
+	fn verify_write ( &self, table : DataTable, address : u16, quantity : usize ) -> Result< (), u8 >
+	{
+		match self.rules.iter ().find ( | rule | rule.is_overlapping ( table, address, quantity ) )
+		{
+			Some( rule )	=> { return Err( rule.get_protection ().get_exception_code () ); }
+			None			=> { return Ok( () ); }
+		}
+	}
+}
+
+//	===============================================================================================
+
+/// The coils, discrete inputs, holding registers and input registers of a server.
+/// Clones share the data, so the application keeps a clone to update inputs and
+/// to read setpoints while the server answers requests on other threads.
+///
+/// Dense stores hold every address up to their size, sparse stores only the addresses
+/// set by the application; all others answer with an illegal data address exception.
+/// Writes of a master are checked against the access rules and reported to the
+/// write callbacks, writes of the application are not.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::core::datatable::DataTable;
+/// use modbus_iiot::server::datastore::{AccessRule, DataStore, Protection};
+/// use modbus_iiot::server::requesthandler::process_request;
+///
+/// let store = DataStore::new_dense(100);
+/// store.add_rule(AccessRule::new(DataTable::HoldingRegisters, 0, 10, Protection::ReadOnly));
+/// store.add_write_callback(Box::new(| event | println!("{} {} changed", event.get_table(), event.get_address())));
+///
+/// let mut model = store.clone();
+/// assert_eq!(process_request(&mut model, &[ 0x06, 0x00, 0x0A, 0x00, 0x2A ]), vec![ 0x06, 0x00, 0x0A, 0x00, 0x2A ]);
+/// assert_eq!(process_request(&mut model, &[ 0x06, 0x00, 0x01, 0x00, 0x2A ]), vec![ 0x86, 0x02 ]);
+/// assert_eq!(store.get_registers(DataTable::HoldingRegisters, 10, 1), Some(vec![ 42 ]));
+/// ```
+#[derive( Clone )]
+pub struct DataStore
+{
+	banks : Arc< Mutex< DataBanks > >,
+	callbacks : Arc< Mutex< Vec< WriteCallback > > >
+}
+
+impl DataStore
+{
+	///	Creates a store with `size` addresses in each table, all set to 0.
+	pub fn new_dense ( size : usize ) -> DataStore
+	{
+		return Self::new_with_size ( Some( size ) );
+	}
+
+	///	Creates a store without any defined address.
+	pub fn new_sparse () -> DataStore
+	{
+		return Self::new_with_size ( None );
+	}
+
+	fn new_with_size ( size : Option< usize > ) -> DataStore
+	{
+		return DataStore
+		{
+			banks : Arc::new ( Mutex::new ( DataBanks
+											{
+												coils : Bank::new ( size ),
+												discrete_inputs : Bank::new ( size ),
+												holding_registers : Bank::new ( size ),
+												input_registers : Bank::new ( size ),
+												rules : vec![]
+											} ) ),
+			callbacks : Arc::new ( Mutex::new ( vec![] ) )
+		};
+	}
+
+	///	Adds a callback for writes of a master.
+	pub fn add_write_callback ( &self, callback : WriteCallback )
+	{
+		lock ( &self.callbacks ).push ( callback );
+	}
+
+	///	Adds a rule protecting an address range against writes of a master.
+	pub fn add_rule ( &self, rule : AccessRule )
+	{
+		lock ( &self.banks ).rules.push ( rule );
+	}
+
+	///	Returns coils or discrete inputs, `None` if an address is not defined.
+	pub fn get_bits ( &self, table : DataTable, address : u16, quantity : u16 ) -> Option< Vec< bool > >
+	{
+		return lock ( &self.banks ).get_bit_bank ( table )?.get ( address,
+																  quantity );
+	}
+
+	///	Returns holding or input registers, `None` if an address is not defined.
+	pub fn get_registers ( &self, table : DataTable, address : u16, quantity : u16 ) -> Option< Vec< u16 > >
+	{
+		return lock ( &self.banks ).get_register_bank ( table )?.get ( address,
+																	   quantity );
+	}
+
+	///	Sets coils or discrete inputs, sparse stores define missing addresses.
+	pub fn set_bits ( &self, table : DataTable, address : u16, values : &[ bool ] ) -> Result< (), String >
+	{
+		let mut banks : MutexGuard< '_, DataBanks > = lock ( &self.banks );
+
+		match banks.get_bit_bank ( table )
+		{
+			Some( bank )	=> { return verify_set ( bank.set ( address, values, true ), address, values.len () ); }
+			None			=> { return Err( format! ( "{} has no bits", table ) ); }
+		}
+	}
+
+	///	Sets holding or input registers, sparse stores define missing addresses.
+	pub fn set_registers ( &self, table : DataTable, address : u16, values : &[ u16 ] ) -> Result< (), String >
+	{
+		let mut banks : MutexGuard< '_, DataBanks > = lock ( &self.banks );
+
+		match banks.get_register_bank ( table )
+		{
+			Some( bank )	=> { return verify_set ( bank.set ( address, values, true ), address, values.len () ); }
+			None			=> { return Err( format! ( "{} has no registers", table ) ); }
+		}
+	}
+
+	fn notify ( &self, event : WriteEvent )
+	{
+		for callback in lock ( &self.callbacks ).iter ()
+		{
+			callback ( &event );
+		}
+	}
+}
+
+impl DataModel for DataStore
+{
+	fn read_bits ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< bool >, u8 >
+	{
+		return self.get_bits ( table, address, quantity ).ok_or ( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS );
+	}
+
+	fn read_registers ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< u16 >, u8 >
+	{
+		return self.get_registers ( table, address, quantity ).ok_or ( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS );
+	}
+
+	fn write_bits ( &mut self, address : u16, values : &[ bool ] ) -> Result< (), u8 >
+	{
+		{
+			let mut banks : MutexGuard< '_, DataBanks > = lock ( &self.banks );
+
+			banks.verify_write ( DataTable::Coils, address, values.len () )?;
+
+			if !banks.coils.set ( address, values, false )
+			{
+				return Err( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS );
+			}
+		}
+
+		self.notify ( WriteEvent::new ( DataTable::Coils,
+										address,
+										WriteValues::Bits( values.to_vec () ) ) );
+
+		return Ok( () );
+	}
+
+	fn write_registers ( &mut self, address : u16, values : &[ u16 ] ) -> Result< (), u8 >
+	{
+		{
+			let mut banks : MutexGuard< '_, DataBanks > = lock ( &self.banks );
+
+			banks.verify_write ( DataTable::HoldingRegisters, address, values.len () )?;
+
+			if !banks.holding_registers.set ( address, values, false )
+			{
+				return Err( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS );
+			}
+		}
+
+		self.notify ( WriteEvent::new ( DataTable::HoldingRegisters,
+										address,
+										WriteValues::Registers( values.to_vec () ) ) );
+
+		return Ok( () );
+	}
+}
+
+//	===============================================================================================
+
+fn lock< T > ( mutex : &Mutex< T > ) -> MutexGuard< '_, T >
+{
+	return mutex.lock ().unwrap_or_else ( | error | error.into_inner () );
+}
+
+fn verify_set ( is_set : bool, address : u16, quantity : usize ) -> Result< (), String >
+{
+	if is_set
+	{
+		return Ok( () );
+	}
+
+	return Err( format! ( "addresses {} to {} are out of the table", address, address as usize + quantity - 1 ) );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use server::requesthandler::process_request;
+
+#[test]
+fn test_data_store_dense ()
+{
+	let store : DataStore = DataStore::new_dense ( 20 );
+	let mut model : DataStore = store.clone ();
+
+	store.set_bits ( DataTable::DiscreteInputs, 18, &[ true, true ] ).unwrap ();
+	store.set_registers ( DataTable::InputRegisters, 0, &[ 0x1234 ] ).unwrap ();
+
+	assert_eq! ( process_request ( &mut model, &[ 0x02, 0x00, 0x11, 0x00, 0x03 ] ), vec![ 0x02, 0x01, 0x06 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x04, 0x00, 0x00, 0x00, 0x01 ] ), vec![ 0x04, 0x02, 0x12, 0x34 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x04, 0x00, 0x13, 0x00, 0x02 ] ), vec![ 0x84, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x0F, 0x00, 0x00, 0x00, 0x09, 0x02, 0xFF, 0x01 ] ), vec![ 0x0F, 0x00, 0x00, 0x00, 0x09 ] );
+	assert_eq! ( store.get_bits ( DataTable::Coils, 7, 3 ), Some( vec![ true, true, false ] ) );
+
+	assert! ( store.set_registers ( DataTable::HoldingRegisters, 19, &[ 1, 2 ] ).is_err () );
+	assert! ( store.set_bits ( DataTable::InputRegisters, 0, &[ true ] ).is_err () );
+}
+
+#[test]
+fn test_data_store_sparse ()
+{
+	let store : DataStore = DataStore::new_sparse ();
+	let mut model : DataStore = store.clone ();
+
+	store.set_registers ( DataTable::HoldingRegisters, 40000, &[ 1, 2 ] ).unwrap ();
+
+	assert_eq! ( process_request ( &mut model, &[ 0x03, 0x9C, 0x40, 0x00, 0x02 ] ), vec![ 0x03, 0x04, 0x00, 0x01, 0x00, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x03, 0x9C, 0x40, 0x00, 0x03 ] ), vec![ 0x83, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x06, 0x9C, 0x41, 0x00, 0x07 ] ), vec![ 0x06, 0x9C, 0x41, 0x00, 0x07 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x06, 0x9C, 0x42, 0x00, 0x07 ] ), vec![ 0x86, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x01, 0x00, 0x00, 0x00, 0x01 ] ), vec![ 0x81, 0x02 ] );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 40000, 2 ), Some( vec![ 1, 7 ] ) );
+}
+
+#[test]
+fn test_data_store_end_of_address_range ()
+{
+	let store : DataStore = DataStore::new_sparse ();
+	let mut model : DataStore = store.clone ();
+
+	store.set_registers ( DataTable::HoldingRegisters, 0, &[ 9 ] ).unwrap ();
+	store.set_registers ( DataTable::HoldingRegisters, 65535, &[ 3 ] ).unwrap ();
+	assert! ( store.set_registers ( DataTable::HoldingRegisters, 65535, &[ 3, 4 ] ).is_err () );
+
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 65535, 1 ), Some( vec![ 3 ] ) );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 65535, 2 ), None );
+	assert_eq! ( process_request ( &mut model, &[ 0x03, 0xFF, 0xFF, 0x00, 0x02 ] ), vec![ 0x83, 0x02 ] );
+
+	let dense : DataStore = DataStore::new_dense ( 0x10000 );
+
+	assert_eq! ( dense.get_bits ( DataTable::Coils, 65535, 1 ), Some( vec![ false ] ) );
+	assert_eq! ( dense.get_bits ( DataTable::Coils, 65535, 2 ), None );
+}
+
+#[test]
+fn test_data_store_rules_and_callbacks ()
+{
+	let store : DataStore = DataStore::new_dense ( 100 );
+	let mut model : DataStore = store.clone ();
+	let events : Arc< Mutex< Vec< WriteEvent > > > = Arc::new ( Mutex::new ( vec![] ) );
+	let callback_events : Arc< Mutex< Vec< WriteEvent > > > = events.clone ();
+
+	store.add_rule ( AccessRule::new ( DataTable::HoldingRegisters, 10, 5, Protection::ReadOnly ) );
+	store.add_rule ( AccessRule::new ( DataTable::Coils, 0, 1, Protection::WriteProtected ) );
+	store.add_write_callback ( Box::new ( move | event | callback_events.lock ().unwrap ().push ( event.clone () ) ) );
+
+	assert_eq! ( process_request ( &mut model, &[ 0x10, 0x00, 0x08, 0x00, 0x03, 0x06, 0x00, 0x01, 0x00, 0x02, 0x00, 0x03 ] ), vec![ 0x90, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x16, 0x00, 0x0E, 0x00, 0x00, 0x00, 0x01 ] ), vec![ 0x96, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x05, 0x00, 0x00, 0xFF, 0x00 ] ), vec![ 0x85, 0x03 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x03, 0x00, 0x0A, 0x00, 0x01 ] ), vec![ 0x03, 0x02, 0x00, 0x00 ] );
+	assert! ( events.lock ().unwrap ().is_empty () );
+
+	store.set_registers ( DataTable::HoldingRegisters, 10, &[ 5 ] ).unwrap ();
+	assert_eq! ( process_request ( &mut model, &[ 0x10, 0x00, 0x0F, 0x00, 0x02, 0x04, 0x00, 0x01, 0x00, 0x02 ] ), vec![ 0x10, 0x00, 0x0F, 0x00, 0x02 ] );
+	assert_eq! ( process_request ( &mut model, &[ 0x05, 0x00, 0x01, 0xFF, 0x00 ] ), vec![ 0x05, 0x00, 0x01, 0xFF, 0x00 ] );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 10, 1 ), Some( vec![ 5 ] ) );
+
+	assert_eq! ( *events.lock ().unwrap (),
+				 vec![ WriteEvent::new ( DataTable::HoldingRegisters, 15, WriteValues::Registers( vec![ 1, 2 ] ) ),
+					   WriteEvent::new ( DataTable::Coils, 1, WriteValues::Bits( vec![ true ] ) ) ] );
+}
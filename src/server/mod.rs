@@ -0,0 +1,7 @@
+
+
+pub mod datamodel;
+pub mod datastore;
+pub mod requesthandler;
+pub mod tcpproxy;
+pub mod tcpserver;
@@ -0,0 +1,121 @@
+use core::consts::*;
+use core::datatable::DataTable;
+use core::pdu::*;
+use server::datamodel::DataModel;
+
+//	===============================================================================================
+
+///	Processes a request PDU (function code and data) with the data model and returns
+/// the response PDU. Supports the function codes 1, 2, 3, 4, 5, 6, 15, 16, 22 and 23,
+/// all others are answered with an illegal function exception.
+pub fn process_request< M : DataModel > ( model : &mut M, pdu : &[ u8 ] ) -> Vec< u8 >
+{
+	let function_code : u8 = pdu.first ().cloned ().unwrap_or ( 0x00 );
+
+	let reply : Result< Vec< u8 >, u8 >;
+
+	match RequestPdu::decode ( pdu )
+	{
+		Ok( request )							=> { reply = process_request_pdu ( model, &request ); }
+		Err( _ ) if pdu.is_empty ()				=> { reply = Err( MODBUS_EXCEPTION_ILLEGAL_FUNCTION ); }
+		Err( PduError::AddressOverflow { .. } )	=> { reply = Err( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS ); }
+		Err( _ )								=> { reply = Err( MODBUS_EXCEPTION_ILLEGAL_DATA_VALUE ); }
+	}
+
+	match reply
+	{
+		Ok( response )			=> { return response; }
+		Err( exception_code )	=> { return create_exception_response ( function_code, exception_code ); }
+	}
+}
+
+///	Processes a decoded request with the data model and returns the encoded response PDU or the exception code.
+pub fn process_request_pdu< M : DataModel > ( model : &mut M, request : &RequestPdu ) -> Result< Vec< u8 >, u8 >
+{
+	match *request
+	{
+		RequestPdu::ReadCoils { address, quantity }				=>
+		{
+			let values : Vec< bool > = model.read_bits ( DataTable::Coils, address, quantity )?;
+
+			return encode_response ( ResponsePdu::ReadCoils { values : Coils::Unpacked( &values ) } );
+		}
+		RequestPdu::ReadDiscreteInputs { address, quantity }	=>
+		{
+			let values : Vec< bool > = model.read_bits ( DataTable::DiscreteInputs, address, quantity )?;
+
+			return encode_response ( ResponsePdu::ReadDiscreteInputs { values : Coils::Unpacked( &values ) } );
+		}
+		RequestPdu::ReadHoldingRegisters { address, quantity }	=>
+		{
+			let values : Vec< u16 > = model.read_registers ( DataTable::HoldingRegisters, address, quantity )?;
+
+			return encode_response ( ResponsePdu::ReadHoldingRegisters { values : Registers::Words( &values ) } );
+		}
+		RequestPdu::ReadInputRegisters { address, quantity }	=>
+		{
+			let values : Vec< u16 > = model.read_registers ( DataTable::InputRegisters, address, quantity )?;
+
+			return encode_response ( ResponsePdu::ReadInputRegisters { values : Registers::Words( &values ) } );
+		}
+		RequestPdu::WriteSingleCoil { address, value }			=>
+		{
+			model.write_bits ( address, &[ value ] )?;
+
+			return encode_response ( ResponsePdu::WriteSingleCoil { address : address, value : value } );
+		}
+		RequestPdu::WriteSingleRegister { address, value }		=>
+		{
+			model.write_registers ( address, &[ value ] )?;
+
+			return encode_response ( ResponsePdu::WriteSingleRegister { address : address, value : value } );
+		}
+		RequestPdu::WriteMultipleCoils { address, values }		=>
+		{
+			model.write_bits ( address, &values.to_vec () )?;
+
+			return encode_response ( ResponsePdu::WriteMultipleCoils { address : address, quantity : values.len () as u16 } );
+		}
+		RequestPdu::WriteMultipleRegisters { address, values }	=>
+		{
+			model.write_registers ( address, &values.to_vec () )?;
+
+			return encode_response ( ResponsePdu::WriteMultipleRegisters { address : address, quantity : values.len () as u16 } );
+		}
+		RequestPdu::MaskWriteRegister { address, and_mask, or_mask }	=>
+		{
+			let current_value : u16 = model.read_registers ( DataTable::HoldingRegisters, address, 1 )?[ 0 ];
+
+			model.write_registers ( address, &[ ( current_value & and_mask ) | ( or_mask & !and_mask ) ] )?;
+
+			return encode_response ( ResponsePdu::MaskWriteRegister { address : address, and_mask : and_mask, or_mask : or_mask } );
+		}
+		RequestPdu::ReadWriteMultipleRegisters { read_address, read_quantity, write_address, values }	=>
+		{
+			model.write_registers ( write_address, &values.to_vec () )?;
+
+			let values : Vec< u16 > = model.read_registers ( DataTable::HoldingRegisters, read_address, read_quantity )?;
+
+			return encode_response ( ResponsePdu::ReadWriteMultipleRegisters { values : Registers::Words( &values ) } );
+		}
+		_														=>
+		{
+			return Err( MODBUS_EXCEPTION_ILLEGAL_FUNCTION );
+		}
+	}
+}
+
+//	===============================================================================================
+
+///	Creates the PDU of an exception response.
+pub fn create_exception_response ( function_code : u8, exception_code : u8 ) -> Vec< u8 >
+{
+	return vec![ function_code | MODBUS_EXCEPTION_FLAG, exception_code ];
+}
+
+//	===============================================================================================
+
+fn encode_response ( response : ResponsePdu ) -> Result< Vec< u8 >, u8 >
+{
+	return response.to_bytes ().map_err ( | _ | MODBUS_EXCEPTION_SERVER_DEVICE_FAILURE );
+}
@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use core::consts::*;
+use core::datatransformation::transform_bytes_to_word;
+use server::requesthandler::create_exception_response;
+use server::tcpserver::*;
+use tcp::master::TcpClient;
+
+//	===============================================================================================
+
+type ProxyResponse = Result< Vec< u8 >, String >;
+
+struct ProxyRequest
+{
+	unit_identifier : u8,
+	pdu : Vec< u8 >,
+	response : Sender< ProxyResponse >
+}
+
+struct CachedResponse
+{
+	time : Instant,
+	pdu : Vec< u8 >
+}
+
+///	The generation counts the writes, responses of reads sent
+/// before a write are not cached afterwards.
+struct ResponseCache
+{
+	time_to_live : Duration,
+	generation : u64,
+	responses : HashMap< ( u8, Vec< u8 > ), CachedResponse >
+}
+
+impl ResponseCache
+{
+	fn get ( &mut self, unit_identifier : u8, pdu : &[ u8 ] ) -> Option< Vec< u8 > >
+	{
+		let time_to_live : Duration = self.time_to_live;
+
+		self.responses.retain ( | _, response | response.time.elapsed () < time_to_live );
+
+		return self.responses.get ( &( unit_identifier, pdu.to_vec () ) ).map ( | response | response.pdu.clone () );
+	}
+
+	fn insert ( &mut self, generation : u64, unit_identifier : u8, pdu : &[ u8 ], response : &[ u8 ] )
+	{
+		if generation != self.generation
+		{
+			return;
+		}
+
+		self.responses.insert ( ( unit_identifier, pdu.to_vec () ),
+								CachedResponse
+								{
+									time : Instant::now (),
+									pdu : response.to_vec ()
+								} );
+	}
+
+	fn invalidate ( &mut self )
+	{
+		self.generation += 1;
+		self.responses.clear ();
+	}
+}
+
+//	===============================================================================================
+
+/// A MODBUS TCP proxy, which lets many clients share the one connection of a
+/// `TcpClient` to a device. The requests of all clients are sent one after the
+/// other in the order of their arrival, each with a transaction identifier of
+/// the upstream connection; responses get back the identifier of the client.
+/// Failed requests answer with exception 0x0B and reconnect the upstream client.
+///
+/// With a cache, responses of reads are reused for the same request until their
+/// time to live is over. Every write clears the cache before and after it is sent.
+/// Frames of other protocols than MODBUS are not answered.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::time::Duration;
+/// use modbus_iiot::server::tcpproxy::TcpProxy;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let mut upstream = TcpClient::new("192.168.0.10");
+/// upstream.connect().unwrap();
+///
+/// let _proxy = TcpProxy::start_with_cache("0.0.0.0:502", upstream, Duration::from_millis(500)).unwrap();
+/// ```
+pub struct TcpProxy
+{
+	server : TcpServer
+}
+
+impl TcpProxy
+{
+	///	Starts the proxy on the address, e.g. `0.0.0.0:502`.
+	pub fn start ( address : &str, upstream : TcpClient ) -> Result< TcpProxy, String >
+	{
+		return Self::start_with_cache_option ( address,
+											   upstream,
+											   None );
+	}
+
+	///	Starts the proxy with a cache for read responses.
+	pub fn start_with_cache ( address : &str, upstream : TcpClient, time_to_live : Duration ) -> Result< TcpProxy, String >
+	{
+		return Self::start_with_cache_option ( address,
+											   upstream,
+											   Some( time_to_live ) );
+	}
+
+	fn start_with_cache_option ( address : &str, upstream : TcpClient, time_to_live : Option< Duration > ) -> Result< TcpProxy, String >
+	{
+		let ( sender, receiver ) : ( Sender< ProxyRequest >, Receiver< ProxyRequest > ) = channel ();
+		let cache : Option< Arc< Mutex< ResponseCache > > > = time_to_live.map ( | time_to_live | Arc::new ( Mutex::new ( ResponseCache
+																														   {
+																															   time_to_live : time_to_live,
+																															   generation : 0,
+																															   responses : HashMap::new ()
+																														   } ) ) );
+
+		let server : TcpServer = TcpServer::start_with_frame_handler ( address,
+																	   move | frame : &[ u8 ] | process_frame ( frame,
+																												 &sender,
+																												 &cache ) )?;
+
+		thread::spawn ( move || forward_requests ( upstream,
+												   receiver ) );
+
+		return Ok( TcpProxy
+		{
+			server : server
+		} );
+	}
+
+	///	Returns the address the proxy listens on.
+	pub fn get_local_address ( &self ) -> SocketAddr
+	{
+		return self.server.get_local_address ();
+	}
+
+	///	Stops accepting and closes all client connections. The upstream
+	/// client is dropped after its last request.
+	pub fn stop ( &mut self )
+	{
+		self.server.stop ();
+	}
+}
+
+//	===============================================================================================
+
+fn lock_cache ( cache : &Mutex< ResponseCache > ) -> MutexGuard< '_, ResponseCache >
+{
+	return cache.lock ().unwrap_or_else ( | error | error.into_inner () );
+}
+
+fn is_read_function_code ( function_code : u8 ) -> bool
+{
+	return ( FUNCTION_CODE_READ_COILS..=FUNCTION_CODE_READ_INPUT_REGISTERS ).contains ( &function_code );
+}
+
+fn process_frame ( frame : &[ u8 ], sender : &Sender< ProxyRequest >, cache : &Option< Arc< Mutex< ResponseCache > > > ) -> Option< Vec< u8 > >
+{
+	if frame.len () <= MODBUS_HEADER_SIZE as usize || transform_bytes_to_word ( frame, 2 ) != MODBUS_PROTOCOL_IDENTIFIER_TCP
+	{
+		return None;
+	}
+
+	let unit_identifier : u8 = frame[ 6 ];
+	let pdu : &[ u8 ] = &frame[ MODBUS_HEADER_SIZE as usize.. ];
+	let is_read : bool = is_read_function_code ( pdu[ 0 ] );
+	let mut generation : u64 = 0;
+
+	if let Some( ref cache ) = *cache
+	{
+		let mut locked_cache : MutexGuard< ResponseCache > = lock_cache ( cache );
+
+		if !is_read
+		{
+			locked_cache.invalidate ();
+		}
+		else if let Some( response ) = locked_cache.get ( unit_identifier, pdu )
+		{
+			return Some( create_response_frame ( frame, &response ) );
+		}
+
+		generation = locked_cache.generation;
+	}
+
+	let ( response_sender, response_receiver ) : ( Sender< ProxyResponse >, Receiver< ProxyResponse > ) = channel ();
+	let request : ProxyRequest = ProxyRequest
+	{
+		unit_identifier : unit_identifier,
+		pdu : pdu.to_vec (),
+		response : response_sender
+	};
+
+	let response_pdu : Vec< u8 >;
+
+	match sender.send ( request ).map_err ( | error | error.to_string () ).and_then ( | _ | response_receiver.recv ().map_err ( | error | error.to_string () ) )
+	{
+		Ok( Ok( response ) )	=>
+		{
+			if let Some( ref cache ) = *cache
+			{
+				if !is_read
+				{
+					lock_cache ( cache ).invalidate ();
+				}
+				else if response[ 0 ] & MODBUS_EXCEPTION_FLAG == 0
+				{
+					lock_cache ( cache ).insert ( generation, unit_identifier, pdu, &response );
+				}
+			}
+
+			response_pdu = response;
+		}
+		_						=>
+		{
+			response_pdu = create_exception_response ( pdu[ 0 ],
+													   MODBUS_EXCEPTION_GATEWAY_TARGET_FAILED_TO_RESPOND );
+		}
+	}
+
+	return Some( create_response_frame ( frame,
+										 &response_pdu ) );
+}
+
+///	Sends the requests of all clients in the order of their arrival. A client waits
+/// for its response before it sends the next request, so no client is left behind.
+fn forward_requests ( mut upstream : TcpClient, receiver : Receiver< ProxyRequest > )
+{
+	for request in receiver.iter ()
+	{
+		let response : ProxyResponse = upstream.send_pdu ( request.unit_identifier,
+																		  &request.pdu );
+
+		if response.is_err ()
+		{
+			upstream.disconnect ();
+			let _ = upstream.connect ();
+		}
+
+		let _ = request.response.send ( response );
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use std::io::{Read, Write};
+#[cfg( test )]
+use std::net::TcpStream;
+#[cfg( test )]
+use sim::device::SimDevice;
+#[cfg( test )]
+use sim::fault::{Fault, FaultAction};
+#[cfg( test )]
+use sim::simulator::Simulator;
+#[cfg( test )]
+use tcp::masteraccess::*;
+
+#[cfg( test )]
+fn start_test_proxy ( time_to_live : Option< Duration > ) -> ( Simulator, TcpProxy )
+{
+	let simulator : Simulator = Simulator::start ( SimDevice::new_with_size ( 100 ) ).unwrap ();
+	let mut upstream : TcpClient = simulator.create_client ();
+
+	upstream.connect ().unwrap ();
+
+	let proxy : TcpProxy = TcpProxy::start_with_cache_option ( "127.0.0.1:0", upstream, time_to_live ).unwrap ();
+
+	return ( simulator, proxy );
+}
+
+#[cfg( test )]
+fn create_proxy_client ( proxy : &TcpProxy ) -> TcpClient
+{
+	let mut client : TcpClient = TcpClient::new_with_port ( "127.0.0.1", proxy.get_local_address ().port () );
+
+	client.connect ().unwrap ();
+
+	return client;
+}
+
+#[test]
+fn test_tcp_proxy_many_clients ()
+{
+	let ( simulator, proxy ) = start_test_proxy ( None );
+
+	simulator.get_device ().set_registers ( DataTable::InputRegisters, 0, &[ 1, 2, 3 ] ).unwrap ();
+
+	let threads : Vec< thread::JoinHandle< () > > = ( 0..4 ).map ( | index |
+	{
+		let mut client : TcpClient = create_proxy_client ( &proxy );
+
+		thread::spawn ( move ||
+		{
+			for _ in 0..10
+			{
+				assert_eq! ( client.read_input_registers ( 0, 3 ), vec![ 1, 2, 3 ] );
+				assert! ( client.write_single_register ( index, index + 10 ) );
+			}
+
+			client.disconnect ();
+		} )
+	} ).collect ();
+
+	for thread in threads
+	{
+		thread.join ().unwrap ();
+	}
+
+	assert_eq! ( simulator.get_device ().get_registers ( DataTable::HoldingRegisters, 0, 4 ), Some( vec![ 10, 11, 12, 13 ] ) );
+	assert_eq! ( simulator.get_request_count (), 80 );
+}
+
+#[test]
+fn test_tcp_proxy_cache ()
+{
+	let ( simulator, proxy ) = start_test_proxy ( Some( Duration::from_millis ( 300 ) ) );
+	let mut client : TcpClient = create_proxy_client ( &proxy );
+
+	simulator.get_device ().set_registers ( DataTable::HoldingRegisters, 0, &[ 1 ] ).unwrap ();
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 1 ] );
+
+	simulator.get_device ().set_registers ( DataTable::HoldingRegisters, 0, &[ 2 ] ).unwrap ();
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 1 ] );
+	assert! ( client.read_holding_registers ( 99, 2 ).is_empty () );
+	assert! ( client.read_holding_registers ( 99, 2 ).is_empty () );
+	assert_eq! ( simulator.get_request_count (), 3 );
+
+	assert! ( client.write_single_register ( 1, 5 ) );
+	assert_eq! ( client.read_holding_registers ( 0, 2 ), vec![ 2, 5 ] );
+
+	thread::sleep ( Duration::from_millis ( 400 ) );
+	simulator.get_device ().set_registers ( DataTable::HoldingRegisters, 0, &[ 3 ] ).unwrap ();
+	assert_eq! ( client.read_holding_registers ( 0, 2 ), vec![ 3, 5 ] );
+	assert_eq! ( simulator.get_request_count (), 6 );
+
+	client.disconnect ();
+}
+
+#[test]
+fn test_tcp_proxy_cache_with_concurrent_write ()
+{
+	let ( simulator, proxy ) = start_test_proxy ( Some( Duration::from_secs ( 10 ) ) );
+	let mut reading_client : TcpClient = create_proxy_client ( &proxy );
+	let mut writing_client : TcpClient = create_proxy_client ( &proxy );
+
+	simulator.get_device ().set_registers ( DataTable::HoldingRegisters, 0, &[ 1 ] ).unwrap ();
+
+	let mut slow_read : Fault = Fault::new_for_function_code ( FaultAction::Delay( Duration::from_millis ( 300 ) ), FUNCTION_CODE_READ_HOLDING_REGISTERS );
+	slow_read.set_count ( 1 );
+	simulator.add_fault ( slow_read );
+
+	let reader : thread::JoinHandle< Vec< u16 > > = thread::spawn ( move || reading_client.read_holding_registers ( 0, 1 ) );
+
+	// the write is sent after the slow read, whose response arrives after the write cleared the cache
+	thread::sleep ( Duration::from_millis ( 100 ) );
+	assert! ( writing_client.write_single_register ( 0, 2 ) );
+	assert_eq! ( reader.join ().unwrap (), vec![ 1 ] );
+	assert_eq! ( writing_client.read_holding_registers ( 0, 1 ), vec![ 2 ] );
+	assert_eq! ( simulator.get_request_count (), 3 );
+
+	writing_client.disconnect ();
+}
+
+#[test]
+fn test_tcp_proxy_other_protocol ()
+{
+	let ( simulator, proxy ) = start_test_proxy ( None );
+	let mut stream : TcpStream = TcpStream::connect ( proxy.get_local_address () ).unwrap ();
+	let mut response : [ u8; 16 ] = [ 0; 16 ];
+
+	stream.set_read_timeout ( Some( Duration::from_millis ( 200 ) ) ).unwrap ();
+	stream.write_all ( &[ 0x00, 0x01, 0x00, 0x01, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x01 ] ).unwrap ();
+	assert! ( stream.read ( &mut response ).is_err () );
+
+	stream.write_all ( &[ 0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x01 ] ).unwrap ();
+	assert_eq! ( stream.read ( &mut response ).unwrap (), 11 );
+	assert_eq! ( &response[ ..2 ], &[ 0x00, 0x02 ] );
+	assert_eq! ( simulator.get_request_count (), 1 );
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use core::consts::*;
+use core::datatransformation::*;
+use server::datamodel::DataModel;
+use server::requesthandler::process_request;
+use tcp::streamtelegram::*;
+
+//	===============================================================================================
+
+const SERVER_ACCEPT_INTERVAL : u64 = 10;
+
+//	===============================================================================================
+
+/// A MODBUS TCP server. Each connection is served by its own thread,
+/// the server stops when it is dropped.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::core::datatable::DataTable;
+/// use modbus_iiot::server::datastore::DataStore;
+/// use modbus_iiot::server::tcpserver::TcpServer;
+///
+/// let store = DataStore::new_dense(1000);
+/// let _server = TcpServer::start("0.0.0.0:502", store.clone()).unwrap();
+///
+/// store.set_registers(DataTable::InputRegisters, 0, &[ 230 ]).unwrap();
+/// ```
+pub struct TcpServer
+{
+	address : SocketAddr,
+	running : Arc< AtomicBool >,
+	connections : Arc< Mutex< HashMap< u64, TcpStream > > >,
+	thread : Option< JoinHandle< () > >
+}
+
+impl TcpServer
+{
+	///	Starts serving the data model on the address, e.g. `0.0.0.0:502`.
+	/// Requests of all connections are processed one after the other.
+	pub fn start< M > ( address : &str, model : M ) -> Result< TcpServer, String >
+		where M : DataModel + Send + 'static
+	{
+		let shared_model : Arc< Mutex< M > > = Arc::new ( Mutex::new ( model ) );
+
+		return Self::start_with_frame_handler ( address,
+												move | frame : &[ u8 ] |
+												{
+													let pdu : Vec< u8 > = process_request ( &mut *shared_model.lock ().unwrap_or_else ( | error | error.into_inner () ),
+																						   &frame[ MODBUS_HEADER_SIZE as usize.. ] );
+
+													return Some( create_response_frame ( frame,
+																						 &pdu ) );
+												} );
+	}
+
+	///	Starts a server which answers each request frame (MBAP header and PDU)
+	/// with the frame returned by the handler, `None` sends no response.
+	pub fn start_with_frame_handler< F > ( address : &str, handler : F ) -> Result< TcpServer, String >
+		where F : Fn ( &[ u8 ] ) -> Option< Vec< u8 > > + Clone + Send + 'static
+	{
+		return Self::start_with_connection_handler ( address,
+													 move | mut stream : TcpStream | serve_frames ( &mut stream,
+																									 &handler ) );
+	}
+
+	///	Starts a server which passes each accepted connection to the handler
+	/// on its own thread, the connection ends when the handler returns.
+	pub fn start_with_connection_handler< F > ( address : &str, handler : F ) -> Result< TcpServer, String >
+		where F : Fn ( TcpStream ) + Clone + Send + 'static
+	{
+		let listener : TcpListener = TcpListener::bind ( address ).map_err ( | error | error.to_string () )?;
+		let local_address : SocketAddr = listener.local_addr ().map_err ( | error | error.to_string () )?;
+
+		listener.set_nonblocking ( true ).map_err ( | error | error.to_string () )?;
+
+		let running : Arc< AtomicBool > = Arc::new ( AtomicBool::new ( true ) );
+		let connections : Arc< Mutex< HashMap< u64, TcpStream > > > = Arc::new ( Mutex::new ( HashMap::new () ) );
+
+		let thread_running : Arc< AtomicBool > = running.clone ();
+		let thread_connections : Arc< Mutex< HashMap< u64, TcpStream > > > = connections.clone ();
+		let thread : JoinHandle< () > = thread::spawn ( move || accept_connections ( listener,
+																					 thread_running,
+																					 thread_connections,
+																					 handler ) );
+
+		return Ok( TcpServer
+		{
+			address : local_address,
+			running : running,
+			connections : connections,
+			thread : Some( thread )
+		} );
+	}
+
+	///	Returns the address the server listens on, useful with port 0.
+	pub fn get_local_address ( &self ) -> SocketAddr
+	{
+		return self.address;
+	}
+
+	///	Stops accepting and closes all connections.
+	pub fn stop ( &mut self )
+	{
+		self.running.store ( false,
+							 Ordering::SeqCst );
+
+		if let Some( thread ) = self.thread.take ()
+		{
+			let _ = thread.join ();
+		}
+
+		for ( _, connection ) in self.connections.lock ().unwrap_or_else ( | error | error.into_inner () ).drain ()
+		{
+			let _ = connection.shutdown ( Shutdown::Both );
+		}
+	}
+}
+
+impl Drop for TcpServer
+{
+	fn drop ( &mut self )
+	{
+		self.stop ();
+	}
+}
+
+//	===============================================================================================
+
+///	Creates the response frame to a request frame with the response PDU.
+pub fn create_response_frame ( request : &[ u8 ], pdu : &[ u8 ] ) -> Vec< u8 >
+{
+	return create_frame ( transform_bytes_to_word ( &request[ 0..2 ], 0 ),
+						  request[ 6 ],
+						  pdu );
+}
+
+//	===============================================================================================
+
+fn accept_connections< F > ( listener : TcpListener, running : Arc< AtomicBool >, connections : Arc< Mutex< HashMap< u64, TcpStream > > >, handler : F )
+	where F : Fn ( TcpStream ) + Clone + Send + 'static
+{
+	let mut connection_id : u64 = 0;
+
+	while running.load ( Ordering::SeqCst )
+	{
+		match listener.accept ()
+		{
+			Ok( ( stream, _ ) ) =>
+			{
+				let _ = stream.set_nonblocking ( false );
+				let _ = stream.set_nodelay ( true );
+
+				connection_id += 1;
+
+				if let Ok( connection ) = stream.try_clone ()
+				{
+					connections.lock ().unwrap_or_else ( | error | error.into_inner () ).insert ( connection_id, connection );
+				}
+
+				let connection_handler : F = handler.clone ();
+				let handler_connections : Arc< Mutex< HashMap< u64, TcpStream > > > = connections.clone ();
+				let handler_connection_id : u64 = connection_id;
+
+				//	the clone kept for `stop` is closed with the connection
+				thread::spawn ( move ||
+				{
+					connection_handler ( stream );
+					handler_connections.lock ().unwrap_or_else ( | error | error.into_inner () ).remove ( &handler_connection_id );
+				} );
+			}
+			Err( _ ) =>
+			{
+				thread::sleep ( Duration::from_millis ( SERVER_ACCEPT_INTERVAL ) );
+			}
+		}
+	}
+}
+
+///	Answers the request frames of a connection until it is closed.
+pub fn serve_frames< T, F > ( stream : &mut T, handler : &F )
+	where T : Read + Write, F : Fn ( &[ u8 ] ) -> Option< Vec< u8 > >
+{
+	while let Ok( frame ) = read_frame_from_stream ( stream )
+	{
+		if let Some( response ) = handler ( &frame )
+		{
+			if stream.write_all ( &response ).is_err ()
+			{
+				break;
+			}
+		}
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use core::pdu::*;
+#[cfg( test )]
+use server::datastore::DataStore;
+#[cfg( test )]
+use tcp::master::TcpClient;
+#[cfg( test )]
+use tcp::masteraccess::*;
+
+#[test]
+fn test_tcp_server ()
+{
+	let store : DataStore = DataStore::new_dense ( 100 );
+	let server : TcpServer = TcpServer::start ( "127.0.0.1:0", store.clone () ).unwrap ();
+	let mut client : TcpClient = TcpClient::new_with_port ( "127.0.0.1", server.get_local_address ().port () );
+
+	client.connect ().unwrap ();
+	store.set_registers ( DataTable::InputRegisters, 5, &[ 7, 8 ] ).unwrap ();
+
+	assert_eq! ( client.read_input_registers ( 5, 2 ), vec![ 7, 8 ] );
+	assert! ( client.write_multiple_registers ( 10, vec![ 1, 2, 3 ] ) );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 10, 3 ), Some( vec![ 1, 2, 3 ] ) );
+	assert! ( client.read_holding_registers ( 99, 2 ).is_empty () );
+
+	let mut buffer : [ u8; MODBUS_MAXIMUM_PDU_SIZE as usize ] = [ 0; MODBUS_MAXIMUM_PDU_SIZE as usize ];
+	let request : RequestPdu = RequestPdu::ReadWriteMultipleRegisters { read_address : 9, read_quantity : 3, write_address : 11, values : Registers::Words( &[ 5 ] ) };
+
+	match client.send_request ( 1, &request, &mut buffer )
+	{
+		Ok( ResponsePdu::ReadWriteMultipleRegisters { values } )	=> { assert_eq! ( values.to_vec (), vec![ 0, 1, 5 ] ); }
+		other														=> { panic! ( "unexpected response {:?}", other ); }
+	}
+
+	client.disconnect ();
+}
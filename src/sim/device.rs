@@ -0,0 +1,165 @@
+use core::datatable::DataTable;
+use server::datamodel::DataModel;
+use server::datastore::DataStore;
+use server::requesthandler::process_request;
+
+//	===============================================================================================
+
+const SIM_DEFAULT_TABLE_SIZE : usize = 0x10000;
+
+//	===============================================================================================
+
+/// The coils, discrete inputs, holding registers and input registers
+/// of a simulated device, which answers request PDUs like a real one.
+/// The tables are kept in a dense `DataStore`, clones of the device share them.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::core::datatable::DataTable;
+/// use modbus_iiot::sim::device::SimDevice;
+///
+/// let mut device = SimDevice::new_with_size(100);
+/// device.set_registers(DataTable::HoldingRegisters, 10, &[ 1, 2, 3 ]).unwrap();
+///
+/// let response = device.process_request(&[ 0x03, 0x00, 0x0A, 0x00, 0x02 ]);
+/// assert_eq!(response, vec![ 0x03, 0x04, 0x00, 0x01, 0x00, 0x02 ]);
+/// ```
+#[derive( Clone )]
+pub struct SimDevice
+{
+	store : DataStore
+}
+
+impl Default for SimDevice
+{
+	fn default () -> SimDevice
+	{
+		return Self::new ();
+	}
+}
+
+impl SimDevice
+{
+	///	Creates a device with all 65536 addresses of each table set to 0.
+	pub fn new () -> SimDevice
+	{
+		return Self::new_with_size ( SIM_DEFAULT_TABLE_SIZE );
+	}
+
+	///	Creates a device with `size` addresses in each table,
+	/// requests beyond answer with an illegal data address exception.
+	pub fn new_with_size ( size : usize ) -> SimDevice
+	{
+		return Self::new_with_store ( DataStore::new_dense ( size.min ( SIM_DEFAULT_TABLE_SIZE ) ) );
+	}
+
+	///	Creates a device answering from the store, e.g. with access rules or write callbacks.
+	pub fn new_with_store ( store : DataStore ) -> SimDevice
+	{
+		return SimDevice
+		{
+			store : store
+		};
+	}
+
+	///	Returns the store holding the tables.
+	pub fn get_store ( &self ) -> &DataStore
+	{
+		return &self.store;
+	}
+
+	///	Returns the bits of coils or discrete inputs, `None` outside of the table.
+	pub fn get_bits ( &self, table : DataTable, address : u16, quantity : u16 ) -> Option< Vec< bool > >
+	{
+		return self.store.get_bits ( table,
+									 address,
+									 quantity );
+	}
+
+	///	Returns holding or input registers, `None` outside of the table.
+	pub fn get_registers ( &self, table : DataTable, address : u16, quantity : u16 ) -> Option< Vec< u16 > >
+	{
+		return self.store.get_registers ( table,
+										  address,
+										  quantity );
+	}
+
+	///	Sets coils or discrete inputs.
+	pub fn set_bits ( &self, table : DataTable, address : u16, values : &[ bool ] ) -> Result< (), String >
+	{
+		return self.store.set_bits ( table,
+									 address,
+									 values );
+	}
+
+	///	Sets holding or input registers.
+	pub fn set_registers ( &self, table : DataTable, address : u16, values : &[ u16 ] ) -> Result< (), String >
+	{
+		return self.store.set_registers ( table,
+										  address,
+										  values );
+	}
+
+	///	Processes a request PDU (function code and data) and returns the response PDU.
+	pub fn process_request ( &mut self, pdu : &[ u8 ] ) -> Vec< u8 >
+	{
+		return process_request ( &mut self.store,
+								 pdu );
+	}
+}
+
+impl DataModel for SimDevice
+{
+	fn read_bits ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< bool >, u8 >
+	{
+		return self.store.read_bits ( table, address, quantity );
+	}
+
+	fn read_registers ( &mut self, table : DataTable, address : u16, quantity : u16 ) -> Result< Vec< u16 >, u8 >
+	{
+		return self.store.read_registers ( table, address, quantity );
+	}
+
+	fn write_bits ( &mut self, address : u16, values : &[ bool ] ) -> Result< (), u8 >
+	{
+		return self.store.write_bits ( address, values );
+	}
+
+	fn write_registers ( &mut self, address : u16, values : &[ u16 ] ) -> Result< (), u8 >
+	{
+		return self.store.write_registers ( address, values );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_process_request ()
+{
+	let mut device : SimDevice = SimDevice::new_with_size ( 20 );
+
+	assert_eq! ( device.process_request ( &[ 0x05, 0x00, 0x02, 0xFF, 0x00 ] ), vec![ 0x05, 0x00, 0x02, 0xFF, 0x00 ] );
+	assert_eq! ( device.process_request ( &[ 0x01, 0x00, 0x00, 0x00, 0x0A ] ), vec![ 0x01, 0x02, 0x04, 0x00 ] );
+	assert_eq! ( device.process_request ( &[ 0x05, 0x00, 0x02, 0x12, 0x34 ] ), vec![ 0x85, 0x03 ] );
+
+	assert_eq! ( device.process_request ( &[ 0x0F, 0x00, 0x08, 0x00, 0x0A, 0x02, 0xCD, 0x01 ] ), vec![ 0x0F, 0x00, 0x08, 0x00, 0x0A ] );
+	assert_eq! ( device.get_bits ( DataTable::Coils, 8, 3 ), Some( vec![ true, false, true ] ) );
+	assert_eq! ( device.process_request ( &[ 0x0F, 0x00, 0x08, 0x00, 0x0A, 0x01, 0xCD ] ), vec![ 0x8F, 0x03 ] );
+
+	assert_eq! ( device.process_request ( &[ 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02 ] ), vec![ 0x10, 0x00, 0x01, 0x00, 0x02 ] );
+	assert_eq! ( device.process_request ( &[ 0x03, 0x00, 0x00, 0x00, 0x03 ] ), vec![ 0x03, 0x06, 0x00, 0x00, 0x00, 0x0A, 0x01, 0x02 ] );
+	assert_eq! ( device.process_request ( &[ 0x06, 0x00, 0x00, 0x00, 0x12 ] ), vec![ 0x06, 0x00, 0x00, 0x00, 0x12 ] );
+	assert_eq! ( device.process_request ( &[ 0x16, 0x00, 0x00, 0x00, 0xF2, 0x00, 0x25 ] ), vec![ 0x16, 0x00, 0x00, 0x00, 0xF2, 0x00, 0x25 ] );
+	assert_eq! ( device.get_registers ( DataTable::HoldingRegisters, 0, 1 ), Some( vec![ 0x0017 ] ) );
+
+	assert_eq! ( device.process_request ( &[ 0x04, 0x00, 0x13, 0x00, 0x02 ] ), vec![ 0x84, 0x02 ] );
+	assert_eq! ( device.process_request ( &[ 0x04, 0x00, 0x00, 0x00, 0x7E ] ), vec![ 0x84, 0x03 ] );
+	assert_eq! ( device.process_request ( &[ 0x03, 0x00 ] ), vec![ 0x83, 0x03 ] );
+	assert_eq! ( device.process_request ( &[ 0x2B, 0x0E, 0x01, 0x00 ] ), vec![ 0xAB, 0x01 ] );
+	assert_eq! ( device.process_request ( &[] ), vec![ 0x80, 0x01 ] );
+
+	assert! ( device.set_registers ( DataTable::InputRegisters, 19, &[ 1, 2 ] ).is_err () );
+	assert! ( device.set_bits ( DataTable::HoldingRegisters, 0, &[ true ] ).is_err () );
+}
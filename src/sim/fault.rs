@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+//	===============================================================================================
+
+/// What the `Simulator` does instead of a normal response.
+#[derive( Clone, Copy, Debug, PartialEq )]
+pub enum FaultAction
+{
+	///	Answers after an additional delay.
+	Delay( Duration ),
+	///	Sends no response at all.
+	Drop,
+	///	Answers with the exception code.
+	Exception( u8 ),
+	///	Sends the response with every PDU byte inverted.
+	Garble
+}
+
+//	===============================================================================================
+
+/// A scripted fault of the `Simulator`. A fault applies to all requests
+/// or to the requests of one function code, either always or a number of times.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::sim::fault::{Fault, FaultAction};
+///
+/// //	the next two reads of holding registers fail with a server device busy exception
+/// let mut fault = Fault::new_for_function_code(FaultAction::Exception(0x06), 0x03);
+/// fault.set_count(2);
+/// ```
+#[derive( Clone, Copy, Debug, PartialEq )]
+pub struct Fault
+{
+	action : FaultAction,
+	function_code : Option< u8 >,
+	remaining_count : Option< usize >
+}
+
+impl Fault
+{
+	///	Creates a fault for all requests.
+	pub fn new ( action : FaultAction ) -> Fault
+	{
+		return Fault
+		{
+			action : action,
+			function_code : None,
+			remaining_count : None
+		};
+	}
+
+	///	Creates a fault for the requests with the function code.
+	pub fn new_for_function_code ( action : FaultAction, function_code : u8 ) -> Fault
+	{
+		let mut reply : Fault = Fault::new ( action );
+		reply.function_code = Some( function_code );
+
+		return reply;
+	}
+
+	///	Limits the fault to the next `count` matching requests.
+	pub fn set_count ( &mut self, count : usize )
+	{
+		self.remaining_count = Some( count );
+	}
+
+	pub fn get_action ( &self ) -> FaultAction
+	{
+		return self.action;
+	}
+
+	pub fn get_function_code ( &self ) -> Option< u8 >
+	{
+		return self.function_code;
+	}
+
+	///	Number of matching requests the fault still applies to, `None` for always.
+	pub fn get_remaining_count ( &self ) -> Option< usize >
+	{
+		return self.remaining_count;
+	}
+
+	///	Returns true if the fault is not used up and matches the function code.
+	pub fn is_matching ( &self, function_code : u8 ) -> bool
+	{
+		let function_code_matches : bool = self.function_code.is_none_or ( | code | code == function_code );
+
+		return function_code_matches && self.remaining_count != Some( 0 );
+	}
+
+	///	Applies the fault to a request and returns its action.
+	pub fn trigger ( &mut self ) -> FaultAction
+	{
+		if let Some( count ) = self.remaining_count
+		{
+			self.remaining_count = Some( count.saturating_sub ( 1 ) );
+		}
+
+		return self.action;
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_fault_matching ()
+{
+	let mut fault : Fault = Fault::new_for_function_code ( FaultAction::Drop,
+														   0x03 );
+	fault.set_count ( 2 );
+
+	assert! ( fault.is_matching ( 0x03 ) );
+	assert! ( !fault.is_matching ( 0x04 ) );
+	assert_eq! ( fault.trigger (), FaultAction::Drop );
+	assert_eq! ( fault.trigger (), FaultAction::Drop );
+	assert! ( !fault.is_matching ( 0x03 ) );
+	assert_eq! ( fault.get_remaining_count (), Some( 0 ) );
+
+	let mut always : Fault = Fault::new ( FaultAction::Garble );
+	always.trigger ();
+	assert! ( always.is_matching ( 0x10 ) );
+	assert_eq! ( always.get_remaining_count (), None );
+}
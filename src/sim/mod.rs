@@ -0,0 +1,5 @@
+
+
+pub mod device;
+pub mod fault;
+pub mod simulator;
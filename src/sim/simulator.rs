@@ -0,0 +1,318 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use core::consts::*;
+use server::requesthandler::create_exception_response;
+use server::tcpserver::{create_response_frame, TcpServer};
+use sim::device::*;
+use sim::fault::*;
+use tcp::master::TcpClient;
+
+//	===============================================================================================
+
+const SIM_ADDRESS : &str = "127.0.0.1";
+
+//	===============================================================================================
+
+struct SimulatorState
+{
+	faults : Vec< Fault >,
+	latency : Duration,
+	request_count : u64
+}
+
+//	===============================================================================================
+
+/// A simulated MODBUS TCP device in the own process, listening on 127.0.0.1
+/// with a port chosen by the operating system. The device is served by a
+/// `TcpServer` whose frame handler injects the faults, the server stops
+/// when the `Simulator` is dropped.
+///
+/// # Example
+///
+/// ```rust
+///
+/// use modbus_iiot::core::datatable::DataTable;
+/// use modbus_iiot::sim::device::SimDevice;
+/// use modbus_iiot::sim::fault::{Fault, FaultAction};
+/// use modbus_iiot::sim::simulator::Simulator;
+/// use modbus_iiot::tcp::masteraccess::MasterAccess;
+///
+/// let simulator = Simulator::start(SimDevice::new()).unwrap();
+/// simulator.get_device().set_registers(DataTable::HoldingRegisters, 0, &[ 42 ]).unwrap();
+///
+/// let mut client = simulator.create_client();
+/// client.connect().unwrap();
+/// assert_eq!(client.read_holding_registers(0, 1), vec![ 42 ]);
+///
+/// let mut fault = Fault::new(FaultAction::Exception(0x04));
+/// fault.set_count(1);
+/// simulator.add_fault(fault);
+/// assert!(client.read_holding_registers(0, 1).is_empty());
+/// assert_eq!(client.read_holding_registers(0, 1), vec![ 42 ]);
+///
+/// client.disconnect();
+/// ```
+pub struct Simulator
+{
+	server : TcpServer,
+	device : SimDevice,
+	state : Arc< Mutex< SimulatorState > >
+}
+
+impl Simulator
+{
+	///	Starts serving the device.
+	pub fn start ( device : SimDevice ) -> Result< Simulator, String >
+	{
+		let state : Arc< Mutex< SimulatorState > > = Arc::new ( Mutex::new ( SimulatorState
+																			  {
+																				  faults : vec![],
+																				  latency : Duration::from_millis ( 0 ),
+																				  request_count : 0
+																			  } ) );
+
+		let handler_device : SimDevice = device.clone ();
+		let handler = inject_faults ( move | frame : &[ u8 ] | Some( create_response_frame ( frame,
+																							 &handler_device.clone ().process_request ( &frame[ MODBUS_HEADER_SIZE as usize.. ] ) ) ),
+									  state.clone () );
+		let server : TcpServer = TcpServer::start_with_frame_handler ( &format! ( "{}:0", SIM_ADDRESS ),
+																	   handler )?;
+
+		return Ok( Simulator
+		{
+			server : server,
+			device : device,
+			state : state
+		} );
+	}
+
+	pub fn get_address ( &self ) -> &str
+	{
+		return SIM_ADDRESS;
+	}
+
+	pub fn get_port ( &self ) -> u16
+	{
+		return self.server.get_local_address ().port ();
+	}
+
+	///	Creates a not yet connected client for the simulator.
+	pub fn create_client ( &self ) -> TcpClient
+	{
+		return TcpClient::new_with_port ( SIM_ADDRESS,
+										  self.get_port () );
+	}
+
+	///	Returns the device to read or change its tables while it is served.
+	pub fn get_device ( &self ) -> &SimDevice
+	{
+		return &self.device;
+	}
+
+	///	Adds a fault, the first matching fault is applied to a request.
+	pub fn add_fault ( &self, fault : Fault )
+	{
+		lock_state ( &self.state ).faults.push ( fault );
+	}
+
+	pub fn clear_faults ( &self )
+	{
+		lock_state ( &self.state ).faults.clear ();
+	}
+
+	///	Sets a delay which is added to every response.
+	pub fn set_latency ( &self, latency : Duration )
+	{
+		lock_state ( &self.state ).latency = latency;
+	}
+
+	///	Number of requests received since the start.
+	pub fn get_request_count ( &self ) -> u64
+	{
+		return lock_state ( &self.state ).request_count;
+	}
+
+	///	Stops accepting and closes all connections.
+	pub fn stop ( &mut self )
+	{
+		self.server.stop ();
+	}
+}
+
+//	===============================================================================================
+
+fn lock_state ( state : &Mutex< SimulatorState > ) -> MutexGuard< '_, SimulatorState >
+{
+	return state.lock ().unwrap_or_else ( | error | error.into_inner () );
+}
+
+//	===============================================================================================
+
+///	Wraps a frame handler of a `TcpServer` with the faults and the latency of the state.
+fn inject_faults< F > ( handler : F, state : Arc< Mutex< SimulatorState > > ) -> impl Fn ( &[ u8 ] ) -> Option< Vec< u8 > > + Clone + Send + 'static
+	where F : Fn ( &[ u8 ] ) -> Option< Vec< u8 > > + Clone + Send + 'static
+{
+	return move | frame : &[ u8 ] |
+	{
+		let function_code : u8 = frame[ MODBUS_HEADER_SIZE as usize ];
+		let mut action : Option< FaultAction > = None;
+		let mut delay : Duration;
+
+		{
+			let mut locked_state : MutexGuard< SimulatorState > = lock_state ( &state );
+			locked_state.request_count += 1;
+			delay = locked_state.latency;
+
+			if let Some( fault ) = locked_state.faults.iter_mut ().find ( | fault | fault.is_matching ( function_code ) )
+			{
+				action = Some( fault.trigger () );
+			}
+		}
+
+		let mut response : Option< Vec< u8 > >;
+
+		match action
+		{
+			Some( FaultAction::Exception( exception_code ) )	=> { response = Some( create_response_frame ( frame, &create_exception_response ( function_code, exception_code ) ) ); }
+			Some( FaultAction::Drop )							=> { response = None; }
+			_													=> { response = handler ( frame ); }
+		}
+
+		match action
+		{
+			Some( FaultAction::Delay( fault_delay ) )	=> { delay += fault_delay; }
+			Some( FaultAction::Garble )					=> { response = response.map ( garble_pdu ); }
+			_											=> {}
+		}
+
+		if delay > Duration::from_millis ( 0 )
+		{
+			thread::sleep ( delay );
+		}
+
+		return response;
+	};
+}
+
+///	Inverts the bytes of the PDU and keeps the MBAP header.
+fn garble_pdu ( mut frame : Vec< u8 > ) -> Vec< u8 >
+{
+	for byte in frame.iter_mut ().skip ( MODBUS_HEADER_SIZE as usize )
+	{
+		*byte = !*byte;
+	}
+
+	return frame;
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use core::modbusreturn::*;
+#[cfg( test )]
+use tcp::masteraccess::*;
+
+#[cfg( test )]
+const SIM_TEST_TIMEOUT : u64 = 200;
+
+#[cfg( test )]
+fn start_test_simulator () -> ( Simulator, TcpClient )
+{
+	let simulator : Simulator = Simulator::start ( SimDevice::new_with_size ( 1000 ) ).unwrap ();
+	let mut client : TcpClient = simulator.create_client ();
+	client.set_timeout ( Duration::from_millis ( SIM_TEST_TIMEOUT ) );
+	client.connect ().unwrap ();
+
+	return ( simulator, client );
+}
+
+#[test]
+fn test_simulator_master_access ()
+{
+	let ( simulator, mut client ) = start_test_simulator ();
+
+	let values : Vec< u16 > = ( 0..125 ).collect ();
+	simulator.get_device ().set_registers ( DataTable::InputRegisters, 100, &values ).unwrap ();
+	simulator.get_device ().set_bits ( DataTable::DiscreteInputs, 3, &[ true, true ] ).unwrap ();
+
+	assert_eq! ( client.read_input_registers ( 100, 125 ), values );
+	assert_eq! ( client.read_discrete_inputs ( 2, 3 ), vec![ CoilValue::Off, CoilValue::On, CoilValue::On ] );
+
+	assert! ( client.write_single_coil ( 7, CoilValue::On ) );
+	assert! ( client.write_multiple_coils ( 8, vec![ CoilValue::On; 8 ] ) );
+	assert_eq! ( client.read_coils ( 6, 11 ).iter ().filter ( | coil | CoilValue::get ( coil ) ).count (), 9 );
+
+	assert! ( client.write_single_register ( 0, 0x0012 ) );
+	assert! ( client.write_multiple_registers ( 1, vec![ 3, 4 ] ) );
+	assert_eq! ( client.read_holding_registers ( 0, 3 ), vec![ 0x0012, 3, 4 ] );
+
+	assert! ( client.write_register_bit ( 0, 0, true ) );
+	assert_eq! ( client.read_register_bit ( 0, 0 ), Some( true ) );
+	assert_eq! ( simulator.get_device ().get_registers ( DataTable::HoldingRegisters, 0, 1 ), Some( vec![ 0x0013 ] ) );
+
+	assert! ( client.read_holding_registers ( 999, 2 ).is_empty () );
+	assert_eq! ( simulator.get_request_count (), 11 );
+
+	client.disconnect ();
+}
+
+#[test]
+fn test_simulator_exceptions ()
+{
+	let ( simulator, mut client ) = start_test_simulator ();
+
+	let response_1 : ModbusReturnRegisters = ::core::ethernet::EthernetMaster::read_holding_registers ( &mut client, 999, 2 );
+	assert_eq! ( response_1.unwrap_bad ().get_exception_code (), MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS );
+
+	let mut fault : Fault = Fault::new_for_function_code ( FaultAction::Exception( 0x06 ), FUNCTION_CODE_READ_INPUT_REGISTERS );
+	fault.set_count ( 1 );
+	simulator.add_fault ( fault );
+
+	let response_2 : ModbusReturnRegisters = ::core::ethernet::EthernetMaster::read_input_registers ( &mut client, 0, 1 );
+	let bad : ReturnBad = response_2.unwrap_bad ();
+	assert_eq! ( bad.get_error_code (), 0x84 );
+	assert_eq! ( bad.get_exception_code (), 0x06 );
+	assert! ( ::core::ethernet::EthernetMaster::read_input_registers ( &mut client, 0, 1 ).is_good () );
+
+	simulator.add_fault ( Fault::new_for_function_code ( FaultAction::Exception( MODBUS_EXCEPTION_ILLEGAL_FUNCTION ), FUNCTION_CODE_MASK_WRITE_REGISTER ) );
+	assert! ( client.write_register_bit ( 5, 15, true ) );
+	assert! ( client.write_register_bit ( 5, 0, true ) );
+	assert_eq! ( client.read_holding_registers ( 5, 1 ), vec![ 0x8001 ] );
+	assert_eq! ( simulator.get_request_count (), 9 );
+
+	client.disconnect ();
+}
+
+#[test]
+fn test_simulator_dropped_and_garbled_frames ()
+{
+	let ( simulator, mut client ) = start_test_simulator ();
+
+	let mut dropped : Fault = Fault::new ( FaultAction::Drop );
+	dropped.set_count ( 1 );
+	simulator.add_fault ( dropped );
+	assert! ( client.read_holding_registers ( 0, 1 ).is_empty () );
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 0 ] );
+
+	let mut garbled : Fault = Fault::new ( FaultAction::Garble );
+	garbled.set_count ( 1 );
+	simulator.add_fault ( garbled );
+	assert! ( ::core::ethernet::EthernetMaster::read_holding_registers ( &mut client, 0, 1 ).is_bad () );
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 0 ] );
+
+	let late_response : Duration = Duration::from_millis ( SIM_TEST_TIMEOUT * 3 );
+	let mut delayed : Fault = Fault::new ( FaultAction::Delay( late_response ) );
+	delayed.set_count ( 1 );
+	simulator.add_fault ( delayed );
+	simulator.get_device ().set_registers ( DataTable::HoldingRegisters, 0, &[ 7 ] ).unwrap ();
+	assert! ( client.read_holding_registers ( 0, 1 ).is_empty () );
+
+	// the late response arrives before the next request and is skipped by its transaction identifier
+	thread::sleep ( late_response );
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 7 ] );
+
+	client.disconnect ();
+}
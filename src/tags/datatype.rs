@@ -0,0 +1,341 @@
+use std::fmt;
+
+//	===============================================================================================
+
+/// The data type a tag occupies in coils or registers.
+#[derive( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum DataType
+{
+	Bool,
+	Int16,
+	Int32,
+	Int64,
+	Float32,
+	Float64,
+	UInt16,
+	UInt32,
+	UInt64
+}
+
+impl DataType
+{
+	///	Parses names like `u16`, `int32`, `float` or `bool` (case insensitive).
+	pub fn parse ( name : &str ) -> Result< DataType, String >
+	{
+		let reply : Result< DataType, String >;
+
+		match name.trim ().to_lowercase ().as_str ()
+		{
+			"bool" | "bit" | "boolean"			=> { reply = Ok( DataType::Bool ); }
+			"i16" | "int16" | "int" | "short"	=> { reply = Ok( DataType::Int16 ); }
+			"i32" | "int32" | "dint" | "long"	=> { reply = Ok( DataType::Int32 ); }
+			"i64" | "int64" | "lint"			=> { reply = Ok( DataType::Int64 ); }
+			"f32" | "float32" | "float" | "real"=> { reply = Ok( DataType::Float32 ); }
+			"f64" | "float64" | "double" | "lreal" => { reply = Ok( DataType::Float64 ); }
+			"" | "u16" | "uint16" | "word"		=> { reply = Ok( DataType::UInt16 ); }
+			"u32" | "uint32" | "dword"			=> { reply = Ok( DataType::UInt32 ); }
+			"u64" | "uint64" | "lword"			=> { reply = Ok( DataType::UInt64 ); }
+			_									=> { reply = Err( format! ( "unknown data type '{}'", name ) ); }
+		}
+
+		return reply;
+	}
+
+	///	Returns the number of coils or registers the type occupies.
+	pub fn get_quantity ( &self ) -> u16
+	{
+		let reply : u16;
+
+		match *self
+		{
+			DataType::Bool | DataType::Int16 | DataType::UInt16		=> { reply = 1; }
+			DataType::Int32 | DataType::UInt32 | DataType::Float32	=> { reply = 2; }
+			DataType::Int64 | DataType::UInt64 | DataType::Float64	=> { reply = 4; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the smallest and the largest value the type can hold.
+	pub fn get_range ( &self ) -> ( f64, f64 )
+	{
+		let reply : ( f64, f64 );
+
+		match *self
+		{
+			DataType::Bool		=> { reply = ( 0.0, 1.0 ); }
+			DataType::Int16		=> { reply = ( i16::MIN as f64, i16::MAX as f64 ); }
+			DataType::Int32		=> { reply = ( i32::MIN as f64, i32::MAX as f64 ); }
+			DataType::Int64		=> { reply = ( i64::MIN as f64, i64::MAX as f64 ); }
+			DataType::Float32	=> { reply = ( f32::MIN as f64, f32::MAX as f64 ); }
+			DataType::Float64	=> { reply = ( f64::MIN, f64::MAX ); }
+			DataType::UInt16	=> { reply = ( 0.0, u16::MAX as f64 ); }
+			DataType::UInt32	=> { reply = ( 0.0, u32::MAX as f64 ); }
+			DataType::UInt64	=> { reply = ( 0.0, u64::MAX as f64 ); }
+		}
+
+		return reply;
+	}
+
+	pub fn is_float ( &self ) -> bool
+	{
+		return *self == DataType::Float32 || *self == DataType::Float64;
+	}
+}
+
+impl fmt::Display for DataType
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		let name : &str;
+
+		match *self
+		{
+			DataType::Bool		=> { name = "bool"; }
+			DataType::Int16		=> { name = "i16"; }
+			DataType::Int32		=> { name = "i32"; }
+			DataType::Int64		=> { name = "i64"; }
+			DataType::Float32	=> { name = "f32"; }
+			DataType::Float64	=> { name = "f64"; }
+			DataType::UInt16	=> { name = "u16"; }
+			DataType::UInt32	=> { name = "u32"; }
+			DataType::UInt64	=> { name = "u64"; }
+		}
+
+		return write! ( f, "{}", name );
+	}
+}
+
+//	===============================================================================================
+
+/// The order of the bytes of a multi register value, named after
+/// the position of the most significant byte `A` on the wire.
+#[derive( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum WordOrder
+{
+	///	ABCD - big endian, high word first (MODBUS default)
+	BigEndian,
+	///	BADC - high word first, bytes of each word swapped
+	BigEndianByteSwap,
+	///	DCBA - little endian
+	LittleEndian,
+	///	CDAB - low word first, bytes in big endian order
+	LittleEndianByteSwap
+}
+
+impl WordOrder
+{
+	///	Parses `ABCD`, `BADC`, `CDAB`, `DCBA` or the names `big`, `little`,
+	/// `big_swap` and `little_swap` (case insensitive).
+	pub fn parse ( name : &str ) -> Result< WordOrder, String >
+	{
+		let reply : Result< WordOrder, String >;
+
+		match name.trim ().to_lowercase ().as_str ()
+		{
+			"" | "abcd" | "big" | "big_endian"				=> { reply = Ok( WordOrder::BigEndian ); }
+			"badc" | "big_swap" | "big_endian_byte_swap"	=> { reply = Ok( WordOrder::BigEndianByteSwap ); }
+			"dcba" | "little" | "little_endian"				=> { reply = Ok( WordOrder::LittleEndian ); }
+			"cdab" | "little_swap" | "word_swap" | "little_endian_byte_swap" => { reply = Ok( WordOrder::LittleEndianByteSwap ); }
+			_												=> { reply = Err( format! ( "unknown word order '{}'", name ) ); }
+		}
+
+		return reply;
+	}
+
+	fn is_byte_swapped ( &self ) -> bool
+	{
+		return *self == WordOrder::BigEndianByteSwap || *self == WordOrder::LittleEndian;
+	}
+
+	fn is_word_swapped ( &self ) -> bool
+	{
+		return *self == WordOrder::LittleEndian || *self == WordOrder::LittleEndianByteSwap;
+	}
+}
+
+impl fmt::Display for WordOrder
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		let name : &str;
+
+		match *self
+		{
+			WordOrder::BigEndian			=> { name = "ABCD"; }
+			WordOrder::BigEndianByteSwap	=> { name = "BADC"; }
+			WordOrder::LittleEndian			=> { name = "DCBA"; }
+			WordOrder::LittleEndianByteSwap	=> { name = "CDAB"; }
+		}
+
+		return write! ( f, "{}", name );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_order_registers ()
+{
+	let test_data : Vec< u16 > = vec![ 0x0102, 0x0304 ];
+
+	assert_eq! ( order_registers ( &test_data, WordOrder::BigEndian ), vec![ 0x0102, 0x0304 ] );
+	assert_eq! ( order_registers ( &test_data, WordOrder::BigEndianByteSwap ), vec![ 0x0201, 0x0403 ] );
+	assert_eq! ( order_registers ( &test_data, WordOrder::LittleEndian ), vec![ 0x0403, 0x0201 ] );
+	assert_eq! ( order_registers ( &test_data, WordOrder::LittleEndianByteSwap ), vec![ 0x0304, 0x0102 ] );
+}
+
+///	Converts registers between the wire order and big endian order.
+/// The conversion is its own inverse.
+pub fn order_registers ( registers : &[ u16 ], word_order : WordOrder ) -> Vec< u16 >
+{
+	let mut reply : Vec< u16 > = registers.to_vec ();
+
+	if word_order.is_word_swapped ()
+	{
+		reply.reverse ();
+	}
+
+	if word_order.is_byte_swapped ()
+	{
+		for register in reply.iter_mut ()
+		{
+			*register = register.swap_bytes ();
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_decode_registers ()
+{
+	assert_eq! ( decode_registers ( &[ 0xFFFF ], DataType::Int16, WordOrder::BigEndian ), Ok( -1.0 ) );
+	assert_eq! ( decode_registers ( &[ 0xFFFF ], DataType::UInt16, WordOrder::BigEndian ), Ok( 65535.0 ) );
+	assert_eq! ( decode_registers ( &[ 0x0001, 0x0000 ], DataType::UInt32, WordOrder::BigEndian ), Ok( 65536.0 ) );
+	assert_eq! ( decode_registers ( &[ 0x0000, 0x0001 ], DataType::UInt32, WordOrder::LittleEndianByteSwap ), Ok( 65536.0 ) );
+	assert_eq! ( decode_registers ( &[ 0xFFFF, 0xFFFE ], DataType::Int32, WordOrder::BigEndian ), Ok( -2.0 ) );
+	assert_eq! ( decode_registers ( &[ 0xC020, 0x0000 ], DataType::Float32, WordOrder::BigEndian ), Ok( -2.5 ) );
+	assert_eq! ( decode_registers ( &[ 0x0000, 0xC020 ], DataType::Float32, WordOrder::LittleEndianByteSwap ), Ok( -2.5 ) );
+	assert_eq! ( decode_registers ( &[ 0x0000, 0x0000, 0x0000, 0x0002 ], DataType::Int64, WordOrder::BigEndian ), Ok( 2.0 ) );
+	assert_eq! ( decode_registers ( &[ 0x4000, 0x0000, 0x0000, 0x0000 ], DataType::Float64, WordOrder::BigEndian ), Ok( 2.0 ) );
+	assert_eq! ( decode_registers ( &[ 0x0002 ], DataType::Bool, WordOrder::BigEndian ), Ok( 1.0 ) );
+	assert! ( decode_registers ( &[ 0x0001 ], DataType::UInt32, WordOrder::BigEndian ).is_err () );
+}
+
+///	Decodes the registers of a value into a number.
+pub fn decode_registers ( registers : &[ u16 ], data_type : DataType, word_order : WordOrder ) -> Result< f64, String >
+{
+	let reply : Result< f64, String >;
+
+	if registers.len () < data_type.get_quantity () as usize
+	{
+		reply = Err( format! ( "{} needs {} registers, got {}", data_type, data_type.get_quantity (), registers.len () ) );
+	}
+	else
+	{
+		let ordered : Vec< u16 > = order_registers ( &registers[ 0..data_type.get_quantity () as usize ],
+													 word_order );
+		let mut bits : u64 = 0;
+
+		for register in ordered.iter ()
+		{
+			bits = ( bits << 16 ) | *register as u64;
+		}
+
+		let value : f64;
+
+		match data_type
+		{
+			DataType::Bool		=> { value = if bits != 0 { 1.0 } else { 0.0 }; }
+			DataType::Int16		=> { value = bits as u16 as i16 as f64; }
+			DataType::Int32		=> { value = bits as u32 as i32 as f64; }
+			DataType::Int64		=> { value = bits as i64 as f64; }
+			DataType::Float32	=> { value = f32::from_bits ( bits as u32 ) as f64; }
+			DataType::Float64	=> { value = f64::from_bits ( bits ); }
+			DataType::UInt16	=> { value = bits as u16 as f64; }
+			DataType::UInt32	=> { value = bits as u32 as f64; }
+			DataType::UInt64	=> { value = bits as f64; }
+		}
+
+		reply = Ok( value );
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_encode_registers ()
+{
+	assert_eq! ( encode_registers ( -1.0, DataType::Int16, WordOrder::BigEndian ), Ok( vec![ 0xFFFF ] ) );
+	assert_eq! ( encode_registers ( 65536.0, DataType::UInt32, WordOrder::BigEndian ), Ok( vec![ 0x0001, 0x0000 ] ) );
+	assert_eq! ( encode_registers ( 65536.0, DataType::UInt32, WordOrder::LittleEndianByteSwap ), Ok( vec![ 0x0000, 0x0001 ] ) );
+	assert_eq! ( encode_registers ( -2.0, DataType::Int32, WordOrder::BigEndian ), Ok( vec![ 0xFFFF, 0xFFFE ] ) );
+	assert_eq! ( encode_registers ( -2.5, DataType::Float32, WordOrder::BigEndian ), Ok( vec![ 0xC020, 0x0000 ] ) );
+	assert_eq! ( encode_registers ( 2.0, DataType::Float64, WordOrder::LittleEndian ), Ok( vec![ 0x0000, 0x0000, 0x0000, 0x0040 ] ) );
+	assert_eq! ( encode_registers ( 12.4, DataType::UInt16, WordOrder::BigEndian ), Ok( vec![ 12 ] ) );
+	assert_eq! ( encode_registers ( 12.5, DataType::UInt16, WordOrder::BigEndian ), Ok( vec![ 13 ] ) );
+	assert! ( encode_registers ( 65536.0, DataType::UInt16, WordOrder::BigEndian ).is_err () );
+	assert! ( encode_registers ( -1.0, DataType::UInt32, WordOrder::BigEndian ).is_err () );
+	assert! ( encode_registers ( f64::NAN, DataType::Int16, WordOrder::BigEndian ).is_err () );
+}
+
+///	Encodes a number into the registers of the data type. Integer types
+/// are rounded to the nearest value; values out of range are refused.
+pub fn encode_registers ( value : f64, data_type : DataType, word_order : WordOrder ) -> Result< Vec< u16 >, String >
+{
+	let reply : Result< Vec< u16 >, String >;
+
+	let ( minimum, maximum ) = data_type.get_range ();
+	let number : f64;
+
+	if data_type.is_float ()
+	{
+		number = value;
+	}
+	else
+	{
+		number = value.round ();
+	}
+
+	if number.is_nan () || number < minimum || number > maximum
+	{
+		reply = Err( format! ( "value {} is out of range for {}", value, data_type ) );
+	}
+	else
+	{
+		let bits : u64;
+
+		match data_type
+		{
+			DataType::Bool		=> { bits = if number != 0.0 { 1 } else { 0 }; }
+			DataType::Int16		=> { bits = number as i16 as u16 as u64; }
+			DataType::Int32		=> { bits = number as i32 as u32 as u64; }
+			DataType::Int64		=> { bits = number as i64 as u64; }
+			DataType::Float32	=> { bits = ( number as f32 ).to_bits () as u64; }
+			DataType::Float64	=> { bits = number.to_bits (); }
+			DataType::UInt16	=> { bits = number as u16 as u64; }
+			DataType::UInt32	=> { bits = number as u32 as u64; }
+			DataType::UInt64	=> { bits = number as u64; }
+		}
+
+		let quantity : u16 = data_type.get_quantity ();
+		let mut registers : Vec< u16 > = vec![];
+
+		for index in ( 0..quantity ).rev ()
+		{
+			registers.push ( ( bits >> ( 16 * index ) ) as u16 );
+		}
+
+		reply = Ok( order_registers ( &registers,
+									  word_order ) );
+	}
+
+	return reply;
+}
@@ -0,0 +1,8 @@
+
+
+pub mod datatype;
+pub mod tag;
+pub mod tagcsv;
+pub mod tagdatabase;
+#[cfg( feature = "json" )]
+pub mod tagjson;
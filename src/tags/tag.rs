@@ -0,0 +1,372 @@
+use std::fmt;
+use core::batchreader::BatchTag;
+use core::datatable::*;
+use core::scaling::Scaling;
+use tags::datatype::*;
+
+//	===============================================================================================
+
+/// An engineering value of a tag.
+#[derive( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub enum TagValue
+{
+	Bool( bool ),
+	Number( f64 )
+}
+
+impl TagValue
+{
+	///	Returns the value as a number, `true` is 1.0.
+	pub fn as_number ( &self ) -> f64
+	{
+		let reply : f64;
+
+		match *self
+		{
+			TagValue::Bool( true )		=> { reply = 1.0; }
+			TagValue::Bool( false )		=> { reply = 0.0; }
+			TagValue::Number( number )	=> { reply = number; }
+		}
+
+		return reply;
+	}
+
+	///	Returns the value as a bool, every number except 0.0 is true.
+	pub fn as_bool ( &self ) -> bool
+	{
+		return self.as_number () != 0.0;
+	}
+}
+
+impl fmt::Display for TagValue
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		let reply : fmt::Result;
+
+		match *self
+		{
+			TagValue::Bool( value )		=> { reply = write! ( f, "{}", value ); }
+			TagValue::Number( value )	=> { reply = write! ( f, "{}", value ); }
+		}
+
+		return reply;
+	}
+}
+
+//	===============================================================================================
+
+/// A named item of a register map with its data type and the linear
+/// scaling `engineering value = raw value * scale + offset`.
+/// The sign of the raw value follows the data type of the tag.
+#[derive( Clone, Debug, PartialEq )]
+pub struct Tag
+{
+	name : String,
+	table : DataTable,
+	address : u16,
+	data_type : DataType,
+	word_order : WordOrder,
+	scaling : Scaling,
+	unit : String
+}
+
+impl Tag
+{
+	///	Creates an unscaled tag. Tags of coils and discrete inputs are always `Bool`.
+	pub fn new ( name : &str, table : DataTable, address : u16, data_type : DataType ) -> Tag
+	{
+		let tag_type : DataType;
+
+		if table.is_bit_table ()
+		{
+			tag_type = DataType::Bool;
+		}
+		else
+		{
+			tag_type = data_type;
+		}
+
+		return Tag
+		{
+			name : name.to_string (),
+			table : table,
+			address : address,
+			data_type : tag_type,
+			word_order : WordOrder::BigEndian,
+			scaling : Scaling::new_identity (),
+			unit : String::new ()
+		};
+	}
+
+	pub fn set_scaling ( &mut self, scale : f64, offset : f64 )
+	{
+		self.scaling = Scaling::new ( scale,
+									  offset );
+	}
+
+	///	Sets the engineering range, read values are clamped to it
+	/// and written values outside of it are refused.
+	pub fn set_range ( &mut self, minimum : f64, maximum : f64 ) -> Result< (), String >
+	{
+		return self.scaling.set_range ( minimum,
+										maximum );
+	}
+
+	pub fn set_unit ( &mut self, unit : &str )
+	{
+		self.unit = unit.to_string ();
+	}
+
+	pub fn set_word_order ( &mut self, word_order : WordOrder )
+	{
+		self.word_order = word_order;
+	}
+
+	pub fn get_address ( &self ) -> u16
+	{
+		return self.address;
+	}
+
+	pub fn get_data_type ( &self ) -> DataType
+	{
+		return self.data_type;
+	}
+
+	pub fn get_name ( &self ) -> &str
+	{
+		return &self.name;
+	}
+
+	pub fn get_offset ( &self ) -> f64
+	{
+		return self.scaling.get_offset ();
+	}
+
+	pub fn get_scale ( &self ) -> f64
+	{
+		return self.scaling.get_gain ();
+	}
+
+	pub fn get_scaling ( &self ) -> Scaling
+	{
+		return self.scaling;
+	}
+
+	pub fn get_table ( &self ) -> DataTable
+	{
+		return self.table;
+	}
+
+	pub fn get_unit ( &self ) -> &str
+	{
+		return &self.unit;
+	}
+
+	pub fn get_word_order ( &self ) -> WordOrder
+	{
+		return self.word_order;
+	}
+
+	///	Returns the coils or registers of the tag for a `BatchReader`,
+	/// `None` if they exceed the address range.
+	pub fn get_batch_tag ( &self ) -> Option< BatchTag >
+	{
+		return BatchTag::new ( self.table,
+							   self.address,
+							   self.data_type.get_quantity () );
+	}
+
+	///	Converts the raw registers of the tag into its engineering value.
+	pub fn decode_registers ( &self, registers : &[ u16 ] ) -> Result< TagValue, String >
+	{
+		let reply : Result< TagValue, String >;
+
+		let raw_value : Result< f64, String > = decode_registers ( registers,
+																   self.data_type,
+																   self.word_order );
+
+		match raw_value
+		{
+			Ok( value ) if self.data_type == DataType::Bool	=> { reply = Ok( TagValue::Bool( value != 0.0 ) ); }
+			Ok( value )										=> { reply = Ok( TagValue::Number( self.scaling.scale_value ( value ) ) ); }
+			Err( message )									=> { reply = Err( message ); }
+		}
+
+		return reply;
+	}
+
+	///	Converts an engineering value into the raw registers of the tag.
+	pub fn encode_registers ( &self, value : TagValue ) -> Result< Vec< u16 >, String >
+	{
+		let reply : Result< Vec< u16 >, String >;
+
+		if self.data_type == DataType::Bool
+		{
+			reply = encode_registers ( value.as_number (),
+									   DataType::Bool,
+									   self.word_order );
+		}
+		else
+		{
+			reply = self.scaling.unscale_value ( value.as_number () )
+								.and_then ( | raw_value | encode_registers ( raw_value,
+																			 self.data_type,
+																			 self.word_order ) )
+								.map_err ( | message | format! ( "tag {}: {}", self.name, message ) );
+		}
+
+		return reply;
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_tag_scaling ()
+{
+	let mut tag : Tag = Tag::new ( "temperature",
+								   DataTable::InputRegisters,
+								   0,
+								   DataType::Int16 );
+	tag.set_scaling ( 0.1,
+					  -40.0 );
+
+	assert_eq! ( tag.decode_registers ( &[ 650 ] ), Ok( TagValue::Number( 25.0 ) ) );
+	assert_eq! ( tag.encode_registers ( TagValue::Number( 25.0 ) ), Ok( vec![ 650 ] ) );
+	assert_eq! ( tag.encode_registers ( TagValue::Number( -50.0 ) ), Ok( vec![ 0xFF9C ] ) );
+	assert! ( tag.encode_registers ( TagValue::Number( 4000.0 ) ).is_err () );
+	assert_eq! ( tag.encode_registers ( TagValue::Number( 20.05 ) ), Ok( vec![ 601 ] ) );
+
+	tag.set_range ( -40.0, 85.0 ).unwrap ();
+	assert_eq! ( tag.decode_registers ( &[ 1300 ] ), Ok( TagValue::Number( 85.0 ) ) );
+	assert! ( tag.encode_registers ( TagValue::Number( 90.0 ) ).is_err () );
+
+	let coil : Tag = Tag::new ( "pump",
+								DataTable::Coils,
+								5,
+								DataType::Float32 );
+	assert_eq! ( coil.get_data_type (), DataType::Bool );
+	assert_eq! ( coil.decode_registers ( &[ 1 ] ), Ok( TagValue::Bool( true ) ) );
+	assert_eq! ( coil.get_batch_tag ().unwrap ().get_quantity (), 1 );
+}
+
+//	===============================================================================================
+
+/// The text fields of a tag as found in a register map file.
+/// Empty fields fall back to defaults: data type `u16`, word order `ABCD`,
+/// scale 1, offset 0 and no range limits. Without a table the address is read as a
+/// Modicon reference like 40001.
+#[derive( Clone, Debug, Default, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub struct TagDefinition
+{
+	pub name : String,
+	pub table : String,
+	pub address : String,
+	pub data_type : String,
+	pub word_order : String,
+	pub scale : String,
+	pub offset : String,
+	pub minimum : String,
+	pub maximum : String,
+	pub unit : String
+}
+
+impl TagDefinition
+{
+	pub fn create_tag ( &self ) -> Result< Tag, String >
+	{
+		let name : &str = self.name.trim ();
+
+		if name.is_empty ()
+		{
+			return Err( "tag name is empty".to_string () );
+		}
+
+		let ( table, address ) = parse_table_and_address ( &self.table,
+														   &self.address )?;
+
+		let mut tag : Tag = Tag::new ( name,
+									   table,
+									   address,
+									   DataType::parse ( &self.data_type )? );
+		tag.set_word_order ( WordOrder::parse ( &self.word_order )? );
+		tag.set_scaling ( parse_number ( &self.scale, 1.0 )?,
+						  parse_number ( &self.offset, 0.0 )? );
+		tag.set_range ( parse_number ( &self.minimum, f64::NEG_INFINITY )?,
+						parse_number ( &self.maximum, f64::INFINITY )? )?;
+		tag.set_unit ( self.unit.trim () );
+
+		return Ok( tag );
+	}
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_table_and_address ()
+{
+	assert_eq! ( parse_table_and_address ( "hr", "10" ), Ok( ( DataTable::HoldingRegisters, 10 ) ) );
+	assert_eq! ( parse_table_and_address ( "", "40011" ), Ok( ( DataTable::HoldingRegisters, 10 ) ) );
+	assert_eq! ( parse_table_and_address ( "coil", "0x0010" ), Ok( ( DataTable::Coils, 16 ) ) );
+	assert! ( parse_table_and_address ( "hr", "70000" ).is_err () );
+	assert! ( parse_table_and_address ( "", "20001" ).is_err () );
+	assert! ( parse_table_and_address ( "hr", "" ).is_err () );
+}
+
+fn parse_table_and_address ( table : &str, address : &str ) -> Result< ( DataTable, u16 ), String >
+{
+	let reply : Result< ( DataTable, u16 ), String >;
+
+	let address_text : &str = address.trim ();
+	let number : Result< u32, String >;
+
+	if address_text.starts_with ( "0x" ) || address_text.starts_with ( "0X" )
+	{
+		number = u32::from_str_radix ( &address_text[ 2.. ], 16 ).map_err ( | _ | format! ( "invalid address '{}'", address ) );
+	}
+	else
+	{
+		number = address_text.parse::< u32 > ().map_err ( | _ | format! ( "invalid address '{}'", address ) );
+	}
+
+	match number
+	{
+		Ok( value ) if table.trim ().is_empty () =>
+		{
+			reply = parse_reference ( value ).ok_or ( format! ( "invalid reference '{}'", address ) );
+		}
+		Ok( value ) if value <= 0xFFFF =>
+		{
+			reply = DataTable::parse ( table ).map ( | data_table | ( data_table, value as u16 ) );
+		}
+		Ok( _ ) =>
+		{
+			reply = Err( format! ( "address '{}' is over 65535", address ) );
+		}
+		Err( message ) =>
+		{
+			reply = Err( message );
+		}
+	}
+
+	return reply;
+}
+
+fn parse_number ( text : &str, default_value : f64 ) -> Result< f64, String >
+{
+	let reply : Result< f64, String >;
+
+	if text.trim ().is_empty ()
+	{
+		reply = Ok( default_value );
+	}
+	else
+	{
+		reply = text.trim ().parse::< f64 > ().map_err ( | _ | format! ( "invalid number '{}'", text ) );
+	}
+
+	return reply;
+}
@@ -0,0 +1,169 @@
+use tags::tag::*;
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_tags_csv ()
+{
+	let text : &str = "# plant register map\n\
+					   Name;Table;Address;Type;Word Order;Scale;Offset;Unit;Min;Max\n\
+					   temperature;ir;0;i16;;0.1;-40;\"°C\";-40;85\n\
+					   energy;hr;10;u32;CDAB;;;kWh\n\
+					   \n\
+					   pump;coil;5;;;;;\n\
+					   setpoint;;40021;f32;;;;\"bar; abs\";0;\n";
+
+	let result : Result< Vec< Tag >, String > = parse_tags_csv ( text );
+	assert! ( result.is_ok () );
+
+	let tags : Vec< Tag > = result.unwrap ();
+	assert_eq! ( tags.len (), 4 );
+	assert_eq! ( tags[ 0 ].get_name (), "temperature" );
+	assert_eq! ( tags[ 0 ].get_scale (), 0.1 );
+	assert_eq! ( tags[ 0 ].get_unit (), "°C" );
+	assert_eq! ( tags[ 0 ].get_scaling ().get_maximum (), 85.0 );
+	assert_eq! ( tags[ 3 ].get_scaling ().get_minimum (), 0.0 );
+	assert_eq! ( tags[ 3 ].get_scaling ().get_maximum (), f64::INFINITY );
+	assert_eq! ( tags[ 1 ].get_word_order ().to_string (), "CDAB" );
+	assert_eq! ( tags[ 2 ].get_address (), 5 );
+	assert_eq! ( tags[ 3 ].get_address (), 20 );
+	assert_eq! ( tags[ 3 ].get_unit (), "bar; abs" );
+
+	let result_2 : Result< Vec< Tag >, String > = parse_tags_csv ( "name,table,address\nflow,hr,x\n" );
+	assert_eq! ( result_2, Err( "line 2: invalid address 'x'".to_string () ) );
+
+	let result_3 : Result< Vec< Tag >, String > = parse_tags_csv ( "table,address\nhr,1\n" );
+	assert! ( result_3.is_err () );
+}
+
+///	Parses a register map exported from a spreadsheet. The first line which is
+/// not empty or a `#` comment names the columns `name`, `table`, `address`,
+/// `data_type` (or `type`), `word_order` (or `order`), `scale`, `offset`,
+/// `minimum` (or `min`), `maximum` (or `max`) and `unit`
+/// in any order. Columns are separated by `,` or `;` and may be quoted with `"`.
+pub fn parse_tags_csv ( text : &str ) -> Result< Vec< Tag >, String >
+{
+	let mut reply : Vec< Tag > = vec![];
+
+	let mut columns : Option< Vec< String > > = None;
+	let mut delimiter : char = ',';
+
+	for ( index, line ) in text.lines ().enumerate ()
+	{
+		if line.trim ().is_empty () || line.trim_start ().starts_with ( '#' )
+		{
+			continue;
+		}
+
+		if columns.is_none ()
+		{
+			if line.contains ( ';' ) && !line.contains ( ',' )
+			{
+				delimiter = ';';
+			}
+
+			let names : Vec< String > = split_csv_line ( line,
+														 delimiter ).iter ()
+																	.map ( | name | name.trim ().to_lowercase ().replace ( ' ', "_" ) )
+																	.collect ();
+
+			if !names.contains ( &"name".to_string () ) || !names.contains ( &"address".to_string () )
+			{
+				return Err( format! ( "line {}: header needs the columns name and address", index + 1 ) );
+			}
+
+			columns = Some( names );
+		}
+		else
+		{
+			let fields : Vec< String > = split_csv_line ( line,
+														  delimiter );
+			let mut definition : TagDefinition = TagDefinition::default ();
+
+			for ( column, field ) in columns.iter ().flatten ().zip ( fields )
+			{
+				match column.as_str ()
+				{
+					"name"					=> { definition.name = field; }
+					"table"					=> { definition.table = field; }
+					"address"				=> { definition.address = field; }
+					"data_type" | "type"	=> { definition.data_type = field; }
+					"word_order" | "order"	=> { definition.word_order = field; }
+					"scale"					=> { definition.scale = field; }
+					"offset"				=> { definition.offset = field; }
+					"minimum" | "min"		=> { definition.minimum = field; }
+					"maximum" | "max"		=> { definition.maximum = field; }
+					"unit"					=> { definition.unit = field; }
+					_						=> {}
+				}
+			}
+
+			match definition.create_tag ()
+			{
+				Ok( tag )		=> { reply.push ( tag ); }
+				Err( message )	=> { return Err( format! ( "line {}: {}", index + 1, message ) ); }
+			}
+		}
+	}
+
+	return Ok( reply );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_split_csv_line ()
+{
+	assert_eq! ( split_csv_line ( "a,b,,c", ',' ), vec![ "a", "b", "", "c" ] );
+	assert_eq! ( split_csv_line ( "a;\"b;c\";d", ';' ), vec![ "a", "b;c", "d" ] );
+	assert_eq! ( split_csv_line ( "\"say \"\"hi\"\"\",x", ',' ), vec![ "say \"hi\"", "x" ] );
+}
+
+fn split_csv_line ( line : &str, delimiter : char ) -> Vec< String >
+{
+	let mut reply : Vec< String > = vec![];
+
+	let mut field : String = String::new ();
+	let mut quoted : bool = false;
+	let mut characters = line.chars ().peekable ();
+
+	while let Some( character ) = characters.next ()
+	{
+		if quoted
+		{
+			if character == '"'
+			{
+				if characters.peek () == Some( &'"' )
+				{
+					field.push ( '"' );
+					characters.next ();
+				}
+				else
+				{
+					quoted = false;
+				}
+			}
+			else
+			{
+				field.push ( character );
+			}
+		}
+		else if character == '"'
+		{
+			quoted = true;
+		}
+		else if character == delimiter
+		{
+			reply.push ( field );
+			field = String::new ();
+		}
+		else
+		{
+			field.push ( character );
+		}
+	}
+
+	reply.push ( field );
+
+	return reply;
+}
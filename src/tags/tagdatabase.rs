@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::Read;
+use core::datatable::DataTable;
+use tags::tag::*;
+use tags::tagcsv::parse_tags_csv;
+#[cfg( feature = "json" )]
+use tags::tagjson::parse_tags_json;
+use tcp::masteraccess::*;
+
+//	===============================================================================================
+
+/// A set of named tags which are read and written as engineering values.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::tags::tag::TagValue;
+/// use modbus_iiot::tags::tagdatabase::TagDatabase;
+/// use modbus_iiot::tcp::master::TcpClient;
+///
+/// let database = TagDatabase::load_csv_file("registers.csv").unwrap();
+/// let mut client = TcpClient::new("127.0.0.1");
+///
+/// if client.connect().is_ok()
+/// {
+///     println!("temperature = {:?}", database.read_tag(&mut client, "temperature"));
+///
+///     let _ = database.write_tag(&mut client, "setpoint", TagValue::Number(21.5));
+///
+///     client.disconnect();
+/// }
+/// ```
+pub struct TagDatabase
+{
+	tags : Vec< Tag >
+}
+
+impl Default for TagDatabase
+{
+	fn default () -> TagDatabase
+	{
+		return Self::new ();
+	}
+}
+
+impl TagDatabase
+{
+	pub fn new () -> TagDatabase
+	{
+		return TagDatabase
+		{
+			tags : vec![]
+		};
+	}
+
+	///	Creates a database from the text of a CSV register map.
+	pub fn load_csv ( text : &str ) -> Result< TagDatabase, String >
+	{
+		return Self::new_with_tags ( parse_tags_csv ( text )? );
+	}
+
+	pub fn load_csv_file ( path : &str ) -> Result< TagDatabase, String >
+	{
+		return Self::load_csv ( &read_text_file ( path )? );
+	}
+
+	///	Creates a database from the text of a JSON register map.
+	#[cfg( feature = "json" )]
+	pub fn load_json ( text : &str ) -> Result< TagDatabase, String >
+	{
+		return Self::new_with_tags ( parse_tags_json ( text )? );
+	}
+
+	#[cfg( feature = "json" )]
+	pub fn load_json_file ( path : &str ) -> Result< TagDatabase, String >
+	{
+		return Self::load_json ( &read_text_file ( path )? );
+	}
+
+	fn new_with_tags ( tags : Vec< Tag > ) -> Result< TagDatabase, String >
+	{
+		let mut reply : TagDatabase = TagDatabase::new ();
+
+		for tag in tags
+		{
+			reply.add_tag ( tag )?;
+		}
+
+		return Ok( reply );
+	}
+
+	///	Adds a tag, names have to be unique.
+	pub fn add_tag ( &mut self, tag : Tag ) -> Result< (), String >
+	{
+		let reply : Result< (), String >;
+
+		if self.get_tag ( tag.get_name () ).is_some ()
+		{
+			reply = Err( format! ( "tag {} is defined twice", tag.get_name () ) );
+		}
+		else
+		{
+			self.tags.push ( tag );
+			reply = Ok( () );
+		}
+
+		return reply;
+	}
+
+	pub fn get_tag ( &self, name : &str ) -> Option< &Tag >
+	{
+		return self.tags.iter ().find ( | tag | tag.get_name () == name );
+	}
+
+	pub fn get_tags ( &self ) -> &Vec< Tag >
+	{
+		return &self.tags;
+	}
+
+	///	Reads the tag from the device and returns its engineering value.
+	pub fn read_tag< T : MasterAccess > ( &self, master : &mut T, name : &str ) -> Result< TagValue, String >
+	{
+		let tag : &Tag = self.get_tag ( name ).ok_or ( format! ( "unknown tag {}", name ) )?;
+		let quantity : u16 = tag.get_data_type ().get_quantity ();
+
+		let registers : Vec< u16 >;
+
+		match tag.get_table ()
+		{
+			DataTable::Coils			=> { registers = transform_coils_to_registers ( master.read_coils ( tag.get_address (), 1 ) ); }
+			DataTable::DiscreteInputs	=> { registers = transform_coils_to_registers ( master.read_discrete_inputs ( tag.get_address (), 1 ) ); }
+			DataTable::HoldingRegisters	=> { registers = master.read_holding_registers ( tag.get_address (), quantity ); }
+			DataTable::InputRegisters	=> { registers = master.read_input_registers ( tag.get_address (), quantity ); }
+		}
+
+		if registers.is_empty ()
+		{
+			return Err( format! ( "reading tag {} failed", name ) );
+		}
+
+		return tag.decode_registers ( &registers );
+	}
+
+	///	Converts the engineering value and writes it to the device.
+	pub fn write_tag< T : MasterAccess > ( &self, master : &mut T, name : &str, value : TagValue ) -> Result< (), String >
+	{
+		let tag : &Tag = self.get_tag ( name ).ok_or ( format! ( "unknown tag {}", name ) )?;
+
+		if !tag.get_table ().is_writable ()
+		{
+			return Err( format! ( "tag {} is read only", name ) );
+		}
+
+		let registers : Vec< u16 > = tag.encode_registers ( value )?;
+		let written : bool;
+
+		if tag.get_table () == DataTable::Coils
+		{
+			written = master.write_single_coil ( tag.get_address (),
+												 CoilValue::set ( registers[ 0 ] != 0 ) );
+		}
+		else if registers.len () == 1
+		{
+			written = master.write_single_register ( tag.get_address (),
+													 registers[ 0 ] );
+		}
+		else
+		{
+			written = master.write_multiple_registers ( tag.get_address (),
+														registers );
+		}
+
+		if written
+		{
+			return Ok( () );
+		}
+		else
+		{
+			return Err( format! ( "writing tag {} failed", name ) );
+		}
+	}
+}
+
+//	===============================================================================================
+
+fn read_text_file ( path : &str ) -> Result< String, String >
+{
+	let mut reply : String = String::new ();
+
+	let mut file : File = File::open ( path ).map_err ( | error | format! ( "{}: {}", path, error ) )?;
+	file.read_to_string ( &mut reply ).map_err ( | error | format! ( "{}: {}", path, error ) )?;
+
+	return Ok( reply );
+}
+
+fn transform_coils_to_registers ( coils : Vec< CoilValue > ) -> Vec< u16 >
+{
+	return coils.iter ().map ( | coil | CoilValue::get ( coil ) as u16 ).collect ();
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::testmaster::TestAccess;
+
+#[test]
+fn test_tag_database ()
+{
+	let text : &str = "name,table,address,data_type,word_order,scale,offset,unit\n\
+					   temperature,ir,0,i16,,0.1,-40,°C\n\
+					   energy,hr,2,u32,CDAB,,,kWh\n\
+					   pump,coil,1,,,,,\n\
+					   alarm,di,0,,,,,\n";
+
+	let database : TagDatabase = TagDatabase::load_csv ( text ).unwrap ();
+	assert_eq! ( database.get_tags ().len (), 4 );
+
+	let mut master : TestAccess = TestAccess::new ( vec![ 650, 0, 0x0001, 0x0002, 0 ],
+												   vec![ CoilValue::Off, CoilValue::On ] );
+
+	assert_eq! ( database.read_tag ( &mut master, "temperature" ), Ok( TagValue::Number( 25.0 ) ) );
+	assert_eq! ( database.read_tag ( &mut master, "energy" ), Ok( TagValue::Number( 131073.0 ) ) );
+	assert_eq! ( database.read_tag ( &mut master, "pump" ), Ok( TagValue::Bool( true ) ) );
+	assert! ( database.read_tag ( &mut master, "alarm" ).is_err () );
+	assert! ( database.read_tag ( &mut master, "missing" ).is_err () );
+
+	assert_eq! ( database.write_tag ( &mut master, "energy", TagValue::Number( 65536.0 ) ), Ok( () ) );
+	assert_eq! ( master.registers[ 2..4 ].to_vec (), vec![ 0x0000, 0x0001 ] );
+	assert_eq! ( database.write_tag ( &mut master, "pump", TagValue::Bool( false ) ), Ok( () ) );
+	assert_eq! ( master.coils[ 1 ], CoilValue::Off );
+	assert! ( database.write_tag ( &mut master, "temperature", TagValue::Number( 20.0 ) ).is_err () );
+
+	assert! ( TagDatabase::load_csv ( "name,address\npump,1\npump,2\n" ).is_err () );
+}
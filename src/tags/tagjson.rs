@@ -0,0 +1,104 @@
+use serde_json;
+use tags::tag::*;
+
+//	===============================================================================================
+
+#[derive( Deserialize )]
+#[serde( untagged )]
+enum JsonField
+{
+	Number( f64 ),
+	Text( String )
+}
+
+impl JsonField
+{
+	fn into_string ( self ) -> String
+	{
+		let reply : String;
+
+		match self
+		{
+			JsonField::Number( number )	=> { reply = number.to_string (); }
+			JsonField::Text( text )		=> { reply = text; }
+		}
+
+		return reply;
+	}
+}
+
+#[derive( Deserialize )]
+struct JsonTag
+{
+	name : String,
+	#[serde( default )]
+	table : Option< String >,
+	address : JsonField,
+	#[serde( default, alias = "type" )]
+	data_type : Option< String >,
+	#[serde( default, alias = "order" )]
+	word_order : Option< String >,
+	#[serde( default )]
+	scale : Option< f64 >,
+	#[serde( default )]
+	offset : Option< f64 >,
+	#[serde( default, alias = "min" )]
+	minimum : Option< f64 >,
+	#[serde( default, alias = "max" )]
+	maximum : Option< f64 >,
+	#[serde( default )]
+	unit : Option< String >
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_tags_json ()
+{
+	let text : &str = r#"[
+		{ "name" : "temperature", "table" : "ir", "address" : 0, "type" : "i16", "scale" : 0.1, "offset" : -40, "unit" : "°C", "max" : 85 },
+		{ "name" : "energy", "address" : "40011", "data_type" : "u32", "word_order" : "CDAB" }
+	]"#;
+
+	let result : Result< Vec< Tag >, String > = parse_tags_json ( text );
+	assert! ( result.is_ok () );
+
+	let tags : Vec< Tag > = result.unwrap ();
+	assert_eq! ( tags.len (), 2 );
+	assert_eq! ( tags[ 0 ].get_offset (), -40.0 );
+	assert_eq! ( tags[ 0 ].get_scaling ().get_maximum (), 85.0 );
+	assert_eq! ( tags[ 1 ].get_address (), 10 );
+
+	assert! ( parse_tags_json ( "[ { \"name\" : \"x\" } ]" ).is_err () );
+	assert! ( parse_tags_json ( "[ { \"name\" : \"x\", \"address\" : 1, \"table\" : \"y\" } ]" ).is_err () );
+}
+
+///	Parses a JSON array of tag objects with the same fields as the CSV columns.
+pub fn parse_tags_json ( text : &str ) -> Result< Vec< Tag >, String >
+{
+	let mut reply : Vec< Tag > = vec![];
+
+	let json_tags : Vec< JsonTag > = serde_json::from_str ( text ).map_err ( | error | error.to_string () )?;
+
+	for json_tag in json_tags
+	{
+		let definition : TagDefinition = TagDefinition
+										  {
+											  name : json_tag.name,
+											  table : json_tag.table.unwrap_or_default (),
+											  address : json_tag.address.into_string (),
+											  data_type : json_tag.data_type.unwrap_or_default (),
+											  word_order : json_tag.word_order.unwrap_or_default (),
+											  scale : json_tag.scale.map ( | scale | scale.to_string () ).unwrap_or_default (),
+											  offset : json_tag.offset.map ( | offset | offset.to_string () ).unwrap_or_default (),
+											  minimum : json_tag.minimum.map ( | minimum | minimum.to_string () ).unwrap_or_default (),
+											  maximum : json_tag.maximum.map ( | maximum | maximum.to_string () ).unwrap_or_default (),
+											  unit : json_tag.unit.unwrap_or_default ()
+										  };
+
+		let tag : Tag = definition.create_tag ().map_err ( | message | format! ( "tag {}: {}", definition.name, message ) )?;
+		reply.push ( tag );
+	}
+
+	return Ok( reply );
+}
@@ -1,27 +1,45 @@
 
 
-use std::net::{TcpStream, Shutdown};
+use std::net::TcpStream;
 use std::result::Result;
-use std::time::Duration;
-use network::common::create_tcp_stream;
+use std::time::{Duration, Instant};
+use capture::observer::BoxedFrameObserver;
+use network::common::create_tcp_stream_with_timeout;
+use network::stream::NetworkStream;
 use core::consts::*;
 use core::ethernet::*;
-use core::modbustelegram::*;
 use core::modbusreturn::*;
 use core::methods::*;
+use core::pdu::*;
+use core::statistics::*;
 use core::timehandling::*;
+use core::transport::*;
 use tcp::masteraccess::*;
 use tcp::streamtelegram::*;
+#[cfg( feature = "tls" )]
+use tls::config::TlsClientConfig;
+
+//	===============================================================================================
+
+const TCP_DEFAULT_TIMEOUT : u64 = 500;
 
 //	===============================================================================================
 
 pub struct TcpClient
 {	
 	address : String,	
+	connect_timeout : Option< Duration >,
+	frame_observer : Option< BoxedFrameObserver >,
+	has_connected : bool,
 	last_transaction_id : u16,	
     port : u16,    
-    stream : Option < TcpStream >,     
-	unit_identifier : u8    
+	statistics : ClientStatistics,
+    stream : Option < NetworkStream >,     
+	timeout : Duration,
+	unit_identifier : u8,
+	mask_write_supported : bool,
+	#[cfg( feature = "tls" )]
+	tls_config : Option< TlsClientConfig >
 }
 
 impl TcpClient
@@ -89,13 +107,79 @@ impl TcpClient
 		return TcpClient
 		{
 			address : address.to_string (),
+			connect_timeout : None,
+			frame_observer : None,
+			has_connected : false,
 			last_transaction_id : MODBUS_TRANSACTION_ID_INITIALIZER,
 			port : port,
+			statistics : ClientStatistics::new (),
 			stream : None,
-			unit_identifier : unit_id
+			timeout : Duration::from_millis ( TCP_DEFAULT_TIMEOUT ),
+			unit_identifier : unit_id,
+			mask_write_supported : true,
+			#[cfg( feature = "tls" )]
+			tls_config : None
 		};
 	}
 
+	/// Creates a new `TcpClient` for MODBUS/TCP Security, which connects
+	/// with TLS and authenticates with the certificate of the settings.
+	/// The default port of MODBUS/TCP Security is `MODBUS_TLS_PORT` (802).
+	#[cfg( feature = "tls" )]
+	pub fn new_with_tls ( address : &str, port : u16, tls_config : TlsClientConfig ) -> TcpClient
+	{
+		let mut reply : TcpClient = Self::new_with_port ( address,
+														  port );
+
+		reply.tls_config = Some( tls_config );
+
+		return reply;
+	}
+
+	///	Sets the read and write timeout of the connection (500 ms by default),
+	/// which applies from the next `connect`.
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	///	Sets how long `connect` waits for the connection,
+	/// without a timeout it waits as long as the operating system.
+	pub fn set_connect_timeout ( &mut self, timeout : Option< Duration > )
+	{
+		self.connect_timeout = timeout;
+	}
+
+	///	Sets the observer which gets every frame sent to and received from the device,
+	/// e.g. a `capture::pcap::PcapWriter`.
+	pub fn set_frame_observer ( &mut self, observer : Option< BoxedFrameObserver > )
+	{
+		self.frame_observer = observer;
+	}
+
+	///	Returns a snapshot of the statistics of the requests since the creation or the last reset.
+	pub fn get_statistics ( &self ) -> ClientStatistics
+	{
+		return self.statistics.clone ();
+	}
+
+	pub fn reset_statistics ( &mut self )
+	{
+		self.statistics.reset ();
+	}
+
+	///	Returns true from a successful `connect` until `disconnect`.
+	pub fn is_connected ( &self ) -> bool
+	{
+		return self.stream.is_some ();
+	}
+
+	///	Sets the unit identifier of the following requests.
+	pub fn set_unit_identifier ( &mut self, unit_identifier : u8 )
+	{
+		self.unit_identifier = unit_identifier;
+	}
+
 	///	Opens the connection to the device.
 	/// If the connection is open the `Result` is Ok
 	/// otherwise Err. Err than contais an error message.
@@ -121,26 +205,45 @@ impl TcpClient
 	{
 		let reply : Result< (), String >;
 
-		let connection_result : Result< TcpStream, String > = create_tcp_stream ( &self.address, 
-																				  self.port );
+		let connection_result : Result< TcpStream, String > = create_tcp_stream_with_timeout ( &self.address,
+																							   self.port,
+																							   self.connect_timeout );
 
-		if connection_result.is_ok ()
+		match connection_result
 		{
-			let connection : TcpStream = connection_result.unwrap ();
-
-			let timeout : Duration = Duration::from_millis ( 500 );
-
-			let _ = connection.set_read_timeout ( Some( timeout ) );
-			let _ = connection.set_write_timeout ( Some( timeout ) );
-			let _ = connection.set_nodelay ( true );
-
-			self.stream = Some( connection );
+			Ok( connection )	=>
+			{
+				let _ = connection.set_read_timeout ( Some( self.timeout ) );
+				let _ = connection.set_write_timeout ( Some( self.timeout ) );
+				let _ = connection.set_nodelay ( true );
 
-			reply = Ok( () );
+				match self.create_network_stream ( connection )
+				{
+					Ok( stream )	=>
+					{
+						if self.has_connected
+						{
+							self.statistics.record_reconnect ();
+						}
+
+						self.has_connected = true;
+						self.stream = Some( stream );
+						reply = Ok( () );
+					}
+					Err( message )	=> { reply = Err( message ); }
+				}
+			}
+			Err( message )		=> { reply = Err( message ); }
 		}
-		else
+
+		match reply
 		{
-			reply = Err( connection_result.unwrap_err () );
+			Ok( _ )				=> { log_event! ( info, "connected to {}:{}", self.address, self.port ); }
+			Err( ref message )	=>
+			{
+				log_event! ( warn, "connection to {}:{} failed: {}", self.address, self.port, message );
+				self.statistics.record_error ( ErrorCategory::Connection );
+			}
 		}
 
 		return reply;
@@ -153,29 +256,122 @@ impl TcpClient
 
 		if self.stream.is_some ()
 		{			
-			if let Some( connection ) = self.stream.take ()
+			if let Some( mut connection ) = self.stream.take ()
 			{
-				if let Ok( _ ) = connection.shutdown ( Shutdown:: Both )
+				if connection.shutdown ().is_ok ()
 				{
 					reply = true;
 				}				
+
+				log_event! ( info, "disconnected from {}:{}", self.address, self.port );
 			}
 		}
 
 		return reply;
 	}
 
-	fn process_telegram ( &mut self, request : &Option< ModbusTelegram > ) -> Option< ModbusTelegram >
+	#[cfg( not( feature = "tls" ) )]
+	fn create_network_stream ( &self, connection : TcpStream ) -> Result< NetworkStream, String >
 	{
-		let mut reply : Option< ModbusTelegram > = None;
+		return Ok( NetworkStream::Tcp( connection ) );
+	}
 
-		if let Some( mut stream ) = self.stream.take ()
-		{			
-			reply = process_modbus_telegram ( &mut stream, 
-											  &request );
+	#[cfg( feature = "tls" )]
+	fn create_network_stream ( &self, connection : TcpStream ) -> Result< NetworkStream, String >
+	{
+		match self.tls_config
+		{
+			Some( ref tls_config )	=> { return tls_config.connect ( connection ); }
+			None					=> { return Ok( NetworkStream::Tcp( connection ) ); }
+		}
+	}
 
-			self.stream = Some ( stream );
-			self.update_last_transaction_id ();
+	///	Sends a request PDU (function code and data) to the unit and returns the response PDU.
+	/// Exception responses are returned like all other responses.
+	pub fn send_pdu ( &mut self, unit_identifier : u8, pdu : &[ u8 ] ) -> Result< Vec< u8 >, String >
+	{
+		if self.stream.is_none ()
+		{
+			return Err( "not connected".to_string () );
+		}
+
+		let transaction_identifier : u16 = self.last_transaction_id;
+
+		self.update_last_transaction_id ();
+
+		let request : Vec< u8 > = create_frame ( transaction_identifier,
+												 unit_identifier,
+												 pdu );
+		let response : Vec< u8 > = self.exchange ( &request )?;
+
+		return Ok( response[ MODBUS_HEADER_SIZE as usize.. ].to_vec () );
+	}
+
+	///	Sends a request to the unit and returns the response decoded from the buffer.
+	/// The buffer has to hold the whole response PDU.
+	pub fn send_request< 'a > ( &mut self, unit_identifier : u8, request : &RequestPdu, buffer : &'a mut [ u8 ] ) -> Result< ResponsePdu< 'a >, String >
+	{
+		let pdu : Vec< u8 > = request.to_bytes ()?;
+		let response : Vec< u8 > = self.send_pdu ( unit_identifier,
+												   &pdu )?;
+
+		return Ok( ResponsePdu::decode_into ( &response,
+											  buffer )? );
+	}
+
+	///	Exchanges the frames on the open connection and records the statistics.
+	fn exchange ( &mut self, request : &[ u8 ] ) -> Result< Vec< u8 >, ExchangeError >
+	{
+		let reply : Result< Vec< u8 >, ExchangeError >;
+		let start_time : Instant = Instant::now ();
+		let transaction_identifier : u16 = ( ( request[ 0 ] as u16 ) << 8 ) | request[ 1 ] as u16;
+
+		log_event! ( debug, "request of function 0x{:02X} to unit {} with transaction {}", request[ MODBUS_HEADER_SIZE as usize ], request[ MODBUS_HEADER_SIZE as usize - 1 ], transaction_identifier );
+
+		self.statistics.record_request ( request[ MODBUS_HEADER_SIZE as usize ],
+										 request.len () );
+
+		match self.stream
+		{
+			Some( ref mut stream )	=> { reply = exchange_modbus_frame ( stream, request, &mut self.frame_observer ); }
+			None					=> { reply = Err( ExchangeError::Connection( "not connected".to_string () ) ); }
+		}
+
+		match reply
+		{
+			Ok( ref response )						=>
+			{
+				let function_code : u8 = response[ MODBUS_HEADER_SIZE as usize ];
+
+				log_event! ( debug, "response of function 0x{:02X} with transaction {} after {:?}", function_code, transaction_identifier, start_time.elapsed () );
+
+				self.statistics.record_response ( response.len (),
+												  start_time.elapsed () );
+
+				if function_code & MODBUS_EXCEPTION_FLAG != 0
+				{
+					let exception_code : u8 = response.get ( MODBUS_HEADER_SIZE as usize + 1 ).cloned ().unwrap_or ( 0 );
+
+					log_event! ( warn, "exception response to function 0x{:02X}: {}", function_code & !MODBUS_EXCEPTION_FLAG, get_message_of_exception_code ( exception_code ).unwrap_or_default () );
+
+					self.statistics.record_error ( ErrorCategory::Exception( exception_code ) );
+				}
+			}
+			Err( ExchangeError::Timeout )			=>
+			{
+				log_event! ( warn, "no response to transaction {} within {:?}", transaction_identifier, self.timeout );
+				self.statistics.record_error ( ErrorCategory::Timeout );
+			}
+			Err( ExchangeError::Framing( ref message ) )		=>
+			{
+				log_event! ( warn, "invalid response to transaction {}: {}", transaction_identifier, message );
+				self.statistics.record_error ( ErrorCategory::Framing );
+			}
+			Err( ExchangeError::Connection( ref message ) )	=>
+			{
+				log_event! ( warn, "transaction {} failed: {}", transaction_identifier, message );
+				self.statistics.record_error ( ErrorCategory::Connection );
+			}
 		}
 
 		return reply;
@@ -189,76 +385,120 @@ impl TcpClient
 
 //	===============================================================================================
 
-#[test]
-fn test_count_up_last_transaction_id ()
+/// The connection settings of a `TcpClient`, e.g. loaded from a configuration file.
+/// Port, unit identifier and timeout fall back to the defaults of `TcpClient`.
+#[derive( Clone, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
+pub struct TcpClientConfiguration
+{
+	pub address : String,
+	#[cfg_attr( feature = "serde", serde( default = "get_default_port" ) )]
+	pub port : u16,
+	#[cfg_attr( feature = "serde", serde( default = "get_default_unit_identifier" ) )]
+	pub unit_identifier : u8,
+	#[cfg_attr( feature = "serde", serde( default = "get_default_timeout" ) )]
+	pub timeout_in_milliseconds : u64,
+	#[cfg_attr( feature = "serde", serde( default ) )]
+	pub connect_timeout_in_milliseconds : Option< u64 >
+}
+
+impl TcpClientConfiguration
+{
+	pub fn new ( address : &str ) -> TcpClientConfiguration
+	{
+		return TcpClientConfiguration
+		{
+			address : address.to_string (),
+			port : MODBUS_TCP_PORT,
+			unit_identifier : MODBUS_DEFAULT_UNIT_IDENTIFIER,
+			timeout_in_milliseconds : TCP_DEFAULT_TIMEOUT,
+			connect_timeout_in_milliseconds : None
+		};
+	}
+
+	///	Creates a client with the settings, which is not connected yet.
+	pub fn create_client ( &self ) -> TcpClient
+	{
+		let mut reply : TcpClient = TcpClient::new_with_port_and_unitid ( &self.address,
+																		  self.port,
+																		  self.unit_identifier );
+
+		reply.set_timeout ( Duration::from_millis ( self.timeout_in_milliseconds ) );
+		reply.set_connect_timeout ( self.connect_timeout_in_milliseconds.map ( Duration::from_millis ) );
+
+		return reply;
+	}
+}
+
+#[cfg( feature = "serde" )]
+fn get_default_port () -> u16
 {
-	let test_data_1 : u16 = 0x0001;
-	let result_data_1 : u16 = count_up_last_transaction_id ( test_data_1 );
-	assert_eq! ( result_data_1, 0x0002 );
+	return MODBUS_TCP_PORT;
+}
 
-	let test_data_2 : u16 = 0xFFFF;
-	let result_data_2 : u16 = count_up_last_transaction_id ( test_data_2 );
-	assert_eq! ( result_data_2, 0x0001 );
+#[cfg( feature = "serde" )]
+fn get_default_unit_identifier () -> u8
+{
+	return MODBUS_DEFAULT_UNIT_IDENTIFIER;
 }
 
-fn count_up_last_transaction_id ( last_transaction_id : u16 ) -> u16
+#[cfg( feature = "serde" )]
+fn get_default_timeout () -> u64
 {
-	let reply : u16;
+	return TCP_DEFAULT_TIMEOUT;
+}
 
-	if last_transaction_id == 0xFFFF
+//	===============================================================================================
+
+impl TelegramTransport for TcpClient
+{
+	fn get_unit_identifier ( &self ) -> u8
 	{
-		reply = MODBUS_TRANSACTION_ID_INITIALIZER;
+		return self.unit_identifier;
 	}
-	else
+
+	fn process_pdu ( &mut self, request : &[ u8 ] ) -> Result< Vec< u8 >, String >
 	{
-		reply = last_transaction_id + 1;
+		let unit_identifier : u8 = self.unit_identifier;
+		let reply : Vec< u8 > = self.send_pdu ( unit_identifier,
+												 request )?;
+
+		if let Err( error ) = ResponsePdu::decode ( &reply )
+		{
+			self.statistics.record_error ( ErrorCategory::Framing );
+
+			return Err( error.to_string () );
+		}
+
+		return Ok( reply );
 	}
 
-	return reply;
+	fn is_mask_write_supported ( &self ) -> bool
+	{
+		return self.mask_write_supported;
+	}
+
+	fn set_mask_write_supported ( &mut self, supported : bool )
+	{
+		self.mask_write_supported = supported;
+	}
 }
 
 //	===============================================================================================
 
-impl EthernetMaster for TcpClient
+impl< T : TelegramTransport > EthernetMaster for T
 {
 	fn read_coils ( &mut self, starting_address : u16, quantity_of_coils : u16 ) -> ModbusReturnCoils
 	{
 		let reply : ModbusReturnCoils;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result < ModbusTelegram, String > = create_request_read_coils ( self.last_transaction_id, 
-																							   self.unit_identifier, 
-																							   starting_address, 
-																							   quantity_of_coils );
-	
-		if request_telegram.is_ok ()
-		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
+		let request : RequestPdu = RequestPdu::ReadCoils { address : starting_address, quantity : quantity_of_coils };
 
-			if let Some( response ) = self.process_telegram ( &request )
-			{
-				if verify_function_code ( &request.unwrap (), 
-										  &response )
-				{
-					let response_data : Vec< bool > = prepare_response_read_coils ( &response.get_payload ().unwrap (),
-																					quantity_of_coils );
-
-					reply = process_response_of_coils ( response_data,
-														&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}				
-			}
-			else
-			{
-				reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
+		match process_request ( self, &request, get_coils_of_response )
 		{
-			reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_coils ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnCoils::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -269,39 +509,12 @@ impl EthernetMaster for TcpClient
 		let reply : ModbusReturnCoils;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_read_discrete_inputs ( self.last_transaction_id, 
-																					 		   			self.unit_identifier, 
-																					 		   			starting_address, 
-																					 		   			quantity_of_inputs );
-	
-		if request_telegram.is_ok ()
-		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
-
-			if let Some( response ) = self.process_telegram ( &request ) 
-			{					
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< bool > = prepare_response_read_discrete_inputs ( &response.get_payload ().unwrap (),
-																							  quantity_of_inputs );
+		let request : RequestPdu = RequestPdu::ReadDiscreteInputs { address : starting_address, quantity : quantity_of_inputs };
 
-					reply = process_response_of_coils ( response_data,
-														&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
+		match process_request ( self, &request, get_coils_of_response )
 		{
-			reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_coils ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnCoils::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -312,41 +525,15 @@ impl EthernetMaster for TcpClient
 		let reply : ModbusReturnRegisters;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_read_holding_registers ( self.last_transaction_id, 
-																										  self.unit_identifier,
-																										  starting_address,
-																										  quantity_of_registers );
+		let request : RequestPdu = RequestPdu::ReadHoldingRegisters { address : starting_address, quantity : quantity_of_registers };
 
-		if request_telegram.is_ok ()
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
-
-			if let Some( response ) = self.process_telegram ( &request )
-			{				
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< u16 > = prepare_response_read_holding_registers ( &response.get_payload ().unwrap () );
-
-					reply = process_response_of_registers ( response_data,
-															&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
-		{
-			reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( &request_telegram.err ( ).unwrap ( ) ) );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
 		}
 
-		return reply;		
+		return reply;
 	}
 
 	fn read_input_registers ( &mut self, starting_address : u16, quantity_of_input_registers : u16 ) -> ModbusReturnRegisters
@@ -354,38 +541,12 @@ impl EthernetMaster for TcpClient
 		let reply : ModbusReturnRegisters;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_read_input_registers ( self.last_transaction_id, 
-																							   			self.unit_identifier, 
-																							   			starting_address, 
-																							   			quantity_of_input_registers );
+		let request : RequestPdu = RequestPdu::ReadInputRegisters { address : starting_address, quantity : quantity_of_input_registers };
 
-		if request_telegram.is_ok ()
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
-
-			if let Some( response ) = self.process_telegram ( &request )
-			{
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< u16 > = prepare_response_read_input_registers ( &response.get_payload ().unwrap () );
-
-					reply = process_response_of_registers ( response_data,
-															&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
-		{
-			reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -396,38 +557,23 @@ impl EthernetMaster for TcpClient
 		let reply : ModbusReturnCoils;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_write_single_coil ( self.last_transaction_id, 
-																						    		 self.unit_identifier, 
-																						    		 output_address, 
-																						    		 output_value );
 
-		if request_telegram.is_ok ()
+		match output_value
 		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
-
-			if let Some( response ) = self.process_telegram ( &request )
+			0xFF00 | 0x0000	=>
 			{
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< bool > = prepare_response_write_single_coil ( &response.get_payload ().unwrap () );
+				let request : RequestPdu = RequestPdu::WriteSingleCoil { address : output_address, value : output_value == 0xFF00 };
 
-					reply = process_response_of_coils ( response_data,
-														&start_time );
-				}
-				else
+				match process_request ( self, &request, get_coil_of_response )
 				{
-					reply = ModbusReturnCoils::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
+					Ok( response_data )	=> { reply = process_response_of_coils ( response_data, &start_time ); }
+					Err( return_bad )	=> { reply = ModbusReturnCoils::Bad( return_bad ); }
+				}
 			}
-			else
+			_				=>
 			{
-				reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}			
-		}
-		else
-		{
-			reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+				reply = ModbusReturnCoils::Bad( create_return_bad_of_invalid_request ( &PduError::IllegalCoilValue( output_value ).to_string () ) );
+			}
 		}
 
 		return reply;
@@ -438,38 +584,12 @@ impl EthernetMaster for TcpClient
 		let reply : ModbusReturnRegisters;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_write_single_register ( self.last_transaction_id, 
-																						    			 self.unit_identifier, 
-																						    			 register_address, 
-																						    			 register_value );
+		let request : RequestPdu = RequestPdu::WriteSingleRegister { address : register_address, value : register_value };
 
-		if request_telegram.is_ok ()
-		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
-			
-			if let Some( response ) = self.process_telegram ( &request )
-			{
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< u16 > = prepare_response_write_single_register ( &response.get_payload ().unwrap () );
-
-					reply = process_response_of_registers ( response_data,
-															&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}			
-		}
-		else
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -477,42 +597,16 @@ impl EthernetMaster for TcpClient
 
 	fn write_multiple_coils ( &mut self, starting_address : u16, quantity_of_outputs : u16, outputs_value : Vec< u8 > ) -> ModbusReturnRegisters
 	{
-		let reply: ModbusReturnRegisters;
+		let reply : ModbusReturnRegisters;
 
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_write_multiple_coils ( self.last_transaction_id, 
-																						       			self.unit_identifier, 
-																						       			starting_address, 
-																						       			quantity_of_outputs,
-																							   			outputs_value );
-
-		if request_telegram.is_ok ()
-		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
+		let request : RequestPdu = RequestPdu::WriteMultipleCoils { address : starting_address,
+																	values : Coils::Packed { bytes : &outputs_value, quantity : quantity_of_outputs as usize } };
 
-			if let Some( response ) = self.process_telegram ( &request )
-			{
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< u16 > = prepare_response_write_multiple_coils ( &response.get_payload ().unwrap () );
-
-					reply = process_response_of_registers ( response_data,
-															&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -520,41 +614,31 @@ impl EthernetMaster for TcpClient
 
 	fn write_multiple_registers ( &mut self, starting_address : u16, register_values : Vec< u16 > ) -> ModbusReturnRegisters
 	{
-		let reply: ModbusReturnRegisters;
-		
+		let reply : ModbusReturnRegisters;
+
 		let start_time : Timestamp = Timestamp::new ();
-		let request_telegram : Result< ModbusTelegram, String > = create_request_write_multiple_registers ( self.last_transaction_id, 
-																								   			self.unit_identifier, 
-																								   			starting_address, 
-																								   			register_values );
+		let request : RequestPdu = RequestPdu::WriteMultipleRegisters { address : starting_address, values : Registers::Words( &register_values ) };
 
-		if request_telegram.is_ok ()
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			let request : Option< ModbusTelegram > = Some( request_telegram.unwrap () );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
+		}
 
-			if let Some( response ) = self.process_telegram ( &request )
-			{
-				if verify_function_code ( &request.unwrap (),
-										  &response )
-				{
-					let response_data : Vec< u16 > = prepare_response_write_multiple_registers ( &response.get_payload ().unwrap () );
+		return reply;
+	}
 
-					reply = process_response_of_registers ( response_data,
-															&start_time );
-				}
-				else
-				{
-					reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( response.get_function_code ().unwrap (), 1 ) );
-				}					
-			}
-			else
-			{
-				reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "created modbus telegram is invalid" ) );
-			}
-		}
-		else
+	fn mask_write_register ( &mut self, reference_address : u16, and_mask : u16, or_mask : u16 ) -> ModbusReturnRegisters
+	{
+		let reply : ModbusReturnRegisters;
+
+		let start_time : Timestamp = Timestamp::new ();
+		let request : RequestPdu = RequestPdu::MaskWriteRegister { address : reference_address, and_mask : and_mask, or_mask : or_mask };
+
+		match process_request ( self, &request, get_registers_of_response )
 		{
-			reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( &request_telegram.err ().unwrap () ) );
+			Ok( response_data )	=> { reply = process_response_of_registers ( response_data, &start_time ); }
+			Err( return_bad )	=> { reply = ModbusReturnRegisters::Bad( return_bad ); }
 		}
 
 		return reply;
@@ -563,6 +647,238 @@ impl EthernetMaster for TcpClient
 
 //	===============================================================================================
 
+#[cfg( test )]
+struct ResponseTransport
+{
+	response : Vec< u8 >
+}
+
+#[cfg( test )]
+impl TelegramTransport for ResponseTransport
+{
+	fn get_unit_identifier ( &self ) -> u8
+	{
+		return 0x01;
+	}
+
+	fn process_pdu ( &mut self, _request : &[ u8 ] ) -> Result< Vec< u8 >, String >
+	{
+		return Ok( self.response.clone () );
+	}
+
+	fn is_mask_write_supported ( &self ) -> bool
+	{
+		return true;
+	}
+
+	fn set_mask_write_supported ( &mut self, _supported : bool )
+	{
+	}
+}
+
+#[test]
+fn test_process_request ()
+{
+	let mut transport : ResponseTransport = ResponseTransport { response : vec![ 0x03, 0x02, 0x00, 0x0A ] };
+	assert! ( EthernetMaster::read_holding_registers ( &mut transport, 0x0000, 1 ).is_good () );
+	assert! ( EthernetMaster::read_input_registers ( &mut transport, 0x0000, 1 ).is_bad () );
+	assert! ( EthernetMaster::read_holding_registers ( &mut transport, 0x0000, 2 ).is_bad () );
+
+	transport.response = vec![ 0x10, 0x00, 0x00, 0x00, 0x01 ];
+	assert! ( EthernetMaster::read_holding_registers ( &mut transport, 0x0000, 1 ).is_bad () );
+	assert! ( EthernetMaster::write_multiple_registers ( &mut transport, 0x0000, vec![ 0x000A ] ).is_good () );
+	assert! ( EthernetMaster::write_multiple_registers ( &mut transport, 0x0001, vec![ 0x000A ] ).is_bad () );
+
+	transport.response = vec![ 0x02, 0x01, 0x05 ];
+	assert! ( EthernetMaster::read_discrete_inputs ( &mut transport, 0x0000, 3 ).is_good () );
+	assert! ( EthernetMaster::read_coils ( &mut transport, 0x0000, 3 ).is_bad () );
+	assert! ( EthernetMaster::read_discrete_inputs ( &mut transport, 0x0000, 9 ).is_bad () );
+
+	transport.response = vec![ 0x83, 0x02 ];
+
+	match EthernetMaster::read_holding_registers ( &mut transport, 0x0000, 1 )
+	{
+		ModbusReturnRegisters::Bad( return_bad )	=> { assert_eq! ( return_bad.get_exception_code (), 0x02 ); }
+		_											=> { panic! ( "exception response is not bad" ); }
+	}
+}
+
+///	Sends the request over the transport and returns the data, which `get_data`
+/// takes out of the response. Responses with another function code, exceptions
+/// and responses which do not match the request are returned as `ReturnBad`.
+fn process_request< T, D, F > ( transport : &mut T, request : &RequestPdu, get_data : F ) -> Result< Vec< D >, ReturnBad >
+	where T : TelegramTransport, F : Fn ( &RequestPdu, &ResponsePdu ) -> Option< Vec< D > >
+{
+	let pdu : Vec< u8 >;
+
+	match request.to_bytes ()
+	{
+		Ok( bytes )		=> { pdu = bytes; }
+		Err( message )	=> { return Err( create_return_bad_of_invalid_request ( &message ) ); }
+	}
+
+	let response_pdu : Vec< u8 > = transport.process_pdu ( &pdu ).map_err ( | message | ReturnBad::new_with_message ( &message ) )?;
+	let response : ResponsePdu = ResponsePdu::decode ( &response_pdu ).map_err ( | error | ReturnBad::new_with_message ( &error.to_string () ) )?;
+
+	if response.get_function_code () != request.get_function_code ()
+	{
+		return Err( create_return_bad_of_response ( &response ) );
+	}
+
+	match get_data ( request, &response )
+	{
+		Some( data )	=> { return Ok( data ); }
+		None			=> { return Err( create_return_bad_of_mismatch ( &response ) ); }
+	}
+}
+
+#[test]
+fn test_get_coils_of_response ()
+{
+	let request : RequestPdu = RequestPdu::ReadCoils { address : 0x0000, quantity : 10 };
+	assert_eq! ( get_coils_of_response ( &request, &ResponsePdu::ReadCoils { values : Coils::Packed { bytes : &[ 0x05, 0x02 ], quantity : 16 } } ),
+				 Some( vec![ true, false, true, false, false, false, false, false, false, true ] ) );
+	assert_eq! ( get_coils_of_response ( &request, &ResponsePdu::ReadCoils { values : Coils::Packed { bytes : &[ 0x05 ], quantity : 8 } } ), None );
+	assert_eq! ( get_coils_of_response ( &request, &ResponsePdu::ReadDiscreteInputs { values : Coils::Packed { bytes : &[ 0x05, 0x02 ], quantity : 16 } } ), None );
+}
+
+///	Returns the requested coils or discrete inputs of a read response,
+/// None if the response holds another count of bytes.
+fn get_coils_of_response ( request : &RequestPdu, response : &ResponsePdu ) -> Option< Vec< bool > >
+{
+	let reply : Option< Vec< bool > >;
+
+	match ( request, response )
+	{
+		( &RequestPdu::ReadCoils { quantity, .. }, &ResponsePdu::ReadCoils { ref values } ) |
+		( &RequestPdu::ReadDiscreteInputs { quantity, .. }, &ResponsePdu::ReadDiscreteInputs { ref values } )
+			if values.len () == ( quantity as usize ).div_ceil ( 8 ) * 8		=> { reply = Some( values.iter ().take ( quantity as usize ).collect () ); }
+		_																		=> { reply = None; }
+	}
+
+	return reply;
+}
+
+///	Returns the coil of a write single coil response, None if it does not echo the request.
+fn get_coil_of_response ( request : &RequestPdu, response : &ResponsePdu ) -> Option< Vec< bool > >
+{
+	let reply : Option< Vec< bool > >;
+
+	match ( request, response )
+	{
+		( &RequestPdu::WriteSingleCoil { address : request_address, value : request_value },
+		  &ResponsePdu::WriteSingleCoil { address, value } ) if address == request_address && value == request_value	=> { reply = Some( vec![ value ] ); }
+		_																													=> { reply = None; }
+	}
+
+	return reply;
+}
+
+#[test]
+fn test_get_registers_of_response ()
+{
+	let read_request : RequestPdu = RequestPdu::ReadHoldingRegisters { address : 0x0000, quantity : 2 };
+	assert_eq! ( get_registers_of_response ( &read_request, &ResponsePdu::ReadHoldingRegisters { values : Registers::Bytes( &[ 0x00, 0x0A, 0x01, 0x02 ] ) } ), Some( vec![ 0x000A, 0x0102 ] ) );
+	assert_eq! ( get_registers_of_response ( &read_request, &ResponsePdu::ReadHoldingRegisters { values : Registers::Bytes( &[ 0x00, 0x0A ] ) } ), None );
+	assert_eq! ( get_registers_of_response ( &read_request, &ResponsePdu::ReadInputRegisters { values : Registers::Bytes( &[ 0x00, 0x0A, 0x01, 0x02 ] ) } ), None );
+	assert_eq! ( get_registers_of_response ( &read_request, &ResponsePdu::WriteMultipleRegisters { address : 0x0000, quantity : 0x0002 } ), None );
+
+	let write_request : RequestPdu = RequestPdu::WriteSingleRegister { address : 0x0001, value : 0x0003 };
+	assert_eq! ( get_registers_of_response ( &write_request, &ResponsePdu::WriteSingleRegister { address : 0x0001, value : 0x0003 } ), Some( vec![ 0x0001, 0x0003 ] ) );
+	assert_eq! ( get_registers_of_response ( &write_request, &ResponsePdu::WriteSingleRegister { address : 0x0001, value : 0x0004 } ), None );
+
+	let coils_request : RequestPdu = RequestPdu::WriteMultipleCoils { address : 0x0001, values : Coils::Unpacked( &[ true, false, true ] ) };
+	assert_eq! ( get_registers_of_response ( &coils_request, &ResponsePdu::WriteMultipleCoils { address : 0x0001, quantity : 0x0003 } ), Some( vec![ 0x0001, 0x0003 ] ) );
+	assert_eq! ( get_registers_of_response ( &coils_request, &ResponsePdu::WriteMultipleCoils { address : 0x0001, quantity : 0x0002 } ), None );
+
+	let mask_request : RequestPdu = RequestPdu::MaskWriteRegister { address : 0x0004, and_mask : 0x00F2, or_mask : 0x0025 };
+	assert_eq! ( get_registers_of_response ( &mask_request, &ResponsePdu::MaskWriteRegister { address : 0x0004, and_mask : 0x00F2, or_mask : 0x0025 } ), Some( vec![ 0x0004, 0x00F2, 0x0025 ] ) );
+	assert_eq! ( get_registers_of_response ( &mask_request, &ResponsePdu::MaskWriteRegister { address : 0x0004, and_mask : 0x00F2, or_mask : 0x0024 } ), None );
+	assert_eq! ( get_registers_of_response ( &mask_request, &ResponsePdu::WriteSingleCoil { address : 0x0001, value : true } ), None );
+}
+
+///	Returns the requested registers of a read response, or the echoed fields of a write response.
+/// None if the response holds another count of registers or does not echo the request.
+fn get_registers_of_response ( request : &RequestPdu, response : &ResponsePdu ) -> Option< Vec< u16 > >
+{
+	let reply : Option< Vec< u16 > >;
+
+	match ( request, response )
+	{
+		( &RequestPdu::ReadHoldingRegisters { quantity, .. }, &ResponsePdu::ReadHoldingRegisters { ref values } ) |
+		( &RequestPdu::ReadInputRegisters { quantity, .. }, &ResponsePdu::ReadInputRegisters { ref values } )
+			if values.len () == quantity as usize																=> { reply = Some( values.to_vec () ); }
+		( &RequestPdu::WriteSingleRegister { address : request_address, value : request_value },
+		  &ResponsePdu::WriteSingleRegister { address, value } )
+			if address == request_address && value == request_value												=> { reply = Some( vec![ address, value ] ); }
+		( &RequestPdu::WriteMultipleCoils { address : request_address, ref values },
+		  &ResponsePdu::WriteMultipleCoils { address, quantity } )
+			if address == request_address && quantity as usize == values.len ()									=> { reply = Some( vec![ address, quantity ] ); }
+		( &RequestPdu::WriteMultipleRegisters { address : request_address, ref values },
+		  &ResponsePdu::WriteMultipleRegisters { address, quantity } )
+			if address == request_address && quantity as usize == values.len ()									=> { reply = Some( vec![ address, quantity ] ); }
+		( &RequestPdu::MaskWriteRegister { address : request_address, and_mask : request_and_mask, or_mask : request_or_mask },
+		  &ResponsePdu::MaskWriteRegister { address, and_mask, or_mask } )
+			if address == request_address && and_mask == request_and_mask && or_mask == request_or_mask		=> { reply = Some( vec![ address, and_mask, or_mask ] ); }
+		_																										=> { reply = None; }
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_create_return_bad_of_response ()
+{
+	let result_1 : ReturnBad = create_return_bad_of_response ( &ResponsePdu::WriteSingleRegister { address : 0x0001, value : 0x0003 } );
+	assert_eq! ( result_1.get_error_code (), FUNCTION_CODE_WRITE_SINGLE_REGISTER );
+	assert_eq! ( result_1.get_exception_code (), MODBUS_EXCEPTION_ILLEGAL_FUNCTION );
+
+	let result_2 : ReturnBad = create_return_bad_of_response ( &ResponsePdu::Exception { function_code : 0x03, exception_code : 0x02 } );
+	assert_eq! ( result_2.get_error_code (), 0x83 );
+	assert_eq! ( result_2.get_exception_code (), 0x02 );
+}
+
+///	Creates the `ReturnBad` of a response with an unexpected function code.
+/// Exception responses keep their exception code, all others report an illegal function.
+fn create_return_bad_of_response ( response : &ResponsePdu ) -> ReturnBad
+{
+	let exception_code : u8;
+
+	match *response
+	{
+		ResponsePdu::Exception { exception_code : code, .. }	=> { exception_code = code; }
+		_														=>
+		{
+			log_event! ( warn, "response with unexpected function code 0x{:02X}", response.get_function_code () );
+			exception_code = MODBUS_EXCEPTION_ILLEGAL_FUNCTION;
+		}
+	}
+
+	return ReturnBad::new_with_codes ( response.get_function_code (),
+									   exception_code );
+}
+
+///	Creates the `ReturnBad` of a response with the function code of the request,
+/// but with data which do not match the request.
+fn create_return_bad_of_mismatch ( response : &ResponsePdu ) -> ReturnBad
+{
+	log_event! ( warn, "response with function code 0x{:02X} does not match the request", response.get_function_code () );
+
+	return ReturnBad::new_with_message ( "modbus response does not match the request" );
+}
+
+///	Creates the `ReturnBad` of a request which could not be created, e.g. with an invalid quantity.
+fn create_return_bad_of_invalid_request ( message : &str ) -> ReturnBad
+{
+	log_event! ( warn, "invalid request: {}", message );
+
+	return ReturnBad::new_with_message ( message );
+}
+
+//	===============================================================================================
+
 #[test]
 fn test_response_of_coils ()
 {
@@ -583,13 +899,14 @@ fn process_response_of_coils ( response_data : Vec< bool >, start_time : &Timest
 {
 	let reply : ModbusReturnCoils;
 
-	if response_data.len () > 0
+	if !response_data.is_empty ()
 	{
 		reply = ModbusReturnCoils::Good( ReturnGood::new ( response_data, 
 														   start_time.elapsed_milliseconds () ) );
 	}
 	else
 	{
+		log_event! ( warn, "response without coils" );
 		reply = ModbusReturnCoils::Bad( ReturnBad::new_with_message ( "modbus response data is invalid" ) );
 	}
 
@@ -618,13 +935,14 @@ fn process_response_of_registers ( response_data : Vec< u16 >, start_time : &Tim
 {
 	let reply : ModbusReturnRegisters;
 
-	if response_data.len () > 0
+	if !response_data.is_empty ()
 	{
 		reply = ModbusReturnRegisters::Good( ReturnGood::new ( response_data, 
 															   start_time.elapsed_milliseconds () ) );
 	}
 	else
 	{
+		log_event! ( warn, "response without registers" );
 		reply = ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "modbus response data is invalid" ) );
 	}
 
@@ -633,7 +951,7 @@ fn process_response_of_registers ( response_data : Vec< u16 >, start_time : &Tim
 
 //	===============================================================================================
 
-impl MasterAccess for TcpClient
+impl< T : TelegramTransport > MasterAccess for T
 {
 	fn read_coils ( &mut self, address : u16, quantity : u16 ) -> Vec< CoilValue >
 	{
@@ -744,7 +1062,7 @@ impl MasterAccess for TcpClient
 	{
 		let mut reply : bool = false;
 
-		if coils.len () > 0
+		if !coils.is_empty ()
 		{
 			let values : Vec< u8 > = transform_coils_to_bytearray ( &coils );
 			let response : ModbusReturnRegisters = EthernetMaster::write_multiple_coils ( self, 
@@ -766,6 +1084,167 @@ impl MasterAccess for TcpClient
 
 		return response.is_good ();
 	}
+
+	fn read_register_bit ( &mut self, address : u16, bit : u8 ) -> Option< bool >
+	{
+		return read_register_bit_of_master ( self,
+											 address,
+											 bit );
+	}
+
+	fn write_register_bit ( &mut self, address : u16, bit : u8, value : bool ) -> bool
+	{
+		let mut mask_write_supported : bool = self.is_mask_write_supported ();
+
+		let reply : bool = write_register_bit_of_master ( self,
+														  address,
+														  bit,
+														  value,
+														  &mut mask_write_supported );
+
+		self.set_mask_write_supported ( mask_write_supported );
+
+		return reply;
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::testmaster::TestMaster;
+
+#[test]
+fn test_read_register_bit_of_master ()
+{
+	let mut master : TestMaster = TestMaster::new ( vec![ 0x8001 ] );
+
+	assert_eq! ( read_register_bit_of_master ( &mut master, 0, 0 ), Some( true ) );
+	assert_eq! ( read_register_bit_of_master ( &mut master, 0, 1 ), Some( false ) );
+	assert_eq! ( read_register_bit_of_master ( &mut master, 0, 15 ), Some( true ) );
+	assert_eq! ( read_register_bit_of_master ( &mut master, 0, 16 ), None );
+}
+
+fn read_register_bit_of_master< T : EthernetMaster > ( master : &mut T, address : u16, bit : u8 ) -> Option< bool >
+{
+	let mut reply : Option< bool > = None;
+
+	if bit < 16
+	{
+		let registers : Vec< u16 > = transform_modbus_return_registers ( master.read_holding_registers ( address,
+																										 1 ) );
+
+		if let Some( bits ) = prepare_register_bits ( &registers ).first ()
+		{
+			reply = Some( bits[ bit as usize ] );
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_write_register_bit_of_master ()
+{
+	let mut master : TestMaster = TestMaster::new ( vec![ 0x0012 ] );
+	let mut mask_write_supported : bool = true;
+
+	assert! ( write_register_bit_of_master ( &mut master, 0, 0, true, &mut mask_write_supported ) );
+	assert_eq! ( master.registers[ 0 ], 0x0013 );
+	assert_eq! ( master.requests.last (), Some( &( FUNCTION_CODE_MASK_WRITE_REGISTER, 0, 1 ) ) );
+	assert! ( mask_write_supported );
+
+	master.mask_write_supported = false;
+
+	assert! ( write_register_bit_of_master ( &mut master, 0, 4, false, &mut mask_write_supported ) );
+	assert_eq! ( master.registers[ 0 ], 0x0003 );
+	assert! ( !mask_write_supported );
+	assert_eq! ( master.requests.len (), 4 );
+	assert_eq! ( master.requests[ 2 ].0, FUNCTION_CODE_READ_HOLDING_REGISTERS );
+	assert_eq! ( master.requests[ 3 ].0, FUNCTION_CODE_WRITE_SINGLE_REGISTER );
+
+	assert! ( write_register_bit_of_master ( &mut master, 0, 15, true, &mut mask_write_supported ) );
+	assert_eq! ( master.registers[ 0 ], 0x8003 );
+	assert_eq! ( master.requests.len (), 6 );
+
+	assert! ( !write_register_bit_of_master ( &mut master, 0, 16, true, &mut mask_write_supported ) );
+	assert! ( !write_register_bit_of_master ( &mut master, 1, 0, true, &mut mask_write_supported ) );
+}
+
+///	Sets a bit with a mask write register request (FC 22). Devices which answer
+/// with an illegal function exception are remembered in `mask_write_supported`
+/// and get a read-modify-write with FC 3 and FC 6 instead. The read-modify-write
+/// is not atomic, the device may change other bits of the register in between.
+fn write_register_bit_of_master< T : EthernetMaster > ( master : &mut T, address : u16, bit : u8, value : bool, mask_write_supported : &mut bool ) -> bool
+{
+	let mut reply : bool = false;
+
+	if let Some( ( and_mask, or_mask ) ) = prepare_register_bit_masks ( bit,
+																	   value )
+	{
+		let mut use_fallback : bool = !*mask_write_supported;
+
+		if *mask_write_supported
+		{
+			let response : ModbusReturnRegisters = master.mask_write_register ( address,
+																				and_mask,
+																				or_mask );
+
+			if response.is_good ()
+			{
+				reply = true;
+			}
+			else if is_illegal_function_response ( &response )
+			{
+				log_event! ( info, "mask write register not supported, writing register {} by read and write", address );
+				*mask_write_supported = false;
+				use_fallback = true;
+			}
+		}
+
+		if use_fallback
+		{
+			let registers : Vec< u16 > = transform_modbus_return_registers ( master.read_holding_registers ( address,
+																											 1 ) );
+
+			if let Some( register ) = registers.first ()
+			{
+				let response : ModbusReturnRegisters = master.write_single_register ( address,
+																					  apply_register_masks ( *register,
+																											 and_mask,
+																											 or_mask ) );
+				reply = response.is_good ();
+			}
+		}
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_is_illegal_function_response ()
+{
+	assert! ( is_illegal_function_response ( &ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( 0x96, 0x01 ) ) ) );
+	assert! ( !is_illegal_function_response ( &ModbusReturnRegisters::Bad( ReturnBad::new_with_codes ( 0x96, 0x02 ) ) ) );
+	assert! ( !is_illegal_function_response ( &ModbusReturnRegisters::Bad( ReturnBad::new_with_message ( "timeout" ) ) ) );
+	assert! ( !is_illegal_function_response ( &ModbusReturnRegisters::None ) );
+}
+
+fn is_illegal_function_response ( response : &ModbusReturnRegisters ) -> bool
+{
+	let reply : bool;
+
+	match response
+	{
+		ModbusReturnRegisters::Bad( bad )	=> { reply = bad.get_error_code () & MODBUS_EXCEPTION_FLAG != 0 &&
+													 bad.get_exception_code () == MODBUS_EXCEPTION_ILLEGAL_FUNCTION; }
+		_									=> { reply = false; }
+	}
+
+	return reply;
 }
 
 //	===============================================================================================
@@ -830,3 +1309,67 @@ fn transform_modbus_return_registers ( returned_registers : ModbusReturnRegister
 
 	return reply;
 }
+
+//	===============================================================================================
+
+#[cfg( test )]
+use server::datastore::DataStore;
+#[cfg( test )]
+use server::tcpserver::TcpServer;
+#[cfg( all( test, feature = "json" ) )]
+use serde_json;
+
+#[test]
+fn test_tcp_client_statistics ()
+{
+	let server : TcpServer = TcpServer::start ( "127.0.0.1:0", DataStore::new_dense ( 10 ) ).unwrap ();
+	let mut client : TcpClient = TcpClient::new_with_port ( "127.0.0.1", server.get_local_address ().port () );
+
+	assert! ( !client.is_connected () );
+	assert! ( client.send_pdu ( 1, &[ 0x03, 0x00, 0x00, 0x00, 0x01 ] ).is_err () );
+	assert_eq! ( client.last_transaction_id, MODBUS_TRANSACTION_ID_INITIALIZER );
+	assert_eq! ( client.get_statistics (), ClientStatistics::new () );
+
+	client.connect ().unwrap ();
+
+	assert! ( client.is_connected () );
+	assert! ( EthernetMaster::read_holding_registers ( &mut client, 0, 2 ).is_good () );
+	assert! ( client.send_pdu ( 1, &[ 0x03, 0x00, 0x64, 0x00, 0x01 ] ).is_ok () );
+
+	client.disconnect ();
+	client.connect ().unwrap ();
+
+	let statistics : ClientStatistics = client.get_statistics ();
+
+	assert_eq! ( statistics.get_requests_per_function_code ().get ( &0x03 ), Some( &2 ) );
+	assert_eq! ( statistics.get_response_count (), 2 );
+	assert_eq! ( statistics.get_error_count ( ErrorCategory::Exception( 0x02 ) ), 1 );
+	assert_eq! ( statistics.get_bytes_sent (), 24 );
+	assert_eq! ( statistics.get_bytes_received (), 13 + 9 );
+	assert_eq! ( statistics.get_reconnect_count (), 1 );
+	assert! ( statistics.get_maximum_latency ().is_some () );
+
+	client.reset_statistics ();
+
+	assert_eq! ( client.get_statistics (), ClientStatistics::new () );
+
+	client.disconnect ();
+
+	assert! ( !client.is_connected () );
+}
+
+#[cfg( feature = "json" )]
+#[test]
+fn test_tcp_client_configuration ()
+{
+	let configuration : TcpClientConfiguration = serde_json::from_str ( r#"{ "address" : "192.168.0.10", "unit_identifier" : 3 }"# ).unwrap ();
+
+	assert_eq! ( configuration, TcpClientConfiguration { unit_identifier : 3, ..TcpClientConfiguration::new ( "192.168.0.10" ) } );
+
+	let client : TcpClient = configuration.create_client ();
+
+	assert_eq! ( client.get_unit_identifier (), 3 );
+	assert_eq! ( client.port, MODBUS_TCP_PORT );
+	assert_eq! ( client.timeout, Duration::from_millis ( TCP_DEFAULT_TIMEOUT ) );
+	assert_eq! ( client.connect_timeout, None );
+}
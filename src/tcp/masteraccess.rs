@@ -8,6 +8,7 @@ const WRITE_SINGLE_COIL_ON : u16 = 0xFF00;
 //  ===============================================================================================
 
 #[derive ( Clone, Copy, Debug, PartialEq )]
+#[cfg_attr( feature = "serde", derive( Serialize, Deserialize ) )]
 pub enum CoilValue
 {
     Off,
@@ -318,6 +319,84 @@ pub trait MasterAccess
     /// }
     /// ```
     fn write_multiple_registers ( &mut self, address : u16, values : Vec< u16 > ) -> bool;
+
+    /// Reads a single bit (0 to 15) of a holding register.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// 
+    /// use modbus_iiot::tcp::master::TcpClient;
+    /// use modbus_iiot::tcp::masteraccess::MasterAccess;
+    /// 
+    /// let mut client = TcpClient::new("127.0.0.1");
+    ///
+    /// if let Err(message) = client.connect()
+    /// {
+    ///     println!("failure = {}", message);
+    /// }
+    /// else
+    /// {
+    ///     let response = client.read_register_bit(64, 3);
+    ///     println!("Response of read_register_bit: {:?}", response);
+    /// 
+    ///     client.disconnect();    
+    /// }
+    /// ```
+    fn read_register_bit ( &mut self, address : u16, bit : u8 ) -> Option< bool >
+    {
+        let mut reply : Option< bool > = None;
+
+        if bit < 16
+        {
+            reply = self.read_holding_registers ( address, 1 ).first ().map ( | register | register & ( 1 << bit ) != 0 );
+        }
+
+        return reply;
+    }
+
+    /// Sets a single bit (0 to 15) of a holding register and leaves the others unchanged.
+    /// Uses MODBUS Function Code 22 (0x16) and falls back to a read-modify-write
+    /// with Function Code 3 and 6 for devices which do not support it. The default
+    /// implementation always does the read-modify-write.
+    /// 
+    /// # Example
+    /// 
+    /// ```rust,no_run
+    /// 
+    /// use modbus_iiot::tcp::master::TcpClient;
+    /// use modbus_iiot::tcp::masteraccess::MasterAccess;
+    /// 
+    /// let mut client = TcpClient::new("127.0.0.1");
+    ///
+    /// if let Err(message) = client.connect()
+    /// {
+    ///     println!("failure = {}", message);
+    /// }
+    /// else
+    /// {
+    ///     client.write_register_bit(64, 3, true);
+    /// 
+    ///     client.disconnect();    
+    /// }
+    /// ```
+    fn write_register_bit ( &mut self, address : u16, bit : u8, value : bool ) -> bool
+    {
+        let mut reply : bool = false;
+
+        if bit < 16
+        {
+            if let Some( register ) = self.read_holding_registers ( address, 1 ).first ()
+            {
+                let mask : u16 = 1 << bit;
+
+                reply = self.write_single_register ( address,
+                                                     if value { register | mask } else { register & !mask } );
+            }
+        }
+
+        return reply;
+    }
 }
 
 //  ===============================================================================================
@@ -332,38 +411,43 @@ fn test_transform_coils_to_bytearray ()
     assert_eq! ( result.len (), 2 );
     assert_eq! ( result[ 0 ], 0xCD );
     assert_eq! ( result[ 1 ], 0x01 );
+
+    let result_2 : Vec< u8 > = transform_coils_to_bytearray ( &[ CoilValue::On; 8 ] );
+    assert_eq! ( result_2, vec![ 0xFF ] );
+    assert_eq! ( transform_coils_to_bytearray ( &[] ).len (), 0 );
 }
 
-pub fn transform_coils_to_bytearray ( coils : &Vec< CoilValue > ) -> Vec< u8 >
+pub fn transform_coils_to_bytearray ( coils : &[ CoilValue ] ) -> Vec< u8 >
 {
-	let mut reply : Vec< u8 > = vec![];
+	let mut reply : Vec< u8 > = vec![ 0x00; coils.len ().div_ceil ( 8 ) ];
 
-	if coils.len () > 0
+	for ( index, coil ) in coils.iter ().enumerate ()
 	{
-        reply.push ( 0x00 );
-		let mut index : usize = 0;
-		let mut bit : u8 = 0x00;
-
-		for coil in coils
+		if CoilValue::get ( coil )
 		{
-			if CoilValue::get ( coil )
-			{
-				reply[ index ] |= 1 << bit;
-			}
-			
-			if bit == 7
-			{                
-				reply.push ( 0x00 );
-                
-                index += 1;
-				bit = 0x00;
-			}
-			else
-			{
-				bit += 1;
-			}
+			reply[ index / 8 ] |= 1 << ( index % 8 );
 		}
 	}
 
 	return reply;
 }
+
+//  ===============================================================================================
+
+#[cfg( test )]
+use core::testmaster::TestAccess;
+
+#[test]
+fn test_register_bit_default ()
+{
+    let mut master : TestAccess = TestAccess::new ( vec![ 0x0012 ], vec![] );
+
+    assert_eq! ( master.read_register_bit ( 0, 1 ), Some( true ) );
+    assert_eq! ( master.read_register_bit ( 0, 0 ), Some( false ) );
+    assert_eq! ( master.read_register_bit ( 0, 16 ), None );
+
+    assert! ( master.write_register_bit ( 0, 0, true ) );
+    assert! ( master.write_register_bit ( 0, 4, false ) );
+    assert! ( !master.write_register_bit ( 0, 16, true ) );
+    assert_eq! ( master.registers, vec![ 0x0003 ] );
+}
@@ -2,4 +2,6 @@
 
 pub mod master;
 pub mod masteraccess;
+pub mod scaledaccess;
+pub mod scanner;
 pub mod streamtelegram;
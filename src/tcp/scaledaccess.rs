@@ -0,0 +1,129 @@
+use core::scaling::Scaling;
+use tcp::masteraccess::MasterAccess;
+
+//	===============================================================================================
+
+/// Register access in engineering values, available for every `MasterAccess`.
+///
+/// Values are validated and converted with the `Scaling` of the registers
+/// before a write request is built, so an out of range setpoint never
+/// reaches the device.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::core::scaling::Scaling;
+/// use modbus_iiot::tcp::master::TcpClient;
+/// use modbus_iiot::tcp::scaledaccess::ScaledAccess;
+///
+/// let mut client = TcpClient::new("127.0.0.1");
+///
+/// if client.connect().is_ok()
+/// {
+///     let mut pressure = Scaling::new(0.01, 0.0);
+///     pressure.set_range(0.0, 10.0).unwrap();
+///
+///     println!("pressure = {:?}", client.read_scaled_input_registers(0, 2, &pressure));
+///
+///     if let Err(message) = client.write_scaled_single_register(10, 12.5, &pressure)
+///     {
+///         println!("failure = {}", message);
+///     }
+///
+///     client.disconnect();
+/// }
+/// ```
+pub trait ScaledAccess
+{
+	///	Reads holding registers (FC 3) and returns their engineering values.
+	fn read_scaled_holding_registers ( &mut self, address : u16, quantity : u16, scaling : &Scaling ) -> Vec< f64 >;
+
+	///	Reads input registers (FC 4) and returns their engineering values.
+	fn read_scaled_input_registers ( &mut self, address : u16, quantity : u16, scaling : &Scaling ) -> Vec< f64 >;
+
+	///	Writes a single holding register (FC 6) from an engineering value.
+	fn write_scaled_single_register ( &mut self, address : u16, value : f64, scaling : &Scaling ) -> Result< (), String >;
+
+	///	Writes holding registers (FC 16) from engineering values. Nothing is
+	/// written if one of the values is refused.
+	fn write_scaled_multiple_registers ( &mut self, address : u16, values : &[ f64 ], scaling : &Scaling ) -> Result< (), String >;
+}
+
+impl< T : MasterAccess > ScaledAccess for T
+{
+	fn read_scaled_holding_registers ( &mut self, address : u16, quantity : u16, scaling : &Scaling ) -> Vec< f64 >
+	{
+		return scaling.scale_registers ( &self.read_holding_registers ( address,
+																		quantity ) );
+	}
+
+	fn read_scaled_input_registers ( &mut self, address : u16, quantity : u16, scaling : &Scaling ) -> Vec< f64 >
+	{
+		return scaling.scale_registers ( &self.read_input_registers ( address,
+																	  quantity ) );
+	}
+
+	fn write_scaled_single_register ( &mut self, address : u16, value : f64, scaling : &Scaling ) -> Result< (), String >
+	{
+		let register : u16 = scaling.unscale_register ( value )?;
+
+		return process_write_result ( self.write_single_register ( address,
+																   register ) );
+	}
+
+	fn write_scaled_multiple_registers ( &mut self, address : u16, values : &[ f64 ], scaling : &Scaling ) -> Result< (), String >
+	{
+		let registers : Vec< u16 > = scaling.unscale_registers ( values )?;
+
+		return process_write_result ( self.write_multiple_registers ( address,
+																	  registers ) );
+	}
+}
+
+//	===============================================================================================
+
+fn process_write_result ( written : bool ) -> Result< (), String >
+{
+	let reply : Result< (), String >;
+
+	if written
+	{
+		reply = Ok( () );
+	}
+	else
+	{
+		reply = Err( "write request failed".to_string () );
+	}
+
+	return reply;
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::testmaster::TestAccess;
+
+#[test]
+fn test_scaled_access ()
+{
+	let mut scaling : Scaling = Scaling::new_signed ( 0.1,
+													  0.0 );
+	scaling.set_range ( -50.0, 150.0 ).unwrap ();
+
+	let mut master : TestAccess = TestAccess::new ( vec![ 215, 0xFF9C, 0 ],
+													vec![] );
+
+	assert_eq! ( master.read_scaled_holding_registers ( 0, 2, &scaling ), vec![ 21.5, -10.0 ] );
+	assert_eq! ( master.read_scaled_input_registers ( 1, 1, &scaling ), vec![ -10.0 ] );
+
+	assert_eq! ( master.write_scaled_single_register ( 2, 12.35, &scaling ), Ok( () ) );
+	assert_eq! ( master.registers[ 2 ], 124 );
+
+	assert! ( master.write_scaled_single_register ( 2, 150.1, &scaling ).is_err () );
+	assert! ( master.write_scaled_multiple_registers ( 0, &[ 1.0, -60.0 ], &scaling ).is_err () );
+	assert_eq! ( master.registers, vec![ 215, 0xFF9C, 124 ] );
+
+	assert_eq! ( master.write_scaled_multiple_registers ( 0, &[ 1.0, -6.0 ], &scaling ), Ok( () ) );
+	assert_eq! ( master.registers, vec![ 10, 0xFFC4, 124 ] );
+}
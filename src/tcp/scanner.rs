@@ -0,0 +1,416 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use core::consts::*;
+use core::ethernet::EthernetMaster;
+use core::modbusreturn::*;
+use core::timehandling::Timestamp;
+use tcp::master::TcpClient;
+#[cfg( test )]
+use server::tcpserver::{create_response_frame, TcpServer};
+
+//	===============================================================================================
+
+const SCANNER_DEFAULT_CONNECT_TIMEOUT : u64 = 300;
+const SCANNER_DEFAULT_THREADS : usize = 32;
+const SCANNER_DEFAULT_TIMEOUT : u64 = 300;
+const SCANNER_MAXIMUM_HOSTS : u64 = 0x10000;
+
+//	===============================================================================================
+
+///	A unit identifier which answered the probe.
+#[derive( Clone, Debug, PartialEq )]
+pub struct UnitResult
+{
+	unit_identifier : u8,
+	duration_in_milliseconds : u64,
+	exception_code : Option< u8 >
+}
+
+impl UnitResult
+{
+	pub fn get_unit_identifier ( &self ) -> u8
+	{
+		return self.unit_identifier;
+	}
+
+	///	Returns the response time of the probe.
+	pub fn get_duration_in_milliseconds ( &self ) -> u64
+	{
+		return self.duration_in_milliseconds;
+	}
+
+	///	Returns the exception code if the unit answered with an exception,
+	/// which still shows that the unit exists.
+	pub fn get_exception_code ( &self ) -> Option< u8 >
+	{
+		return self.exception_code;
+	}
+}
+
+//	===============================================================================================
+
+///	A host which accepted the connection, with the unit identifiers which answered.
+#[derive( Clone, Debug, PartialEq )]
+pub struct ScanResult
+{
+	address : IpAddr,
+	port : u16,
+	units : Vec< UnitResult >
+}
+
+impl ScanResult
+{
+	pub fn get_address ( &self ) -> IpAddr
+	{
+		return self.address;
+	}
+
+	pub fn get_port ( &self ) -> u16
+	{
+		return self.port;
+	}
+
+	pub fn get_units ( &self ) -> &Vec< UnitResult >
+	{
+		return &self.units;
+	}
+}
+
+//	===============================================================================================
+
+///	Finds MODBUS devices by probing each host with a read of one holding register
+/// (FC 3, address 0) for each unit identifier. A unit counts as present if it answers,
+/// even with an exception, but not with a gateway exception (0x0A, 0x0B).
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use modbus_iiot::tcp::scanner::{parse_address_range, Scanner};
+///
+/// let mut scanner = Scanner::new(502);
+/// scanner.set_unit_identifiers((1..=247).collect());
+///
+/// for result in scanner.scan(&parse_address_range("192.168.1.0/24").unwrap())
+/// {
+///     println!("{} answers with {} unit(s)", result.get_address(), result.get_units().len());
+/// }
+/// ```
+#[derive( Clone, Debug )]
+pub struct Scanner
+{
+	port : u16,
+	unit_identifiers : Vec< u8 >,
+	connect_timeout : Duration,
+	timeout : Duration,
+	threads : usize
+}
+
+impl Scanner
+{
+	///	Creates a scanner for the port, which probes the default unit identifier.
+	pub fn new ( port : u16 ) -> Scanner
+	{
+		return Scanner
+		{
+			port : port,
+			unit_identifiers : vec![ MODBUS_DEFAULT_UNIT_IDENTIFIER ],
+			connect_timeout : Duration::from_millis ( SCANNER_DEFAULT_CONNECT_TIMEOUT ),
+			timeout : Duration::from_millis ( SCANNER_DEFAULT_TIMEOUT ),
+			threads : SCANNER_DEFAULT_THREADS
+		};
+	}
+
+	///	Sets the unit identifiers to probe on each host, like 1 to 247 behind gateways.
+	pub fn set_unit_identifiers ( &mut self, unit_identifiers : Vec< u8 > )
+	{
+		self.unit_identifiers = unit_identifiers;
+	}
+
+	///	Sets how long to wait for the connection to each host (300 ms by default).
+	pub fn set_connect_timeout ( &mut self, timeout : Duration )
+	{
+		self.connect_timeout = timeout;
+	}
+
+	///	Sets how long to wait for each response (300 ms by default).
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	///	Sets how many hosts are probed at the same time (32 by default).
+	pub fn set_threads ( &mut self, threads : usize )
+	{
+		self.threads = threads.max ( 1 );
+	}
+
+	///	Probes all hosts and returns the ones which accepted the connection, in the order of the addresses.
+	pub fn scan ( &self, addresses : &[ IpAddr ] ) -> Vec< ScanResult >
+	{
+		let pending : Arc< Mutex< Vec< IpAddr > > > = Arc::new ( Mutex::new ( addresses.iter ().rev ().cloned ().collect () ) );
+		let results : Arc< Mutex< Vec< ScanResult > > > = Arc::new ( Mutex::new ( vec![] ) );
+
+		let workers : Vec< thread::JoinHandle< () > > = ( 0..self.threads.min ( addresses.len () ) ).map ( | _ |
+		{
+			let scanner : Scanner = self.clone ();
+			let pending : Arc< Mutex< Vec< IpAddr > > > = pending.clone ();
+			let results : Arc< Mutex< Vec< ScanResult > > > = results.clone ();
+
+			thread::spawn ( move ||
+			{
+				loop
+				{
+					let next : Option< IpAddr > = pending.lock ().unwrap ().pop ();
+
+					match next
+					{
+						Some( address )	=>
+						{
+							if let Some( result ) = scanner.scan_host ( address )
+							{
+								results.lock ().unwrap ().push ( result );
+							}
+						}
+						None			=> { break; }
+					}
+				}
+			} )
+		} ).collect ();
+
+		for worker in workers
+		{
+			let _ = worker.join ();
+		}
+
+		let mut reply : Vec< ScanResult > = results.lock ().unwrap ().drain ( .. ).collect ();
+		reply.sort_by_key ( | result | result.address );
+
+		return reply;
+	}
+
+	///	Probes the unit identifiers of one host, `None` if the connection fails.
+	pub fn scan_host ( &self, address : IpAddr ) -> Option< ScanResult >
+	{
+		let mut client : TcpClient = TcpClient::new_with_port ( &address.to_string (),
+																self.port );
+
+		client.set_connect_timeout ( Some( self.connect_timeout ) );
+		client.set_timeout ( self.timeout );
+
+		if client.connect ().is_err ()
+		{
+			return None;
+		}
+
+		let mut units : Vec< UnitResult > = vec![];
+
+		for unit_identifier in &self.unit_identifiers
+		{
+			client.set_unit_identifier ( *unit_identifier );
+
+			let start_time : Timestamp = Timestamp::new ();
+
+			match client.read_holding_registers ( 0x0000, 1 )
+			{
+				ModbusReturnRegisters::Good( result )	=>
+				{
+					units.push ( UnitResult
+								 {
+									 unit_identifier : *unit_identifier,
+									 duration_in_milliseconds : result.get_duration_in_milliseconds (),
+									 exception_code : None
+								 } );
+				}
+				ModbusReturnRegisters::Bad( result ) if result.get_error_code () & MODBUS_EXCEPTION_FLAG != 0	=>
+				{
+					let exception_code : u8 = result.get_exception_code ();
+
+					if exception_code != MODBUS_EXCEPTION_GATEWAY_PATH_UNAVAILABLE && exception_code != MODBUS_EXCEPTION_GATEWAY_TARGET_FAILED_TO_RESPOND
+					{
+						units.push ( UnitResult
+									 {
+										 unit_identifier : *unit_identifier,
+										 duration_in_milliseconds : start_time.elapsed_milliseconds (),
+										 exception_code : Some( exception_code )
+									 } );
+					}
+				}
+				_										=>
+				{
+					//	devices may close the connection after a request without answer
+					client.disconnect ();
+
+					if client.connect ().is_err ()
+					{
+						break;
+					}
+				}
+			}
+		}
+
+		client.disconnect ();
+
+		return Some( ScanResult
+					 {
+						 address : address,
+						 port : self.port,
+						 units : units
+					 } );
+	}
+}
+
+#[test]
+fn test_scanner ()
+{
+	let server : TcpServer = TcpServer::start_with_frame_handler ( "127.0.0.1:0",
+																	| request : &[ u8 ] |
+																	{
+																		let pdu : Vec< u8 >;
+
+																		match request[ 6 ]
+																		{
+																			1 => { pdu = vec![ 0x03, 0x02, 0x00, 0x2A ]; }
+																			2 => { pdu = vec![ 0x83, 0x02 ]; }
+																			3 => { pdu = vec![ 0x83, 0x0B ]; }
+																			_ => { return None; }
+																		}
+
+																		return Some( create_response_frame ( request, &pdu ) );
+																	} ).unwrap ();
+	let port : u16 = server.get_local_address ().port ();
+
+	let mut scanner : Scanner = Scanner::new ( port );
+	scanner.set_unit_identifiers ( vec![ 1, 2, 3, 4 ] );
+	scanner.set_timeout ( Duration::from_millis ( 100 ) );
+
+	let results : Vec< ScanResult > = scanner.scan ( &[ "127.0.0.1".parse ().unwrap () ] );
+
+	assert_eq! ( results.len (), 1 );
+	assert_eq! ( results[ 0 ].get_port (), port );
+
+	let units : &Vec< UnitResult > = results[ 0 ].get_units ();
+
+	assert_eq! ( units.len (), 2 );
+	assert_eq! ( units[ 0 ].get_unit_identifier (), 1 );
+	assert_eq! ( units[ 0 ].get_exception_code (), None );
+	assert_eq! ( units[ 1 ].get_unit_identifier (), 2 );
+	assert_eq! ( units[ 1 ].get_exception_code (), Some( MODBUS_EXCEPTION_ILLEGAL_DATA_ADDRESS ) );
+
+	drop ( server );
+
+	assert! ( scanner.scan ( &[ "127.0.0.1".parse ().unwrap () ] ).is_empty () );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_address_range ()
+{
+	assert_eq! ( parse_address_range ( "10.0.0.7" ).unwrap (), vec![ "10.0.0.7".parse::< IpAddr > ().unwrap () ] );
+	assert_eq! ( parse_address_range ( "::1" ).unwrap (), vec![ "::1".parse::< IpAddr > ().unwrap () ] );
+	assert_eq! ( parse_address_range ( "10.0.0.254-10.0.1.1" ).unwrap ().len (), 4 );
+	assert_eq! ( parse_address_range ( "10.0.0.10-12" ).unwrap ().last (), Some( &"10.0.0.12".parse::< IpAddr > ().unwrap () ) );
+	assert_eq! ( parse_address_range ( "192.168.1.0/24" ).unwrap ().len (), 254 );
+	assert_eq! ( parse_address_range ( "192.168.1.0/24" ).unwrap ()[ 0 ], "192.168.1.1".parse::< IpAddr > ().unwrap () );
+	assert_eq! ( parse_address_range ( "192.168.1.5/32" ).unwrap ().len (), 1 );
+	assert! ( parse_address_range ( "10.0.0.9-10.0.0.1" ).is_err () );
+	assert! ( parse_address_range ( "10.0.0.0/8" ).is_err () );
+	assert! ( parse_address_range ( "host" ).is_err () );
+}
+
+///	Parses a single IPv4 or IPv6 address, an IPv4 range like `192.168.1.10-192.168.1.20`
+/// or `192.168.1.10-20`, or an IPv4 network like `192.168.1.0/24` without its network
+/// and broadcast address. Ranges are limited to 65536 hosts.
+pub fn parse_address_range ( range : &str ) -> Result< Vec< IpAddr >, String >
+{
+	let range : &str = range.trim ();
+
+	let first : u32;
+	let last : u32;
+
+	if let Some( ( network, prefix ) ) = range.split_once ( '/' )
+	{
+		let prefix : u32 = prefix.parse::< u32 > ().ok ().filter ( | prefix | *prefix <= 32 ).ok_or ( format! ( "invalid prefix in '{}'", range ) )?;
+		let mask : u32 = if prefix == 0 { 0 } else { u32::MAX << ( 32 - prefix ) };
+		let network : u32 = u32::from ( parse_ipv4_address ( network )? ) & mask;
+
+		first = if prefix < 31 { network + 1 } else { network };
+		last = if prefix < 31 { ( network | !mask ) - 1 } else { network | !mask };
+	}
+	else if let Some( ( start, end ) ) = range.split_once ( '-' )
+	{
+		let start : Ipv4Addr = parse_ipv4_address ( start )?;
+
+		first = u32::from ( start );
+
+		if end.contains ( '.' )
+		{
+			last = u32::from ( parse_ipv4_address ( end )? );
+		}
+		else
+		{
+			let octet : u8 = end.trim ().parse::< u8 > ().map_err ( | _ | format! ( "invalid range '{}'", range ) )?;
+
+			last = ( first & 0xFFFF_FF00 ) | octet as u32;
+		}
+	}
+	else
+	{
+		return range.parse::< IpAddr > ().map ( | address | vec![ address ] ).map_err ( | _ | format! ( "invalid address '{}'", range ) );
+	}
+
+	if last < first
+	{
+		return Err( format! ( "empty range '{}'", range ) );
+	}
+
+	if ( last - first ) as u64 >= SCANNER_MAXIMUM_HOSTS
+	{
+		return Err( format! ( "range '{}' has more than {} hosts", range, SCANNER_MAXIMUM_HOSTS ) );
+	}
+
+	return Ok( ( first..=last ).map ( | address | IpAddr::V4( Ipv4Addr::from ( address ) ) ).collect () );
+}
+
+fn parse_ipv4_address ( address : &str ) -> Result< Ipv4Addr, String >
+{
+	return address.trim ().parse::< Ipv4Addr > ().map_err ( | _ | format! ( "invalid IPv4 address '{}'", address ) );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_parse_unit_identifiers ()
+{
+	assert_eq! ( parse_unit_identifiers ( "1" ).unwrap (), vec![ 1 ] );
+	assert_eq! ( parse_unit_identifiers ( "1-3,10" ).unwrap (), vec![ 1, 2, 3, 10 ] );
+	assert_eq! ( parse_unit_identifiers ( "1-247" ).unwrap ().len (), 247 );
+	assert! ( parse_unit_identifiers ( "5-1" ).is_err () );
+	assert! ( parse_unit_identifiers ( "256" ).is_err () );
+}
+
+///	Parses a list of unit identifiers and ranges like `1-10,100,247`.
+pub fn parse_unit_identifiers ( list : &str ) -> Result< Vec< u8 >, String >
+{
+	let mut reply : Vec< u8 > = vec![];
+
+	for item in list.split ( ',' )
+	{
+		let ( start, end ) = item.split_once ( '-' ).unwrap_or ( ( item, item ) );
+
+		let first : u8 = start.trim ().parse::< u8 > ().map_err ( | _ | format! ( "invalid unit identifier '{}'", item ) )?;
+		let last : u8 = end.trim ().parse::< u8 > ().map_err ( | _ | format! ( "invalid unit identifier '{}'", item ) )?;
+
+		if last < first
+		{
+			return Err( format! ( "empty range '{}'", item ) );
+		}
+
+		reply.extend ( first..=last );
+	}
+
+	return Ok( reply );
+}
@@ -1,70 +1,276 @@
 
 
-use std::io::{Write, Read};
-use std::net::TcpStream;
+use std::fmt;
+use std::io::{self, Write, Read};
+use capture::hexlog::format_hex;
+use capture::observer::*;
+use core::consts::*;
+use core::datatransformation::*;
 use core::modbustelegram::ModbusTelegram;
 
 //	===============================================================================================
 
-fn read_telegram_from_stream ( stream : &mut TcpStream, expected_bytes : u8 ) -> Result< ModbusTelegram, String >
+#[test]
+fn test_read_frame_from_stream ()
 {
-	let mut reply : Result< ModbusTelegram, String > = Err( "Tcp Read Failed".to_string () );
+	let test_data : Vec< u8 > = vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02,
+									  0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x01, 0x00, 0x03 ];
+	let mut stream : &[ u8 ] = &test_data;
 
-	let mut data : Vec< u8 > = vec![ 0; expected_bytes as usize ];
-	let response = stream.read ( &mut data );
+	let result_1 : Result< Vec< u8 >, String > = read_frame_from_stream ( &mut stream );
+	assert_eq! ( result_1, Ok( test_data[ 0..9 ].to_vec () ) );
 
-	if response.is_ok ()
+	let result_2 : Result< Vec< u8 >, String > = read_frame_from_stream ( &mut stream );
+	assert_eq! ( result_2, Ok( test_data[ 9.. ].to_vec () ) );
+
+	let result_3 : Result< Vec< u8 >, String > = read_frame_from_stream ( &mut stream );
+	assert! ( result_3.is_err () );
+
+	let mut invalid_length : &[ u8 ] = &[ 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x03 ];
+	assert! ( read_frame_from_stream ( &mut invalid_length ).is_err () );
+
+	let mut truncated : &[ u8 ] = &[ 0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06 ];
+	assert! ( read_frame_from_stream ( &mut truncated ).is_err () );
+}
+
+///	Reads one MODBUS TCP frame (MBAP header and PDU) by the length field of its header.
+pub fn read_frame_from_stream< T : Read > ( stream : &mut T ) -> Result< Vec< u8 >, String >
+{
+	return Ok( read_frame ( stream )? );
+}
+
+fn read_frame< T : Read > ( stream : &mut T ) -> Result< Vec< u8 >, ExchangeError >
+{
+	let mut reply : Vec< u8 > = vec![ 0; MODBUS_HEADER_SIZE as usize ];
+
+	stream.read_exact ( &mut reply ).map_err ( | error | ExchangeError::from_io_error ( &error ) )?;
+
+	let length : usize = ( ( reply[ 4 ] as usize ) << 8 ) | reply[ 5 ] as usize;
+
+	if length < 2 || length > MODBUS_MAXIMUM_PDU_SIZE as usize + 1
 	{
-        if let Some( telegram ) = ModbusTelegram::new_from_bytes ( &data )
-        {
-            reply = Ok( telegram );
-        }
+		return Err( ExchangeError::Framing( format! ( "invalid frame length {}", length ) ) );
 	}
 
-	return reply;
+	let mut pdu : Vec< u8 > = vec![ 0; length - 1 ];
+
+	stream.read_exact ( &mut pdu ).map_err ( | error | ExchangeError::from_io_error ( &error ) )?;
+	reply.append ( &mut pdu );
+
+	return Ok( reply );
 }
 
 //	===============================================================================================
 
-fn write_telegram_to_stream ( stream : &mut TcpStream, telegram : &ModbusTelegram ) -> Result< bool, String >
+/// The failure of an exchange of a request and its response.
+#[derive( Clone, Debug, Eq, PartialEq )]
+pub enum ExchangeError
 {
-	let mut reply : Result< bool, String > = Err( "Tcp Write Failed".to_string () );
+	///	No response arrived in time.
+	Timeout,
+	///	The response was no valid frame.
+	Framing( String ),
+	///	The connection failed or was closed.
+	Connection( String )
+}
 
-	if let Some( bytes ) = telegram.get_bytes ()
+impl ExchangeError
+{
+	///	Read and write timeouts of sockets end as `WouldBlock` or `TimedOut` depending on the platform.
+	pub fn from_io_error ( error : &io::Error ) -> ExchangeError
 	{
-		let response = stream.write_all ( &bytes );
-		
-		if response.is_ok ()
+		let reply : ExchangeError;
+
+		match error.kind ()
 		{
-			reply = Ok( true );
+			io::ErrorKind::WouldBlock |
+			io::ErrorKind::TimedOut		=> { reply = ExchangeError::Timeout; }
+			_							=> { reply = ExchangeError::Connection( error.to_string () ); }
 		}
+
+		return reply;
 	}
+}
 
-	return reply;
+impl fmt::Display for ExchangeError
+{
+	fn fmt ( &self, f : &mut fmt::Formatter ) -> fmt::Result
+	{
+		match *self
+		{
+			ExchangeError::Timeout					=> { return write! ( f, "no response in time" ); }
+			ExchangeError::Framing( ref message ) |
+			ExchangeError::Connection( ref message )	=> { return write! ( f, "{}", message ); }
+		}
+	}
+}
+
+impl From< ExchangeError > for String
+{
+	fn from ( error : ExchangeError ) -> String
+	{
+		return error.to_string ();
+	}
+}
+
+#[cfg( test )]
+struct TestStream
+{
+	incoming : io::Cursor< Vec< u8 > >,
+	written : Vec< u8 >
+}
+
+#[cfg( test )]
+impl TestStream
+{
+	fn new ( incoming : Vec< u8 > ) -> TestStream
+	{
+		return TestStream { incoming : io::Cursor::new ( incoming ), written : vec![] };
+	}
+}
+
+#[cfg( test )]
+impl Read for TestStream
+{
+	fn read ( &mut self, buffer : &mut [ u8 ] ) -> io::Result< usize >
+	{
+		return self.incoming.read ( buffer );
+	}
+}
+
+#[cfg( test )]
+impl Write for TestStream
+{
+	fn write ( &mut self, buffer : &[ u8 ] ) -> io::Result< usize >
+	{
+		return self.written.write ( buffer );
+	}
+
+	fn flush ( &mut self ) -> io::Result< () >
+	{
+		return Ok( () );
+	}
+}
+
+#[test]
+fn test_exchange_modbus_frame ()
+{
+	let mut stream : TestStream = TestStream::new ( vec![ 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02,
+														  0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x01, 0x00, 0x03 ] );
+	let request : Vec< u8 > = create_frame ( 0x0002, 0x01, &[ 0x06, 0x00, 0x01, 0x00, 0x03 ] );
+	let mut frames : Option< BoxedFrameObserver > = None;
+
+	assert_eq! ( exchange_modbus_frame ( &mut stream, &request, &mut frames ), Ok( request.clone () ) );
+	assert_eq! ( stream.written, request );
+	assert_eq! ( exchange_modbus_frame ( &mut stream, &request, &mut frames ), Err( ExchangeError::Connection( "failed to fill whole buffer".to_string () ) ) );
+
+	let mut invalid : TestStream = TestStream::new ( vec![ 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01 ] );
+
+	assert_eq! ( exchange_modbus_frame ( &mut invalid, &request, &mut frames ), Err( ExchangeError::Framing( "invalid frame length 0".to_string () ) ) );
+}
+
+///	Sends the request frame and returns the response frame with the same transaction identifier.
+/// Late responses of earlier requests are skipped, the observer gets all frames.
+pub fn exchange_modbus_frame< T : Read + Write > ( stream : &mut T, request : &[ u8 ], observer : &mut Option< BoxedFrameObserver > ) -> Result< Vec< u8 >, ExchangeError >
+{
+	log_event! ( trace, "sent {}", format_hex ( request ) );
+
+	notify_observer ( observer,
+					  FrameDirection::Sent,
+					  request );
+
+	stream.write_all ( request ).map_err ( | error | ExchangeError::from_io_error ( &error ) )?;
+
+	loop
+	{
+		let response : Vec< u8 > = read_frame ( stream )?;
+
+		log_event! ( trace, "received {}", format_hex ( &response ) );
+
+		notify_observer ( observer,
+						  FrameDirection::Received,
+						  &response );
+
+		if response[ 0..2 ] == request[ 0..2 ]
+		{
+			return Ok( response );
+		}
+	}
 }
 
 //	===============================================================================================
 
-pub fn process_modbus_telegram ( stream : &mut TcpStream, telegram : &Option< ModbusTelegram > ) -> Option< ModbusTelegram >
+///	Sends the telegram and returns the response with the same transaction identifier.
+/// Late responses of earlier requests are skipped.
+pub fn process_modbus_telegram< T : Read + Write > ( stream : &mut T, telegram : &Option< ModbusTelegram > ) -> Option< ModbusTelegram >
+{
+	return process_modbus_telegram_with_observer ( stream,
+												   telegram,
+												   &mut None );
+}
+
+///	Like `process_modbus_telegram`, and passes every sent and received frame to the observer.
+pub fn process_modbus_telegram_with_observer< T : Read + Write > ( stream : &mut T, telegram : &Option< ModbusTelegram >, observer : &mut Option< BoxedFrameObserver > ) -> Option< ModbusTelegram >
 {
 	let mut reply : Option< ModbusTelegram > = None;
 
-	if telegram.is_some ()
+	if let Some( bytes ) = telegram.as_ref ().and_then ( | telegram | telegram.get_bytes () )
 	{
-		let write_telegram : &ModbusTelegram = telegram.as_ref ().unwrap ();
-		let write_response : Result< bool, String > = write_telegram_to_stream ( stream, 
-																				  write_telegram );
-		if write_response.is_ok ()
+		if let Ok( response ) = exchange_modbus_frame ( stream, &bytes, observer )
 		{
-			let expected_bytes : Option< u8 > = write_telegram.get_expected_byte_count ();
-			let read_response : Result< ModbusTelegram, String > = read_telegram_from_stream ( stream, 
-																							   expected_bytes.unwrap () );
-			if read_response.is_ok ()
-			{
-				reply = Some( read_response.unwrap ( ) );
-			}
+			reply = ModbusTelegram::new_from_bytes ( &response );
 		}
 	}
-	
+
 	return reply;
 }
+
+//	===============================================================================================
+
+#[test]
+fn test_create_frame ()
+{
+	assert_eq! ( create_frame ( 0x1234, 0x11, &[ 0x03, 0x00, 0x00, 0x00, 0x01 ] ), vec![ 0x12, 0x34, 0x00, 0x00, 0x00, 0x06, 0x11, 0x03, 0x00, 0x00, 0x00, 0x01 ] );
+}
+
+///	Creates a MODBUS TCP frame of the MBAP header and the PDU.
+pub fn create_frame ( transaction_identifier : u16, unit_identifier : u8, pdu : &[ u8 ] ) -> Vec< u8 >
+{
+	let mut reply : Vec< u8 > = vec![];
+
+	append_word_to_bytearray ( &mut reply,
+							   transaction_identifier );
+	append_word_to_bytearray ( &mut reply,
+							   MODBUS_PROTOCOL_IDENTIFIER_TCP );
+	append_word_to_bytearray ( &mut reply,
+							   pdu.len () as u16 + MODBUS_UNIT_IDENTIFIER_LENGTH );
+	append_byte_to_bytearray ( &mut reply,
+							   unit_identifier );
+	reply.extend_from_slice ( pdu );
+
+	return reply;
+}
+
+///	Sends the PDU and returns the PDU of the response with the same transaction identifier.
+/// Late responses of earlier requests are skipped.
+pub fn process_modbus_frame< T : Read + Write > ( stream : &mut T, transaction_identifier : u16, unit_identifier : u8, pdu : &[ u8 ] ) -> Result< Vec< u8 >, String >
+{
+	return process_modbus_frame_with_observer ( stream,
+												transaction_identifier,
+												unit_identifier,
+												pdu,
+												&mut None );
+}
+
+///	Like `process_modbus_frame`, and passes every sent and received frame to the observer.
+pub fn process_modbus_frame_with_observer< T : Read + Write > ( stream : &mut T, transaction_identifier : u16, unit_identifier : u8, pdu : &[ u8 ], observer : &mut Option< BoxedFrameObserver > ) -> Result< Vec< u8 >, String >
+{
+	let request : Vec< u8 > = create_frame ( transaction_identifier,
+											 unit_identifier,
+											 pdu );
+	let response : Vec< u8 > = exchange_modbus_frame ( stream,
+													   &request,
+													   observer )?;
+
+	return Ok( response[ MODBUS_HEADER_SIZE as usize.. ].to_vec () );
+}
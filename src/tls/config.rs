@@ -0,0 +1,157 @@
+use std::convert::TryFrom;
+use std::net::TcpStream;
+use std::sync::Arc;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, StreamOwned};
+use rustls::crypto::CryptoProvider;
+use rustls::crypto::ring::default_provider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::pki_types::pem::PemObject;
+use rustls::server::WebPkiClientVerifier;
+use network::stream::NetworkStream;
+
+//	===============================================================================================
+
+/// The TLS settings of a client for MODBUS/TCP Security: the certificate authority
+/// of the server, the own certificate with its private key and the server name,
+/// which is checked against the certificate of the server.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::fs;
+/// use modbus_iiot::core::consts::MODBUS_TLS_PORT;
+/// use modbus_iiot::tcp::master::TcpClient;
+/// use modbus_iiot::tls::config::TlsClientConfig;
+///
+/// let tls_config = TlsClientConfig::new(&fs::read("ca.pem").unwrap(),
+///                                       &fs::read("client.pem").unwrap(),
+///                                       &fs::read("client.key").unwrap(),
+///                                       "plc.local").unwrap();
+///
+/// let mut client = TcpClient::new_with_tls("plc.local", MODBUS_TLS_PORT, tls_config);
+/// client.connect().unwrap();
+/// ```
+#[derive( Clone )]
+pub struct TlsClientConfig
+{
+	config : Arc< ClientConfig >,
+	server_name : ServerName< 'static >
+}
+
+impl TlsClientConfig
+{
+	///	Creates the settings from PEM encoded certificates and a PKCS#8, PKCS#1 or SEC1 key.
+	pub fn new ( authority_pem : &[ u8 ], certificate_pem : &[ u8 ], key_pem : &[ u8 ], server_name : &str ) -> Result< TlsClientConfig, String >
+	{
+		let config : ClientConfig = ClientConfig::builder_with_provider ( create_provider () )
+												 .with_safe_default_protocol_versions ()
+												 .map_err ( | error | error.to_string () )?
+												 .with_root_certificates ( create_root_store ( authority_pem )? )
+												 .with_client_auth_cert ( load_certificates ( certificate_pem )?,
+																		  load_private_key ( key_pem )? )
+												 .map_err ( | error | error.to_string () )?;
+
+		return Self::new_with_config ( Arc::new ( config ),
+									   server_name );
+	}
+
+	///	Creates the settings from a rustls configuration.
+	pub fn new_with_config ( config : Arc< ClientConfig >, server_name : &str ) -> Result< TlsClientConfig, String >
+	{
+		let name : ServerName< 'static > = ServerName::try_from ( server_name.to_string () ).map_err ( | error | error.to_string () )?;
+
+		return Ok( TlsClientConfig
+		{
+			config : config,
+			server_name : name
+		} );
+	}
+
+	///	Performs the TLS handshake on the connection.
+	pub fn connect ( &self, mut stream : TcpStream ) -> Result< NetworkStream, String >
+	{
+		let mut connection : ClientConnection = ClientConnection::new ( self.config.clone (),
+																		self.server_name.clone () ).map_err ( | error | error.to_string () )?;
+
+		while connection.is_handshaking ()
+		{
+			connection.complete_io ( &mut stream ).map_err ( | error | error.to_string () )?;
+		}
+
+		return Ok( NetworkStream::Tls( Box::new ( StreamOwned::new ( connection,
+																	  stream ) ) ) );
+	}
+}
+
+//	===============================================================================================
+
+///	Creates the configuration of a server requiring a certificate of each client,
+/// issued by the certificate authority.
+pub fn create_server_config ( authority_pem : &[ u8 ], certificate_pem : &[ u8 ], key_pem : &[ u8 ] ) -> Result< Arc< ServerConfig >, String >
+{
+	let provider : Arc< CryptoProvider > = create_provider ();
+	let verifier = WebPkiClientVerifier::builder_with_provider ( Arc::new ( create_root_store ( authority_pem )? ),
+																 provider.clone () )
+										.build ()
+										.map_err ( | error | error.to_string () )?;
+
+	let config : ServerConfig = ServerConfig::builder_with_provider ( provider )
+											 .with_safe_default_protocol_versions ()
+											 .map_err ( | error | error.to_string () )?
+											 .with_client_cert_verifier ( verifier )
+											 .with_single_cert ( load_certificates ( certificate_pem )?,
+																 load_private_key ( key_pem )? )
+											 .map_err ( | error | error.to_string () )?;
+
+	return Ok( Arc::new ( config ) );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_load_certificates_and_key ()
+{
+	assert_eq! ( load_certificates ( include_bytes! ( "testcerts/ca.pem" ) ).map ( | certificates | certificates.len () ), Ok( 1 ) );
+	assert! ( load_certificates ( b"no certificate" ).is_err () );
+
+	assert! ( load_private_key ( include_bytes! ( "testcerts/client.key" ) ).is_ok () );
+	assert! ( load_private_key ( include_bytes! ( "testcerts/client.pem" ) ).is_err () );
+}
+
+///	Loads all certificates of a PEM file.
+pub fn load_certificates ( pem : &[ u8 ] ) -> Result< Vec< CertificateDer< 'static > >, String >
+{
+	let reply : Vec< CertificateDer< 'static > > = CertificateDer::pem_slice_iter ( pem ).collect::< Result< _, _ > > ()
+																						   .map_err ( | error | error.to_string () )?;
+
+	if reply.is_empty ()
+	{
+		return Err( "no certificate found".to_string () );
+	}
+
+	return Ok( reply );
+}
+
+///	Loads the first private key of a PEM file.
+pub fn load_private_key ( pem : &[ u8 ] ) -> Result< PrivateKeyDer< 'static >, String >
+{
+	return PrivateKeyDer::from_pem_slice ( pem ).map_err ( | error | error.to_string () );
+}
+
+fn create_root_store ( authority_pem : &[ u8 ] ) -> Result< RootCertStore, String >
+{
+	let mut reply : RootCertStore = RootCertStore::empty ();
+
+	for certificate in load_certificates ( authority_pem )?
+	{
+		reply.add ( certificate ).map_err ( | error | error.to_string () )?;
+	}
+
+	return Ok( reply );
+}
+
+fn create_provider () -> Arc< CryptoProvider >
+{
+	return Arc::new ( default_provider () );
+}
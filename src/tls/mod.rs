@@ -0,0 +1,5 @@
+
+
+pub mod config;
+pub mod role;
+pub mod server;
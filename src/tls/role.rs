@@ -0,0 +1,135 @@
+//	===============================================================================================
+
+///	The encoded OID 1.3.6.1.4.1.50316.802.1 of the role extension
+/// of MODBUS/TCP Security certificates.
+pub const MODBUS_ROLE_OID : [ u8; 11 ] = [ 0x2B, 0x06, 0x01, 0x04, 0x01, 0x83, 0x89, 0x0C, 0x86, 0x22, 0x01 ];
+
+const ASN1_TAG_EXTENSIONS : u8 = 0xA3;
+const ASN1_TAG_OBJECT_IDENTIFIER : u8 = 0x06;
+const ASN1_TAG_SEQUENCE : u8 = 0x30;
+const ASN1_TAG_UTF8_STRING : u8 = 0x0C;
+
+//	===============================================================================================
+
+#[test]
+fn test_read_element ()
+{
+	assert_eq! ( read_element ( &[ 0x30, 0x03, 0x02, 0x01, 0x05, 0xFF ], 0 ), Some( ( 0x30, 2, 5 ) ) );
+	assert_eq! ( read_element ( &[ 0x04, 0x81, 0x02, 0xAA, 0xBB ], 0 ), Some( ( 0x04, 3, 5 ) ) );
+	assert_eq! ( read_element ( &[ 0x04, 0x82, 0x00, 0x01, 0xAA ], 0 ), Some( ( 0x04, 4, 5 ) ) );
+	assert_eq! ( read_element ( &[ 0x04, 0x03, 0xAA ], 0 ), None );
+	assert_eq! ( read_element ( &[ 0x04, 0x85, 0x00 ], 0 ), None );
+	assert_eq! ( read_element ( &[ 0x04 ], 0 ), None );
+}
+
+///	Reads the DER element at the index and returns its tag,
+/// the index of its content and the index after its end.
+fn read_element ( bytes : &[ u8 ], index : usize ) -> Option< ( u8, usize, usize ) >
+{
+	let tag : u8 = *bytes.get ( index )?;
+	let first_length_byte : u8 = *bytes.get ( index + 1 )?;
+
+	let mut content_index : usize = index + 2;
+	let mut length : usize = first_length_byte as usize;
+
+	if first_length_byte & 0x80 != 0
+	{
+		let length_byte_count : usize = ( first_length_byte & 0x7F ) as usize;
+
+		if length_byte_count == 0 || length_byte_count > 4
+		{
+			return None;
+		}
+
+		length = 0;
+
+		for byte in bytes.get ( content_index..content_index + length_byte_count )?
+		{
+			length = ( length << 8 ) | *byte as usize;
+		}
+
+		content_index += length_byte_count;
+	}
+
+	if content_index + length > bytes.len ()
+	{
+		return None;
+	}
+
+	return Some( ( tag, content_index, content_index + length ) );
+}
+
+///	Returns the elements inside of the content between begin and end.
+fn read_elements ( bytes : &[ u8 ], begin : usize, end : usize ) -> Option< Vec< ( u8, usize, usize ) > >
+{
+	let mut reply : Vec< ( u8, usize, usize ) > = vec![];
+	let mut index : usize = begin;
+
+	while index < end
+	{
+		let element : ( u8, usize, usize ) = read_element ( &bytes[ 0..end ], index )?;
+
+		index = element.2;
+		reply.push ( element );
+	}
+
+	return Some( reply );
+}
+
+//	===============================================================================================
+
+#[test]
+fn test_extract_role ()
+{
+	let certificate : Vec< u8 > = ::tls::config::load_certificates ( include_bytes! ( "testcerts/client.pem" ) ).unwrap ().remove ( 0 ).to_vec ();
+	assert_eq! ( extract_role ( &certificate ), Ok( Some( "Operator".to_string () ) ) );
+
+	let certificate_without_role : Vec< u8 > = ::tls::config::load_certificates ( include_bytes! ( "testcerts/server.pem" ) ).unwrap ().remove ( 0 ).to_vec ();
+	assert_eq! ( extract_role ( &certificate_without_role ), Ok( None ) );
+
+	assert! ( extract_role ( &certificate[ 0..100 ] ).is_err () );
+}
+
+///	Extracts the role of a DER encoded X.509 certificate, `None` if the certificate has no role.
+pub fn extract_role ( certificate : &[ u8 ] ) -> Result< Option< String >, String >
+{
+	let invalid_certificate = || "invalid certificate".to_string ();
+
+	let ( certificate_tag, certificate_begin, certificate_end ) = read_element ( certificate, 0 ).ok_or_else ( invalid_certificate )?;
+	let ( tbs_tag, tbs_begin, tbs_end ) = read_element ( certificate, certificate_begin ).ok_or_else ( invalid_certificate )?;
+
+	if certificate_tag != ASN1_TAG_SEQUENCE || tbs_tag != ASN1_TAG_SEQUENCE || tbs_end > certificate_end
+	{
+		return Err( invalid_certificate () );
+	}
+
+	let tbs_elements : Vec< ( u8, usize, usize ) > = read_elements ( certificate, tbs_begin, tbs_end ).ok_or_else ( invalid_certificate )?;
+
+	for &( _, begin, _ ) in tbs_elements.iter ().filter ( | element | element.0 == ASN1_TAG_EXTENSIONS )
+	{
+		let ( _, extensions_begin, extensions_end ) = read_element ( certificate, begin ).ok_or_else ( invalid_certificate )?;
+
+		for ( _, extension_begin, extension_end ) in read_elements ( certificate, extensions_begin, extensions_end ).ok_or_else ( invalid_certificate )?
+		{
+			let fields : Vec< ( u8, usize, usize ) > = read_elements ( certificate, extension_begin, extension_end ).ok_or_else ( invalid_certificate )?;
+
+			match ( fields.first (), fields.last () )
+			{
+				( Some( &( ASN1_TAG_OBJECT_IDENTIFIER, oid_begin, oid_end ) ), Some( &( _, value_begin, value_end ) ) ) if certificate[ oid_begin..oid_end ] == MODBUS_ROLE_OID =>
+				{
+					let ( value_tag, role_begin, role_end ) = read_element ( &certificate[ 0..value_end ], value_begin ).ok_or_else ( invalid_certificate )?;
+
+					if value_tag != ASN1_TAG_UTF8_STRING
+					{
+						return Err( "role is not a UTF8String".to_string () );
+					}
+
+					return String::from_utf8 ( certificate[ role_begin..role_end ].to_vec () ).map ( Some ).map_err ( | error | error.to_string () );
+				}
+				_ => {}
+			}
+		}
+	}
+
+	return Ok( None );
+}
@@ -0,0 +1,194 @@
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use core::consts::*;
+use server::datamodel::DataModel;
+use server::requesthandler::*;
+use server::tcpserver::*;
+use tls::role::extract_role;
+
+//	===============================================================================================
+
+/// A MODBUS/TCP Security server (TLS, usually on port 802). Clients have to
+/// authenticate with a certificate, the role of their certificate is passed
+/// to the authorization of each request.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::fs;
+/// use modbus_iiot::server::datastore::DataStore;
+/// use modbus_iiot::tls::config::create_server_config;
+/// use modbus_iiot::tls::server::TlsServer;
+///
+/// let config = create_server_config(&fs::read("ca.pem").unwrap(),
+///                                   &fs::read("server.pem").unwrap(),
+///                                   &fs::read("server.key").unwrap()).unwrap();
+///
+/// // operators may read, only engineers may write
+/// let _server = TlsServer::start("0.0.0.0:802", config, DataStore::new_dense(1000),
+///                                | role, pdu | pdu[ 0 ] <= 0x04 || role == Some("Engineer")).unwrap();
+/// ```
+pub struct TlsServer
+{
+	server : TcpServer
+}
+
+impl TlsServer
+{
+	///	Starts serving the data model. Requests the authorization refuses
+	/// for the role answer with an illegal function exception.
+	pub fn start< M, A > ( address : &str, config : Arc< ServerConfig >, model : M, authorize : A ) -> Result< TlsServer, String >
+		where M : DataModel + Send + 'static, A : Fn ( Option< &str >, &[ u8 ] ) -> bool + Clone + Send + 'static
+	{
+		let shared_model : Arc< Mutex< M > > = Arc::new ( Mutex::new ( model ) );
+
+		return Self::start_with_frame_handler ( address,
+												config,
+												move | frame : &[ u8 ], role : Option< &str > |
+												{
+													let request : &[ u8 ] = &frame[ MODBUS_HEADER_SIZE as usize.. ];
+													let pdu : Vec< u8 >;
+
+													if authorize ( role, request )
+													{
+														pdu = process_request ( &mut *shared_model.lock ().unwrap_or_else ( | error | error.into_inner () ),
+																				request );
+													}
+													else
+													{
+														pdu = create_exception_response ( request[ 0 ],
+																						  MODBUS_EXCEPTION_ILLEGAL_FUNCTION );
+													}
+
+													return Some( create_response_frame ( frame,
+																						 &pdu ) );
+												} );
+	}
+
+	///	Starts a server which answers each request frame with the frame returned by
+	/// the handler, which gets the role of the client certificate with the frame.
+	pub fn start_with_frame_handler< F > ( address : &str, config : Arc< ServerConfig >, handler : F ) -> Result< TlsServer, String >
+		where F : Fn ( &[ u8 ], Option< &str > ) -> Option< Vec< u8 > > + Clone + Send + 'static
+	{
+		let server : TcpServer = TcpServer::start_with_connection_handler ( address,
+																			move | stream : TcpStream | serve_tls_connection ( stream,
+																															   &config,
+																															   &handler ) )?;
+
+		return Ok( TlsServer
+		{
+			server : server
+		} );
+	}
+
+	///	Returns the address the server listens on.
+	pub fn get_local_address ( &self ) -> SocketAddr
+	{
+		return self.server.get_local_address ();
+	}
+
+	///	Stops accepting and closes all connections.
+	pub fn stop ( &mut self )
+	{
+		self.server.stop ();
+	}
+}
+
+//	===============================================================================================
+
+fn serve_tls_connection< F > ( mut stream : TcpStream, config : &Arc< ServerConfig >, handler : &F )
+	where F : Fn ( &[ u8 ], Option< &str > ) -> Option< Vec< u8 > >
+{
+	let mut connection : ServerConnection;
+
+	match ServerConnection::new ( config.clone () )
+	{
+		Ok( new_connection )	=> { connection = new_connection; }
+		Err( _ )				=> { return; }
+	}
+
+	while connection.is_handshaking ()
+	{
+		if connection.complete_io ( &mut stream ).is_err ()
+		{
+			return;
+		}
+	}
+
+	let role : Option< String > = connection.peer_certificates ()
+											.and_then ( | certificates | certificates.first () )
+											.and_then ( | certificate | extract_role ( certificate ).unwrap_or ( None ) );
+
+	let mut tls_stream : StreamOwned< ServerConnection, TcpStream > = StreamOwned::new ( connection,
+																						 stream );
+
+	serve_frames ( &mut tls_stream,
+				   &| frame : &[ u8 ] | handler ( frame, role.as_deref () ) );
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use server::datastore::DataStore;
+#[cfg( test )]
+use tcp::master::TcpClient;
+#[cfg( test )]
+use tcp::masteraccess::*;
+#[cfg( test )]
+use tls::config::*;
+
+#[cfg( test )]
+fn start_test_server ( store : &DataStore ) -> TlsServer
+{
+	let config : Arc< ServerConfig > = create_server_config ( include_bytes! ( "testcerts/ca.pem" ),
+															  include_bytes! ( "testcerts/server.pem" ),
+															  include_bytes! ( "testcerts/server.key" ) ).unwrap ();
+
+	return TlsServer::start ( "127.0.0.1:0",
+							  config,
+							  store.clone (),
+							  | role, pdu | pdu[ 0 ] <= FUNCTION_CODE_READ_INPUT_REGISTERS || role == Some( "Engineer" ) ).unwrap ();
+}
+
+#[test]
+fn test_tls_server_with_role ()
+{
+	let store : DataStore = DataStore::new_dense ( 10 );
+	let server : TlsServer = start_test_server ( &store );
+	let tls_config : TlsClientConfig = TlsClientConfig::new ( include_bytes! ( "testcerts/ca.pem" ),
+															  include_bytes! ( "testcerts/client.pem" ),
+															  include_bytes! ( "testcerts/client.key" ),
+															  "localhost" ).unwrap ();
+	let mut client : TcpClient = TcpClient::new_with_tls ( "127.0.0.1", server.get_local_address ().port (), tls_config );
+
+	client.connect ().unwrap ();
+	store.set_registers ( DataTable::HoldingRegisters, 0, &[ 802 ] ).unwrap ();
+
+	assert_eq! ( client.read_holding_registers ( 0, 1 ), vec![ 802 ] );
+	assert_eq! ( client.send_pdu ( 1, &[ 0x06, 0x00, 0x00, 0x00, 0x01 ] ), Ok( vec![ 0x86, 0x01 ] ) );
+	assert_eq! ( store.get_registers ( DataTable::HoldingRegisters, 0, 1 ), Some( vec![ 802 ] ) );
+
+	client.disconnect ();
+}
+
+#[test]
+fn test_tls_server_refuses_unknown_clients ()
+{
+	let store : DataStore = DataStore::new_dense ( 10 );
+	let server : TlsServer = start_test_server ( &store );
+
+	let untrusted_config : TlsClientConfig = TlsClientConfig::new ( include_bytes! ( "testcerts/server.pem" ),
+																	include_bytes! ( "testcerts/client.pem" ),
+																	include_bytes! ( "testcerts/client.key" ),
+																	"localhost" ).unwrap ();
+	let mut untrusted_client : TcpClient = TcpClient::new_with_tls ( "127.0.0.1", server.get_local_address ().port (), untrusted_config );
+	assert! ( untrusted_client.connect ().is_err () );
+
+	let mut plain_client : TcpClient = TcpClient::new_with_port ( "127.0.0.1", server.get_local_address ().port () );
+	plain_client.connect ().unwrap ();
+	assert! ( plain_client.read_holding_registers ( 0, 1 ).is_empty () );
+}
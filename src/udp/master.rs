@@ -0,0 +1,304 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+use core::consts::*;
+use core::transport::*;
+use network::socket::parse_network_address;
+use tcp::streamtelegram::*;
+
+//	===============================================================================================
+
+const UDP_DEFAULT_RETRIES : u8 = 2;
+const UDP_DEFAULT_TIMEOUT : u64 = 500;
+const UDP_MAXIMUM_DATAGRAM_SIZE : usize = 260;
+
+//	===============================================================================================
+
+/// A MODBUS UDP master, which sends MBAP frames as datagrams. Requests without
+/// a response in time are sent again with the same transaction identifier,
+/// responses with other transaction identifiers are skipped.
+/// The master operations are the same as of `TcpClient`.
+///
+/// # Example
+///
+/// ```rust,no_run
+///
+/// use std::time::Duration;
+/// use modbus_iiot::tcp::masteraccess::MasterAccess;
+/// use modbus_iiot::udp::master::UdpClient;
+///
+/// let mut client = UdpClient::new("192.168.0.20");
+/// client.set_timeout(Duration::from_millis(1000));
+/// client.set_retries(3);
+///
+/// if client.connect().is_ok()
+/// {
+///     println!("HR 0 = {:?}", client.read_holding_registers(0, 1));
+///     client.disconnect();
+/// }
+/// ```
+pub struct UdpClient
+{
+	address : String,
+	last_transaction_id : u16,
+	port : u16,
+	socket : Option< UdpSocket >,
+	unit_identifier : u8,
+	mask_write_supported : bool,
+	timeout : Duration,
+	retries : u8
+}
+
+impl UdpClient
+{
+	/// Creates a new `UdpClient` with the IPv4 or IPv6 address
+	/// and the default MODBUS port 502.
+	pub fn new ( address : &str ) -> UdpClient
+	{
+		return Self::new_with_port ( address,
+									 MODBUS_TCP_PORT );
+	}
+
+	/// Creates a new `UdpClient` with the IPv4 or IPv6 address and the port.
+	pub fn new_with_port ( address : &str, port : u16 ) -> UdpClient
+	{
+		return Self::new_with_port_and_unitid ( address,
+												port,
+												MODBUS_DEFAULT_UNIT_IDENTIFIER );
+	}
+
+	/// Creates a new `UdpClient` with the IPv4 or IPv6 address,
+	/// the port and the unit id of the device.
+	pub fn new_with_port_and_unitid ( address : &str, port : u16, unit_id : u8 ) -> UdpClient
+	{
+		return UdpClient
+		{
+			address : address.to_string (),
+			last_transaction_id : MODBUS_TRANSACTION_ID_INITIALIZER,
+			port : port,
+			socket : None,
+			unit_identifier : unit_id,
+			mask_write_supported : true,
+			timeout : Duration::from_millis ( UDP_DEFAULT_TIMEOUT ),
+			retries : UDP_DEFAULT_RETRIES
+		};
+	}
+
+	///	Sets the time to wait for the response of each transmission (500 ms by default).
+	pub fn set_timeout ( &mut self, timeout : Duration )
+	{
+		self.timeout = timeout;
+	}
+
+	///	Sets how often a request is sent again without a response (2 by default).
+	pub fn set_retries ( &mut self, retries : u8 )
+	{
+		self.retries = retries;
+	}
+
+	///	Opens a socket for the device. UDP has no connection,
+	/// so the device is first contacted by a request.
+	pub fn connect ( &mut self ) -> Result< (), String >
+	{
+		let address : SocketAddr = parse_network_address ( &self.address,
+														   self.port )?;
+		let local_address : &str;
+
+		if address.is_ipv4 ()
+		{
+			local_address = "0.0.0.0:0";
+		}
+		else
+		{
+			local_address = "[::]:0";
+		}
+
+		let socket : UdpSocket = UdpSocket::bind ( local_address ).map_err ( | error | error.to_string () )?;
+
+		socket.connect ( address ).map_err ( | error | error.to_string () )?;
+		self.socket = Some( socket );
+
+		return Ok( () );
+	}
+
+	///	Closes the socket if it is open.
+	pub fn disconnect ( &mut self ) -> bool
+	{
+		return self.socket.take ().is_some ();
+	}
+}
+
+impl TelegramTransport for UdpClient
+{
+	fn get_unit_identifier ( &self ) -> u8
+	{
+		return self.unit_identifier;
+	}
+
+	fn process_pdu ( &mut self, request : &[ u8 ] ) -> Result< Vec< u8 >, String >
+	{
+		let mut reply : Option< Vec< u8 > > = None;
+		let transaction_identifier : u16 = self.last_transaction_id;
+
+		let socket : &UdpSocket = self.socket.as_ref ().ok_or ( "not connected".to_string () )?;
+		let bytes : Vec< u8 > = create_frame ( transaction_identifier,
+											   self.unit_identifier,
+											   request );
+
+		self.last_transaction_id = count_up_last_transaction_id ( self.last_transaction_id );
+
+		for attempt in 0..self.retries as u16 + 1
+		{
+			if attempt > 0
+			{
+				log_event! ( debug, "retransmitting transaction {} ({} of {})", transaction_identifier, attempt, self.retries );
+			}
+
+			socket.send ( &bytes ).map_err ( | error | error.to_string () )?;
+
+			reply = receive_response ( socket,
+									   &bytes,
+									   self.timeout );
+
+			if reply.is_some ()
+			{
+				break;
+			}
+		}
+
+		if reply.is_none ()
+		{
+			log_event! ( warn, "no response to transaction {} after {} retransmissions", transaction_identifier, self.retries );
+		}
+
+		return reply.ok_or ( format! ( "no response to transaction {}", transaction_identifier ) );
+	}
+
+	fn is_mask_write_supported ( &self ) -> bool
+	{
+		return self.mask_write_supported;
+	}
+
+	fn set_mask_write_supported ( &mut self, supported : bool )
+	{
+		self.mask_write_supported = supported;
+	}
+}
+
+//	===============================================================================================
+
+///	Receives datagrams until the response with the transaction, protocol and unit
+/// identifier of the request arrives and returns its PDU, `None` after the timeout.
+fn receive_response ( socket : &UdpSocket, request : &[ u8 ], timeout : Duration ) -> Option< Vec< u8 > >
+{
+	let deadline : Instant = Instant::now () + timeout;
+	let mut buffer : [ u8; UDP_MAXIMUM_DATAGRAM_SIZE ] = [ 0x00; UDP_MAXIMUM_DATAGRAM_SIZE ];
+
+	loop
+	{
+		let remaining : Duration = deadline.saturating_duration_since ( Instant::now () );
+
+		if remaining == Duration::from_millis ( 0 ) || socket.set_read_timeout ( Some( remaining ) ).is_err ()
+		{
+			return None;
+		}
+
+		match socket.recv ( &mut buffer )
+		{
+			Ok( length )	=>
+			{
+				if let Ok( frame ) = read_frame_from_stream ( &mut &buffer[ 0..length ] )
+				{
+					if frame.len () == length && frame[ 0..4 ] == request[ 0..4 ] && frame[ 6 ] == request[ 6 ]
+					{
+						return Some( frame[ MODBUS_HEADER_SIZE as usize.. ].to_vec () );
+					}
+				}
+			}
+			Err( _ )		=> { return None; }
+		}
+	}
+}
+
+//	===============================================================================================
+
+#[cfg( test )]
+use std::thread;
+#[cfg( test )]
+use core::datatable::DataTable;
+#[cfg( test )]
+use server::tcpserver::create_response_frame;
+#[cfg( test )]
+use sim::device::SimDevice;
+#[cfg( test )]
+use tcp::masteraccess::*;
+
+///	Starts a device answering datagrams, which ignores the first `dropped_requests`
+/// requests and answers each other request three times (first with an exception
+/// of another unit, last with a wrong transaction identifier).
+#[cfg( test )]
+fn start_test_device ( device : SimDevice, dropped_requests : usize ) -> u16
+{
+	let socket : UdpSocket = UdpSocket::bind ( "127.0.0.1:0" ).unwrap ();
+	let port : u16 = socket.local_addr ().unwrap ().port ();
+
+	thread::spawn ( move ||
+	{
+		let mut device : SimDevice = device;
+		let mut buffer : [ u8; UDP_MAXIMUM_DATAGRAM_SIZE ] = [ 0x00; UDP_MAXIMUM_DATAGRAM_SIZE ];
+		let mut request_count : usize = 0;
+
+		while let Ok( ( length, sender ) ) = socket.recv_from ( &mut buffer )
+		{
+			request_count += 1;
+
+			if request_count > dropped_requests
+			{
+				let pdu : Vec< u8 > = device.process_request ( &buffer[ MODBUS_HEADER_SIZE as usize..length ] );
+				let mut other_unit : Vec< u8 > = create_response_frame ( &buffer[ 0..length ], &[ pdu[ 0 ] | MODBUS_EXCEPTION_FLAG, MODBUS_EXCEPTION_GATEWAY_TARGET_FAILED_TO_RESPOND ] );
+				let mut response : Vec< u8 > = create_response_frame ( &buffer[ 0..length ], &pdu );
+
+				other_unit[ 6 ] ^= 0xFF;
+				let _ = socket.send_to ( &other_unit, sender );
+				let _ = socket.send_to ( &response, sender );
+				response[ 1 ] ^= 0xFF;
+				let _ = socket.send_to ( &response, sender );
+			}
+		}
+	} );
+
+	return port;
+}
+
+#[test]
+fn test_udp_client ()
+{
+	let device : SimDevice = SimDevice::new_with_size ( 10 );
+	device.set_registers ( DataTable::InputRegisters, 0, &[ 1, 2 ] ).unwrap ();
+
+	let mut client : UdpClient = UdpClient::new_with_port ( "127.0.0.1", start_test_device ( device, 0 ) );
+
+	assert! ( client.read_input_registers ( 0, 2 ).is_empty () );
+
+	client.connect ().unwrap ();
+	assert_eq! ( client.read_input_registers ( 0, 2 ), vec![ 1, 2 ] );
+	assert! ( client.write_multiple_registers ( 0, vec![ 3, 4 ] ) );
+	assert! ( client.write_register_bit ( 1, 0, true ) );
+	assert_eq! ( client.read_holding_registers ( 0, 2 ), vec![ 3, 5 ] );
+	assert! ( client.read_holding_registers ( 9, 2 ).is_empty () );
+	assert! ( client.disconnect () );
+}
+
+#[test]
+fn test_udp_client_retransmission ()
+{
+	let port : u16 = start_test_device ( SimDevice::new_with_size ( 10 ), 2 );
+	let mut client : UdpClient = UdpClient::new_with_port ( "127.0.0.1", port );
+
+	client.set_timeout ( Duration::from_millis ( 100 ) );
+	client.set_retries ( 2 );
+	client.connect ().unwrap ();
+	assert_eq! ( client.read_coils ( 0, 1 ), vec![ CoilValue::Off ] );
+
+	client.set_retries ( 0 );
+	assert_eq! ( client.read_coils ( 0, 1 ), vec![ CoilValue::Off ] );
+}
@@ -0,0 +1,24 @@
+use std::env;
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+//	===============================================================================================
+
+/// Builds the library without the default feature `std` for a target without `std`,
+/// where only the `protocol` module remains. Any use of `std` there fails the build.
+/// The target has to be installed with `rustup target add thumbv7m-none-eabi`.
+#[test]
+fn test_build_without_std ()
+{
+	let cargo : String = env::var ( "CARGO" ).unwrap_or ( "cargo".to_string () );
+	let target_directory : &Path = Path::new ( env! ( "CARGO_TARGET_TMPDIR" ) );
+
+	let status : ExitStatus = Command::new ( cargo ).args ( [ "build", "--lib", "--no-default-features", "--target", "thumbv7m-none-eabi" ] )
+												   .arg ( "--target-dir" )
+												   .arg ( target_directory.join ( "no_std" ) )
+												   .current_dir ( env! ( "CARGO_MANIFEST_DIR" ) )
+												   .status ()
+												   .unwrap ();
+
+	assert! ( status.success () );
+}